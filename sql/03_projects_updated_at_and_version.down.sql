@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS "projects_touch_row" ON "projects";
+DROP FUNCTION IF EXISTS "projects_touch_row"();
+
+ALTER TABLE "projects" DROP COLUMN IF EXISTS "version";
@@ -0,0 +1,15 @@
+ALTER TABLE "projects" ADD COLUMN IF NOT EXISTS "version" INTEGER NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION "projects_touch_row"() RETURNS TRIGGER AS $$
+BEGIN
+  NEW."updated_at" = CURRENT_TIMESTAMP;
+  NEW."version" = OLD."version" + 1;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS "projects_touch_row" ON "projects";
+CREATE TRIGGER "projects_touch_row"
+  BEFORE UPDATE ON "projects"
+  FOR EACH ROW
+  EXECUTE FUNCTION "projects_touch_row"();
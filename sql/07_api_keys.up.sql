@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS "api_keys" (
+  "id" SERIAL PRIMARY KEY,
+  "name" VARCHAR(255) NOT NULL,
+  "subject" VARCHAR(255) NOT NULL,
+  "prefix" VARCHAR(16) NOT NULL,
+  "key_hash" VARCHAR(64) NOT NULL UNIQUE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "expires_at" TIMESTAMP,
+  "revoked_at" TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS "api_keys_subject_idx" ON "api_keys" ("subject");
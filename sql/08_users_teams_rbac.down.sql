@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS "projects_team_id_idx";
+DROP INDEX IF EXISTS "projects_owner_id_idx";
+
+ALTER TABLE "projects" DROP COLUMN IF EXISTS "team_id";
+ALTER TABLE "projects" DROP COLUMN IF EXISTS "owner_id";
+
+DROP TABLE IF EXISTS "memberships";
+DROP TABLE IF EXISTS "teams";
+DROP TABLE IF EXISTS "users";
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS "users" (
+  "id" SERIAL PRIMARY KEY,
+  "subject" VARCHAR(255) NOT NULL UNIQUE,
+  "is_admin" BOOLEAN NOT NULL DEFAULT FALSE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS "teams" (
+  "id" SERIAL PRIMARY KEY,
+  "name" VARCHAR(255) NOT NULL UNIQUE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS "memberships" (
+  "team_id" INTEGER NOT NULL REFERENCES "teams" ("id") ON DELETE CASCADE,
+  "user_id" INTEGER NOT NULL REFERENCES "users" ("id") ON DELETE CASCADE,
+  "role" VARCHAR(16) NOT NULL CHECK ("role" IN ('viewer', 'editor', 'admin')),
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY ("team_id", "user_id")
+);
+
+CREATE INDEX IF NOT EXISTS "memberships_user_id_idx" ON "memberships" ("user_id");
+
+ALTER TABLE "projects" ADD COLUMN IF NOT EXISTS "owner_id" INTEGER REFERENCES "users" ("id") ON DELETE SET NULL;
+ALTER TABLE "projects" ADD COLUMN IF NOT EXISTS "team_id" INTEGER REFERENCES "teams" ("id") ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS "projects_owner_id_idx" ON "projects" ("owner_id");
+CREATE INDEX IF NOT EXISTS "projects_team_id_idx" ON "projects" ("team_id");
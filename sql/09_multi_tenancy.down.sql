@@ -0,0 +1,39 @@
+ALTER TABLE "projects" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "project_status_history" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "audit_log" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "api_keys" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "users" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "teams" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "memberships" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "status_types_dictionary" DISABLE ROW LEVEL SECURITY;
+ALTER TABLE "status_transitions" DISABLE ROW LEVEL SECURITY;
+
+DROP POLICY IF EXISTS "tenant_isolation" ON "projects";
+DROP POLICY IF EXISTS "tenant_isolation" ON "project_status_history";
+DROP POLICY IF EXISTS "tenant_isolation" ON "audit_log";
+DROP POLICY IF EXISTS "tenant_isolation" ON "api_keys";
+DROP POLICY IF EXISTS "tenant_isolation" ON "users";
+DROP POLICY IF EXISTS "tenant_isolation" ON "teams";
+DROP POLICY IF EXISTS "tenant_isolation" ON "memberships";
+DROP POLICY IF EXISTS "tenant_isolation" ON "status_types_dictionary";
+DROP POLICY IF EXISTS "tenant_isolation" ON "status_transitions";
+
+ALTER TABLE "teams" DROP CONSTRAINT IF EXISTS "teams_tenant_id_name_key";
+ALTER TABLE "teams" ADD CONSTRAINT "teams_name_key" UNIQUE ("name");
+ALTER TABLE "users" DROP CONSTRAINT IF EXISTS "users_tenant_id_subject_key";
+ALTER TABLE "users" ADD CONSTRAINT "users_subject_key" UNIQUE ("subject");
+
+DROP INDEX IF EXISTS "status_types_dictionary_tenant_name_key";
+CREATE UNIQUE INDEX IF NOT EXISTS "status_types_dictionary_name_key" ON "status_types_dictionary" ("name");
+
+ALTER TABLE "status_transitions" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "status_types_dictionary" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "memberships" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "teams" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "users" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "api_keys" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "audit_log" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "project_status_history" DROP COLUMN IF EXISTS "tenant_id";
+ALTER TABLE "projects" DROP COLUMN IF EXISTS "tenant_id";
+
+DROP TABLE IF EXISTS "tenants";
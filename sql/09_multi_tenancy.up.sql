@@ -0,0 +1,67 @@
+CREATE TABLE IF NOT EXISTS "tenants" (
+  "id" VARCHAR(64) PRIMARY KEY,
+  "name" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO "tenants" ("id", "name") VALUES ('default', 'Default') ON CONFLICT ("id") DO NOTHING;
+
+ALTER TABLE "projects" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+ALTER TABLE "project_status_history" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+ALTER TABLE "audit_log" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+ALTER TABLE "api_keys" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+ALTER TABLE "users" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+ALTER TABLE "teams" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+ALTER TABLE "memberships" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id");
+-- Status types and transitions without tenant are the workflow shared by every tenant.
+ALTER TABLE "status_types_dictionary" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) REFERENCES "tenants" ("id");
+ALTER TABLE "status_transitions" ADD COLUMN IF NOT EXISTS "tenant_id" VARCHAR(64) REFERENCES "tenants" ("id");
+
+CREATE INDEX IF NOT EXISTS "projects_tenant_id_idx" ON "projects" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "project_status_history_tenant_id_idx" ON "project_status_history" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "audit_log_tenant_id_idx" ON "audit_log" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "api_keys_tenant_id_idx" ON "api_keys" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "memberships_tenant_id_idx" ON "memberships" ("tenant_id");
+
+DROP INDEX IF EXISTS "status_types_dictionary_name_key";
+CREATE UNIQUE INDEX IF NOT EXISTS "status_types_dictionary_tenant_name_key" ON "status_types_dictionary" (COALESCE("tenant_id", ''), "name");
+
+ALTER TABLE "users" DROP CONSTRAINT IF EXISTS "users_subject_key";
+ALTER TABLE "users" DROP CONSTRAINT IF EXISTS "users_tenant_id_subject_key";
+ALTER TABLE "users" ADD CONSTRAINT "users_tenant_id_subject_key" UNIQUE ("tenant_id", "subject");
+ALTER TABLE "teams" DROP CONSTRAINT IF EXISTS "teams_name_key";
+ALTER TABLE "teams" DROP CONSTRAINT IF EXISTS "teams_tenant_id_name_key";
+ALTER TABLE "teams" ADD CONSTRAINT "teams_tenant_id_name_key" UNIQUE ("tenant_id", "name");
+
+-- Row level security policies, only enforced when the service starts with
+-- PG_ROW_LEVEL_SECURITY=true. The tenant comes from the ninoverse.tenant_id
+-- setting of the connection.
+DROP POLICY IF EXISTS "tenant_isolation" ON "projects";
+CREATE POLICY "tenant_isolation" ON "projects"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "project_status_history";
+CREATE POLICY "tenant_isolation" ON "project_status_history"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "audit_log";
+CREATE POLICY "tenant_isolation" ON "audit_log"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "api_keys";
+CREATE POLICY "tenant_isolation" ON "api_keys"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "users";
+CREATE POLICY "tenant_isolation" ON "users"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "teams";
+CREATE POLICY "tenant_isolation" ON "teams"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "memberships";
+CREATE POLICY "tenant_isolation" ON "memberships"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "status_types_dictionary";
+CREATE POLICY "tenant_isolation" ON "status_types_dictionary"
+  USING ("tenant_id" IS NULL OR "tenant_id" = current_setting('ninoverse.tenant_id', true))
+  WITH CHECK ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "status_transitions";
+CREATE POLICY "tenant_isolation" ON "status_transitions"
+  USING ("tenant_id" IS NULL OR "tenant_id" = current_setting('ninoverse.tenant_id', true))
+  WITH CHECK ("tenant_id" = current_setting('ninoverse.tenant_id', true));
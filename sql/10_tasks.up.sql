@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS "tasks" (
+  "id" SERIAL PRIMARY KEY,
+  "project_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "parent_id" INTEGER REFERENCES "tasks" ("id") ON DELETE CASCADE,
+  "title" VARCHAR(255) NOT NULL,
+  "description" TEXT,
+  "assignee_id" INTEGER REFERENCES "users" ("id") ON DELETE SET NULL,
+  "status" VARCHAR(16) NOT NULL DEFAULT 'todo' CHECK ("status" IN ('todo', 'in_progress', 'done')),
+  "priority" VARCHAR(16) NOT NULL DEFAULT 'medium' CHECK ("priority" IN ('low', 'medium', 'high', 'urgent')),
+  "due_date" DATE,
+  "position" INTEGER NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "updated_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "tasks_project_id_idx" ON "tasks" ("project_id", "position");
+CREATE INDEX IF NOT EXISTS "tasks_parent_id_idx" ON "tasks" ("parent_id");
+CREATE INDEX IF NOT EXISTS "tasks_assignee_id_idx" ON "tasks" ("assignee_id");
+CREATE INDEX IF NOT EXISTS "tasks_tenant_id_idx" ON "tasks" ("tenant_id");
+
+DROP POLICY IF EXISTS "tenant_isolation" ON "tasks";
+CREATE POLICY "tenant_isolation" ON "tasks"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
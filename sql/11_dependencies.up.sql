@@ -0,0 +1,34 @@
+-- Projects in a completed status no longer block the projects depending on them.
+ALTER TABLE "status_types_dictionary" ADD COLUMN IF NOT EXISTS "completed" BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE "status_types_dictionary" SET "completed" = TRUE WHERE "name" = 'done' AND "tenant_id" IS NULL;
+
+-- "blocked_id is blocked by blocking_id".
+CREATE TABLE IF NOT EXISTS "project_dependencies" (
+  "blocked_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "blocking_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  PRIMARY KEY ("blocked_id", "blocking_id"),
+  CHECK ("blocked_id" <> "blocking_id")
+);
+
+CREATE TABLE IF NOT EXISTS "task_dependencies" (
+  "blocked_id" INTEGER NOT NULL REFERENCES "tasks" ("id") ON DELETE CASCADE,
+  "blocking_id" INTEGER NOT NULL REFERENCES "tasks" ("id") ON DELETE CASCADE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  PRIMARY KEY ("blocked_id", "blocking_id"),
+  CHECK ("blocked_id" <> "blocking_id")
+);
+
+CREATE INDEX IF NOT EXISTS "project_dependencies_blocking_id_idx" ON "project_dependencies" ("blocking_id");
+CREATE INDEX IF NOT EXISTS "project_dependencies_tenant_id_idx" ON "project_dependencies" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "task_dependencies_blocking_id_idx" ON "task_dependencies" ("blocking_id");
+CREATE INDEX IF NOT EXISTS "task_dependencies_tenant_id_idx" ON "task_dependencies" ("tenant_id");
+
+DROP POLICY IF EXISTS "tenant_isolation" ON "project_dependencies";
+CREATE POLICY "tenant_isolation" ON "project_dependencies"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
+DROP POLICY IF EXISTS "tenant_isolation" ON "task_dependencies";
+CREATE POLICY "tenant_isolation" ON "task_dependencies"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
@@ -0,0 +1,12 @@
+DROP TRIGGER IF EXISTS "comments_search_vector" ON "comments";
+DROP TRIGGER IF EXISTS "tasks_search_vector" ON "tasks";
+DROP TRIGGER IF EXISTS "projects_search_vector" ON "projects";
+DROP FUNCTION IF EXISTS "comments_search_vector"();
+DROP FUNCTION IF EXISTS "tasks_search_vector"();
+DROP FUNCTION IF EXISTS "projects_search_vector"();
+ALTER TABLE "comments" DROP COLUMN IF EXISTS "search_vector";
+ALTER TABLE "tasks" DROP COLUMN IF EXISTS "search_vector";
+ALTER TABLE "projects" DROP COLUMN IF EXISTS "search_vector";
+DROP FUNCTION IF EXISTS "search_vector"(TEXT, TEXT);
+DROP FUNCTION IF EXISTS "search_language"();
+DROP TABLE IF EXISTS "search_settings";
@@ -0,0 +1,66 @@
+-- The text search configuration used for stemming. The service sets it from
+-- SEARCH_LANGUAGE when it starts and rebuilds the search vectors when it
+-- changes, NULL until then.
+CREATE TABLE IF NOT EXISTS "search_settings" (
+  "id" BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK ("id"),
+  "language" REGCONFIG
+);
+
+INSERT INTO "search_settings" ("id") VALUES (TRUE) ON CONFLICT ("id") DO NOTHING;
+
+CREATE OR REPLACE FUNCTION "search_language"() RETURNS REGCONFIG AS $$
+  SELECT COALESCE((SELECT "language" FROM "search_settings"), 'simple'::REGCONFIG)
+$$ LANGUAGE sql STABLE;
+
+-- Matches in titles rank above matches in descriptions and bodies.
+CREATE OR REPLACE FUNCTION "search_vector"("title" TEXT, "body" TEXT) RETURNS TSVECTOR AS $$
+  SELECT setweight(to_tsvector("search_language"(), COALESCE("title", '')), 'A')
+    || setweight(to_tsvector("search_language"(), COALESCE("body", '')), 'B')
+$$ LANGUAGE sql STABLE;
+
+ALTER TABLE "projects" ADD COLUMN IF NOT EXISTS "search_vector" TSVECTOR;
+ALTER TABLE "tasks" ADD COLUMN IF NOT EXISTS "search_vector" TSVECTOR;
+ALTER TABLE "comments" ADD COLUMN IF NOT EXISTS "search_vector" TSVECTOR;
+
+CREATE INDEX IF NOT EXISTS "projects_search_vector_idx" ON "projects" USING GIN ("search_vector");
+CREATE INDEX IF NOT EXISTS "tasks_search_vector_idx" ON "tasks" USING GIN ("search_vector");
+CREATE INDEX IF NOT EXISTS "comments_search_vector_idx" ON "comments" USING GIN ("search_vector");
+
+CREATE OR REPLACE FUNCTION "projects_search_vector"() RETURNS TRIGGER AS $$
+BEGIN
+  NEW."search_vector" = "search_vector"(NEW."name", NEW."description");
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION "tasks_search_vector"() RETURNS TRIGGER AS $$
+BEGIN
+  NEW."search_vector" = "search_vector"(NEW."title", NEW."description");
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION "comments_search_vector"() RETURNS TRIGGER AS $$
+BEGIN
+  NEW."search_vector" = "search_vector"(NULL, NEW."body");
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS "projects_search_vector" ON "projects";
+CREATE TRIGGER "projects_search_vector"
+  BEFORE INSERT OR UPDATE OF "name", "description" ON "projects"
+  FOR EACH ROW
+  EXECUTE FUNCTION "projects_search_vector"();
+
+DROP TRIGGER IF EXISTS "tasks_search_vector" ON "tasks";
+CREATE TRIGGER "tasks_search_vector"
+  BEFORE INSERT OR UPDATE OF "title", "description" ON "tasks"
+  FOR EACH ROW
+  EXECUTE FUNCTION "tasks_search_vector"();
+
+DROP TRIGGER IF EXISTS "comments_search_vector" ON "comments";
+CREATE TRIGGER "comments_search_vector"
+  BEFORE INSERT OR UPDATE OF "body" ON "comments"
+  FOR EACH ROW
+  EXECUTE FUNCTION "comments_search_vector"();
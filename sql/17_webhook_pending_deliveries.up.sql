@@ -0,0 +1,21 @@
+-- Events waiting for their next attempt at a webhook, the retry queue of the
+-- deliveries. "attempt" is the number of the next attempt, a row is removed
+-- once the webhook accepted the event or the attempts ran out.
+CREATE TABLE IF NOT EXISTS "webhook_pending_deliveries" (
+  "id" SERIAL PRIMARY KEY,
+  "webhook_id" INTEGER NOT NULL REFERENCES "webhooks" ("id") ON DELETE CASCADE,
+  "event_id" VARCHAR(255) NOT NULL,
+  "event_type" VARCHAR(64) NOT NULL,
+  "body" TEXT NOT NULL,
+  "attempt" INTEGER NOT NULL DEFAULT 1,
+  "next_attempt_at" TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "webhook_pending_deliveries_due_idx"
+  ON "webhook_pending_deliveries" ("tenant_id", "next_attempt_at");
+
+DROP POLICY IF EXISTS "tenant_isolation" ON "webhook_pending_deliveries";
+CREATE POLICY "tenant_isolation" ON "webhook_pending_deliveries"
+  USING ("tenant_id" = current_setting('ninoverse.tenant_id', true));
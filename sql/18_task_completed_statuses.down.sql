@@ -0,0 +1,3 @@
+-- Fails while tasks are in another status than these, move them first.
+ALTER TABLE "tasks" ALTER COLUMN "status" TYPE VARCHAR(16);
+ALTER TABLE "tasks" ADD CONSTRAINT "tasks_status_check" CHECK ("status" IN ('todo', 'in_progress', 'done'));
@@ -0,0 +1,4 @@
+-- Tasks may end in any completed status type, not only in 'done', and no
+-- longer block their dependents once they do.
+ALTER TABLE "tasks" DROP CONSTRAINT IF EXISTS "tasks_status_check";
+ALTER TABLE "tasks" ALTER COLUMN "status" TYPE VARCHAR(255);
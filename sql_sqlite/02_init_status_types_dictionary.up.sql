@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS "status_types_dictionary" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "name" VARCHAR(255) NOT NULL
+);
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS "projects_touch_row";
+
+ALTER TABLE "projects" DROP COLUMN "version";
@@ -0,0 +1,13 @@
+ALTER TABLE "projects" ADD COLUMN "version" INTEGER NOT NULL DEFAULT 1;
+
+-- The repository bumps version and updated_at itself so that RETURNING sees
+-- the new values, the trigger covers rows edited by hand.
+CREATE TRIGGER IF NOT EXISTS "projects_touch_row"
+  AFTER UPDATE ON "projects"
+  FOR EACH ROW
+  WHEN NEW."version" = OLD."version"
+BEGIN
+  UPDATE "projects"
+  SET "updated_at" = CURRENT_TIMESTAMP, "version" = OLD."version" + 1
+  WHERE "id" = NEW."id";
+END;
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS "project_status_history";
+DROP TABLE IF EXISTS "status_transitions";
+
+DELETE FROM "status_types_dictionary" WHERE "name" IN ('draft', 'active', 'done');
+
+DROP INDEX IF EXISTS "status_types_dictionary_name_key";
@@ -0,0 +1,35 @@
+CREATE UNIQUE INDEX IF NOT EXISTS "status_types_dictionary_name_key" ON "status_types_dictionary" ("name");
+
+INSERT INTO "status_types_dictionary" ("name")
+VALUES ('draft'), ('active'), ('done')
+ON CONFLICT ("name") DO NOTHING;
+
+-- "required_fields" holds a JSON array of project field names.
+CREATE TABLE IF NOT EXISTS "status_transitions" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "from_status_id" INTEGER NOT NULL REFERENCES "status_types_dictionary" ("id"),
+  "to_status_id" INTEGER NOT NULL REFERENCES "status_types_dictionary" ("id"),
+  "required_fields" TEXT NOT NULL DEFAULT '[]',
+  UNIQUE ("from_status_id", "to_status_id")
+);
+
+INSERT INTO "status_transitions" ("from_status_id", "to_status_id", "required_fields")
+SELECT "from_status"."id", "to_status"."id", "transition"."required_fields"
+FROM (
+  SELECT 'draft' AS "from_name", 'active' AS "to_name", '["description"]' AS "required_fields"
+  UNION ALL SELECT 'active', 'draft', '[]'
+  UNION ALL SELECT 'active', 'done', '[]'
+) AS "transition"
+JOIN "status_types_dictionary" AS "from_status" ON "from_status"."name" = "transition"."from_name"
+JOIN "status_types_dictionary" AS "to_status" ON "to_status"."name" = "transition"."to_name"
+WHERE TRUE
+ON CONFLICT ("from_status_id", "to_status_id") DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS "project_status_history" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "project_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "from_status" VARCHAR(255) NOT NULL,
+  "to_status" VARCHAR(255) NOT NULL,
+  "actor" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
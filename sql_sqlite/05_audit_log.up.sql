@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS "audit_log" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "actor" VARCHAR(255) NOT NULL,
+  "action" VARCHAR(255) NOT NULL,
+  "entity_type" VARCHAR(255) NOT NULL,
+  "entity_id" INTEGER NOT NULL,
+  "before" TEXT,
+  "after" TEXT,
+  "diff" TEXT NOT NULL DEFAULT '{}',
+  "request_id" VARCHAR(255),
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS "audit_log_entity_idx" ON "audit_log" ("entity_type", "entity_id");
+CREATE INDEX IF NOT EXISTS "audit_log_actor_idx" ON "audit_log" ("actor");
+CREATE INDEX IF NOT EXISTS "audit_log_created_at_idx" ON "audit_log" ("created_at");
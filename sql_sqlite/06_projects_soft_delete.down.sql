@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS "projects_deleted_at_idx";
+
+ALTER TABLE "projects" DROP COLUMN "deleted_at";
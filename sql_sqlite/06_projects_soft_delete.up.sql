@@ -0,0 +1,3 @@
+ALTER TABLE "projects" ADD COLUMN "deleted_at" TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS "projects_deleted_at_idx" ON "projects" ("deleted_at");
@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS "projects_team_id_idx";
+DROP INDEX IF EXISTS "projects_owner_id_idx";
+
+ALTER TABLE "projects" DROP COLUMN "team_id";
+ALTER TABLE "projects" DROP COLUMN "owner_id";
+
+DROP TABLE IF EXISTS "memberships";
+DROP TABLE IF EXISTS "teams";
+DROP TABLE IF EXISTS "users";
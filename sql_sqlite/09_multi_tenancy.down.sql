@@ -0,0 +1,60 @@
+DROP INDEX IF EXISTS "status_types_dictionary_tenant_name_key";
+CREATE UNIQUE INDEX IF NOT EXISTS "status_types_dictionary_name_key" ON "status_types_dictionary" ("name");
+
+DROP INDEX IF EXISTS "memberships_tenant_id_idx";
+DROP INDEX IF EXISTS "api_keys_tenant_id_idx";
+DROP INDEX IF EXISTS "audit_log_tenant_id_idx";
+DROP INDEX IF EXISTS "project_status_history_tenant_id_idx";
+DROP INDEX IF EXISTS "projects_tenant_id_idx";
+
+ALTER TABLE "status_transitions" DROP COLUMN "tenant_id";
+ALTER TABLE "status_types_dictionary" DROP COLUMN "tenant_id";
+ALTER TABLE "memberships" DROP COLUMN "tenant_id";
+ALTER TABLE "api_keys" DROP COLUMN "tenant_id";
+ALTER TABLE "audit_log" DROP COLUMN "tenant_id";
+ALTER TABLE "project_status_history" DROP COLUMN "tenant_id";
+ALTER TABLE "projects" DROP COLUMN "tenant_id";
+
+DROP TRIGGER IF EXISTS "projects_touch_row";
+CREATE TEMP TABLE "memberships_backup" AS SELECT * FROM "memberships";
+CREATE TEMP TABLE "projects_access_backup" AS SELECT "id", "owner_id", "team_id" FROM "projects";
+
+CREATE TABLE "teams_old" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "name" VARCHAR(255) NOT NULL UNIQUE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO "teams_old" ("id", "name", "created_at")
+SELECT "id", "name", "created_at" FROM "teams";
+DROP TABLE "teams";
+ALTER TABLE "teams_old" RENAME TO "teams";
+
+CREATE TABLE "users_old" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "subject" VARCHAR(255) NOT NULL UNIQUE,
+  "is_admin" BOOLEAN NOT NULL DEFAULT FALSE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO "users_old" ("id", "subject", "is_admin", "created_at")
+SELECT "id", "subject", "is_admin", "created_at" FROM "users";
+DROP TABLE "users";
+ALTER TABLE "users_old" RENAME TO "users";
+
+INSERT INTO "memberships" ("team_id", "user_id", "role", "created_at")
+SELECT "team_id", "user_id", "role", "created_at" FROM "memberships_backup";
+UPDATE "projects" SET
+  "owner_id" = (SELECT "owner_id" FROM "projects_access_backup" WHERE "projects_access_backup"."id" = "projects"."id"),
+  "team_id" = (SELECT "team_id" FROM "projects_access_backup" WHERE "projects_access_backup"."id" = "projects"."id");
+DROP TABLE "memberships_backup";
+DROP TABLE "projects_access_backup";
+CREATE TRIGGER IF NOT EXISTS "projects_touch_row"
+  AFTER UPDATE ON "projects"
+  FOR EACH ROW
+  WHEN NEW."version" = OLD."version"
+BEGIN
+  UPDATE "projects"
+  SET "updated_at" = CURRENT_TIMESTAMP, "version" = OLD."version" + 1
+  WHERE "id" = NEW."id";
+END;
+
+DROP TABLE IF EXISTS "tenants";
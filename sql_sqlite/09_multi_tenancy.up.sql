@@ -0,0 +1,77 @@
+CREATE TABLE IF NOT EXISTS "tenants" (
+  "id" VARCHAR(64) PRIMARY KEY,
+  "name" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO "tenants" ("id", "name") VALUES ('default', 'Default') ON CONFLICT ("id") DO NOTHING;
+
+-- The per tenant uniqueness of users and teams needs a table rebuild. The
+-- migration runs with foreign keys enforced, dropping the old tables would
+-- cascade to the memberships and clear the project owners and teams, so they
+-- are kept aside meanwhile. Restoring them must not bump the project versions.
+DROP TRIGGER IF EXISTS "projects_touch_row";
+CREATE TEMP TABLE "memberships_backup" AS SELECT * FROM "memberships";
+CREATE TEMP TABLE "projects_access_backup" AS SELECT "id", "owner_id", "team_id" FROM "projects";
+
+CREATE TABLE "users_new" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  "subject" VARCHAR(255) NOT NULL,
+  "is_admin" BOOLEAN NOT NULL DEFAULT FALSE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE ("tenant_id", "subject")
+);
+INSERT INTO "users_new" ("id", "subject", "is_admin", "created_at")
+SELECT "id", "subject", "is_admin", "created_at" FROM "users";
+DROP TABLE "users";
+ALTER TABLE "users_new" RENAME TO "users";
+
+CREATE TABLE "teams_new" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  "name" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE ("tenant_id", "name")
+);
+INSERT INTO "teams_new" ("id", "name", "created_at")
+SELECT "id", "name", "created_at" FROM "teams";
+DROP TABLE "teams";
+ALTER TABLE "teams_new" RENAME TO "teams";
+
+INSERT INTO "memberships" ("team_id", "user_id", "role", "created_at")
+SELECT "team_id", "user_id", "role", "created_at" FROM "memberships_backup";
+UPDATE "projects" SET
+  "owner_id" = (SELECT "owner_id" FROM "projects_access_backup" WHERE "projects_access_backup"."id" = "projects"."id"),
+  "team_id" = (SELECT "team_id" FROM "projects_access_backup" WHERE "projects_access_backup"."id" = "projects"."id");
+DROP TABLE "memberships_backup";
+DROP TABLE "projects_access_backup";
+CREATE TRIGGER IF NOT EXISTS "projects_touch_row"
+  AFTER UPDATE ON "projects"
+  FOR EACH ROW
+  WHEN NEW."version" = OLD."version"
+BEGIN
+  UPDATE "projects"
+  SET "updated_at" = CURRENT_TIMESTAMP, "version" = OLD."version" + 1
+  WHERE "id" = NEW."id";
+END;
+
+-- SQLite can't add a column referencing another table with a default value
+-- while foreign keys are enforced, the tenant is only checked by the service.
+ALTER TABLE "projects" ADD COLUMN "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE "project_status_history" ADD COLUMN "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE "audit_log" ADD COLUMN "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE "api_keys" ADD COLUMN "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE "memberships" ADD COLUMN "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default';
+-- Status types and transitions without tenant are the workflow shared by every tenant.
+ALTER TABLE "status_types_dictionary" ADD COLUMN "tenant_id" VARCHAR(64) REFERENCES "tenants" ("id");
+ALTER TABLE "status_transitions" ADD COLUMN "tenant_id" VARCHAR(64) REFERENCES "tenants" ("id");
+
+CREATE INDEX IF NOT EXISTS "projects_tenant_id_idx" ON "projects" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "project_status_history_tenant_id_idx" ON "project_status_history" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "audit_log_tenant_id_idx" ON "audit_log" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "api_keys_tenant_id_idx" ON "api_keys" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "memberships_tenant_id_idx" ON "memberships" ("tenant_id");
+
+DROP INDEX IF EXISTS "status_types_dictionary_name_key";
+CREATE UNIQUE INDEX IF NOT EXISTS "status_types_dictionary_tenant_name_key" ON "status_types_dictionary" (COALESCE("tenant_id", ''), "name");
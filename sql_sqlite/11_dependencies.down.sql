@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS "task_dependencies";
+DROP TABLE IF EXISTS "project_dependencies";
+
+ALTER TABLE "status_types_dictionary" DROP COLUMN "completed";
@@ -0,0 +1,30 @@
+-- Items reference tags by id, renaming a tag renames it everywhere.
+CREATE TABLE IF NOT EXISTS "tags" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  "name" VARCHAR(64) NOT NULL,
+  "color" VARCHAR(7) NOT NULL DEFAULT '#9e9e9e',
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE ("tenant_id", "name")
+);
+
+CREATE TABLE IF NOT EXISTS "project_tags" (
+  "project_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "tag_id" INTEGER NOT NULL REFERENCES "tags" ("id") ON DELETE CASCADE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  PRIMARY KEY ("project_id", "tag_id")
+);
+
+CREATE TABLE IF NOT EXISTS "task_tags" (
+  "task_id" INTEGER NOT NULL REFERENCES "tasks" ("id") ON DELETE CASCADE,
+  "tag_id" INTEGER NOT NULL REFERENCES "tags" ("id") ON DELETE CASCADE,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id"),
+  PRIMARY KEY ("task_id", "tag_id")
+);
+
+CREATE INDEX IF NOT EXISTS "project_tags_tag_id_idx" ON "project_tags" ("tag_id");
+CREATE INDEX IF NOT EXISTS "project_tags_tenant_id_idx" ON "project_tags" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "task_tags_tag_id_idx" ON "task_tags" ("tag_id");
+CREATE INDEX IF NOT EXISTS "task_tags_tenant_id_idx" ON "task_tags" ("tenant_id");
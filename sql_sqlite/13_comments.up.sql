@@ -0,0 +1,30 @@
+-- Comments on a task keep the project of the task, replies the item of
+-- their parent.
+CREATE TABLE IF NOT EXISTS "comments" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "project_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "task_id" INTEGER REFERENCES "tasks" ("id") ON DELETE CASCADE,
+  "parent_id" INTEGER REFERENCES "comments" ("id") ON DELETE CASCADE,
+  "author" VARCHAR(255) NOT NULL,
+  "body" TEXT NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "updated_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+-- The body of a comment before each edit.
+CREATE TABLE IF NOT EXISTS "comment_revisions" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "comment_id" INTEGER NOT NULL REFERENCES "comments" ("id") ON DELETE CASCADE,
+  "body" TEXT NOT NULL,
+  "edited_by" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "comments_project_id_idx" ON "comments" ("project_id", "created_at");
+CREATE INDEX IF NOT EXISTS "comments_task_id_idx" ON "comments" ("task_id");
+CREATE INDEX IF NOT EXISTS "comments_parent_id_idx" ON "comments" ("parent_id");
+CREATE INDEX IF NOT EXISTS "comments_tenant_id_idx" ON "comments" ("tenant_id");
+CREATE INDEX IF NOT EXISTS "comment_revisions_comment_id_idx" ON "comment_revisions" ("comment_id");
+CREATE INDEX IF NOT EXISTS "comment_revisions_tenant_id_idx" ON "comment_revisions" ("tenant_id");
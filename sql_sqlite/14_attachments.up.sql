@@ -0,0 +1,17 @@
+-- Metadata of the files attached to projects, the content lives in the
+-- blob store under "storage_key".
+CREATE TABLE IF NOT EXISTS "attachments" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "project_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "file_name" VARCHAR(255) NOT NULL,
+  "content_type" VARCHAR(255) NOT NULL,
+  "size" BIGINT NOT NULL,
+  "sha256" CHAR(64) NOT NULL,
+  "storage_key" VARCHAR(512) NOT NULL UNIQUE,
+  "uploaded_by" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "attachments_project_id_idx" ON "attachments" ("project_id");
+CREATE INDEX IF NOT EXISTS "attachments_tenant_id_idx" ON "attachments" ("tenant_id");
@@ -0,0 +1,10 @@
+DROP TRIGGER IF EXISTS "comments_search_delete";
+DROP TRIGGER IF EXISTS "comments_search_update";
+DROP TRIGGER IF EXISTS "comments_search_insert";
+DROP TRIGGER IF EXISTS "tasks_search_delete";
+DROP TRIGGER IF EXISTS "tasks_search_update";
+DROP TRIGGER IF EXISTS "tasks_search_insert";
+DROP TRIGGER IF EXISTS "projects_search_delete";
+DROP TRIGGER IF EXISTS "projects_search_update";
+DROP TRIGGER IF EXISTS "projects_search_insert";
+DROP TABLE IF EXISTS "search_index";
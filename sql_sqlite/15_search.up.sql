@@ -0,0 +1,70 @@
+-- One row per searchable item, kept in sync by the triggers below. SQLite has
+-- no stemming configuration, the porter tokenizer stems English.
+CREATE VIRTUAL TABLE IF NOT EXISTS "search_index" USING fts5(
+  "entity_type" UNINDEXED,
+  "entity_id" UNINDEXED,
+  "project_id" UNINDEXED,
+  "task_id" UNINDEXED,
+  "tenant_id" UNINDEXED,
+  "title",
+  "body",
+  tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+  SELECT 'project', "id", "id", NULL, "tenant_id", "name", "description" FROM "projects";
+INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+  SELECT 'task', "id", "project_id", "id", "tenant_id", "title", "description" FROM "tasks";
+INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+  SELECT 'comment', "id", "project_id", "task_id", "tenant_id", NULL, "body" FROM "comments";
+
+CREATE TRIGGER IF NOT EXISTS "projects_search_insert" AFTER INSERT ON "projects"
+BEGIN
+  INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+    VALUES ('project', NEW."id", NEW."id", NULL, NEW."tenant_id", NEW."name", NEW."description");
+END;
+
+CREATE TRIGGER IF NOT EXISTS "projects_search_update" AFTER UPDATE OF "name", "description" ON "projects"
+BEGIN
+  UPDATE "search_index" SET "title" = NEW."name", "body" = NEW."description"
+    WHERE "entity_type" = 'project' AND "entity_id" = NEW."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "projects_search_delete" AFTER DELETE ON "projects"
+BEGIN
+  DELETE FROM "search_index" WHERE "entity_type" = 'project' AND "entity_id" = OLD."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "tasks_search_insert" AFTER INSERT ON "tasks"
+BEGIN
+  INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+    VALUES ('task', NEW."id", NEW."project_id", NEW."id", NEW."tenant_id", NEW."title", NEW."description");
+END;
+
+CREATE TRIGGER IF NOT EXISTS "tasks_search_update" AFTER UPDATE OF "title", "description" ON "tasks"
+BEGIN
+  UPDATE "search_index" SET "title" = NEW."title", "body" = NEW."description"
+    WHERE "entity_type" = 'task' AND "entity_id" = NEW."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "tasks_search_delete" AFTER DELETE ON "tasks"
+BEGIN
+  DELETE FROM "search_index" WHERE "entity_type" = 'task' AND "entity_id" = OLD."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "comments_search_insert" AFTER INSERT ON "comments"
+BEGIN
+  INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+    VALUES ('comment', NEW."id", NEW."project_id", NEW."task_id", NEW."tenant_id", NULL, NEW."body");
+END;
+
+CREATE TRIGGER IF NOT EXISTS "comments_search_update" AFTER UPDATE OF "body" ON "comments"
+BEGIN
+  UPDATE "search_index" SET "body" = NEW."body"
+    WHERE "entity_type" = 'comment' AND "entity_id" = NEW."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "comments_search_delete" AFTER DELETE ON "comments"
+BEGIN
+  DELETE FROM "search_index" WHERE "entity_type" = 'comment' AND "entity_id" = OLD."id";
+END;
@@ -0,0 +1,36 @@
+-- HTTP callbacks receiving the events of the tenant. "event_types" is a JSON
+-- array filtering the events sent, every event when empty. A webhook is
+-- disabled once "consecutive_failures" deliveries failed in a row.
+CREATE TABLE IF NOT EXISTS "webhooks" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "url" VARCHAR(2048) NOT NULL,
+  "secret" VARCHAR(255) NOT NULL,
+  "event_types" TEXT NOT NULL DEFAULT '[]',
+  "enabled" BOOLEAN NOT NULL DEFAULT TRUE,
+  "consecutive_failures" INTEGER NOT NULL DEFAULT 0,
+  "disabled_at" TIMESTAMP NULL,
+  "created_by" VARCHAR(255) NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "updated_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "webhooks_tenant_id_idx" ON "webhooks" ("tenant_id");
+
+-- Every delivery attempt, "event_id" is the Kafka position of the event.
+CREATE TABLE IF NOT EXISTS "webhook_deliveries" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "webhook_id" INTEGER NOT NULL REFERENCES "webhooks" ("id") ON DELETE CASCADE,
+  "event_id" VARCHAR(255) NOT NULL,
+  "event_type" VARCHAR(64) NOT NULL,
+  "attempt" INTEGER NOT NULL,
+  "status_code" INTEGER NULL,
+  "error" TEXT NULL,
+  "succeeded" BOOLEAN NOT NULL,
+  "duration_ms" BIGINT NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "webhook_deliveries_webhook_id_idx" ON "webhook_deliveries" ("webhook_id");
+CREATE INDEX IF NOT EXISTS "webhook_deliveries_tenant_id_idx" ON "webhook_deliveries" ("tenant_id");
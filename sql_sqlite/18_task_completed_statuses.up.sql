@@ -0,0 +1,68 @@
+-- Tasks may end in any completed status type, not only in 'done', and no
+-- longer block their dependents once they do. SQLite can't drop a
+-- constraint, the table is rebuilt without it. As in the multi tenancy
+-- migration, foreign keys are enforced: dropping "tasks" cascades to their
+-- dependencies, tags and comments, they are kept aside meanwhile.
+DROP TRIGGER IF EXISTS "tasks_search_insert";
+DROP TRIGGER IF EXISTS "tasks_search_update";
+DROP TRIGGER IF EXISTS "tasks_search_delete";
+CREATE TEMP TABLE "task_dependencies_backup" AS SELECT * FROM "task_dependencies";
+CREATE TEMP TABLE "task_tags_backup" AS SELECT * FROM "task_tags";
+CREATE TEMP TABLE "comments_backup" AS SELECT * FROM "comments";
+CREATE TEMP TABLE "comment_revisions_backup" AS SELECT * FROM "comment_revisions";
+
+CREATE TABLE "tasks_new" (
+  "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+  "project_id" INTEGER NOT NULL REFERENCES "projects" ("id") ON DELETE CASCADE,
+  "parent_id" INTEGER REFERENCES "tasks_new" ("id") ON DELETE CASCADE,
+  "title" VARCHAR(255) NOT NULL,
+  "description" TEXT,
+  "assignee_id" INTEGER REFERENCES "users" ("id") ON DELETE SET NULL,
+  "status" VARCHAR(255) NOT NULL DEFAULT 'todo',
+  "priority" VARCHAR(16) NOT NULL DEFAULT 'medium' CHECK ("priority" IN ('low', 'medium', 'high', 'urgent')),
+  "due_date" DATE,
+  "position" INTEGER NOT NULL,
+  "created_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "updated_at" TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+  "tenant_id" VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES "tenants" ("id")
+);
+INSERT INTO "tasks_new" ("id", "project_id", "parent_id", "title", "description", "assignee_id",
+    "status", "priority", "due_date", "position", "created_at", "updated_at", "tenant_id")
+SELECT "id", "project_id", "parent_id", "title", "description", "assignee_id",
+    "status", "priority", "due_date", "position", "created_at", "updated_at", "tenant_id"
+FROM "tasks";
+UPDATE "sqlite_sequence" SET "seq" = (SELECT "seq" FROM "sqlite_sequence" WHERE "name" = 'tasks')
+WHERE "name" = 'tasks_new' AND EXISTS (SELECT 1 FROM "sqlite_sequence" WHERE "name" = 'tasks');
+DROP TABLE "tasks";
+ALTER TABLE "tasks_new" RENAME TO "tasks";
+
+CREATE INDEX IF NOT EXISTS "tasks_project_id_idx" ON "tasks" ("project_id", "position");
+CREATE INDEX IF NOT EXISTS "tasks_parent_id_idx" ON "tasks" ("parent_id");
+CREATE INDEX IF NOT EXISTS "tasks_assignee_id_idx" ON "tasks" ("assignee_id");
+CREATE INDEX IF NOT EXISTS "tasks_tenant_id_idx" ON "tasks" ("tenant_id");
+
+INSERT INTO "task_dependencies" SELECT * FROM "task_dependencies_backup";
+INSERT INTO "task_tags" SELECT * FROM "task_tags_backup";
+INSERT OR IGNORE INTO "comments" SELECT * FROM "comments_backup" ORDER BY "id";
+INSERT OR IGNORE INTO "comment_revisions" SELECT * FROM "comment_revisions_backup";
+DROP TABLE "task_dependencies_backup";
+DROP TABLE "task_tags_backup";
+DROP TABLE "comments_backup";
+DROP TABLE "comment_revisions_backup";
+
+CREATE TRIGGER IF NOT EXISTS "tasks_search_insert" AFTER INSERT ON "tasks"
+BEGIN
+  INSERT INTO "search_index" ("entity_type", "entity_id", "project_id", "task_id", "tenant_id", "title", "body")
+    VALUES ('task', NEW."id", NEW."project_id", NEW."id", NEW."tenant_id", NEW."title", NEW."description");
+END;
+
+CREATE TRIGGER IF NOT EXISTS "tasks_search_update" AFTER UPDATE OF "title", "description" ON "tasks"
+BEGIN
+  UPDATE "search_index" SET "title" = NEW."title", "body" = NEW."description"
+    WHERE "entity_type" = 'task' AND "entity_id" = NEW."id";
+END;
+
+CREATE TRIGGER IF NOT EXISTS "tasks_search_delete" AFTER DELETE ON "tasks"
+BEGIN
+  DELETE FROM "search_index" WHERE "entity_type" = 'task' AND "entity_id" = OLD."id";
+END;
@@ -0,0 +1,44 @@
+use actix_web::{FromRequest, HttpMessage, HttpRequest, dev::Payload, web};
+use futures::future::LocalBoxFuture;
+
+use super::{
+    auth::{AuthSettings, Principal},
+    error::NinoverseApiError,
+    tenant::tenant_repositories,
+};
+use crate::{db_handler::repository::Repositories, permission_handler::Caller};
+
+/// Resolves the authenticated principal to a user, created on its first
+/// request, with its team memberships. Everything is allowed when
+/// authentication is disabled.
+pub(crate) async fn load_caller(
+    repositories: &Repositories,
+    settings: &AuthSettings,
+    principal: Option<Principal>,
+) -> Result<Caller, NinoverseApiError> {
+    if !settings.enabled {
+        return Ok(Caller::Unrestricted);
+    }
+    let principal = principal.ok_or_else(|| NinoverseApiError::Unauthorized {
+        additional_info: "This endpoint requires authentication.".to_string(),
+    })?;
+    let is_admin = settings.admin_subjects.contains(&principal.subject);
+    Ok(Caller::provision(repositories, &principal.subject, is_admin).await?)
+}
+
+impl FromRequest for Caller {
+    type Error = NinoverseApiError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let repositories = tenant_repositories(request);
+        let settings = request.app_data::<web::Data<AuthSettings>>().cloned();
+        let principal = request.extensions().get::<Principal>().cloned();
+        Box::pin(async move {
+            let Some(settings) = settings else {
+                return Ok(Caller::Unrestricted);
+            };
+            load_caller(&repositories, &settings, principal).await
+        })
+    }
+}
@@ -0,0 +1,147 @@
+use actix_web::{HttpResponse, delete, get, post, web};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+use utoipa::ToSchema;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    auth::{API_KEY_PREFIX, Principal, hash_api_key},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{ApiKey, NewApiKey},
+    },
+};
+
+/// Characters of the key kept in clear to recognize it in listings.
+const API_KEY_VISIBLE_LENGTH: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyRequest {
+    pub name: String,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// Returned once at creation, the key can't be read back afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+fn generate_api_key() -> String {
+    format!(
+        "{}{:032x}{:032x}",
+        API_KEY_PREFIX,
+        rand::random::<u128>(),
+        rand::random::<u128>()
+    )
+}
+
+#[utoipa::path(
+    tag = "api_keys",
+    responses(
+        (status = 200, description = "The API keys of the caller.", body = [ApiKey]),
+    )
+)]
+#[get("/api_keys")]
+async fn get_api_keys(
+    repositories: Repositories,
+    principal: Principal,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let api_keys = repositories.api_keys.list(&principal.subject).await?;
+    Ok(HttpResponse::Ok().json(api_keys))
+}
+
+#[utoipa::path(
+    tag = "api_keys",
+    responses(
+        (status = 201, description = "The issued key, its secret is only returned here.", body = IssuedApiKey),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/api_keys")]
+async fn create_api_key(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    principal: Principal,
+    request: web::Json<ApiKeyRequest>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    if request.name.trim().is_empty() {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The API key name can't be empty.".to_string(),
+        });
+    }
+    let key = generate_api_key();
+    let api_key = repositories
+        .api_keys
+        .insert(&NewApiKey {
+            name: request.name.clone(),
+            subject: principal.subject.clone(),
+            prefix: key[..API_KEY_VISIBLE_LENGTH].to_string(),
+            key_hash: hash_api_key(&key),
+            expires_at: request.expires_at,
+        })
+        .await?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "create",
+            entity_type: "api_key",
+            entity_id: api_key.id,
+            before: None,
+            after: Some(&api_key),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Created().json(IssuedApiKey { api_key, key }))
+}
+
+#[utoipa::path(
+    tag = "api_keys",
+    responses(
+        (status = 204, description = "The key was revoked."),
+        (status = 404, description = "The API key does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/api_keys/{id}")]
+async fn revoke_api_key(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    principal: Principal,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let id = path.into_inner();
+    let api_key = repositories
+        .api_keys
+        .revoke(id, &principal.subject)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("API key {} does not exist or is already revoked.", id),
+        })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "revoke",
+            entity_type: "api_key",
+            entity_id: api_key.id,
+            before: None,
+            after: Some(&api_key),
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,462 @@
+use std::sync::Arc;
+
+use actix_multipart::{Field, Multipart, MultipartError};
+use actix_web::{
+    HttpRequest, HttpResponse, delete, get,
+    http::header::{self, ContentDisposition, DispositionParam, DispositionType},
+    post, web,
+};
+use bytes::{Bytes, BytesMut};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc::Sender;
+use utoipa::{
+    PartialSchema, ToSchema,
+    openapi::{KnownFormat, ObjectBuilder, RefOr, Schema, SchemaFormat, Type, schema::SchemaType},
+};
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+    tasks::get_authorized_project,
+};
+use crate::{
+    KafkaChannelMessage,
+    blob_handler::{BlobStore, ByteRange, init_blob_store},
+    configuration_handler::{get_attachment_content_types, get_attachment_max_bytes},
+    db_handler::{
+        repository::Repositories,
+        structs::{Attachment, NewAttachment},
+    },
+    permission_handler::{Caller, Permission},
+};
+
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+/// Where the content of attachments goes and what is accepted.
+pub struct AttachmentSettings {
+    pub blob_store: Arc<dyn BlobStore>,
+    pub max_bytes: usize,
+    /// `type/subtype` or `type/*` patterns, lowercase.
+    pub content_types: Vec<String>,
+}
+
+impl AttachmentSettings {
+    pub fn from_configuration() -> Self {
+        AttachmentSettings {
+            blob_store: init_blob_store(),
+            max_bytes: get_attachment_max_bytes(),
+            content_types: get_attachment_content_types(),
+        }
+    }
+
+    fn check_content_type(&self, content_type: &str) -> Result<(), NinoverseApiError> {
+        let accepted = self
+            .content_types
+            .iter()
+            .any(|pattern| match pattern.strip_suffix("/*") {
+                Some("*") => true,
+                Some(kind) => content_type
+                    .split_once('/')
+                    .is_some_and(|(content_kind, _)| content_kind == kind),
+                None => pattern == content_type,
+            });
+        if !accepted {
+            return Err(NinoverseApiError::UnsupportedMediaType {
+                additional_info: format!(
+                    "Attachments of type {} are not accepted, only {}.",
+                    content_type,
+                    self.content_types.join(", ")
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The form of an upload, only described for the OpenAPI document: the parts
+/// are read by `upload_attachment`.
+struct AttachmentUpload;
+
+impl PartialSchema for AttachmentUpload {
+    fn schema() -> RefOr<Schema> {
+        ObjectBuilder::new()
+            .description(Some(
+                "The form of an upload, the `file` part is held in memory before being stored.",
+            ))
+            .property(
+                "file",
+                ObjectBuilder::new()
+                    .schema_type(Type::String)
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::Binary))),
+            )
+            .required("file")
+            .property(
+                "sha256",
+                ObjectBuilder::new()
+                    .schema_type(SchemaType::from_iter([Type::String, Type::Null]))
+                    .description(Some("Hex encoded SHA-256 the content is checked against.")),
+            )
+            .into()
+    }
+}
+
+impl ToSchema for AttachmentUpload {}
+
+fn multipart_error(error: MultipartError) -> NinoverseApiError {
+    NinoverseApiError::ValidationError {
+        additional_info: format!("Invalid multipart body: {}", error),
+    }
+}
+
+/// The base name of the uploaded file, clients may send a whole path.
+fn file_name(field: &Field) -> String {
+    field
+        .content_disposition()
+        .and_then(ContentDisposition::get_filename)
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("attachment")
+        .chars()
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect()
+}
+
+async fn read_field(mut field: Field, max_bytes: usize) -> Result<Bytes, NinoverseApiError> {
+    let mut content = BytesMut::new();
+    while let Some(chunk) = field.try_next().await.map_err(multipart_error)? {
+        if content.len() + chunk.len() > max_bytes {
+            return Err(NinoverseApiError::PayloadTooLarge {
+                additional_info: format!(
+                    "The {} field is limited to {} bytes.",
+                    field.name().unwrap_or("unnamed"),
+                    max_bytes
+                ),
+            });
+        }
+        content.extend_from_slice(&chunk);
+    }
+    Ok(content.freeze())
+}
+
+/// The single byte range asked for by a `Range` header, `None` for the whole
+/// content. As RFC 9110 allows, other units, malformed values and multiple
+/// ranges are ignored.
+fn requested_range(value: &str, size: u64) -> Result<Option<ByteRange>, NinoverseApiError> {
+    let Some((start, end)) = value
+        .trim()
+        .strip_prefix("bytes=")
+        .filter(|ranges| !ranges.contains(','))
+        .and_then(|range| range.trim().split_once('-'))
+    else {
+        return Ok(None);
+    };
+    let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+        // The last `suffix` bytes.
+        (Err(_), Ok(suffix)) if start.is_empty() => (suffix > 0 && size > 0).then(|| ByteRange {
+            start: size.saturating_sub(suffix),
+            end: size - 1,
+        }),
+        (Ok(start), Err(_)) if end.is_empty() => (start < size).then(|| ByteRange {
+            start,
+            end: size - 1,
+        }),
+        (Ok(start), Ok(end)) if start <= end => (start < size).then(|| ByteRange {
+            start,
+            end: end.min(size - 1),
+        }),
+        _ => return Ok(None),
+    };
+    range
+        .map(Some)
+        .ok_or_else(|| NinoverseApiError::RangeNotSatisfiable {
+            additional_info: format!("The range {} is outside of the {} bytes.", value, size),
+            size,
+        })
+}
+
+async fn get_existing_attachment(
+    repositories: &Repositories,
+    project_id: i32,
+    id: i32,
+) -> Result<Attachment, NinoverseApiError> {
+    repositories
+        .attachments
+        .get(project_id, id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!(
+                "Attachment {} does not exist in project {}.",
+                id, project_id
+            ),
+        })
+}
+
+#[utoipa::path(
+    tag = "attachments",
+    responses(
+        (status = 200, description = "The attachments of the project.", body = [Attachment]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/attachments")]
+async fn get_attachments(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Read).await?;
+    let attachments = repositories.attachments.list(project.id).await?;
+    Ok(HttpResponse::Ok().json(attachments))
+}
+
+/// A `multipart/form-data` body with the content in the `file` field and
+/// optionally its hex encoded SHA-256 in the `sha256` field, checked before
+/// anything is stored. The whole content is held in memory meanwhile, which
+/// is what `ATTACHMENT_MAX_BYTES` bounds.
+#[utoipa::path(
+    tag = "attachments",
+    request_body(content = AttachmentUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "The stored attachment.", body = Attachment),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 413, description = "The file is larger than allowed.", body = ErrorBody),
+        (status = 415, description = "The content type is not accepted.", body = ErrorBody),
+        (status = 422, description = "The file is missing or does not match its checksum.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/attachments")]
+async fn upload_attachment(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    settings: web::Data<AttachmentSettings>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    mut multipart: Multipart,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Write).await?;
+    let mut upload = None;
+    let mut expected_sha256 = None;
+    while let Some(field) = multipart.try_next().await.map_err(multipart_error)? {
+        match field.name() {
+            Some("file") => {
+                let content_type = field
+                    .content_type()
+                    .map(|content_type| content_type.essence_str().to_lowercase())
+                    .unwrap_or_else(|| "application/octet-stream".to_string());
+                settings.check_content_type(&content_type)?;
+                let file_name = file_name(&field);
+                let content = read_field(field, settings.max_bytes).await?;
+                upload = Some((file_name, content_type, content));
+            }
+            Some("sha256") => {
+                let checksum = read_field(field, 128).await?;
+                expected_sha256 = Some(String::from_utf8_lossy(&checksum).trim().to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    let Some((file_name, content_type, content)) = upload else {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The file field is missing.".to_string(),
+        });
+    };
+    let sha256 = hex::encode(Sha256::digest(&content));
+    if let Some(expected_sha256) = expected_sha256.filter(|expected| *expected != sha256) {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "The content has the SHA-256 {}, not {}.",
+                sha256, expected_sha256
+            ),
+        });
+    }
+    let new_attachment = NewAttachment {
+        file_name,
+        content_type,
+        size: content.len() as i64,
+        sha256,
+        storage_key: format!(
+            "{}/{}/{:032x}",
+            repositories.tenant_id,
+            project.id,
+            rand::random::<u128>()
+        ),
+        uploaded_by: context.actor.clone(),
+    };
+    settings
+        .blob_store
+        .put(&new_attachment.storage_key, content)
+        .await?;
+    let attachment = match repositories
+        .attachments
+        .insert(project.id, &new_attachment)
+        .await
+    {
+        Ok(attachment) => attachment,
+        Err(insert_error) => {
+            if let Err(delete_error) = settings
+                .blob_store
+                .delete(&new_attachment.storage_key)
+                .await
+            {
+                println!(
+                    "ATTACHMENTS: Error deleting the orphaned blob {}: {:?}",
+                    new_attachment.storage_key, delete_error
+                );
+            }
+            return Err(insert_error.into());
+        }
+    };
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "create",
+            entity_type: "attachment",
+            entity_id: attachment.id,
+            before: None,
+            after: Some(&attachment),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Created().json(attachment))
+}
+
+#[utoipa::path(
+    tag = "attachments",
+    responses(
+        (status = 200, description = "The attachment.", body = Attachment),
+        (status = 404, description = "The attachment does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/attachments/{attachment_id}")]
+async fn get_attachment(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, attachment_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let attachment = get_existing_attachment(&repositories, project.id, attachment_id).await?;
+    Ok(HttpResponse::Ok().json(attachment))
+}
+
+/// Streams the content, or the single byte range of a `Range` header. The
+/// `ETag` is the SHA-256 of the whole content.
+#[utoipa::path(
+    tag = "attachments",
+    params(("Range" = Option<String>, Header, description = "A single `bytes=` range.")),
+    responses(
+        (status = 200, description = "The content."),
+        (status = 206, description = "The requested range of the content."),
+        (status = 404, description = "The attachment does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 416, description = "The range is outside of the content.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/attachments/{attachment_id}/content")]
+async fn download_attachment(
+    repositories: Repositories,
+    settings: web::Data<AttachmentSettings>,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+    request: HttpRequest,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, attachment_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let attachment = get_existing_attachment(&repositories, project.id, attachment_id).await?;
+    let size = attachment.size as u64;
+    let range = match request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(value) => requested_range(value, size)?,
+        None => None,
+    };
+    let content = settings
+        .blob_store
+        .get(&attachment.storage_key, range)
+        .await?;
+    let mut response = match range {
+        Some(range) => {
+            let mut response = HttpResponse::PartialContent();
+            response.insert_header((
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", range.start, range.end, size),
+            ));
+            response
+        }
+        None => HttpResponse::Ok(),
+    };
+    response
+        .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header((header::CONTENT_TYPE, attachment.content_type.clone()))
+        .insert_header((header::ETAG, format!("\"{}\"", attachment.sha256)))
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(attachment.file_name.clone())],
+        })
+        .no_chunking(range.map_or(size, |range| range.len()));
+    Ok(response.streaming(content))
+}
+
+#[utoipa::path(
+    tag = "attachments",
+    responses(
+        (status = 204, description = "The attachment was deleted."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The attachment does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/projects/{id}/attachments/{attachment_id}")]
+async fn delete_attachment(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    settings: web::Data<AttachmentSettings>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, attachment_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    let current = get_existing_attachment(&repositories, project.id, attachment_id).await?;
+    let attachment = repositories
+        .attachments
+        .delete(project.id, current.id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!(
+                "Attachment {} does not exist in project {}.",
+                current.id, project.id
+            ),
+        })?;
+    // The row goes first so nothing lists a missing content, a content left
+    // behind is an orphan the purge job removes.
+    if let Err(blob_error) = settings.blob_store.delete(&attachment.storage_key).await {
+        println!(
+            "ATTACHMENTS: Content of attachment {} left to the purge job: {}",
+            attachment.id, blob_error
+        );
+    }
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "attachment",
+            entity_id: attachment.id,
+            before: Some(&attachment),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
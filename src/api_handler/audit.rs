@@ -0,0 +1,80 @@
+use actix_web::{HttpResponse, get, web};
+use serde::Serialize;
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{AuditFilter, AuditRecord, NewAuditRecord},
+    },
+    kafka_handler::publish_audit_record,
+    permission_handler::Caller,
+};
+
+pub(super) struct AuditedChange<'a, T> {
+    pub action: &'a str,
+    pub entity_type: &'a str,
+    pub entity_id: i32,
+    pub before: Option<&'a T>,
+    pub after: Option<&'a T>,
+}
+
+/// Stores an audit record for a mutation and mirrors it to Kafka. The
+/// mutation is already committed at this point, so failures are only logged.
+pub(super) async fn record_mutation<T>(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    change: AuditedChange<'_, T>,
+) where
+    T: Serialize,
+{
+    let record = NewAuditRecord {
+        actor: context.actor.clone(),
+        action: change.action.to_string(),
+        entity_type: change.entity_type.to_string(),
+        entity_id: change.entity_id,
+        before: change
+            .before
+            .and_then(|value| serde_json::to_value(value).ok()),
+        after: change
+            .after
+            .and_then(|value| serde_json::to_value(value).ok()),
+        request_id: context.request_id.clone(),
+    };
+    match repositories.audit.insert(&record).await {
+        Ok(record) => {
+            publish_audit_record(kafka_thread_sender, &repositories.tenant_id, &record).await
+        }
+        Err(audit_error) => println!("AUDIT: Error storing audit record: {:?}", audit_error),
+    }
+}
+
+#[utoipa::path(
+    tag = "audit",
+    params(AuditFilter),
+    responses(
+        (status = 200, description = "The matching audit records, latest first.", body = [AuditRecord]),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+    )
+)]
+#[get("/audit")]
+async fn get_audit_records(
+    repositories: Repositories,
+    caller: Caller,
+    filter: web::Query<AuditFilter>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    // Records hold snapshots of projects the caller may not be able to read.
+    if !caller.is_admin() {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: "Only administrators can read the audit log.".to_string(),
+        });
+    }
+    let records = repositories.audit.list(&filter).await?;
+    Ok(HttpResponse::Ok().json(records))
+}
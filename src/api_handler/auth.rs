@@ -0,0 +1,238 @@
+use std::future::{Ready, ready};
+
+use actix_web::{
+    Error, FromRequest, HttpMessage, HttpRequest,
+    body::{EitherBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    web,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{error::NinoverseApiError, tenant::requested_tenant};
+use crate::{
+    configuration_handler::{
+        get_admin_subjects, get_auth_enabled, get_jwt_algorithm, get_jwt_audience, get_jwt_issuer,
+        get_jwt_public_key_path, get_jwt_secret,
+    },
+    db_handler::repository::{DEFAULT_TENANT, Repositories},
+};
+
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_PREFIX: &str = "nvk_";
+/// Paths reachable without credentials, used as liveness probe.
+pub(super) const PUBLIC_PATHS: [&str; 5] = [
+    "/hey",
+    "/openapi.json",
+    "/docs",
+    "/docs/redoc.standalone.js",
+    "/graphql/schema",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum AuthMethod {
+    ApiKey { key_id: i32 },
+    Jwt,
+}
+
+/// The authenticated caller, attached to the request by `auth_middleware`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Principal {
+    pub subject: String,
+    #[serde(flatten)]
+    pub method: AuthMethod,
+    /// The only tenant the credentials are valid for, if bound to one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtClaims {
+    pub sub: String,
+    pub exp: usize,
+    /// Binds the token to a tenant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct JwtSettings {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtSettings {
+    pub fn hs256(secret: &[u8]) -> Self {
+        JwtSettings {
+            decoding_key: DecodingKey::from_secret(secret),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    pub fn rs256(public_key_pem: &[u8]) -> Result<Self, jsonwebtoken::errors::Error> {
+        Ok(JwtSettings {
+            decoding_key: DecodingKey::from_rsa_pem(public_key_pem)?,
+            validation: Validation::new(Algorithm::RS256),
+        })
+    }
+
+    /// Only tokens issued by `issuer` and for `audience` are accepted when
+    /// set, the audience is not checked otherwise.
+    pub fn restricted_to(mut self, issuer: Option<String>, audience: Option<String>) -> Self {
+        if let Some(issuer) = issuer {
+            self.validation.set_issuer(&[issuer]);
+        }
+        match audience {
+            Some(audience) => self.validation.set_audience(&[audience]),
+            None => self.validation.validate_aud = false,
+        }
+        self
+    }
+
+    fn verify(&self, token: &str) -> Result<JwtClaims, jsonwebtoken::errors::Error> {
+        Ok(decode::<JwtClaims>(token, &self.decoding_key, &self.validation)?.claims)
+    }
+}
+
+#[derive(Clone)]
+pub struct AuthSettings {
+    pub enabled: bool,
+    /// `None` when no JWT key is configured, only API keys are accepted then.
+    pub jwt: Option<JwtSettings>,
+    /// Subjects holding every permission on every project and team.
+    pub admin_subjects: Vec<String>,
+}
+
+impl AuthSettings {
+    pub fn from_configuration() -> Self {
+        let jwt = match get_jwt_algorithm().as_str() {
+            "HS256" => get_jwt_secret().map(|secret| JwtSettings::hs256(secret.as_bytes())),
+            "RS256" => get_jwt_public_key_path().map(|path| {
+                let public_key = std::fs::read(&path)
+                    .unwrap_or_else(|_| panic!("AUTH: Can't read the JWT public key {}", path));
+                JwtSettings::rs256(&public_key).expect("AUTH: Invalid JWT public key")
+            }),
+            algorithm => panic!("AUTH: Unsupported JWT_ALGORITHM {}", algorithm),
+        };
+        AuthSettings {
+            enabled: get_auth_enabled(),
+            jwt: jwt.map(|jwt| jwt.restricted_to(get_jwt_issuer(), get_jwt_audience())),
+            admin_subjects: get_admin_subjects(),
+        }
+    }
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn unauthorized(additional_info: &str) -> NinoverseApiError {
+    NinoverseApiError::Unauthorized {
+        additional_info: additional_info.to_string(),
+    }
+}
+
+/// Checks the credentials of an HTTP request or a gRPC call: an API key,
+/// looked up in `requested_tenant`, or else an `Authorization` bearer token.
+pub(crate) async fn authenticate_credentials(
+    settings: &AuthSettings,
+    repositories: &Repositories,
+    api_key: Option<&str>,
+    authorization: Option<&str>,
+    requested_tenant: Option<String>,
+) -> Result<Principal, NinoverseApiError> {
+    if let Some(key) = api_key {
+        // API keys belong to the tenant they were issued in.
+        let tenant_id = requested_tenant.unwrap_or_else(|| DEFAULT_TENANT.to_string());
+        let api_key = repositories
+            .for_tenant(&tenant_id)
+            .api_keys
+            .find_active(&hash_api_key(key))
+            .await?
+            .ok_or_else(|| unauthorized("Unknown, revoked or expired API key."))?;
+        return Ok(Principal {
+            subject: api_key.subject,
+            method: AuthMethod::ApiKey { key_id: api_key.id },
+            tenant: Some(tenant_id),
+        });
+    }
+    let token = authorization
+        .and_then(|authorization| authorization.strip_prefix("Bearer "))
+        .ok_or_else(|| unauthorized("An API key or a bearer token is required."))?;
+    let jwt = settings
+        .jwt
+        .as_ref()
+        .ok_or_else(|| unauthorized("Bearer tokens are not accepted, no JWT key configured."))?;
+    let claims = jwt
+        .verify(token.trim())
+        .map_err(|jwt_error| unauthorized(&format!("Invalid bearer token: {}.", jwt_error)))?;
+    Ok(Principal {
+        subject: claims.sub,
+        method: AuthMethod::Jwt,
+        tenant: claims.tenant,
+    })
+}
+
+async fn authenticate(request: &ServiceRequest) -> Result<Principal, NinoverseApiError> {
+    let settings = request
+        .app_data::<web::Data<AuthSettings>>()
+        .expect("AUTH: Settings missing from the app data");
+    let repositories = request
+        .app_data::<web::Data<Repositories>>()
+        .expect("AUTH: Repositories missing from the app data");
+    let header_value = |name| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    authenticate_credentials(
+        settings,
+        repositories,
+        header_value(header::HeaderName::from_static(API_KEY_HEADER)),
+        header_value(header::AUTHORIZATION),
+        requested_tenant(request),
+    )
+    .await
+}
+
+/// Authenticates the request with the `X-Api-Key` header or an
+/// `Authorization: Bearer` JWT and stores the `Principal` in the request
+/// extensions. Requests without valid credentials are answered with 401.
+pub async fn auth_middleware(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let enabled = request
+        .app_data::<web::Data<AuthSettings>>()
+        .is_some_and(|settings| settings.enabled);
+    if !enabled || PUBLIC_PATHS.contains(&request.path()) {
+        return Ok(next.call(request).await?.map_into_left_body());
+    }
+    match authenticate(&request).await {
+        Ok(principal) => {
+            request.extensions_mut().insert(principal);
+            Ok(next.call(request).await?.map_into_left_body())
+        }
+        Err(auth_error) => Ok(request.error_response(auth_error).map_into_right_body()),
+    }
+}
+
+impl FromRequest for Principal {
+    type Error = NinoverseApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            request
+                .extensions()
+                .get::<Principal>()
+                .cloned()
+                .ok_or_else(|| unauthorized("This endpoint requires authentication.")),
+        )
+    }
+}
@@ -0,0 +1,476 @@
+use std::collections::BTreeSet;
+
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+    tasks::{get_authorized_project, get_existing_task},
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{
+            Activity, ActivityQuery, AuditFilter, Comment, CommentRevision, CommentUpdate,
+            NewComment, Project,
+        },
+    },
+    kafka_handler::{publish_event, structs::KafkaNinoverseEvent},
+    permission_handler::{Caller, Permission},
+};
+
+const DEFAULT_ACTIVITY_LIMIT: usize = 100;
+const MAX_ACTIVITY_LIMIT: usize = 1000;
+
+/// The subjects mentioned as `@subject`. An `@` inside a word, as in an
+/// email address, is not a mention.
+fn mentioned_subjects(body: &str) -> BTreeSet<&str> {
+    body.match_indices('@')
+        .filter(|(start, _)| {
+            !body[..*start]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric)
+        })
+        .map(|(start, _)| {
+            let rest = &body[start + 1..];
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || "_.-".contains(c)))
+                .unwrap_or(rest.len());
+            // A sentence may end right after the mention.
+            rest[..end].trim_end_matches('.')
+        })
+        .filter(|subject| !subject.is_empty())
+        .collect()
+}
+
+/// Notifies the known users mentioned by the comment, except those already
+/// mentioned before an edit and the author.
+async fn notify_mentions(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    comment: &Comment,
+    previous_body: Option<&str>,
+    actor: &str,
+) -> Result<(), NinoverseApiError> {
+    let already_mentioned = previous_body.map(mentioned_subjects).unwrap_or_default();
+    for subject in mentioned_subjects(&comment.body).difference(&already_mentioned) {
+        if *subject == actor || repositories.users.find_by_subject(subject).await?.is_none() {
+            continue;
+        }
+        publish_event(
+            kafka_thread_sender,
+            &repositories.tenant_id,
+            KafkaNinoverseEvent::UserMentioned {
+                comment: comment.clone(),
+                subject: subject.to_string(),
+                actor: actor.to_string(),
+            },
+        )
+        .await;
+    }
+    Ok(())
+}
+
+fn check_body(body: &str) -> Result<(), NinoverseApiError> {
+    if body.trim().is_empty() {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The comment body can't be empty.".to_string(),
+        });
+    }
+    Ok(())
+}
+
+async fn get_existing_comment(
+    repositories: &Repositories,
+    project_id: i32,
+    id: i32,
+) -> Result<Comment, NinoverseApiError> {
+    repositories
+        .comments
+        .get(project_id, id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Comment {} does not exist in project {}.", id, project_id),
+        })
+}
+
+/// Replies stay on the item of the comment they answer.
+async fn check_parent(
+    repositories: &Repositories,
+    project_id: i32,
+    task_id: Option<i32>,
+    parent_id: Option<i32>,
+) -> Result<(), NinoverseApiError> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    let parent = repositories.comments.get(project_id, parent_id).await?;
+    if parent.is_none_or(|parent| parent.task_id != task_id) {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("Comment {} is not a comment on the same item.", parent_id),
+        });
+    }
+    Ok(())
+}
+
+async fn create_comment(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    project: &Project,
+    task_id: Option<i32>,
+    new_comment: &NewComment,
+) -> Result<Comment, NinoverseApiError> {
+    check_body(&new_comment.body)?;
+    check_parent(repositories, project.id, task_id, new_comment.parent_id).await?;
+    let comment = repositories
+        .comments
+        .insert(project.id, task_id, &context.actor, new_comment)
+        .await?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "create",
+            entity_type: "comment",
+            entity_id: comment.id,
+            before: None,
+            after: Some(&comment),
+        },
+    )
+    .await;
+    notify_mentions(
+        repositories,
+        kafka_thread_sender,
+        &comment,
+        None,
+        &context.actor,
+    )
+    .await?;
+    Ok(comment)
+}
+
+/// The comments on the project itself, the comments on its tasks are listed
+/// per task.
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 200, description = "The comments on the project, oldest first.", body = [Comment]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/comments")]
+async fn get_project_comments(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Read).await?;
+    let comments: Vec<Comment> = repositories
+        .comments
+        .list(project.id)
+        .await?
+        .into_iter()
+        .filter(|comment| comment.task_id.is_none())
+        .collect();
+    Ok(HttpResponse::Ok().json(comments))
+}
+
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 201, description = "The created comment.", body = Comment),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project or parent comment does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/comments")]
+async fn create_project_comment(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    new_comment: web::Json<NewComment>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Write).await?;
+    let comment = create_comment(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &project,
+        None,
+        &new_comment,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(comment))
+}
+
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 200, description = "The comments on the task, oldest first.", body = [Comment]),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}/comments")]
+async fn get_task_comments(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    let comments: Vec<Comment> = repositories
+        .comments
+        .list(project.id)
+        .await?
+        .into_iter()
+        .filter(|comment| comment.task_id == Some(task.id))
+        .collect();
+    Ok(HttpResponse::Ok().json(comments))
+}
+
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 201, description = "The created comment.", body = Comment),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The task or parent comment does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/tasks/{task_id}/comments")]
+async fn create_task_comment(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+    new_comment: web::Json<NewComment>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    let comment = create_comment(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &project,
+        Some(task.id),
+        &new_comment,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(comment))
+}
+
+/// Only the author can edit a comment, the previous body is kept as a
+/// revision.
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 200, description = "The edited comment.", body = Comment),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The comment does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[put("/projects/{id}/comments/{comment_id}")]
+async fn update_comment(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+    update: web::Json<CommentUpdate>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, comment_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    let current = get_existing_comment(&repositories, project.id, comment_id).await?;
+    if current.author != context.actor {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: format!("Only {} can edit comment {}.", current.author, current.id),
+        });
+    }
+    check_body(&update.body)?;
+    let comment = repositories
+        .comments
+        .update(project.id, current.id, &update.body, &context.actor)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!(
+                "Comment {} does not exist in project {}.",
+                current.id, project.id
+            ),
+        })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "update",
+            entity_type: "comment",
+            entity_id: comment.id,
+            before: Some(&current),
+            after: Some(&comment),
+        },
+    )
+    .await;
+    notify_mentions(
+        &repositories,
+        &kafka_thread_sender,
+        &comment,
+        Some(&current.body),
+        &context.actor,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(comment))
+}
+
+/// Deletes the comment and its replies. Besides the author, whoever can
+/// delete the project can moderate its comments.
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 204, description = "The comment was deleted."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The comment does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/projects/{id}/comments/{comment_id}")]
+async fn delete_comment(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, comment_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    let current = get_existing_comment(&repositories, project.id, comment_id).await?;
+    if current.author != context.actor {
+        caller.authorize(&project, Permission::Delete)?;
+    }
+    let comment = repositories
+        .comments
+        .delete(project.id, current.id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!(
+                "Comment {} does not exist in project {}.",
+                current.id, project.id
+            ),
+        })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "comment",
+            entity_id: comment.id,
+            before: Some(&comment),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "comments",
+    responses(
+        (status = 200, description = "The earlier bodies of the comment.", body = [CommentRevision]),
+        (status = 404, description = "The comment does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/comments/{comment_id}/revisions")]
+async fn get_comment_revisions(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, comment_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let comment = get_existing_comment(&repositories, project.id, comment_id).await?;
+    let revisions = repositories.comments.list_revisions(comment.id).await?;
+    Ok(HttpResponse::Ok().json(revisions))
+}
+
+/// The comments, status transitions and field edits of the project and its
+/// tasks, oldest first. Only the `limit` most recent entries are returned.
+#[utoipa::path(
+    tag = "comments",
+    params(ActivityQuery),
+    responses(
+        (status = 200, description = "The most recent activity of the project, oldest first.", body = [Activity]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/activity")]
+async fn get_project_activity(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+    query: web::Query<ActivityQuery>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Read).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ACTIVITY_LIMIT)
+        .clamp(1, MAX_ACTIVITY_LIMIT);
+    let mut activity: Vec<Activity> = repositories
+        .comments
+        .list(project.id)
+        .await?
+        .into_iter()
+        .map(Activity::Comment)
+        .collect();
+    activity.extend(
+        repositories
+            .projects
+            .list_status_history(project.id)
+            .await?
+            .into_iter()
+            .map(Activity::StatusTransition),
+    );
+    let mut edited = vec![("project", project.id)];
+    edited.extend(
+        repositories
+            .tasks
+            .list(project.id)
+            .await?
+            .iter()
+            .map(|task| ("task", task.id)),
+    );
+    for (entity_type, entity_id) in edited {
+        let filter = AuditFilter {
+            action: Some("update".to_string()),
+            entity_type: Some(entity_type.to_string()),
+            entity_id: Some(entity_id),
+            limit: Some(limit as i64),
+            ..Default::default()
+        };
+        activity.extend(
+            repositories
+                .audit
+                .list(&filter)
+                .await?
+                .into_iter()
+                .map(Activity::FieldEdit),
+        );
+    }
+    activity.sort_by_key(Activity::created_at);
+    let skipped = activity.len().saturating_sub(limit);
+    Ok(HttpResponse::Ok().json(&activity[skipped..]))
+}
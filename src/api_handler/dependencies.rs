@@ -0,0 +1,511 @@
+use std::collections::BTreeMap;
+
+use actix_web::{HttpResponse, delete, get, post, web};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+    status_types::completed_statuses,
+    tasks::{get_authorized_project, get_existing_task},
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{Dependency, DependencyInsertion, ItemKind, NewDependency, Project, Task},
+    },
+    dependency_handler::DependencyGraph,
+    permission_handler::{Caller, Permission},
+};
+
+/// The part of the graph around an item that is returned.
+#[derive(Clone, Copy)]
+enum Relation {
+    Upstream,
+    Downstream,
+    CriticalPath,
+}
+
+impl Relation {
+    fn ids<F>(&self, graph: &DependencyGraph, id: i32, is_pending: F) -> Vec<i32>
+    where
+        F: Fn(i32) -> bool,
+    {
+        match self {
+            Relation::Upstream => graph.upstream(id).into_iter().collect(),
+            Relation::Downstream => graph.downstream(id).into_iter().collect(),
+            Relation::CriticalPath => graph.critical_path(id, is_pending),
+        }
+    }
+}
+
+async fn list_dependencies(
+    repositories: &Repositories,
+    kind: ItemKind,
+    blocked_id: i32,
+) -> Result<Vec<Dependency>, NinoverseApiError> {
+    Ok(repositories
+        .dependencies
+        .list(kind)
+        .await?
+        .into_iter()
+        .filter(|dependency| dependency.blocked_id == blocked_id)
+        .collect())
+}
+
+/// Both items are already checked, what is left is the graph itself.
+async fn add_dependency(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    kind: ItemKind,
+    blocked_id: i32,
+    blocking_id: i32,
+) -> Result<HttpResponse, NinoverseApiError> {
+    if blocked_id == blocking_id {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "The {} {} can't depend on itself.",
+                kind.as_str(),
+                blocked_id
+            ),
+        });
+    }
+    let dependency = match repositories
+        .dependencies
+        .insert(kind, blocked_id, blocking_id)
+        .await?
+    {
+        DependencyInsertion::Inserted(dependency) => dependency,
+        DependencyInsertion::AlreadyExists => {
+            return Err(NinoverseApiError::ValidationError {
+                additional_info: format!(
+                    "The {} {} already depends on {}.",
+                    kind.as_str(),
+                    blocked_id,
+                    blocking_id
+                ),
+            });
+        }
+        DependencyInsertion::Cycle(path) => {
+            let path: Vec<String> = path.iter().map(i32::to_string).collect();
+            return Err(NinoverseApiError::ValidationError {
+                additional_info: format!(
+                    "The dependency would close the {} cycle {}.",
+                    kind.as_str(),
+                    path.join(" -> ")
+                ),
+            });
+        }
+    };
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "add_dependency",
+            entity_type: kind.as_str(),
+            entity_id: blocked_id,
+            before: None,
+            after: Some(&dependency),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Created().json(dependency))
+}
+
+async fn remove_dependency(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    kind: ItemKind,
+    blocked_id: i32,
+    blocking_id: i32,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let dependency = repositories
+        .dependencies
+        .delete(kind, blocked_id, blocking_id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!(
+                "The {} {} does not depend on {}.",
+                kind.as_str(),
+                blocked_id,
+                blocking_id
+            ),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "remove_dependency",
+            entity_type: kind.as_str(),
+            entity_id: blocked_id,
+            before: Some(&dependency),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Projects the caller can't read are left out, deleted projects are gone
+/// from the graph as far as the caller is concerned.
+async fn related_projects(
+    repositories: &Repositories,
+    caller: &Caller,
+    id: i32,
+    relation: Relation,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project = get_authorized_project(repositories, caller, id, Permission::Read).await?;
+    let graph = DependencyGraph::new(&repositories.dependencies.list(ItemKind::Project).await?);
+    let completed_statuses = completed_statuses(repositories).await?;
+    let mut projects: BTreeMap<i32, Project> = repositories
+        .projects
+        .list(false)
+        .await?
+        .into_iter()
+        .map(|project| (project.id, project))
+        .collect();
+    projects.insert(project.id, project.clone());
+    let related: Vec<Project> = relation
+        .ids(&graph, project.id, |id| {
+            projects
+                .get(&id)
+                .is_some_and(|project| !completed_statuses.contains(&project.status))
+        })
+        .iter()
+        .filter_map(|id| projects.get(id))
+        .filter(|project| caller.project_role(project).is_some())
+        .cloned()
+        .collect();
+    Ok(HttpResponse::Ok().json(related))
+}
+
+async fn related_tasks(
+    repositories: &Repositories,
+    caller: &Caller,
+    id: i32,
+    task_id: i32,
+    relation: Relation,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project = get_authorized_project(repositories, caller, id, Permission::Read).await?;
+    let task = get_existing_task(repositories, project.id, task_id).await?;
+    let graph = DependencyGraph::new(&repositories.dependencies.list(ItemKind::Task).await?);
+    let completed_statuses = completed_statuses(repositories).await?;
+    let tasks: BTreeMap<i32, Task> = repositories
+        .tasks
+        .list(project.id)
+        .await?
+        .into_iter()
+        .map(|task| (task.id, task))
+        .collect();
+    let related: Vec<Task> = relation
+        .ids(&graph, task.id, |id| {
+            tasks
+                .get(&id)
+                .is_some_and(|task| !completed_statuses.contains(&task.status))
+        })
+        .iter()
+        .filter_map(|id| tasks.get(id))
+        .cloned()
+        .collect();
+    Ok(HttpResponse::Ok().json(related))
+}
+
+/// The projects this project waits for.
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "The dependencies of the project.", body = [Dependency]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/dependencies")]
+async fn get_project_dependencies(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Read).await?;
+    let dependencies = list_dependencies(&repositories, ItemKind::Project, project.id).await?;
+    Ok(HttpResponse::Ok().json(dependencies))
+}
+
+/// Changes the blocked project, so it needs write access there, and only
+/// read access on the blocking one.
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 201, description = "The created dependency.", body = Dependency),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "One of the projects does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The dependency would close a cycle.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/dependencies")]
+async fn create_project_dependency(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    new_dependency: web::Json<NewDependency>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Write).await?;
+    let blocking = repositories
+        .projects
+        .get(new_dependency.blocking_id, false)
+        .await?
+        .ok_or_else(|| NinoverseApiError::ValidationError {
+            additional_info: format!("Project {} does not exist.", new_dependency.blocking_id),
+        })?;
+    caller.authorize(&blocking, Permission::Read)?;
+    add_dependency(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        ItemKind::Project,
+        project.id,
+        blocking.id,
+    )
+    .await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 204, description = "The dependency was removed."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The dependency does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/projects/{id}/dependencies/{blocking_id}")]
+async fn delete_project_dependency(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, blocking_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    remove_dependency(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        ItemKind::Project,
+        project.id,
+        blocking_id,
+    )
+    .await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "Every project the project waits for, directly or not.", body = [Project]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/dependencies/upstream")]
+async fn get_project_upstream(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    related_projects(&repositories, &caller, id.into_inner(), Relation::Upstream).await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "Every project waiting for the project, directly or not.", body = [Project]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/dependencies/downstream")]
+async fn get_project_downstream(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    related_projects(
+        &repositories,
+        &caller,
+        id.into_inner(),
+        Relation::Downstream,
+    )
+    .await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "The longest chain of pending projects the project waits for.", body = [Project]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/dependencies/critical_path")]
+async fn get_project_critical_path(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    related_projects(
+        &repositories,
+        &caller,
+        id.into_inner(),
+        Relation::CriticalPath,
+    )
+    .await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "The dependencies of the task.", body = [Dependency]),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}/dependencies")]
+async fn get_task_dependencies(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    let dependencies = list_dependencies(&repositories, ItemKind::Task, task.id).await?;
+    Ok(HttpResponse::Ok().json(dependencies))
+}
+
+/// Tasks can only depend on tasks of the same project.
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 201, description = "The created dependency.", body = Dependency),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "One of the tasks does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The dependency would close a cycle.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/tasks/{task_id}/dependencies")]
+async fn create_task_dependency(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+    new_dependency: web::Json<NewDependency>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    let blocking = repositories
+        .tasks
+        .get(project.id, new_dependency.blocking_id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "Task {} does not exist in project {}.",
+                new_dependency.blocking_id, project.id
+            ),
+        })?;
+    add_dependency(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        ItemKind::Task,
+        task.id,
+        blocking.id,
+    )
+    .await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 204, description = "The dependency was removed."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The dependency does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/projects/{id}/tasks/{task_id}/dependencies/{blocking_id}")]
+async fn delete_task_dependency(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id, blocking_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Write).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    remove_dependency(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        ItemKind::Task,
+        task.id,
+        blocking_id,
+    )
+    .await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "Every task the task waits for, directly or not.", body = [Task]),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}/dependencies/upstream")]
+async fn get_task_upstream(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    related_tasks(&repositories, &caller, id, task_id, Relation::Upstream).await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "Every task waiting for the task, directly or not.", body = [Task]),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}/dependencies/downstream")]
+async fn get_task_downstream(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    related_tasks(&repositories, &caller, id, task_id, Relation::Downstream).await
+}
+
+#[utoipa::path(
+    tag = "dependencies",
+    responses(
+        (status = 200, description = "The longest chain of pending tasks the task waits for.", body = [Task]),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}/dependencies/critical_path")]
+async fn get_task_critical_path(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    related_tasks(&repositories, &caller, id, task_id, Relation::CriticalPath).await
+}
@@ -1,2 +1,162 @@
+use actix_web::{
+    HttpResponse, ResponseError,
+    http::{StatusCode, header},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    blob_handler::error::NinoverseBlobError, db_handler::error::NinoverseDbError,
+    kafka_handler::error::NinoverseKafkaError, permission_handler::error::NinoversePermissionError,
+};
+
+/// The body of every error response.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// What went wrong, the same for every error of a kind.
+    pub error: String,
+    pub additional_info: String,
+}
+
 #[derive(thiserror::Error, Debug)]
-pub enum NinoverseApiError {}
\ No newline at end of file
+pub enum NinoverseApiError {
+    #[error("API_HANDLER: Authentication required.")]
+    Unauthorized { additional_info: String },
+    #[error("API_HANDLER: Permission denied.")]
+    Forbidden { additional_info: String },
+    #[error("API_HANDLER: Resource not found.")]
+    NotFound { additional_info: String },
+    #[error("API_HANDLER: Precondition failed.")]
+    PreconditionFailed { additional_info: String },
+    #[error("API_HANDLER: Status transition not allowed.")]
+    InvalidTransition { additional_info: String },
+    #[error("API_HANDLER: Request validation failed.")]
+    ValidationError { additional_info: String },
+    #[error("API_HANDLER: Payload too large.")]
+    PayloadTooLarge { additional_info: String },
+    #[error("API_HANDLER: Unsupported media type.")]
+    UnsupportedMediaType { additional_info: String },
+    /// `size` is the full size of the resource, reported in `Content-Range`.
+    #[error("API_HANDLER: Range not satisfiable.")]
+    RangeNotSatisfiable { additional_info: String, size: u64 },
+    #[error("API_HANDLER: Error querying the database.")]
+    DatabaseError { additional_info: String },
+    #[error("API_HANDLER: Error talking to the blob store.")]
+    BlobStoreError { additional_info: String },
+    #[error("API_HANDLER: Error talking to the message bus.")]
+    MessageBusError { additional_info: String },
+}
+
+impl NinoverseApiError {
+    pub fn additional_info(&self) -> &str {
+        match self {
+            NinoverseApiError::Unauthorized { additional_info }
+            | NinoverseApiError::Forbidden { additional_info }
+            | NinoverseApiError::NotFound { additional_info }
+            | NinoverseApiError::PreconditionFailed { additional_info }
+            | NinoverseApiError::InvalidTransition { additional_info }
+            | NinoverseApiError::ValidationError { additional_info }
+            | NinoverseApiError::PayloadTooLarge { additional_info }
+            | NinoverseApiError::UnsupportedMediaType { additional_info }
+            | NinoverseApiError::RangeNotSatisfiable {
+                additional_info, ..
+            }
+            | NinoverseApiError::DatabaseError { additional_info }
+            | NinoverseApiError::BlobStoreError { additional_info }
+            | NinoverseApiError::MessageBusError { additional_info } => additional_info,
+        }
+    }
+}
+
+impl From<NinoverseDbError> for NinoverseApiError {
+    fn from(error: NinoverseDbError) -> Self {
+        NinoverseApiError::DatabaseError {
+            additional_info: match error {
+                NinoverseDbError::QueryError { additional_info } => additional_info,
+                other => other.to_string(),
+            },
+        }
+    }
+}
+
+impl From<NinoverseBlobError> for NinoverseApiError {
+    fn from(error: NinoverseBlobError) -> Self {
+        NinoverseApiError::BlobStoreError {
+            additional_info: match error {
+                NinoverseBlobError::NotFound { additional_info }
+                | NinoverseBlobError::StoreError { additional_info } => additional_info,
+            },
+        }
+    }
+}
+
+impl From<NinoverseKafkaError> for NinoverseApiError {
+    fn from(error: NinoverseKafkaError) -> Self {
+        match error {
+            NinoverseKafkaError::UnknownTopic { additional_info } => {
+                NinoverseApiError::NotFound { additional_info }
+            }
+            NinoverseKafkaError::InvalidRequest { additional_info } => {
+                NinoverseApiError::ValidationError { additional_info }
+            }
+            NinoverseKafkaError::BrokerError { additional_info } => {
+                NinoverseApiError::MessageBusError { additional_info }
+            }
+            NinoverseKafkaError::DatabaseError { additional_info } => {
+                NinoverseApiError::DatabaseError { additional_info }
+            }
+        }
+    }
+}
+
+impl From<NinoversePermissionError> for NinoverseApiError {
+    fn from(error: NinoversePermissionError) -> Self {
+        match error {
+            NinoversePermissionError::Forbidden { additional_info } => {
+                NinoverseApiError::Forbidden { additional_info }
+            }
+            NinoversePermissionError::NotFound { additional_info } => {
+                NinoverseApiError::NotFound { additional_info }
+            }
+            NinoversePermissionError::DatabaseError { additional_info } => {
+                NinoverseApiError::DatabaseError { additional_info }
+            }
+        }
+    }
+}
+
+impl ResponseError for NinoverseApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            NinoverseApiError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            NinoverseApiError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            NinoverseApiError::NotFound { .. } => StatusCode::NOT_FOUND,
+            NinoverseApiError::PreconditionFailed { .. } => StatusCode::PRECONDITION_FAILED,
+            NinoverseApiError::InvalidTransition { .. } => StatusCode::CONFLICT,
+            NinoverseApiError::ValidationError { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            NinoverseApiError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            NinoverseApiError::UnsupportedMediaType { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            NinoverseApiError::RangeNotSatisfiable { .. } => StatusCode::RANGE_NOT_SATISFIABLE,
+            NinoverseApiError::DatabaseError { .. }
+            | NinoverseApiError::BlobStoreError { .. }
+            | NinoverseApiError::MessageBusError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status_code());
+        match self {
+            NinoverseApiError::Unauthorized { .. } => {
+                response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+            }
+            NinoverseApiError::RangeNotSatisfiable { size, .. } => {
+                response.insert_header((header::CONTENT_RANGE, format!("bytes */{}", size)));
+            }
+            _ => {}
+        }
+        response.json(ErrorBody {
+            error: self.to_string(),
+            additional_info: self.additional_info().to_string(),
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use std::{future::ready, pin::pin, str::FromStr};
+
+use actix_web::{
+    FromRequest, HttpRequest, HttpResponse,
+    dev::Payload,
+    get,
+    http::header::{self, HeaderValue},
+    post, rt, web,
+};
+use actix_ws::{CloseReason, Message, Session};
+use async_graphql::{
+    Data,
+    http::{WebSocket, WebSocketProtocols, WsMessage},
+};
+use futures::{StreamExt, future::LocalBoxFuture};
+use tokio::sync::mpsc::Sender;
+
+use super::request_context::RequestContext;
+use crate::{
+    KafkaChannelMessage,
+    db_handler::repository::Repositories,
+    graphql_handler::{NinoverseSchema, RequestScope},
+    kafka_handler::live::LiveFeed,
+    permission_handler::Caller,
+};
+
+/// The tenant repositories, caller and context the resolvers act with.
+impl FromRequest for RequestScope {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let repositories = Repositories::extract(request);
+        let kafka_thread_sender = web::Data::<Sender<KafkaChannelMessage>>::extract(request);
+        let context = RequestContext::extract(request);
+        let caller = Caller::from_request(request, payload);
+        Box::pin(async move {
+            Ok(RequestScope {
+                repositories: repositories.await?,
+                kafka_thread_sender: kafka_thread_sender.await?.as_ref().clone(),
+                context: context.await?,
+                caller: caller.await?,
+            })
+        })
+    }
+}
+
+/// Queries and mutations over projects, tasks and status types, with the
+/// same permissions as the REST API. Errors come back in the `errors` of the
+/// response, with the HTTP status they would have in their `code` extension.
+#[utoipa::path(
+    tag = "graphql",
+    request_body(content = serde_json::Value, description = "A GraphQL request: `query`, `variables` and `operationName`."),
+    responses(
+        (status = 200, description = "The GraphQL response: `data` and `errors`.", body = serde_json::Value),
+    )
+)]
+#[post("/graphql")]
+async fn graphql(
+    schema: web::Data<NinoverseSchema>,
+    scope: RequestScope,
+    request: web::Json<async_graphql::Request>,
+) -> HttpResponse {
+    let mut request = request.into_inner();
+    request.data = scope.into_data();
+    HttpResponse::Ok().json(schema.execute(request).await)
+}
+
+/// The schema in the GraphQL schema definition language.
+#[utoipa::path(
+    tag = "graphql",
+    responses(
+        (status = 200, description = "The schema, as `text/plain`."),
+    )
+)]
+#[get("/graphql/schema")]
+async fn graphql_schema(schema: web::Data<NinoverseSchema>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(schema.sdl())
+}
+
+/// Runs a GraphQL WebSocket connection until either side closes it or the
+/// feed is closed.
+async fn run_socket(
+    schema: NinoverseSchema,
+    mut session: Session,
+    client_messages: actix_ws::MessageStream,
+    protocol: WebSocketProtocols,
+    data: Data,
+    live_feed: web::Data<LiveFeed>,
+) {
+    let pong_session = session.clone();
+    let client_messages = client_messages
+        .take_while(|message| {
+            ready(matches!(message, Ok(message) if !matches!(message, Message::Close(_))))
+        })
+        .filter_map(move |message| {
+            let mut session = pong_session.clone();
+            async move {
+                match message {
+                    Ok(Message::Text(text)) => Some(text.into_bytes()),
+                    Ok(Message::Binary(bytes)) => Some(bytes),
+                    Ok(Message::Ping(bytes)) => {
+                        let _ = session.pong(&bytes).await;
+                        None
+                    }
+                    _ => None,
+                }
+            }
+        });
+    let mut server_messages =
+        pin!(WebSocket::new(schema, client_messages, protocol).connection_data(data));
+    loop {
+        tokio::select! {
+            server_message = server_messages.next() => match server_message {
+                Some(WsMessage::Text(text)) => {
+                    if session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                Some(WsMessage::Close(code, reason)) => {
+                    let _ = session
+                        .close(Some(CloseReason {
+                            code: code.into(),
+                            description: Some(reason),
+                        }))
+                        .await;
+                    return;
+                }
+                None => break,
+            },
+            _ = live_feed.closed() => break,
+        }
+    }
+    let _ = session.close(None).await;
+}
+
+/// Subscriptions, and queries and mutations, over the `graphql-transport-ws`
+/// or the older `graphql-ws` protocol, picked from `Sec-WebSocket-Protocol`.
+#[utoipa::path(
+    tag = "graphql",
+    params(("Sec-WebSocket-Protocol" = String, Header, description = "`graphql-transport-ws` or `graphql-ws`.")),
+    responses(
+        (status = 101, description = "A WebSocket speaking the requested protocol."),
+        (status = 400, description = "No supported protocol was requested."),
+    )
+)]
+#[get("/graphql/ws")]
+async fn graphql_socket(
+    schema: web::Data<NinoverseSchema>,
+    live_feed: web::Data<LiveFeed>,
+    scope: RequestScope,
+    request: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    let Some(protocol) = request
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|protocols| {
+            protocols
+                .split(',')
+                .find_map(|protocol| WebSocketProtocols::from_str(protocol.trim()).ok())
+        })
+    else {
+        return Ok(HttpResponse::BadRequest().body("Unsupported WebSocket protocol."));
+    };
+    let (mut response, session, client_messages) = actix_ws::handle(&request, body)?;
+    response.headers_mut().insert(
+        header::SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(protocol.sec_websocket_protocol()),
+    );
+    rt::spawn(run_socket(
+        schema.as_ref().clone(),
+        session,
+        client_messages,
+        protocol,
+        scope.into_data(),
+        live_feed,
+    ));
+    Ok(response)
+}
@@ -0,0 +1,243 @@
+use std::{collections::BTreeSet, convert::Infallible, time::Duration};
+
+use actix_web::{HttpRequest, HttpResponse, get, http::header, rt, web};
+use actix_ws::{Message, Session};
+use bytes::Bytes;
+use futures::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use tokio::time::{Instant, interval_at};
+
+use crate::{
+    db_handler::repository::Repositories,
+    kafka_handler::{
+        live::{LiveFeed, LiveMessage, LiveQuery, LiveReceiver, LiveSubscription},
+        structs::KafkaNinoverseEvent,
+    },
+    permission_handler::Caller,
+};
+
+/// Sent when nothing else was, so that proxies keep idle connections open.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// How long an `EventSource` waits before reconnecting, in milliseconds.
+const RECONNECT_DELAY: &str = "3000";
+
+/// Resumes after the `Last-Event-ID` header sent by reconnecting clients, or
+/// the `last_event_id` query parameter.
+fn subscribe(
+    repositories: &Repositories,
+    live_feed: &LiveFeed,
+    caller: Caller,
+    query: &LiveQuery,
+    request: &HttpRequest,
+) -> LiveReceiver {
+    let last_event_id = request
+        .headers()
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .or(query.last_event_id.as_deref());
+    live_feed.subscribe(
+        &repositories.tenant_id,
+        caller,
+        query.subscription(),
+        last_event_id,
+    )
+}
+
+fn sse_message(message: LiveMessage) -> Bytes {
+    match message {
+        LiveMessage::Event(live_event) => Bytes::from(format!(
+            "id: {}\nevent: {}\ndata: {}\n\n",
+            live_event.id,
+            live_event.event.name(),
+            serde_json::to_string(&live_event.event).unwrap_or_default()
+        )),
+        LiveMessage::Reset => Bytes::from_static(b"event: reset\ndata: {}\n\n"),
+    }
+}
+
+/// Server-Sent Events of the projects the caller can read, filtered by the
+/// `projects` and `tags` of the query. Event ids are Kafka positions, a
+/// reconnecting client gets the events it missed or a `reset` event.
+#[utoipa::path(
+    tag = "live",
+    params(
+        LiveQuery,
+        ("Last-Event-ID" = Option<String>, Header, description = "The id of the last event received."),
+    ),
+    responses(
+        (status = 200, description = "A `text/event-stream` of the events."),
+    )
+)]
+#[get("/events/stream")]
+async fn event_stream(
+    repositories: Repositories,
+    live_feed: web::Data<LiveFeed>,
+    caller: Caller,
+    query: web::Query<LiveQuery>,
+    request: HttpRequest,
+) -> HttpResponse {
+    let receiver = subscribe(&repositories, &live_feed, caller, &query, &request);
+    let keep_alive = interval_at(Instant::now() + KEEP_ALIVE_INTERVAL, KEEP_ALIVE_INTERVAL);
+    let events = stream::unfold(
+        (receiver, keep_alive),
+        |(mut receiver, mut keep_alive)| async move {
+            let chunk = tokio::select! {
+                message = receiver.next() => sse_message(message?),
+                _ = keep_alive.tick() => Bytes::from_static(b": keep-alive\n\n"),
+            };
+            keep_alive.reset();
+            Some((Ok::<_, Infallible>(chunk), (receiver, keep_alive)))
+        },
+    );
+    let retry = Bytes::from(format!("retry: {}\n\n", RECONNECT_DELAY));
+    HttpResponse::Ok()
+        .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .insert_header(("x-accel-buffering", "no"))
+        .streaming(stream::once(async move { Ok(retry) }).chain(events))
+}
+
+/// Sent by WebSocket clients to follow more or fewer projects and tags.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum SocketCommand {
+    Subscribe {
+        #[serde(default)]
+        projects: BTreeSet<i32>,
+        #[serde(default)]
+        tags: BTreeSet<String>,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        projects: BTreeSet<i32>,
+        #[serde(default)]
+        tags: BTreeSet<String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SocketFrame<'a> {
+    Event {
+        id: &'a str,
+        data: &'a KafkaNinoverseEvent,
+    },
+    Reset,
+    /// Acknowledges the connection and every command with the resulting
+    /// subscription.
+    Subscribed {
+        projects: &'a BTreeSet<i32>,
+        tags: &'a BTreeSet<String>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+async fn send_frame(
+    session: &mut Session,
+    frame: &SocketFrame<'_>,
+) -> Result<(), actix_ws::Closed> {
+    session
+        .text(serde_json::to_string(frame).unwrap_or_default())
+        .await
+}
+
+fn subscribed(subscription: &LiveSubscription) -> SocketFrame<'_> {
+    SocketFrame::Subscribed {
+        projects: &subscription.project_ids,
+        tags: &subscription.tags,
+    }
+}
+
+/// Runs a WebSocket connection until either side closes it or the feed is
+/// closed.
+async fn run_socket(
+    mut session: Session,
+    mut client_messages: actix_ws::MessageStream,
+    mut receiver: LiveReceiver,
+) {
+    let mut ping = interval_at(Instant::now() + KEEP_ALIVE_INTERVAL, KEEP_ALIVE_INTERVAL);
+    if send_frame(&mut session, &subscribed(&receiver.subscription))
+        .await
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        let sent = tokio::select! {
+            message = receiver.next() => match message {
+                Some(LiveMessage::Event(live_event)) => {
+                    let frame = SocketFrame::Event {
+                        id: &live_event.id,
+                        data: &live_event.event,
+                    };
+                    send_frame(&mut session, &frame).await
+                }
+                Some(LiveMessage::Reset) => send_frame(&mut session, &SocketFrame::Reset).await,
+                None => break,
+            },
+            client_message = client_messages.next() => match client_message {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<SocketCommand>(&text) {
+                        Ok(SocketCommand::Subscribe { projects, tags }) => {
+                            receiver.subscription.add(LiveSubscription {
+                                project_ids: projects,
+                                tags,
+                            });
+                        }
+                        Ok(SocketCommand::Unsubscribe { projects, tags }) => {
+                            receiver.subscription.remove(&LiveSubscription {
+                                project_ids: projects,
+                                tags,
+                            });
+                        }
+                        Err(command_error) => {
+                            let frame = SocketFrame::Error {
+                                message: format!("Invalid command: {}", command_error),
+                            };
+                            if send_frame(&mut session, &frame).await.is_err() {
+                                return;
+                            }
+                            continue;
+                        }
+                    }
+                    send_frame(&mut session, &subscribed(&receiver.subscription)).await
+                }
+                Some(Ok(Message::Ping(bytes))) => session.pong(&bytes).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => Ok(()),
+            },
+            _ = ping.tick() => session.ping(b"").await,
+        };
+        if sent.is_err() {
+            return;
+        }
+    }
+    let _ = session.close(None).await;
+}
+
+/// The events of `/events/stream` over a WebSocket, as JSON text frames.
+/// Clients change what they follow with `subscribe` and `unsubscribe`
+/// commands.
+#[utoipa::path(
+    tag = "live",
+    params(LiveQuery),
+    responses(
+        (status = 101, description = "A WebSocket sending the events as JSON text frames."),
+    )
+)]
+#[get("/events/ws")]
+async fn event_socket(
+    repositories: Repositories,
+    live_feed: web::Data<LiveFeed>,
+    caller: Caller,
+    query: web::Query<LiveQuery>,
+    request: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    let receiver = subscribe(&repositories, &live_feed, caller, &query, &request);
+    let (response, session, client_messages) = actix_ws::handle(&request, body)?;
+    rt::spawn(run_socket(session, client_messages, receiver));
+    Ok(response)
+}
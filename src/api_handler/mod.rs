@@ -1,27 +1,172 @@
-mod error;
+pub mod access;
+pub mod api_keys;
+pub mod attachments;
+mod audit;
+pub mod auth;
+mod comments;
+mod dependencies;
+pub mod error;
+mod graphql;
+mod live;
+mod openapi;
+pub mod projects;
+pub mod request_context;
+mod search;
+pub mod status_types;
+mod tags;
+pub mod tasks;
+pub mod teams;
+pub mod tenant;
+mod tenants;
+mod topics;
+pub mod transitions;
+pub mod users;
+mod webhooks;
 
-use std::sync::Arc;
+use std::{net::TcpListener, sync::Arc};
 
-use actix_web::{App, HttpResponse, HttpServer, Responder, dev::Server, get, post, web};
-use sqlx::{Pool, Postgres};
+use actix_web::{
+    App, HttpResponse, HttpServer, Responder, dev::Server, get, middleware::from_fn, post, web,
+};
+use attachments::AttachmentSettings;
+use auth::AuthSettings;
+use openapi::{ApiDoc, DocsSettings};
 use tokio::sync::mpsc::Sender;
+use utoipa::OpenApi;
 
-use crate::{KafkaChannelMessage, configuration_handler};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::repository::Repositories,
+    graphql_handler::{GraphqlLimits, build_schema},
+    kafka_handler::{live::LiveFeed, message_bus::MessageBus},
+};
 
 pub fn init_request_handler(
-    pool: Arc<Pool<Postgres>>,
+    repositories: Repositories,
     kafka_thread_sender: Sender<KafkaChannelMessage>,
+    message_bus: Arc<dyn MessageBus>,
+    listener: TcpListener,
+    auth_settings: AuthSettings,
+    attachment_settings: AttachmentSettings,
+    live_feed: Arc<LiveFeed>,
 ) -> Result<Server, Box<dyn std::error::Error>> {
+    let auth_settings = web::Data::new(auth_settings);
+    let attachment_settings = web::Data::new(attachment_settings);
+    let live_feed = web::Data::from(live_feed);
+    let message_bus = web::Data::from(message_bus);
+    let openapi = web::Data::new(ApiDoc::openapi());
+    let docs_settings = web::Data::new(DocsSettings::from_configuration()?);
+    let graphql_schema = web::Data::new(build_schema(
+        live_feed.clone().into_inner(),
+        &GraphqlLimits::from_configuration(),
+    ));
     Ok(HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(repositories.clone()))
             .app_data(web::Data::new(kafka_thread_sender.clone()))
+            .app_data(auth_settings.clone())
+            .app_data(attachment_settings.clone())
+            .app_data(live_feed.clone())
+            .app_data(message_bus.clone())
+            .app_data(openapi.clone())
+            .app_data(docs_settings.clone())
+            .app_data(graphql_schema.clone())
+            .wrap(from_fn(tenant::tenant_middleware))
+            .wrap(from_fn(auth::auth_middleware))
+            .wrap(from_fn(request_context::request_id_middleware))
             .service(hello)
             .service(echo)
+            .service(openapi::openapi_json)
+            .service(openapi::docs)
+            .service(openapi::redoc_bundle)
+            .service(projects::get_projects)
+            .service(projects::get_project)
+            .service(projects::create_project)
+            .service(projects::update_project)
+            .service(projects::delete_project)
+            .service(projects::restore_project)
+            .service(projects::get_project_permissions)
+            .service(dependencies::get_project_upstream)
+            .service(dependencies::get_project_downstream)
+            .service(dependencies::get_project_critical_path)
+            .service(dependencies::get_project_dependencies)
+            .service(dependencies::create_project_dependency)
+            .service(dependencies::delete_project_dependency)
+            .service(status_types::get_status_types)
+            .service(status_types::create_status_type)
+            .service(status_types::update_status_type)
+            .service(status_types::delete_status_type)
+            .service(status_types::get_status_transitions)
+            .service(tasks::reorder_tasks)
+            .service(tasks::get_tasks)
+            .service(tasks::get_task)
+            .service(tasks::create_task)
+            .service(tasks::update_task)
+            .service(tasks::delete_task)
+            .service(dependencies::get_task_upstream)
+            .service(dependencies::get_task_downstream)
+            .service(dependencies::get_task_critical_path)
+            .service(dependencies::get_task_dependencies)
+            .service(dependencies::create_task_dependency)
+            .service(dependencies::delete_task_dependency)
+            .service(tags::get_task_tags)
+            .service(tags::get_project_tags)
+            .service(tags::get_tags)
+            .service(tags::create_tag)
+            .service(tags::update_tag)
+            .service(tags::delete_tag)
+            .service(tags::assign_tag)
+            .service(tags::unassign_tag)
+            .service(comments::get_project_comments)
+            .service(comments::create_project_comment)
+            .service(comments::get_task_comments)
+            .service(comments::create_task_comment)
+            .service(comments::update_comment)
+            .service(comments::delete_comment)
+            .service(comments::get_comment_revisions)
+            .service(comments::get_project_activity)
+            .service(attachments::get_attachments)
+            .service(attachments::upload_attachment)
+            .service(attachments::get_attachment)
+            .service(attachments::download_attachment)
+            .service(attachments::delete_attachment)
+            .service(search::search)
+            .service(live::event_stream)
+            .service(live::event_socket)
+            .service(graphql::graphql)
+            .service(graphql::graphql_schema)
+            .service(graphql::graphql_socket)
+            .service(transitions::get_project_transitions)
+            .service(transitions::create_project_transition)
+            .service(audit::get_audit_records)
+            .service(api_keys::get_api_keys)
+            .service(api_keys::create_api_key)
+            .service(api_keys::revoke_api_key)
+            .service(users::get_users)
+            .service(users::get_current_user)
+            .service(teams::get_teams)
+            .service(teams::create_team)
+            .service(teams::get_team_members)
+            .service(teams::set_team_member)
+            .service(teams::remove_team_member)
+            .service(tenants::get_tenants)
+            .service(tenants::create_tenant)
+            .service(topics::get_topics)
+            .service(topics::get_topic)
+            .service(topics::ensure_topic_exists)
+            .service(topics::update_topic_configs)
+            .service(topics::add_topic_partitions)
+            .service(topics::delete_topic)
+            .service(webhooks::get_webhooks)
+            .service(webhooks::create_webhook)
+            .service(webhooks::get_webhook)
+            .service(webhooks::update_webhook)
+            .service(webhooks::delete_webhook)
+            .service(webhooks::get_webhook_deliveries)
             .route("/hey", web::get().to(manual_hello))
     })
     .disable_signals()
-    .bind(("0.0.0.0", configuration_handler::get_self_port().parse::<u16>()?))?
+    .listen(listener)?
     .run())
 }
 
@@ -0,0 +1,262 @@
+use actix_web::{
+    HttpResponse, get,
+    http::header::{CacheControl, CacheDirective},
+    web,
+};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        self, ContentBuilder, ObjectBuilder, RefOr, ResponseBuilder, Type,
+        path::{Operation, ParameterBuilder, ParameterIn},
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+
+use super::{
+    api_keys, attachments, audit, comments, dependencies, error::ErrorBody, graphql, live,
+    projects, search, status_types, tags, tasks, teams, tenants, topics, transitions, users,
+    webhooks,
+};
+use crate::configuration_handler::get_docs_redoc_bundle_path;
+
+/// The responses every operation can end with, on top of its own.
+const COMMON_RESPONSES: [(&str, &str); 2] = [
+    ("401", "Credentials are missing or invalid."),
+    ("500", "The database or the blob store failed."),
+];
+
+/// Adds the credentials, the tenant header and the common responses to every
+/// operation, rather than repeating them on each handler.
+struct CommonParts;
+
+impl Modify for CommonParts {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        // Nothing to say until the crate has a license.
+        openapi.info.license = None;
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
+        );
+        openapi.security = Some(vec![
+            openapi::SecurityRequirement::new("bearer", Vec::<String>::new()),
+            openapi::SecurityRequirement::new("api_key", Vec::<String>::new()),
+        ]);
+        for path_item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut path_item.get,
+                &mut path_item.post,
+                &mut path_item.put,
+                &mut path_item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                add_common_parts(operation);
+            }
+        }
+    }
+}
+
+fn add_common_parts(operation: &mut Operation) {
+    operation.parameters.get_or_insert_with(Vec::new).push(
+        ParameterBuilder::new()
+            .name("X-Tenant-Id")
+            .parameter_in(ParameterIn::Header)
+            .schema(Some(ObjectBuilder::new().schema_type(Type::String)))
+            .description(Some(
+                "The tenant of the request when the credentials don't name one.",
+            ))
+            .build(),
+    );
+    for (status, description) in COMMON_RESPONSES {
+        operation.responses.responses.insert(
+            status.to_string(),
+            RefOr::T(
+                ResponseBuilder::new()
+                    .description(description)
+                    .content(
+                        "application/json",
+                        ContentBuilder::new()
+                            .schema(Some(RefOr::Ref(openapi::Ref::from_schema_name(
+                                "ErrorBody",
+                            ))))
+                            .build(),
+                    )
+                    .build(),
+            ),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(description = "Projects, their tasks and everything around them."),
+    modifiers(&CommonParts),
+    components(schemas(ErrorBody)),
+    paths(
+        projects::get_projects,
+        projects::get_project,
+        projects::create_project,
+        projects::update_project,
+        projects::delete_project,
+        projects::restore_project,
+        projects::get_project_permissions,
+        dependencies::get_project_upstream,
+        dependencies::get_project_downstream,
+        dependencies::get_project_critical_path,
+        dependencies::get_project_dependencies,
+        dependencies::create_project_dependency,
+        dependencies::delete_project_dependency,
+        status_types::get_status_types,
+        status_types::create_status_type,
+        status_types::update_status_type,
+        status_types::delete_status_type,
+        status_types::get_status_transitions,
+        tasks::reorder_tasks,
+        tasks::get_tasks,
+        tasks::get_task,
+        tasks::create_task,
+        tasks::update_task,
+        tasks::delete_task,
+        dependencies::get_task_upstream,
+        dependencies::get_task_downstream,
+        dependencies::get_task_critical_path,
+        dependencies::get_task_dependencies,
+        dependencies::create_task_dependency,
+        dependencies::delete_task_dependency,
+        tags::get_task_tags,
+        tags::get_project_tags,
+        tags::get_tags,
+        tags::create_tag,
+        tags::update_tag,
+        tags::delete_tag,
+        tags::assign_tag,
+        tags::unassign_tag,
+        comments::get_project_comments,
+        comments::create_project_comment,
+        comments::get_task_comments,
+        comments::create_task_comment,
+        comments::update_comment,
+        comments::delete_comment,
+        comments::get_comment_revisions,
+        comments::get_project_activity,
+        attachments::get_attachments,
+        attachments::upload_attachment,
+        attachments::get_attachment,
+        attachments::download_attachment,
+        attachments::delete_attachment,
+        search::search,
+        live::event_stream,
+        live::event_socket,
+        graphql::graphql,
+        graphql::graphql_schema,
+        graphql::graphql_socket,
+        transitions::get_project_transitions,
+        transitions::create_project_transition,
+        audit::get_audit_records,
+        api_keys::get_api_keys,
+        api_keys::create_api_key,
+        api_keys::revoke_api_key,
+        users::get_users,
+        users::get_current_user,
+        teams::get_teams,
+        teams::create_team,
+        teams::get_team_members,
+        teams::set_team_member,
+        teams::remove_team_member,
+        tenants::get_tenants,
+        tenants::create_tenant,
+        topics::get_topics,
+        topics::get_topic,
+        topics::ensure_topic_exists,
+        topics::update_topic_configs,
+        topics::add_topic_partitions,
+        topics::delete_topic,
+        webhooks::get_webhooks,
+        webhooks::create_webhook,
+        webhooks::get_webhook,
+        webhooks::update_webhook,
+        webhooks::delete_webhook,
+        webhooks::get_webhook_deliveries,
+    )
+)]
+pub struct ApiDoc;
+
+/// Generated once at startup, see `ApiDoc`.
+#[get("/openapi.json")]
+async fn openapi_json(document: web::Data<openapi::OpenApi>) -> HttpResponse {
+    HttpResponse::Ok().json(document.as_ref())
+}
+
+/// The Redoc release rendering the docs.
+const REDOC_VERSION: &str = "2.5.0";
+const REDOC_BUNDLE_PATH: &str = "/docs/redoc.standalone.js";
+
+/// The Redoc bundle read from `DOCS_REDOC_BUNDLE_PATH` at startup, the docs
+/// load the pinned release from the CDN without one.
+pub struct DocsSettings {
+    redoc_bundle: Option<web::Bytes>,
+}
+
+impl DocsSettings {
+    pub fn from_configuration() -> std::io::Result<Self> {
+        Ok(DocsSettings {
+            redoc_bundle: get_docs_redoc_bundle_path()
+                .map(std::fs::read)
+                .transpose()?
+                .map(web::Bytes::from),
+        })
+    }
+
+    fn redoc_url(&self) -> String {
+        match self.redoc_bundle {
+            Some(_) => REDOC_BUNDLE_PATH.to_string(),
+            None => format!(
+                "https://cdn.jsdelivr.net/npm/redoc@{}/bundles/redoc.standalone.js",
+                REDOC_VERSION
+            ),
+        }
+    }
+}
+
+/// Redoc rendering `/openapi.json`.
+#[get("/docs")]
+async fn docs(settings: web::Data<DocsSettings>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(DOCS_PAGE.replace("{redoc_url}", &settings.redoc_url()))
+}
+
+/// 404 unless a bundle is configured.
+#[get("/docs/redoc.standalone.js")]
+async fn redoc_bundle(settings: web::Data<DocsSettings>) -> HttpResponse {
+    match &settings.redoc_bundle {
+        Some(bundle) => HttpResponse::Ok()
+            .content_type("text/javascript; charset=utf-8")
+            .insert_header(CacheControl(vec![CacheDirective::MaxAge(86400)]))
+            .body(bundle.clone()),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+const DOCS_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <title>ninoverse API</title>
+    <meta charset="utf-8"/>
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+  </head>
+  <body>
+    <redoc spec-url="/openapi.json"></redoc>
+    <script src="{redoc_url}" crossorigin="anonymous"></script>
+  </body>
+</html>
+"#;
@@ -0,0 +1,449 @@
+use actix_web::{
+    HttpResponse, delete, get,
+    http::header::{ETag, EntityTag, IfMatch},
+    post, put, web,
+};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+    tags::tagged_items,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{ItemKind, NewProject, Project, ProjectListQuery, ProjectUpdate},
+    },
+    permission_handler::{Caller, Permission, ProjectPermissions, Role},
+};
+
+pub(super) fn project_etag(project: &Project) -> ETag {
+    ETag(EntityTag::new_strong(project.version.to_string()))
+}
+
+/// Turns an `If-Match` header into the list of versions the client accepts.
+/// `None` means any version is fine (no header or `*`). A missing header is
+/// extracted as an empty list of tags, not as `None`.
+pub(super) fn accepted_versions(if_match: Option<web::Header<IfMatch>>) -> Option<Vec<i32>> {
+    match if_match.map(|header| header.into_inner()) {
+        None | Some(IfMatch::Any) => None,
+        Some(IfMatch::Items(tags)) if tags.is_empty() => None,
+        Some(IfMatch::Items(tags)) => Some(
+            tags.iter()
+                .filter(|tag| !tag.weak)
+                .filter_map(|tag| tag.tag().parse::<i32>().ok())
+                .collect(),
+        ),
+    }
+}
+
+/// A project can only be put in a team by its editors.
+async fn check_team_assignment(
+    repositories: &Repositories,
+    caller: &Caller,
+    team_id: i32,
+) -> Result<(), NinoverseApiError> {
+    if repositories.teams.get(team_id).await?.is_none() {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("Team {} does not exist.", team_id),
+        });
+    }
+    if caller
+        .team_role(team_id)
+        .is_none_or(|role| role < Role::Editor)
+    {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: format!(
+                "Only editors of team {} can assign projects to it.",
+                team_id
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Creates the project for `POST /projects` and the GraphQL API.
+pub(crate) async fn insert_project(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    new_project: &NewProject,
+) -> Result<Project, NinoverseApiError> {
+    if !repositories
+        .status_types
+        .exists(&new_project.status)
+        .await?
+    {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("Status {} does not exist.", new_project.status),
+        });
+    }
+    if let Some(team_id) = new_project.team_id {
+        check_team_assignment(repositories, caller, team_id).await?;
+    }
+    let owner_id = caller.user().map(|user| user.id);
+    let project = repositories.projects.insert(new_project, owner_id).await?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "create",
+            entity_type: "project",
+            entity_id: project.id,
+            before: None,
+            after: Some(&project),
+        },
+    )
+    .await;
+    Ok(project)
+}
+
+/// `versions` are the ones the change applies to, any when `None`.
+pub(crate) async fn update_existing_project(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    id: i32,
+    versions: Option<Vec<i32>>,
+    update: &ProjectUpdate,
+) -> Result<Project, NinoverseApiError> {
+    if update.status.is_some() {
+        return Err(NinoverseApiError::InvalidTransition {
+            additional_info: format!(
+                "Status of project {} can only change through /projects/{}/transitions.",
+                id, id
+            ),
+        });
+    }
+    let current =
+        repositories
+            .projects
+            .get(id, false)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist.", id),
+            })?;
+    caller.authorize(&current, Permission::Write)?;
+    if update.owner_id.is_some() || update.team_id.is_some() {
+        caller.authorize(&current, Permission::ManageAccess)?;
+    }
+    if let Some(owner_id) = update.owner_id
+        && repositories.users.get(owner_id).await?.is_none()
+    {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("User {} does not exist.", owner_id),
+        });
+    }
+    if let Some(team_id) = update.team_id {
+        check_team_assignment(repositories, caller, team_id).await?;
+    }
+    let project = repositories
+        .projects
+        .update(id, update, versions)
+        .await?
+        .ok_or_else(|| NinoverseApiError::PreconditionFailed {
+            additional_info: format!(
+                "Project {} was modified concurrently, current version is {}.",
+                id, current.version
+            ),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "update",
+            entity_type: "project",
+            entity_id: project.id,
+            before: Some(&current),
+            after: Some(&project),
+        },
+    )
+    .await;
+    Ok(project)
+}
+
+/// Soft deletes the project, returns it as deleted.
+pub(crate) async fn delete_existing_project(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    id: i32,
+    versions: Option<Vec<i32>>,
+) -> Result<Project, NinoverseApiError> {
+    let current =
+        repositories
+            .projects
+            .get(id, false)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist.", id),
+            })?;
+    caller.authorize(&current, Permission::Delete)?;
+    let project = repositories
+        .projects
+        .soft_delete(id, versions)
+        .await?
+        .ok_or_else(|| NinoverseApiError::PreconditionFailed {
+            additional_info: format!(
+                "Project {} was modified concurrently, current version is {}.",
+                id, current.version
+            ),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "project",
+            entity_id: project.id,
+            before: Some(&current),
+            after: Some(&project),
+        },
+    )
+    .await;
+    Ok(project)
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(ProjectListQuery),
+    responses((status = 200, description = "The projects the caller can read.", body = [Project]))
+)]
+#[get("/projects")]
+async fn get_projects(
+    repositories: Repositories,
+    caller: Caller,
+    query: web::Query<ProjectListQuery>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let tagged_ids = tagged_items(
+        &repositories,
+        ItemKind::Project,
+        query.tags.as_deref(),
+        query.tag_match,
+    )
+    .await?;
+    let projects: Vec<Project> = repositories
+        .projects
+        .list(query.include_deleted)
+        .await?
+        .into_iter()
+        .filter(|project| caller.project_role(project).is_some())
+        .filter(|project| {
+            tagged_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&project.id))
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(projects))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(ProjectListQuery),
+    responses(
+        (status = 200, description = "The project, its version in the `ETag` header.", body = Project),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}")]
+async fn get_project(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+    query: web::Query<ProjectListQuery>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let id = id.into_inner();
+    let project = repositories
+        .projects
+        .get(id, query.include_deleted)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Project {} does not exist.", id),
+        })?;
+    caller.authorize(&project, Permission::Read)?;
+    Ok(HttpResponse::Ok()
+        .insert_header(project_etag(&project))
+        .json(project))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    responses(
+        (status = 201, description = "The created project.", body = Project),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/projects")]
+async fn create_project(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    new_project: web::Json<NewProject>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project = insert_project(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        &new_project,
+    )
+    .await?;
+    Ok(HttpResponse::Created()
+        .insert_header(project_etag(&project))
+        .json(project))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    params(("If-Match" = Option<String>, Header, description = "Versions of the project the change applies to, as returned in its `ETag`.")),
+    responses(
+        (status = 200, description = "The updated project.", body = Project),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 409, description = "The status was changed, which takes a transition.", body = ErrorBody),
+        (status = 412, description = "The project was modified since the version of `If-Match`.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[put("/projects/{id}")]
+async fn update_project(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
+    update: web::Json<ProjectUpdate>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project = update_existing_project(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id.into_inner(),
+        accepted_versions(if_match),
+        &update,
+    )
+    .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(project_etag(&project))
+        .json(project))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    responses(
+        (status = 204, description = "The project was deleted."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/projects/{id}")]
+async fn delete_project(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    delete_existing_project(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id.into_inner(),
+        accepted_versions(if_match),
+    )
+    .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "projects",
+    responses(
+        (status = 200, description = "The restored project.", body = Project),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The deleted project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/restore")]
+async fn restore_project(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let id = id.into_inner();
+    let current = repositories
+        .projects
+        .get(id, true)
+        .await?
+        .filter(|project| project.deleted_at.is_some())
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Project {} does not exist or is not deleted.", id),
+        })?;
+    caller.authorize(&current, Permission::Delete)?;
+    let project =
+        repositories
+            .projects
+            .restore(id)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist or is not deleted.", id),
+            })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "restore",
+            entity_type: "project",
+            entity_id: project.id,
+            before: Some(&current),
+            after: Some(&project),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Ok()
+        .insert_header(project_etag(&project))
+        .json(project))
+}
+
+#[utoipa::path(
+    tag = "projects",
+    responses(
+        (status = 200, description = "The role and permissions of the caller on the project.", body = ProjectPermissions),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/permissions")]
+async fn get_project_permissions(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let id = id.into_inner();
+    let project =
+        repositories
+            .projects
+            .get(id, true)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist.", id),
+            })?;
+    caller.authorize(&project, Permission::Read)?;
+    Ok(HttpResponse::Ok().json(caller.project_permissions(&project)))
+}
@@ -0,0 +1,81 @@
+use std::future::{Ready, ready};
+
+use actix_web::{
+    Error, FromRequest, HttpMessage, HttpRequest,
+    body::MessageBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+};
+
+use super::auth::Principal;
+
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+pub(crate) const ACTOR_HEADER: &str = "x-actor";
+
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Who is performing the request and under which request id, used to
+/// attribute mutations in the audit log.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub actor: String,
+    pub request_id: Option<String>,
+}
+
+pub(crate) fn generate_request_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// Reuses the incoming `X-Request-Id` or generates a new one, stores it in
+/// the request extensions and echoes it back in the response.
+pub async fn request_id_middleware(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+        .unwrap_or_else(generate_request_id);
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
+    let mut response = next.call(request).await?;
+    if let Ok(header_value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), header_value);
+    }
+    Ok(response)
+}
+
+impl FromRequest for RequestContext {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        // The authenticated subject wins over the self-declared actor.
+        let actor = request
+            .extensions()
+            .get::<Principal>()
+            .map(|principal| principal.subject.clone())
+            .unwrap_or_else(|| {
+                request
+                    .headers()
+                    .get(ACTOR_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .filter(|value| !value.is_empty())
+                    .unwrap_or("anonymous")
+                    .to_string()
+            });
+        let request_id = request
+            .extensions()
+            .get::<RequestId>()
+            .map(|request_id| request_id.0.clone());
+        ready(Ok(RequestContext { actor, request_id }))
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeSet;
+
+use actix_web::{HttpResponse, get, web};
+
+use super::{
+    error::{ErrorBody, NinoverseApiError},
+    tags::tagged_items,
+};
+use crate::{
+    db_handler::{
+        repository::Repositories,
+        structs::{ItemKind, SearchEntity, SearchHit, SearchQuery},
+    },
+    permission_handler::Caller,
+};
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+const MAX_SEARCH_TERMS: usize = 16;
+
+/// The lowercase words of the query, anything but letters and digits
+/// separates them.
+fn search_terms(query: &str) -> Vec<String> {
+    query
+        .split(|character: char| !character.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The comma separated values of a filter, `None` without filter.
+fn filter_values(values: Option<&str>) -> Option<BTreeSet<&str>> {
+    let values: BTreeSet<&str> = values?
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect();
+    (!values.is_empty()).then_some(values)
+}
+
+fn requested_entities(entity_types: Option<&str>) -> Result<Vec<SearchEntity>, NinoverseApiError> {
+    let Some(names) = filter_values(entity_types) else {
+        return Ok(SearchEntity::ALL.to_vec());
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            SearchEntity::parse(name).ok_or_else(|| NinoverseApiError::ValidationError {
+                additional_info: format!(
+                    "Unknown type {}, expected project, task or comment.",
+                    name
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Hits in projects the caller can't read, or which are deleted, are left
+/// out. Comments have neither status nor tags, filtering on them leaves the
+/// comments out.
+#[utoipa::path(
+    tag = "search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "The hits, best first.", body = [SearchHit]),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[get("/search")]
+async fn search(
+    repositories: Repositories,
+    caller: Caller,
+    query: web::Query<SearchQuery>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let terms = search_terms(&query.q);
+    if terms.is_empty() || terms.len() > MAX_SEARCH_TERMS {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("The q parameter needs 1 to {} words.", MAX_SEARCH_TERMS),
+        });
+    }
+    let entities = requested_entities(query.entity_types.as_deref())?;
+    let statuses = filter_values(query.status.as_deref());
+    let tagged_projects = tagged_items(
+        &repositories,
+        ItemKind::Project,
+        query.tags.as_deref(),
+        query.tag_match,
+    )
+    .await?;
+    let tagged_tasks = tagged_items(
+        &repositories,
+        ItemKind::Task,
+        query.tags.as_deref(),
+        query.tag_match,
+    )
+    .await?;
+    let readable_projects: BTreeSet<i32> = repositories
+        .projects
+        .list(false)
+        .await?
+        .into_iter()
+        .filter(|project| caller.project_role(project).is_some())
+        .map(|project| project.id)
+        .collect();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let hits: Vec<SearchHit> = repositories
+        .search
+        .search(&terms)
+        .await?
+        .into_iter()
+        .filter(|hit| readable_projects.contains(&hit.project_id))
+        .filter(|hit| {
+            let Some(entity) = SearchEntity::parse(&hit.entity_type) else {
+                return false;
+            };
+            let tagged = match entity {
+                SearchEntity::Project => tagged_projects
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(&hit.id)),
+                SearchEntity::Task => tagged_tasks
+                    .as_ref()
+                    .is_none_or(|ids| ids.contains(&hit.id)),
+                SearchEntity::Comment => tagged_projects.is_none(),
+            };
+            tagged && entities.contains(&entity)
+        })
+        .filter(|hit| {
+            statuses.as_ref().is_none_or(|statuses| {
+                hit.status
+                    .as_deref()
+                    .is_some_and(|status| statuses.contains(status))
+            })
+        })
+        .take(limit)
+        .collect();
+    Ok(HttpResponse::Ok().json(hits))
+}
@@ -0,0 +1,288 @@
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{NewStatusType, StatusTransition, StatusType, StatusTypeUpdate},
+    },
+    permission_handler::Caller,
+};
+
+/// Names of the status types marked `completed`. Projects and tasks in one of
+/// them don't block their dependents.
+pub(crate) async fn completed_statuses(
+    repositories: &Repositories,
+) -> Result<Vec<String>, NinoverseApiError> {
+    Ok(repositories
+        .status_types
+        .list()
+        .await?
+        .into_iter()
+        .filter(|status_type| status_type.completed)
+        .map(|status_type| status_type.name)
+        .collect())
+}
+
+/// Creates the status type for `POST /status_types` and the GraphQL API.
+pub(crate) async fn insert_status_type(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    new_status_type: &NewStatusType,
+) -> Result<StatusType, NinoverseApiError> {
+    // The workflow is shared by every project of the tenant.
+    if !caller.is_admin() {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: "Only administrators can create status types.".to_string(),
+        });
+    }
+    if repositories
+        .status_types
+        .exists(&new_status_type.name)
+        .await?
+    {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("Status {} already exists.", new_status_type.name),
+        });
+    }
+    let status_type = repositories.status_types.insert(new_status_type).await?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "create",
+            entity_type: "status_type",
+            entity_id: status_type.id,
+            before: None,
+            after: Some(&status_type),
+        },
+    )
+    .await;
+    Ok(status_type)
+}
+
+/// The tenant's own status type, the shared ones can't be changed.
+async fn get_existing_status_type(
+    repositories: &Repositories,
+    caller: &Caller,
+    id: i32,
+) -> Result<StatusType, NinoverseApiError> {
+    if !caller.is_admin() {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: "Only administrators can change status types.".to_string(),
+        });
+    }
+    repositories
+        .status_types
+        .get(id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Status type {} does not exist or is shared.", id),
+        })
+}
+
+/// Changes the status type for `PUT /status_types/{id}` and the gRPC API.
+/// Projects, tasks, their history and the transitions refer to it, it is
+/// only renamed while none of them does.
+pub(crate) async fn update_existing_status_type(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    id: i32,
+    update: &StatusTypeUpdate,
+) -> Result<StatusType, NinoverseApiError> {
+    let current = get_existing_status_type(repositories, caller, id).await?;
+    if let Some(name) = update.name.as_ref().filter(|name| **name != current.name) {
+        if repositories.status_types.exists(name).await? {
+            return Err(NinoverseApiError::ValidationError {
+                additional_info: format!("Status {} already exists.", name),
+            });
+        }
+        if repositories.status_types.in_use(&current).await? {
+            return Err(NinoverseApiError::ValidationError {
+                additional_info: format!("Status {} is in use.", current.name),
+            });
+        }
+    }
+    let status_type = repositories
+        .status_types
+        .update(id, update)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Status type {} does not exist or is shared.", id),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "update",
+            entity_type: "status_type",
+            entity_id: status_type.id,
+            before: Some(&current),
+            after: Some(&status_type),
+        },
+    )
+    .await;
+    Ok(status_type)
+}
+
+/// Deletes the status type for `DELETE /status_types/{id}` and the gRPC API,
+/// as long as nothing refers to it.
+pub(crate) async fn delete_existing_status_type(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    id: i32,
+) -> Result<StatusType, NinoverseApiError> {
+    let current = get_existing_status_type(repositories, caller, id).await?;
+    if repositories.status_types.in_use(&current).await? {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("Status {} is in use.", current.name),
+        });
+    }
+    let status_type =
+        repositories
+            .status_types
+            .delete(id)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Status type {} does not exist or is shared.", id),
+            })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "status_type",
+            entity_id: status_type.id,
+            before: Some(&status_type),
+            after: None,
+        },
+    )
+    .await;
+    Ok(status_type)
+}
+
+#[utoipa::path(
+    tag = "status_types",
+    responses(
+        (status = 200, description = "Every status type.", body = [StatusType]),
+    )
+)]
+#[get("/status_types")]
+async fn get_status_types(repositories: Repositories) -> Result<HttpResponse, NinoverseApiError> {
+    let status_types = repositories.status_types.list().await?;
+    Ok(HttpResponse::Ok().json(status_types))
+}
+
+#[utoipa::path(
+    tag = "status_types",
+    responses(
+        (status = 201, description = "The created status type.", body = StatusType),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/status_types")]
+async fn create_status_type(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    new_status_type: web::Json<NewStatusType>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let status_type = insert_status_type(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        &new_status_type,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(status_type))
+}
+
+#[utoipa::path(
+    tag = "status_types",
+    responses(
+        (status = 200, description = "The updated status type.", body = StatusType),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 404, description = "The status type does not exist or is shared.", body = ErrorBody),
+        (status = 422, description = "The name is taken or the renamed status type is in use.", body = ErrorBody),
+    )
+)]
+#[put("/status_types/{id}")]
+async fn update_status_type(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    update: web::Json<StatusTypeUpdate>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let status_type = update_existing_status_type(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id.into_inner(),
+        &update,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(status_type))
+}
+
+#[utoipa::path(
+    tag = "status_types",
+    responses(
+        (status = 204, description = "The status type was deleted."),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 404, description = "The status type does not exist or is shared.", body = ErrorBody),
+        (status = 422, description = "The status type is in use.", body = ErrorBody),
+    )
+)]
+#[delete("/status_types/{id}")]
+async fn delete_status_type(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    delete_existing_status_type(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id.into_inner(),
+    )
+    .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "status_types",
+    responses(
+        (status = 200, description = "Every allowed status transition.", body = [StatusTransition]),
+    )
+)]
+#[get("/status_transitions")]
+async fn get_status_transitions(
+    repositories: Repositories,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let transitions = repositories.status_types.list_transitions().await?;
+    Ok(HttpResponse::Ok().json(transitions))
+}
@@ -0,0 +1,431 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+    tasks::{get_authorized_project, get_existing_task},
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{ItemKind, NewTag, Tag, TagMatch, TagTargets, TagUpdate},
+    },
+    permission_handler::{Caller, Permission},
+};
+
+fn check_name(name: &str) -> Result<(), NinoverseApiError> {
+    if name.trim().is_empty() || name.contains(',') {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The tag name can't be empty or contain commas.".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn check_color(color: &str) -> Result<(), NinoverseApiError> {
+    let is_hex_code = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|digit| digit.is_ascii_hexdigit());
+    if !is_hex_code {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("The color must be a #rrggbb hex code, not {}.", color),
+        });
+    }
+    Ok(())
+}
+
+async fn get_existing_tag(repositories: &Repositories, id: i32) -> Result<Tag, NinoverseApiError> {
+    repositories
+        .tags
+        .get(id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Tag {} does not exist.", id),
+        })
+}
+
+/// Renaming, recolouring and deleting a tag changes projects the caller may
+/// not see.
+fn check_admin(caller: &Caller, action: &str) -> Result<(), NinoverseApiError> {
+    if !caller.is_admin() {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: format!("Only administrators can {} tags.", action),
+        });
+    }
+    Ok(())
+}
+
+/// The ids of the items matching a `tags` filter, `None` without filter.
+/// Unknown tag names are rejected rather than matching nothing.
+pub(super) async fn tagged_items(
+    repositories: &Repositories,
+    kind: ItemKind,
+    tags: Option<&str>,
+    tag_match: TagMatch,
+) -> Result<Option<BTreeSet<i32>>, NinoverseApiError> {
+    let Some(tags) = tags else {
+        return Ok(None);
+    };
+    let tag_ids: BTreeMap<String, i32> = repositories
+        .tags
+        .list()
+        .await?
+        .into_iter()
+        .map(|tag| (tag.name, tag.id))
+        .collect();
+    let mut wanted_ids = BTreeSet::new();
+    for name in tags
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let tag_id = tag_ids
+            .get(name)
+            .ok_or_else(|| NinoverseApiError::ValidationError {
+                additional_info: format!("Tag {} does not exist.", name),
+            })?;
+        wanted_ids.insert(*tag_id);
+    }
+    if wanted_ids.is_empty() {
+        return Ok(None);
+    }
+    let mut item_tags: BTreeMap<i32, BTreeSet<i32>> = BTreeMap::new();
+    for assignment in repositories.tags.list_assignments(kind).await? {
+        if wanted_ids.contains(&assignment.tag_id) {
+            item_tags
+                .entry(assignment.item_id)
+                .or_default()
+                .insert(assignment.tag_id);
+        }
+    }
+    Ok(Some(
+        item_tags
+            .into_iter()
+            .filter(|(_, tag_ids)| tag_match == TagMatch::Any || *tag_ids == wanted_ids)
+            .map(|(item_id, _)| item_id)
+            .collect(),
+    ))
+}
+
+async fn item_tags(
+    repositories: &Repositories,
+    kind: ItemKind,
+    item_id: i32,
+) -> Result<Vec<Tag>, NinoverseApiError> {
+    let tag_ids: BTreeSet<i32> = repositories
+        .tags
+        .list_assignments(kind)
+        .await?
+        .into_iter()
+        .filter(|assignment| assignment.item_id == item_id)
+        .map(|assignment| assignment.tag_id)
+        .collect();
+    Ok(repositories
+        .tags
+        .list()
+        .await?
+        .into_iter()
+        .filter(|tag| tag_ids.contains(&tag.id))
+        .collect())
+}
+
+/// Tagging changes the items, so every one of them needs the write
+/// permission on its project.
+async fn check_targets(
+    repositories: &Repositories,
+    caller: &Caller,
+    targets: &TagTargets,
+) -> Result<(), NinoverseApiError> {
+    for project_id in &targets.project_ids {
+        get_authorized_project(repositories, caller, *project_id, Permission::Write).await?;
+    }
+    for task_id in &targets.task_ids {
+        let task = repositories.tasks.find(*task_id).await?.ok_or_else(|| {
+            NinoverseApiError::NotFound {
+                additional_info: format!("Task {} does not exist.", task_id),
+            }
+        })?;
+        get_authorized_project(repositories, caller, task.project_id, Permission::Write).await?;
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 200, description = "Every tag of the tenant.", body = [Tag]),
+    )
+)]
+#[get("/tags")]
+async fn get_tags(repositories: Repositories) -> Result<HttpResponse, NinoverseApiError> {
+    let tags = repositories.tags.list().await?;
+    Ok(HttpResponse::Ok().json(tags))
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 201, description = "The created tag.", body = Tag),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/tags")]
+async fn create_tag(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    new_tag: web::Json<NewTag>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_name(&new_tag.name)?;
+    check_color(&new_tag.color)?;
+    let tag = repositories.tags.insert(&new_tag).await?.ok_or_else(|| {
+        NinoverseApiError::ValidationError {
+            additional_info: format!("Tag {} already exists.", new_tag.name),
+        }
+    })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "create",
+            entity_type: "tag",
+            entity_id: tag.id,
+            before: None,
+            after: Some(&tag),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Created().json(tag))
+}
+
+/// Items reference their tags by id, a renamed tag is renamed everywhere.
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 200, description = "The updated tag.", body = Tag),
+        (status = 404, description = "The tag does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[put("/tags/{id}")]
+async fn update_tag(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    update: web::Json<TagUpdate>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller, "change")?;
+    let current = get_existing_tag(&repositories, id.into_inner()).await?;
+    if let Some(name) = &update.name {
+        check_name(name)?;
+        if repositories
+            .tags
+            .list()
+            .await?
+            .iter()
+            .any(|tag| tag.name == *name && tag.id != current.id)
+        {
+            return Err(NinoverseApiError::ValidationError {
+                additional_info: format!("Tag {} already exists.", name),
+            });
+        }
+    }
+    if let Some(color) = &update.color {
+        check_color(color)?;
+    }
+    let tag = repositories
+        .tags
+        .update(current.id, &update)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Tag {} does not exist.", current.id),
+        })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "update",
+            entity_type: "tag",
+            entity_id: tag.id,
+            before: Some(&current),
+            after: Some(&tag),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(tag))
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 204, description = "The tag was deleted."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The tag does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/tags/{id}")]
+async fn delete_tag(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller, "delete")?;
+    let id = id.into_inner();
+    let tag = repositories
+        .tags
+        .delete(id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Tag {} does not exist.", id),
+        })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "tag",
+            entity_id: tag.id,
+            before: Some(&tag),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 204, description = "The tag was assigned."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The tag or one of the items does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[post("/tags/{id}/assign")]
+async fn assign_tag(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    targets: web::Json<TagTargets>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let tag = get_existing_tag(&repositories, id.into_inner()).await?;
+    check_targets(&repositories, &caller, &targets).await?;
+    repositories
+        .tags
+        .assign(ItemKind::Project, tag.id, &targets.project_ids)
+        .await?;
+    repositories
+        .tags
+        .assign(ItemKind::Task, tag.id, &targets.task_ids)
+        .await?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "assign",
+            entity_type: "tag",
+            entity_id: tag.id,
+            before: None,
+            after: Some(&targets.into_inner()),
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 204, description = "The tag was removed."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The tag or one of the items does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[post("/tags/{id}/unassign")]
+async fn unassign_tag(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    targets: web::Json<TagTargets>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let tag = get_existing_tag(&repositories, id.into_inner()).await?;
+    check_targets(&repositories, &caller, &targets).await?;
+    repositories
+        .tags
+        .unassign(ItemKind::Project, tag.id, &targets.project_ids)
+        .await?;
+    repositories
+        .tags
+        .unassign(ItemKind::Task, tag.id, &targets.task_ids)
+        .await?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "unassign",
+            entity_type: "tag",
+            entity_id: tag.id,
+            before: Some(&targets.into_inner()),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 200, description = "The tags of the project.", body = [Tag]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tags")]
+async fn get_project_tags(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Read).await?;
+    let tags = item_tags(&repositories, ItemKind::Project, project.id).await?;
+    Ok(HttpResponse::Ok().json(tags))
+}
+
+#[utoipa::path(
+    tag = "tags",
+    responses(
+        (status = 200, description = "The tags of the task.", body = [Tag]),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}/tags")]
+async fn get_task_tags(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    let tags = item_tags(&repositories, ItemKind::Task, task.id).await?;
+    Ok(HttpResponse::Ok().json(tags))
+}
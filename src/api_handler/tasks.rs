@@ -0,0 +1,514 @@
+use std::collections::BTreeMap;
+
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+    status_types::completed_statuses,
+    tags::tagged_items,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{
+            ItemKind, NewTask, Project, TASK_PRIORITIES, TASK_STATUSES, TagFilter, Task, TaskOrder,
+            TaskUpdate,
+        },
+    },
+    kafka_handler::{publish_event, structs::KafkaNinoverseEvent},
+    permission_handler::{Caller, Permission},
+};
+
+/// Tasks of deleted projects can be read but not changed.
+pub(crate) async fn get_authorized_project(
+    repositories: &Repositories,
+    caller: &Caller,
+    id: i32,
+    permission: Permission,
+) -> Result<Project, NinoverseApiError> {
+    let project = repositories
+        .projects
+        .get(id, permission == Permission::Read)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Project {} does not exist.", id),
+        })?;
+    caller.authorize(&project, permission)?;
+    Ok(project)
+}
+
+pub(crate) async fn get_existing_task(
+    repositories: &Repositories,
+    project_id: i32,
+    id: i32,
+) -> Result<Task, NinoverseApiError> {
+    repositories
+        .tasks
+        .get(project_id, id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Task {} does not exist in project {}.", id, project_id),
+        })
+}
+
+fn check_choice(field: &str, value: &str, choices: &[&str]) -> Result<(), NinoverseApiError> {
+    if choices.contains(&value) {
+        Ok(())
+    } else {
+        Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "The {} must be one of {}, not {}.",
+                field,
+                choices.join(", "),
+                value
+            ),
+        })
+    }
+}
+
+/// A status of `TASK_STATUSES` or of a completed status type.
+async fn check_status(repositories: &Repositories, status: &str) -> Result<(), NinoverseApiError> {
+    if TASK_STATUSES.contains(&status) {
+        return Ok(());
+    }
+    let completed_statuses = completed_statuses(repositories).await?;
+    let mut choices: Vec<&str> = TASK_STATUSES.to_vec();
+    choices.extend(
+        completed_statuses
+            .iter()
+            .map(String::as_str)
+            .filter(|completed| !TASK_STATUSES.contains(completed)),
+    );
+    check_choice("status", status, &choices)
+}
+
+async fn check_assignee(
+    repositories: &Repositories,
+    assignee_id: Option<i32>,
+) -> Result<(), NinoverseApiError> {
+    if let Some(assignee_id) = assignee_id
+        && repositories.users.get(assignee_id).await?.is_none()
+    {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("User {} does not exist.", assignee_id),
+        });
+    }
+    Ok(())
+}
+
+/// The parent must be a task of the same project, and moving a task under one
+/// of its own subtasks would detach the whole branch from the project.
+async fn check_parent(
+    repositories: &Repositories,
+    project_id: i32,
+    task_id: Option<i32>,
+    parent_id: Option<i32>,
+) -> Result<(), NinoverseApiError> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    let parents: BTreeMap<i32, Option<i32>> = repositories
+        .tasks
+        .list(project_id)
+        .await?
+        .into_iter()
+        .map(|task| (task.id, task.parent_id))
+        .collect();
+    if !parents.contains_key(&parent_id) {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "Task {} does not exist in project {}.",
+                parent_id, project_id
+            ),
+        });
+    }
+    let Some(task_id) = task_id else {
+        return Ok(());
+    };
+    let mut ancestor = Some(parent_id);
+    while let Some(ancestor_id) = ancestor {
+        if ancestor_id == task_id {
+            return Err(NinoverseApiError::ValidationError {
+                additional_info: format!(
+                    "Task {} can't be nested under itself or its subtask {}.",
+                    task_id, parent_id
+                ),
+            });
+        }
+        ancestor = parents.get(&ancestor_id).copied().flatten();
+    }
+    Ok(())
+}
+
+/// Creates the task for `POST /projects/{id}/tasks` and the GraphQL API.
+pub(crate) async fn insert_task(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    project_id: i32,
+    new_task: &NewTask,
+) -> Result<Task, NinoverseApiError> {
+    let project =
+        get_authorized_project(repositories, caller, project_id, Permission::Write).await?;
+    if new_task.title.trim().is_empty() {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The task title can't be empty.".to_string(),
+        });
+    }
+    check_status(repositories, &new_task.status).await?;
+    check_choice("priority", &new_task.priority, &TASK_PRIORITIES)?;
+    check_assignee(repositories, new_task.assignee_id).await?;
+    check_parent(repositories, project.id, None, new_task.parent_id).await?;
+    let task = repositories.tasks.insert(project.id, new_task).await?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "create",
+            entity_type: "task",
+            entity_id: task.id,
+            before: None,
+            after: Some(&task),
+        },
+    )
+    .await;
+    publish_event(
+        kafka_thread_sender,
+        &repositories.tenant_id,
+        KafkaNinoverseEvent::TaskCreated {
+            task: task.clone(),
+            actor: context.actor.clone(),
+        },
+    )
+    .await;
+    Ok(task)
+}
+
+pub(crate) async fn update_existing_task(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    project_id: i32,
+    task_id: i32,
+    update: &TaskUpdate,
+) -> Result<Task, NinoverseApiError> {
+    let project =
+        get_authorized_project(repositories, caller, project_id, Permission::Write).await?;
+    let current = get_existing_task(repositories, project.id, task_id).await?;
+    if update
+        .title
+        .as_ref()
+        .is_some_and(|title| title.trim().is_empty())
+    {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The task title can't be empty.".to_string(),
+        });
+    }
+    if let Some(status) = &update.status {
+        check_status(repositories, status).await?;
+    }
+    if let Some(priority) = &update.priority {
+        check_choice("priority", priority, &TASK_PRIORITIES)?;
+    }
+    check_assignee(repositories, update.assignee_id).await?;
+    check_parent(repositories, project.id, Some(current.id), update.parent_id).await?;
+    let task = repositories
+        .tasks
+        .update(project.id, current.id, update)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!(
+                "Task {} does not exist in project {}.",
+                current.id, project.id
+            ),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "update",
+            entity_type: "task",
+            entity_id: task.id,
+            before: Some(&current),
+            after: Some(&task),
+        },
+    )
+    .await;
+    publish_event(
+        kafka_thread_sender,
+        &repositories.tenant_id,
+        KafkaNinoverseEvent::TaskUpdated {
+            task: task.clone(),
+            actor: context.actor.clone(),
+        },
+    )
+    .await;
+    Ok(task)
+}
+
+/// Deletes the task and its subtasks, returns the deleted task.
+pub(crate) async fn delete_existing_task(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    project_id: i32,
+    task_id: i32,
+) -> Result<Task, NinoverseApiError> {
+    let project =
+        get_authorized_project(repositories, caller, project_id, Permission::Write).await?;
+    let task = repositories
+        .tasks
+        .delete(project.id, task_id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Task {} does not exist in project {}.", task_id, project.id),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "task",
+            entity_id: task.id,
+            before: Some(&task),
+            after: None,
+        },
+    )
+    .await;
+    publish_event(
+        kafka_thread_sender,
+        &repositories.tenant_id,
+        KafkaNinoverseEvent::TaskDeleted {
+            task: task.clone(),
+            actor: context.actor.clone(),
+        },
+    )
+    .await;
+    Ok(task)
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    params(TagFilter),
+    responses(
+        (status = 200, description = "The tasks of the project, by position.", body = [Task]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks")]
+async fn get_tasks(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+    filter: web::Query<TagFilter>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Read).await?;
+    let tagged_ids = tagged_items(
+        &repositories,
+        ItemKind::Task,
+        filter.tags.as_deref(),
+        filter.tag_match,
+    )
+    .await?;
+    let tasks: Vec<Task> = repositories
+        .tasks
+        .list(project.id)
+        .await?
+        .into_iter()
+        .filter(|task| tagged_ids.as_ref().is_none_or(|ids| ids.contains(&task.id)))
+        .collect();
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    responses(
+        (status = 200, description = "The task.", body = Task),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/tasks/{task_id}")]
+async fn get_task(
+    repositories: Repositories,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let project = get_authorized_project(&repositories, &caller, id, Permission::Read).await?;
+    let task = get_existing_task(&repositories, project.id, task_id).await?;
+    Ok(HttpResponse::Ok().json(task))
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    responses(
+        (status = 201, description = "The created task.", body = Task),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/tasks")]
+async fn create_task(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    new_task: web::Json<NewTask>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let task = insert_task(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id.into_inner(),
+        &new_task,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(task))
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    responses(
+        (status = 200, description = "The updated task.", body = Task),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[put("/projects/{id}/tasks/{task_id}")]
+async fn update_task(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+    update: web::Json<TaskUpdate>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    let task = update_existing_task(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id,
+        task_id,
+        &update,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(task))
+}
+
+/// Deletes the task and its subtasks.
+#[utoipa::path(
+    tag = "tasks",
+    responses(
+        (status = 204, description = "The task and its subtasks were deleted."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The task does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/projects/{id}/tasks/{task_id}")]
+async fn delete_task(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, i32)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, task_id) = path.into_inner();
+    delete_existing_task(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id,
+        task_id,
+    )
+    .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[utoipa::path(
+    tag = "tasks",
+    responses(
+        (status = 200, description = "The tasks of the project in their new order.", body = [Task]),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The ids are not every task of the project.", body = ErrorBody),
+    )
+)]
+// Registered before the `/tasks/{task_id}` routes so that `order` is not
+// taken for a task id.
+#[put("/projects/{id}/tasks/order")]
+async fn reorder_tasks(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    order: web::Json<TaskOrder>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let project =
+        get_authorized_project(&repositories, &caller, id.into_inner(), Permission::Write).await?;
+    let current_ids: Vec<i32> = repositories
+        .tasks
+        .list(project.id)
+        .await?
+        .iter()
+        .map(|task| task.id)
+        .collect();
+    let mut sorted_current_ids = current_ids.clone();
+    sorted_current_ids.sort_unstable();
+    let mut sorted_requested_ids = order.task_ids.clone();
+    sorted_requested_ids.sort_unstable();
+    if sorted_current_ids != sorted_requested_ids {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "The order must list every task of project {} exactly once.",
+                project.id
+            ),
+        });
+    }
+    let tasks = repositories
+        .tasks
+        .reorder(project.id, &order.task_ids)
+        .await?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "reorder_tasks",
+            entity_type: "project",
+            entity_id: project.id,
+            before: Some(&current_ids),
+            after: Some(&order.task_ids),
+        },
+    )
+    .await;
+    publish_event(
+        &kafka_thread_sender,
+        &repositories.tenant_id,
+        KafkaNinoverseEvent::TasksReordered {
+            project_id: project.id,
+            task_ids: order.task_ids.clone(),
+            actor: context.actor.clone(),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(tasks))
+}
@@ -0,0 +1,230 @@
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::Sender;
+use utoipa::ToSchema;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{Membership, NewTeam, Team},
+    },
+    permission_handler::{Caller, Role},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MembershipRequest {
+    pub role: Role,
+}
+
+async fn get_existing_team(
+    repositories: &Repositories,
+    id: i32,
+) -> Result<Team, NinoverseApiError> {
+    repositories
+        .teams
+        .get(id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Team {} does not exist.", id),
+        })
+}
+
+fn check_team_admin(caller: &Caller, team: &Team) -> Result<(), NinoverseApiError> {
+    if caller.team_role(team.id) == Some(Role::Admin) {
+        Ok(())
+    } else {
+        Err(NinoverseApiError::Forbidden {
+            additional_info: format!("Only admins of team {} can manage its members.", team.id),
+        })
+    }
+}
+
+#[utoipa::path(
+    tag = "teams",
+    responses(
+        (status = 200, description = "Every team of the tenant.", body = [Team]),
+    )
+)]
+#[get("/teams")]
+async fn get_teams(repositories: Repositories) -> Result<HttpResponse, NinoverseApiError> {
+    let teams = repositories.teams.list().await?;
+    Ok(HttpResponse::Ok().json(teams))
+}
+
+/// The creator becomes admin of the team.
+#[utoipa::path(
+    tag = "teams",
+    responses(
+        (status = 201, description = "The created team, the caller being its admin.", body = Team),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/teams")]
+async fn create_team(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    new_team: web::Json<NewTeam>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    if new_team.name.trim().is_empty() {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: "The team name can't be empty.".to_string(),
+        });
+    }
+    let team = repositories.teams.insert(&new_team).await?.ok_or_else(|| {
+        NinoverseApiError::ValidationError {
+            additional_info: format!("Team {} already exists.", new_team.name),
+        }
+    })?;
+    if let Some(user) = caller.user() {
+        repositories
+            .teams
+            .set_member(team.id, user.id, Role::Admin.as_str())
+            .await?;
+    }
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "create",
+            entity_type: "team",
+            entity_id: team.id,
+            before: None,
+            after: Some(&team),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Created().json(team))
+}
+
+#[utoipa::path(
+    tag = "teams",
+    responses(
+        (status = 200, description = "The members of the team.", body = [Membership]),
+        (status = 404, description = "The team does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/teams/{id}/members")]
+async fn get_team_members(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let team = get_existing_team(&repositories, id.into_inner()).await?;
+    if caller.team_role(team.id).is_none() {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: format!("Only members of team {} can list its members.", team.id),
+        });
+    }
+    let members = repositories.teams.list_members(team.id).await?;
+    Ok(HttpResponse::Ok().json(members))
+}
+
+/// Adds the user to the team or changes its role, users that never called
+/// the API yet are created.
+#[utoipa::path(
+    tag = "teams",
+    responses(
+        (status = 200, description = "The membership.", body = Membership),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The team does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[put("/teams/{id}/members/{subject}")]
+async fn set_team_member(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, String)>,
+    request: web::Json<MembershipRequest>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, subject) = path.into_inner();
+    let team = get_existing_team(&repositories, id).await?;
+    check_team_admin(&caller, &team)?;
+    let user = match repositories.users.find_by_subject(&subject).await? {
+        Some(user) => user,
+        None => repositories.users.upsert(&subject, false).await?,
+    };
+    let before = repositories
+        .teams
+        .list_members(team.id)
+        .await?
+        .into_iter()
+        .find(|membership| membership.user_id == user.id);
+    let membership = repositories
+        .teams
+        .set_member(team.id, user.id, request.role.as_str())
+        .await?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "set_member",
+            entity_type: "team",
+            entity_id: team.id,
+            before: before.as_ref(),
+            after: Some(&membership),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(membership))
+}
+
+#[utoipa::path(
+    tag = "teams",
+    responses(
+        (status = 204, description = "The member was removed."),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The membership does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/teams/{id}/members/{subject}")]
+async fn remove_team_member(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    path: web::Path<(i32, String)>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (id, subject) = path.into_inner();
+    let team = get_existing_team(&repositories, id).await?;
+    check_team_admin(&caller, &team)?;
+    let not_a_member = || NinoverseApiError::NotFound {
+        additional_info: format!("{} is not a member of team {}.", subject, team.id),
+    };
+    let user = repositories
+        .users
+        .find_by_subject(&subject)
+        .await?
+        .ok_or_else(not_a_member)?;
+    let membership = repositories
+        .teams
+        .remove_member(team.id, user.id)
+        .await?
+        .ok_or_else(not_a_member)?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "remove_member",
+            entity_type: "team",
+            entity_id: team.id,
+            before: Some(&membership),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
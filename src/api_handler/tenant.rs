@@ -0,0 +1,151 @@
+use std::future::{Ready, ready};
+
+use actix_web::{
+    Error, FromRequest, HttpMessage, HttpRequest,
+    body::{EitherBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web,
+};
+
+use super::{
+    auth::{AuthSettings, PUBLIC_PATHS, Principal},
+    error::NinoverseApiError,
+};
+use crate::{
+    configuration_handler::get_tenant_base_domain,
+    db_handler::repository::{DEFAULT_TENANT, Repositories},
+};
+
+pub(crate) const TENANT_HEADER: &str = "x-tenant-id";
+
+/// The tenant the request acts on, attached to the request by
+/// `tenant_middleware`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTenant(pub String);
+
+/// Lowercase letters, digits, `-` and `_`, starting with a letter or a digit,
+/// so that it fits in a host name and a Kafka topic name.
+pub fn is_valid_tenant_id(id: &str) -> bool {
+    (1..=64).contains(&id.len())
+        && id.starts_with(|first: char| first.is_ascii_lowercase() || first.is_ascii_digit())
+        && id.chars().all(|character| {
+            character.is_ascii_lowercase()
+                || character.is_ascii_digit()
+                || character == '-'
+                || character == '_'
+        })
+}
+
+/// The tenant named by the `X-Tenant-Id` header or, when
+/// `TENANT_BASE_DOMAIN` is set, by the subdomain the request was sent to.
+pub(super) fn requested_tenant(request: &ServiceRequest) -> Option<String> {
+    if let Some(tenant_id) = request
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        return Some(tenant_id.to_string());
+    }
+    let base_domain = get_tenant_base_domain()?;
+    let connection_info = request.connection_info();
+    let host = connection_info.host();
+    let host = host.split_once(':').map_or(host, |(name, _)| name);
+    host.strip_suffix(base_domain.as_str())
+        .and_then(|subdomain| subdomain.strip_suffix('.'))
+        .filter(|subdomain| !subdomain.is_empty() && !subdomain.contains('.'))
+        .map(String::from)
+}
+
+/// A tenant bound to the credentials (JWT `tenant` claim, API key) wins over
+/// the requested one, a conflicting request is rejected. Tokens without a
+/// `tenant` claim are bound to the default tenant, only administrators pick
+/// the tenant they act on with them.
+pub(crate) async fn resolve_tenant_of(
+    repositories: &Repositories,
+    settings: &AuthSettings,
+    requested: Option<String>,
+    principal: Option<&Principal>,
+) -> Result<String, NinoverseApiError> {
+    let credential_tenant = principal.and_then(|principal| match &principal.tenant {
+        Some(tenant_id) => Some(tenant_id.clone()),
+        None if settings.admin_subjects.contains(&principal.subject) => None,
+        None => Some(DEFAULT_TENANT.to_string()),
+    });
+    let tenant_id = match (requested, credential_tenant) {
+        (Some(requested), Some(credential)) if requested != credential => {
+            return Err(NinoverseApiError::Unauthorized {
+                additional_info: format!(
+                    "The credentials were issued for tenant {}, not {}.",
+                    credential, requested
+                ),
+            });
+        }
+        (_, Some(credential)) => credential,
+        (Some(requested), None) => requested,
+        (None, None) => DEFAULT_TENANT.to_string(),
+    };
+    if !is_valid_tenant_id(&tenant_id) || repositories.tenants.get(&tenant_id).await?.is_none() {
+        return Err(NinoverseApiError::NotFound {
+            additional_info: format!("Tenant {} does not exist.", tenant_id),
+        });
+    }
+    Ok(tenant_id)
+}
+
+async fn resolve_tenant(request: &ServiceRequest) -> Result<String, NinoverseApiError> {
+    let principal = request.extensions().get::<Principal>().cloned();
+    let repositories = request
+        .app_data::<web::Data<Repositories>>()
+        .expect("TENANT: Repositories missing from the app data");
+    let settings = request
+        .app_data::<web::Data<AuthSettings>>()
+        .expect("TENANT: Settings missing from the app data");
+    resolve_tenant_of(
+        repositories,
+        settings,
+        requested_tenant(request),
+        principal.as_ref(),
+    )
+    .await
+}
+
+/// Resolves the tenant of the request and stores it in the request
+/// extensions. Requests for an unknown tenant are answered with 404.
+pub async fn tenant_middleware(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if PUBLIC_PATHS.contains(&request.path()) {
+        return Ok(next.call(request).await?.map_into_left_body());
+    }
+    match resolve_tenant(&request).await {
+        Ok(tenant_id) => {
+            request.extensions_mut().insert(RequestTenant(tenant_id));
+            Ok(next.call(request).await?.map_into_left_body())
+        }
+        Err(tenant_error) => Ok(request.error_response(tenant_error).map_into_right_body()),
+    }
+}
+
+/// The repositories of the request tenant.
+pub(super) fn tenant_repositories(request: &HttpRequest) -> Repositories {
+    let repositories = request
+        .app_data::<web::Data<Repositories>>()
+        .expect("TENANT: Repositories missing from the app data");
+    match request.extensions().get::<RequestTenant>() {
+        Some(tenant) => repositories.for_tenant(&tenant.0),
+        None => repositories.get_ref().clone(),
+    }
+}
+
+impl FromRequest for Repositories {
+    type Error = NinoverseApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(tenant_repositories(request)))
+    }
+}
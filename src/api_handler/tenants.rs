@@ -0,0 +1,82 @@
+use actix_web::{HttpResponse, get, post, web};
+
+use super::{
+    error::{ErrorBody, NinoverseApiError},
+    tenant::is_valid_tenant_id,
+};
+use crate::{
+    db_handler::{
+        repository::Repositories,
+        structs::{NewTenant, Tenant},
+    },
+    kafka_handler::{init_tenant_topics, message_bus::MessageBus},
+    permission_handler::Caller,
+};
+
+fn check_admin(caller: &Caller) -> Result<(), NinoverseApiError> {
+    if caller.is_admin() {
+        Ok(())
+    } else {
+        Err(NinoverseApiError::Forbidden {
+            additional_info: "Only administrators can manage tenants.".to_string(),
+        })
+    }
+}
+
+#[utoipa::path(
+    tag = "tenants",
+    responses(
+        (status = 200, description = "Every tenant.", body = [Tenant]),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+    )
+)]
+#[get("/tenants")]
+async fn get_tenants(
+    repositories: Repositories,
+    caller: Caller,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    let tenants = repositories.tenants.list().await?;
+    Ok(HttpResponse::Ok().json(tenants))
+}
+
+#[utoipa::path(
+    tag = "tenants",
+    responses(
+        (status = 201, description = "The created tenant.", body = Tenant),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/tenants")]
+async fn create_tenant(
+    repositories: Repositories,
+    message_bus: web::Data<dyn MessageBus>,
+    caller: Caller,
+    new_tenant: web::Json<NewTenant>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    if !is_valid_tenant_id(&new_tenant.id) {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "Tenant id {} must be 1 to 64 lowercase letters, digits, - or _.",
+                new_tenant.id
+            ),
+        });
+    }
+    let tenant = repositories
+        .tenants
+        .insert(&new_tenant)
+        .await?
+        .ok_or_else(|| NinoverseApiError::ValidationError {
+            additional_info: format!("Tenant {} already exists.", new_tenant.id),
+        })?;
+    // The tenant exists either way, its topics are created again at startup.
+    if let Err(bus_error) = init_tenant_topics(message_bus.as_ref(), &tenant.id).await {
+        println!(
+            "TENANTS: Error creating the topics of tenant {}: {}",
+            tenant.id, bus_error
+        );
+    }
+    Ok(HttpResponse::Created().json(tenant))
+}
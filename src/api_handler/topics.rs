@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use actix_web::{HttpResponse, delete, get, post, put, web};
+
+use super::error::{ErrorBody, NinoverseApiError};
+use crate::{
+    db_handler::repository::{DEFAULT_TENANT, Repositories},
+    kafka_handler::{
+        is_service_topic,
+        message_bus::{
+            EnsuredTopic, MessageBus, PartitionCount, TopicDescription, TopicSettings, ensure_topic,
+        },
+    },
+    permission_handler::Caller,
+};
+
+/// Topics are shared by every tenant, they are managed by the administrators
+/// of the default tenant, who run the service.
+fn check_admin(repositories: &Repositories, caller: &Caller) -> Result<(), NinoverseApiError> {
+    if !caller.is_admin() || repositories.tenant_id != DEFAULT_TENANT {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: "Only administrators of the default tenant can manage topics."
+                .to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// The topics of the service are changed with the command line, a request
+/// can't alter or delete them from under the running instances.
+fn check_not_service_topic(name: &str) -> Result<(), NinoverseApiError> {
+    if is_service_topic(name) {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: format!("Topic {} is used by the service.", name),
+        });
+    }
+    Ok(())
+}
+
+/// Without their configs, see `GET /topics/{name}`.
+#[utoipa::path(
+    tag = "topics",
+    responses(
+        (status = 200, description = "Every topic but the internal ones of the broker.", body = [TopicDescription]),
+        (status = 403, description = "The caller is not an administrator of the default tenant.", body = ErrorBody),
+    )
+)]
+#[get("/topics")]
+async fn get_topics(
+    message_bus: web::Data<dyn MessageBus>,
+    repositories: Repositories,
+    caller: Caller,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&repositories, &caller)?;
+    let topics = message_bus.list_topics().await?;
+    Ok(HttpResponse::Ok().json(topics))
+}
+
+#[utoipa::path(
+    tag = "topics",
+    responses(
+        (status = 200, description = "The topic with its partitions and configs.", body = TopicDescription),
+        (status = 403, description = "The caller is not an administrator of the default tenant.", body = ErrorBody),
+        (status = 404, description = "The topic does not exist.", body = ErrorBody),
+    )
+)]
+#[get("/topics/{name}")]
+async fn get_topic(
+    message_bus: web::Data<dyn MessageBus>,
+    repositories: Repositories,
+    caller: Caller,
+    name: web::Path<String>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&repositories, &caller)?;
+    let topic = message_bus.describe_topic(&name).await?;
+    Ok(HttpResponse::Ok().json(topic))
+}
+
+/// Creates the topic unless it exists, an existing topic is left as it is
+/// and its differences with the request are reported as drift.
+#[utoipa::path(
+    tag = "topics",
+    request_body = TopicSettings,
+    responses(
+        (status = 201, description = "The topic was created.", body = EnsuredTopic),
+        (status = 200, description = "The topic already existed.", body = EnsuredTopic),
+        (status = 403, description = "The caller is not an administrator of the default tenant.", body = ErrorBody),
+        (status = 422, description = "The broker rejected the settings.", body = ErrorBody),
+    )
+)]
+#[put("/topics/{name}")]
+async fn ensure_topic_exists(
+    message_bus: web::Data<dyn MessageBus>,
+    repositories: Repositories,
+    caller: Caller,
+    name: web::Path<String>,
+    settings: web::Json<TopicSettings>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&repositories, &caller)?;
+    let ensured_topic = ensure_topic(message_bus.as_ref(), &name, &settings).await?;
+    if ensured_topic.created {
+        Ok(HttpResponse::Created().json(ensured_topic))
+    } else {
+        Ok(HttpResponse::Ok().json(ensured_topic))
+    }
+}
+
+/// The configs of the request are set, the others keep their values.
+#[utoipa::path(
+    tag = "topics",
+    request_body = BTreeMap<String, String>,
+    responses(
+        (status = 200, description = "The updated topic.", body = TopicDescription),
+        (status = 403, description = "The caller is not an administrator of the default tenant or the topic is one of the service.", body = ErrorBody),
+        (status = 404, description = "The topic does not exist.", body = ErrorBody),
+        (status = 422, description = "The broker rejected a config.", body = ErrorBody),
+    )
+)]
+#[put("/topics/{name}/configs")]
+async fn update_topic_configs(
+    message_bus: web::Data<dyn MessageBus>,
+    repositories: Repositories,
+    caller: Caller,
+    name: web::Path<String>,
+    configs: web::Json<BTreeMap<String, String>>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&repositories, &caller)?;
+    check_not_service_topic(&name)?;
+    message_bus.alter_topic_configs(&name, &configs).await?;
+    let topic = message_bus.describe_topic(&name).await?;
+    Ok(HttpResponse::Ok().json(topic))
+}
+
+/// Messages keyed before the change may land in another partition after it.
+#[utoipa::path(
+    tag = "topics",
+    request_body = PartitionCount,
+    responses(
+        (status = 200, description = "The topic with its new partitions.", body = TopicDescription),
+        (status = 403, description = "The caller is not an administrator of the default tenant or the topic is one of the service.", body = ErrorBody),
+        (status = 404, description = "The topic does not exist.", body = ErrorBody),
+        (status = 422, description = "The count is not above the current one.", body = ErrorBody),
+    )
+)]
+#[post("/topics/{name}/partitions")]
+async fn add_topic_partitions(
+    message_bus: web::Data<dyn MessageBus>,
+    repositories: Repositories,
+    caller: Caller,
+    name: web::Path<String>,
+    count: web::Json<PartitionCount>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&repositories, &caller)?;
+    check_not_service_topic(&name)?;
+    message_bus.add_partitions(&name, count.partitions).await?;
+    let topic = message_bus.describe_topic(&name).await?;
+    Ok(HttpResponse::Ok().json(topic))
+}
+
+/// The messages of the topic are lost with it.
+#[utoipa::path(
+    tag = "topics",
+    responses(
+        (status = 204, description = "The topic was deleted."),
+        (status = 403, description = "The caller is not an administrator of the default tenant or the topic is one of the service.", body = ErrorBody),
+        (status = 404, description = "The topic does not exist.", body = ErrorBody),
+    )
+)]
+#[delete("/topics/{name}")]
+async fn delete_topic(
+    message_bus: web::Data<dyn MessageBus>,
+    repositories: Repositories,
+    caller: Caller,
+    name: web::Path<String>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&repositories, &caller)?;
+    check_not_service_topic(&name)?;
+    message_bus.delete_topics(&[name.into_inner()]).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,164 @@
+use actix_web::{HttpResponse, get, http::header::IfMatch, post, web};
+use tokio::sync::mpsc::Sender;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    projects::{accepted_versions, project_etag},
+    request_context::RequestContext,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{Project, ProjectStatusHistoryEntry, TransitionRequest},
+    },
+    kafka_handler::{publish_event, structs::KafkaNinoverseEvent},
+    permission_handler::{Caller, Permission},
+};
+
+/// Moves the project to `to_status` for `POST /projects/{id}/transitions` and
+/// the gRPC API. `versions` are the ones the change applies to, any when
+/// `None`.
+pub(crate) async fn transition_project(
+    repositories: &Repositories,
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    context: &RequestContext,
+    caller: &Caller,
+    id: i32,
+    versions: Option<Vec<i32>>,
+    to_status: &str,
+) -> Result<(Project, ProjectStatusHistoryEntry), NinoverseApiError> {
+    let project =
+        repositories
+            .projects
+            .get(id, false)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist.", id),
+            })?;
+    caller.authorize(&project, Permission::Write)?;
+    let transition = repositories
+        .status_types
+        .get_transition(&project.status, to_status)
+        .await?
+        .ok_or_else(|| NinoverseApiError::InvalidTransition {
+            additional_info: format!(
+                "Project {} can't move from {} to {}.",
+                id, project.status, to_status
+            ),
+        })?;
+    let missing_fields = transition.missing_fields(&project);
+    if !missing_fields.is_empty() {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "Moving to {} requires the fields: {}.",
+                to_status,
+                missing_fields.join(", ")
+            ),
+        });
+    }
+    let (updated_project, history_entry) = repositories
+        .projects
+        .transition_status(
+            id,
+            &transition.from_status,
+            &transition.to_status,
+            &context.actor,
+            versions,
+        )
+        .await?
+        .ok_or_else(|| NinoverseApiError::PreconditionFailed {
+            additional_info: format!("Project {} was modified concurrently.", id),
+        })?;
+    record_mutation(
+        repositories,
+        kafka_thread_sender,
+        context,
+        AuditedChange {
+            action: "transition",
+            entity_type: "project",
+            entity_id: id,
+            before: Some(&project),
+            after: Some(&updated_project),
+        },
+    )
+    .await;
+    publish_event(
+        kafka_thread_sender,
+        &repositories.tenant_id,
+        KafkaNinoverseEvent::ProjectStatusChanged {
+            project_id: id,
+            from_status: history_entry.from_status.clone(),
+            to_status: history_entry.to_status.clone(),
+            actor: history_entry.actor.clone(),
+            changed_at: history_entry.created_at,
+        },
+    )
+    .await;
+    Ok((updated_project, history_entry))
+}
+
+#[utoipa::path(
+    tag = "transitions",
+    responses(
+        (status = 200, description = "The status history of the project.", body = [ProjectStatusHistoryEntry]),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/projects/{id}/transitions")]
+async fn get_project_transitions(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let id = id.into_inner();
+    let project =
+        repositories
+            .projects
+            .get(id, true)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist.", id),
+            })?;
+    caller.authorize(&project, Permission::Read)?;
+    let history = repositories.projects.list_status_history(id).await?;
+    Ok(HttpResponse::Ok().json(history))
+}
+
+#[utoipa::path(
+    tag = "transitions",
+    params(("If-Match" = Option<String>, Header, description = "Versions of the project the change applies to, as returned in its `ETag`.")),
+    responses(
+        (status = 201, description = "The recorded transition, the new version of the project in the `ETag` header.", body = ProjectStatusHistoryEntry),
+        (status = 403, description = "The caller lacks the permission.", body = ErrorBody),
+        (status = 404, description = "The project does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 409, description = "The transition is not allowed.", body = ErrorBody),
+        (status = 412, description = "The project was modified since the version of `If-Match`.", body = ErrorBody),
+        (status = 422, description = "Fields the transition requires are empty.", body = ErrorBody),
+    )
+)]
+#[post("/projects/{id}/transitions")]
+async fn create_project_transition(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    if_match: Option<web::Header<IfMatch>>,
+    request: web::Json<TransitionRequest>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    let (updated_project, history_entry) = transition_project(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        &caller,
+        id.into_inner(),
+        accepted_versions(if_match),
+        &request.to_status,
+    )
+    .await?;
+    Ok(HttpResponse::Created()
+        .insert_header(project_etag(&updated_project))
+        .json(history_entry))
+}
@@ -0,0 +1,49 @@
+use actix_web::{HttpResponse, get};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::error::NinoverseApiError;
+use crate::{
+    db_handler::{
+        repository::Repositories,
+        structs::{Membership, User},
+    },
+    permission_handler::Caller,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CurrentUser {
+    #[serde(flatten)]
+    pub user: User,
+    pub memberships: Vec<Membership>,
+}
+
+#[utoipa::path(
+    tag = "users",
+    responses(
+        (status = 200, description = "Every user of the tenant.", body = [User]),
+    )
+)]
+#[get("/users")]
+async fn get_users(repositories: Repositories) -> Result<HttpResponse, NinoverseApiError> {
+    let users = repositories.users.list().await?;
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[utoipa::path(
+    tag = "users",
+    responses(
+        (status = 200, description = "The caller and their team memberships.", body = CurrentUser),
+    )
+)]
+#[get("/users/me")]
+async fn get_current_user(caller: Caller) -> Result<HttpResponse, NinoverseApiError> {
+    match caller {
+        Caller::User { user, memberships } => {
+            Ok(HttpResponse::Ok().json(CurrentUser { user, memberships }))
+        }
+        Caller::Unrestricted => Err(NinoverseApiError::NotFound {
+            additional_info: "Authentication is disabled, there is no current user.".to_string(),
+        }),
+    }
+}
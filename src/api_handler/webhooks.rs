@@ -0,0 +1,298 @@
+use actix_web::{HttpResponse, delete, get, post, put, web};
+use reqwest::Url;
+use serde::Deserialize;
+use tokio::sync::mpsc::Sender;
+use utoipa::IntoParams;
+
+use super::{
+    audit::{AuditedChange, record_mutation},
+    error::{ErrorBody, NinoverseApiError},
+    request_context::RequestContext,
+};
+use crate::{
+    KafkaChannelMessage,
+    db_handler::{
+        repository::Repositories,
+        structs::{NewWebhook, Webhook, WebhookDelivery, WebhookUpdate},
+    },
+    kafka_handler::structs::KafkaNinoverseEvent,
+    permission_handler::Caller,
+};
+
+const MAX_URL_LENGTH: usize = 2048;
+const MIN_SECRET_LENGTH: usize = 16;
+const MAX_SECRET_LENGTH: usize = 255;
+const DEFAULT_DELIVERIES_LIMIT: i64 = 50;
+const MAX_DELIVERIES_LIMIT: i64 = 200;
+
+/// Webhooks receive the events of every project of the tenant.
+fn check_admin(caller: &Caller) -> Result<(), NinoverseApiError> {
+    if !caller.is_admin() {
+        return Err(NinoverseApiError::Forbidden {
+            additional_info: "Only administrators can manage webhooks.".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn check_url(url: &str) -> Result<(), NinoverseApiError> {
+    let valid = url.len() <= MAX_URL_LENGTH
+        && Url::parse(url)
+            .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some());
+    if !valid {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!("{} is not an http or https URL.", url),
+        });
+    }
+    Ok(())
+}
+
+fn check_secret(secret: &str) -> Result<(), NinoverseApiError> {
+    if !(MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&secret.chars().count()) {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "The secret must be {} to {} characters long.",
+                MIN_SECRET_LENGTH, MAX_SECRET_LENGTH
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn check_event_types(event_types: &[String]) -> Result<(), NinoverseApiError> {
+    if let Some(unknown) = event_types
+        .iter()
+        .find(|event_type| !KafkaNinoverseEvent::NAMES.contains(&event_type.as_str()))
+    {
+        return Err(NinoverseApiError::ValidationError {
+            additional_info: format!(
+                "Unknown event type {}, expected one of {}.",
+                unknown,
+                KafkaNinoverseEvent::NAMES.join(", ")
+            ),
+        });
+    }
+    Ok(())
+}
+
+async fn get_existing_webhook(
+    repositories: &Repositories,
+    id: i32,
+) -> Result<Webhook, NinoverseApiError> {
+    repositories
+        .webhooks
+        .get(id)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Webhook {} does not exist.", id),
+        })
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct DeliveriesQuery {
+    /// 50 deliveries by default, at most 200.
+    pub limit: Option<i64>,
+}
+
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Every webhook of the tenant.", body = [Webhook]),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+    )
+)]
+#[get("/webhooks")]
+async fn get_webhooks(
+    repositories: Repositories,
+    caller: Caller,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    let webhooks = repositories.webhooks.list().await?;
+    Ok(HttpResponse::Ok().json(webhooks))
+}
+
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The webhook.", body = Webhook),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 404, description = "The webhook does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/webhooks/{id}")]
+async fn get_webhook(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    let webhook = get_existing_webhook(&repositories, id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+/// The secret signs the deliveries, it can't be read back.
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 201, description = "The created webhook.", body = Webhook),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[post("/webhooks")]
+async fn create_webhook(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    new_webhook: web::Json<NewWebhook>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    check_url(&new_webhook.url)?;
+    check_secret(&new_webhook.secret)?;
+    check_event_types(&new_webhook.event_types)?;
+    let webhook = repositories
+        .webhooks
+        .insert(&new_webhook, &context.actor)
+        .await?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "create",
+            entity_type: "webhook",
+            entity_id: webhook.id,
+            before: None,
+            after: Some(&webhook),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Created().json(webhook))
+}
+
+/// Enabling a disabled webhook resets its failures.
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The updated webhook.", body = Webhook),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 404, description = "The webhook does not exist or the caller can't read it.", body = ErrorBody),
+        (status = 422, description = "The request is invalid.", body = ErrorBody),
+    )
+)]
+#[put("/webhooks/{id}")]
+async fn update_webhook(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+    update: web::Json<WebhookUpdate>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    let current = get_existing_webhook(&repositories, id.into_inner()).await?;
+    if let Some(url) = &update.url {
+        check_url(url)?;
+    }
+    if let Some(secret) = &update.secret {
+        check_secret(secret)?;
+    }
+    if let Some(event_types) = &update.event_types {
+        check_event_types(event_types)?;
+    }
+    let webhook = repositories
+        .webhooks
+        .update(current.id, &update)
+        .await?
+        .ok_or_else(|| NinoverseApiError::NotFound {
+            additional_info: format!("Webhook {} does not exist.", current.id),
+        })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "update",
+            entity_type: "webhook",
+            entity_id: webhook.id,
+            before: Some(&current),
+            after: Some(&webhook),
+        },
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+/// Pending retries of the webhook are dropped along with it.
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 204, description = "The webhook was deleted."),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 404, description = "The webhook does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[delete("/webhooks/{id}")]
+async fn delete_webhook(
+    repositories: Repositories,
+    kafka_thread_sender: web::Data<Sender<KafkaChannelMessage>>,
+    context: RequestContext,
+    caller: Caller,
+    id: web::Path<i32>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    let id = id.into_inner();
+    let webhook =
+        repositories
+            .webhooks
+            .delete(id)
+            .await?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Webhook {} does not exist.", id),
+            })?;
+    record_mutation(
+        &repositories,
+        &kafka_thread_sender,
+        &context,
+        AuditedChange {
+            action: "delete",
+            entity_type: "webhook",
+            entity_id: webhook.id,
+            before: Some(&webhook),
+            after: None,
+        },
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Every delivery attempt, latest first.
+#[utoipa::path(
+    tag = "webhooks",
+    params(DeliveriesQuery),
+    responses(
+        (status = 200, description = "The delivery attempts, latest first.", body = [WebhookDelivery]),
+        (status = 403, description = "The caller is not an administrator.", body = ErrorBody),
+        (status = 404, description = "The webhook does not exist or the caller can't read it.", body = ErrorBody),
+    )
+)]
+#[get("/webhooks/{id}/deliveries")]
+async fn get_webhook_deliveries(
+    repositories: Repositories,
+    caller: Caller,
+    id: web::Path<i32>,
+    query: web::Query<DeliveriesQuery>,
+) -> Result<HttpResponse, NinoverseApiError> {
+    check_admin(&caller)?;
+    let webhook = get_existing_webhook(&repositories, id.into_inner()).await?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERIES_LIMIT)
+        .clamp(1, MAX_DELIVERIES_LIMIT);
+    let deliveries = repositories
+        .webhooks
+        .list_deliveries(webhook.id, limit)
+        .await?;
+    Ok(HttpResponse::Ok().json(deliveries))
+}
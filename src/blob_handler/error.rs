@@ -0,0 +1,30 @@
+use std::io::ErrorKind;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NinoverseBlobError {
+    #[error("BLOB_HANDLER: Blob not found.")]
+    NotFound { additional_info: String },
+    #[error("BLOB_HANDLER: Error talking to the blob store.")]
+    StoreError { additional_info: String },
+}
+
+impl From<std::io::Error> for NinoverseBlobError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            ErrorKind::NotFound => NinoverseBlobError::NotFound {
+                additional_info: error.to_string(),
+            },
+            _ => NinoverseBlobError::StoreError {
+                additional_info: error.to_string(),
+            },
+        }
+    }
+}
+
+impl From<reqwest::Error> for NinoverseBlobError {
+    fn from(error: reqwest::Error) -> Self {
+        NinoverseBlobError::StoreError {
+            additional_info: error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::{
+    io::{ErrorKind, SeekFrom},
+    path::PathBuf,
+};
+
+use bytes::Bytes;
+use futures::{future::BoxFuture, stream};
+use tokio::{
+    fs::{self, File},
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt},
+};
+
+use super::{BlobEntry, BlobResult, BlobStore, BlobStream, ByteRange};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// One file per blob under `root`, the key is the relative path.
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalBlobStore { root: root.into() }
+    }
+}
+
+fn read_chunks(reader: impl AsyncRead + Unpin + Send + 'static) -> BlobStream {
+    Box::pin(stream::try_unfold(reader, |mut reader| async move {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(read);
+        Ok(Some((Bytes::from(chunk), reader)))
+    }))
+}
+
+/// Missing files and directories are already deleted.
+fn ignore_missing(result: std::io::Result<()>) -> BlobResult<()> {
+    match result {
+        Err(io_error) if io_error.kind() != ErrorKind::NotFound => Err(io_error.into()),
+        _ => Ok(()),
+    }
+}
+
+impl BlobStore for LocalBlobStore {
+    fn put<'a>(&'a self, key: &'a str, content: Bytes) -> BoxFuture<'a, BlobResult<()>> {
+        Box::pin(async move {
+            let path = self.root.join(key);
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory).await?;
+            }
+            // Readers never see a partially written blob.
+            let partial_path = path.with_extension("partial");
+            fs::write(&partial_path, &content).await?;
+            fs::rename(&partial_path, &path).await?;
+            Ok(())
+        })
+    }
+
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> BoxFuture<'a, BlobResult<BlobStream>> {
+        Box::pin(async move {
+            let mut file = File::open(self.root.join(key)).await?;
+            Ok(match range {
+                Some(range) => {
+                    file.seek(SeekFrom::Start(range.start)).await?;
+                    read_chunks(file.take(range.len()))
+                }
+                None => read_chunks(file),
+            })
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, BlobResult<()>> {
+        Box::pin(async move { ignore_missing(fs::remove_file(self.root.join(key)).await) })
+    }
+
+    fn delete_prefix<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, BlobResult<()>> {
+        Box::pin(async move { ignore_missing(fs::remove_dir_all(self.root.join(prefix)).await) })
+    }
+
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, BlobResult<Vec<BlobEntry>>> {
+        Box::pin(async move {
+            let mut entries = vec![];
+            let mut directories = vec![prefix.trim_end_matches('/').to_string()];
+            while let Some(directory) = directories.pop() {
+                let mut children = match fs::read_dir(self.root.join(&directory)).await {
+                    Ok(children) => children,
+                    Err(io_error) if io_error.kind() == ErrorKind::NotFound => continue,
+                    Err(io_error) => return Err(io_error.into()),
+                };
+                while let Some(child) = children.next_entry().await? {
+                    let key = format!("{}/{}", directory, child.file_name().to_string_lossy());
+                    let metadata = child.metadata().await?;
+                    if metadata.is_dir() {
+                        directories.push(key);
+                    } else {
+                        entries.push(BlobEntry {
+                            key,
+                            modified: metadata.modified()?.into(),
+                        });
+                    }
+                }
+            }
+            Ok(entries)
+        })
+    }
+}
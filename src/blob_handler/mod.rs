@@ -0,0 +1,78 @@
+pub mod error;
+pub mod local;
+pub mod s3;
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{future::BoxFuture, stream::BoxStream};
+
+use error::NinoverseBlobError;
+use local::LocalBlobStore;
+use s3::S3BlobStore;
+
+use crate::configuration_handler::{BlobStoreBackend, get_blob_local_path, get_blob_store_backend};
+
+pub type BlobResult<T> = Result<T, NinoverseBlobError>;
+
+pub type BlobStream = BoxStream<'static, BlobResult<Bytes>>;
+
+/// Inclusive byte range, checked against the size of the blob by the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// A stored blob as listed by `BlobStore::list`.
+#[derive(Debug, Clone)]
+pub struct BlobEntry {
+    pub key: String,
+    pub modified: DateTime<Utc>,
+}
+
+impl ByteRange {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+pub trait BlobStore: Send + Sync {
+    /// Stores `content` under `key`, replacing any previous blob.
+    fn put<'a>(&'a self, key: &'a str, content: Bytes) -> BoxFuture<'a, BlobResult<()>>;
+
+    /// Streams the blob, or only `range` of it.
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> BoxFuture<'a, BlobResult<BlobStream>>;
+
+    /// Deleting a missing blob is not an error.
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, BlobResult<()>>;
+
+    /// Deletes every blob under `prefix`, which ends with a `/`.
+    fn delete_prefix<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, BlobResult<()>>;
+
+    /// Every blob under `prefix`, which ends with a `/`.
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, BlobResult<Vec<BlobEntry>>>;
+}
+
+pub fn init_blob_store() -> Arc<dyn BlobStore> {
+    match get_blob_store_backend() {
+        BlobStoreBackend::Local => {
+            let root = get_blob_local_path();
+            println!("BLOB_STORE: Using the local blob store in {}", root);
+            Arc::new(LocalBlobStore::new(root))
+        }
+        BlobStoreBackend::S3 => {
+            let store = S3BlobStore::from_configuration();
+            println!(
+                "BLOB_STORE: Using the S3 blob store at {}",
+                store.endpoint()
+            );
+            Arc::new(store)
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::{TryStreamExt, future::BoxFuture};
+use hmac::{Hmac, Mac};
+use reqwest::{Method, RequestBuilder, Response, StatusCode, Url, header};
+use sha2::{Digest, Sha256};
+
+use super::{BlobEntry, BlobResult, BlobStore, BlobStream, ByteRange, error::NinoverseBlobError};
+use crate::configuration_handler::{
+    get_s3_access_key_id, get_s3_bucket, get_s3_endpoint, get_s3_region, get_s3_secret_access_key,
+};
+
+/// The hash of an empty payload, for requests without body.
+const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Any S3 compatible service, with path style URLs so that a local stand-in
+/// works without DNS tricks. Requests are signed with AWS Signature V4.
+pub struct S3BlobStore {
+    client: reqwest::Client,
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3BlobStore {
+    pub fn new(
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+    ) -> Self {
+        S3BlobStore {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+        }
+    }
+
+    pub fn from_configuration() -> Self {
+        S3BlobStore::new(
+            &get_s3_endpoint(),
+            &get_s3_bucket(),
+            &get_s3_region(),
+            &get_s3_access_key_id(),
+            &get_s3_secret_access_key(),
+        )
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// A request on `key`, or on the bucket itself when `key` is `None`,
+    /// signed over the host, the payload hash and the date.
+    fn request(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, &str)],
+        payload_sha256: &str,
+    ) -> BlobResult<RequestBuilder> {
+        let path = match key {
+            Some(key) => format!(
+                "/{}/{}",
+                uri_encode(&self.bucket, true),
+                uri_encode(key, false)
+            ),
+            None => format!("/{}", uri_encode(&self.bucket, true)),
+        };
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+            .collect();
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<String>>()
+            .join("&");
+        let url = if query.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query)
+        };
+        let url = Url::parse(&url).map_err(|url_error| NinoverseBlobError::StoreError {
+            additional_info: url_error.to_string(),
+        })?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => {
+                return Err(NinoverseBlobError::StoreError {
+                    additional_info: format!("No host in the S3 endpoint {}.", self.endpoint),
+                });
+            }
+        };
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, host, payload_sha256, amz_date, signed_headers, payload_sha256
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.region.as_str(), "s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                date.as_bytes(),
+            ),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_sha256)
+            .header("x-amz-date", amz_date)
+            .header(
+                header::AUTHORIZATION,
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            ))
+    }
+
+    /// The blobs under `prefix`, one page of at most 1000 blobs.
+    async fn list_page(
+        &self,
+        prefix: &str,
+        continuation_token: Option<&str>,
+    ) -> BlobResult<(Vec<BlobEntry>, Option<String>)> {
+        let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+        if let Some(token) = continuation_token {
+            query.push(("continuation-token", token));
+        }
+        let response = self
+            .request(Method::GET, None, &query, EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await?;
+        let listing = check_status(response).await?.text().await?;
+        // Unreadable dates count as recent, such blobs are kept.
+        let entries = xml_values(&listing, "Contents")
+            .iter()
+            .filter_map(|contents| {
+                Some(BlobEntry {
+                    key: xml_values(contents, "Key").pop()?,
+                    modified: xml_values(contents, "LastModified")
+                        .pop()
+                        .and_then(|date| DateTime::parse_from_rfc3339(&date).ok())
+                        .map_or_else(Utc::now, |date| date.with_timezone(&Utc)),
+                })
+            })
+            .collect();
+        Ok((entries, xml_values(&listing, "NextContinuationToken").pop()))
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent encoding as expected by Signature V4, `/` is kept in object keys.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// The text of every `<tag>` element, enough for the listing responses.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let opening = format!("<{}>", tag);
+    let closing = format!("</{}>", tag);
+    xml.split(&opening)
+        .skip(1)
+        .filter_map(|rest| rest.split_once(&closing))
+        .map(|(value, _)| {
+            value
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
+
+async fn check_status(response: Response) -> BlobResult<Response> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND => Err(NinoverseBlobError::NotFound {
+            additional_info: response.text().await.unwrap_or_default(),
+        }),
+        status => Err(NinoverseBlobError::StoreError {
+            additional_info: format!(
+                "S3 answered {}: {}",
+                status,
+                response.text().await.unwrap_or_default()
+            ),
+        }),
+    }
+}
+
+impl BlobStore for S3BlobStore {
+    fn put<'a>(&'a self, key: &'a str, content: Bytes) -> BoxFuture<'a, BlobResult<()>> {
+        Box::pin(async move {
+            let payload_sha256 = hex::encode(Sha256::digest(&content));
+            let response = self
+                .request(Method::PUT, Some(key), &[], &payload_sha256)?
+                .body(content)
+                .send()
+                .await?;
+            check_status(response).await?;
+            Ok(())
+        })
+    }
+
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> BoxFuture<'a, BlobResult<BlobStream>> {
+        Box::pin(async move {
+            let mut request = self.request(Method::GET, Some(key), &[], EMPTY_PAYLOAD_SHA256)?;
+            if let Some(range) = range {
+                request = request.header(
+                    header::RANGE,
+                    format!("bytes={}-{}", range.start, range.end),
+                );
+            }
+            let response = check_status(request.send().await?).await?;
+            let stream: BlobStream = Box::pin(response.bytes_stream().map_err(Into::into));
+            Ok(stream)
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, BlobResult<()>> {
+        Box::pin(async move {
+            let response = self
+                .request(Method::DELETE, Some(key), &[], EMPTY_PAYLOAD_SHA256)?
+                .send()
+                .await?;
+            match check_status(response).await {
+                Ok(_) | Err(NinoverseBlobError::NotFound { .. }) => Ok(()),
+                Err(delete_error) => Err(delete_error),
+            }
+        })
+    }
+
+    fn delete_prefix<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, BlobResult<()>> {
+        Box::pin(async move {
+            for entry in self.list(prefix).await? {
+                self.delete(&entry.key).await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, BlobResult<Vec<BlobEntry>>> {
+        Box::pin(async move {
+            let mut entries = vec![];
+            let mut continuation_token = None;
+            loop {
+                let (page, next_token) = self
+                    .list_page(prefix, continuation_token.as_deref())
+                    .await?;
+                entries.extend(page);
+                if next_token.is_none() {
+                    return Ok(entries);
+                }
+                continuation_token = next_token;
+            }
+        })
+    }
+}
@@ -0,0 +1,31 @@
+use super::error::NinoverseCliError;
+use crate::configuration_handler::{self, error::NinoverseConfigurationError};
+
+const CONFIG_USAGE: &str = "Usage: ninoverse config check";
+
+/// `ninoverse config check`, reports every problem of the configuration.
+pub fn run_config_command(arguments: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match arguments.first().map(String::as_str) {
+        Some("check") => match configuration_handler::test_configuration() {
+            Ok(()) => {
+                println!("CLI: The configuration is valid.");
+                Ok(())
+            }
+            Err(NinoverseConfigurationError::InvalidConfiguration { problems }) => {
+                for problem in &problems {
+                    println!("{}", problem);
+                }
+                Err(Box::new(NinoverseCliError::InvalidConfiguration {
+                    additional_info: format!("{} configuration problems found.", problems.len()),
+                }))
+            }
+        },
+        command => Err(Box::new(NinoverseCliError::UnknownCommand {
+            additional_info: format!(
+                "Unknown config command {}. {}",
+                command.unwrap_or(""),
+                CONFIG_USAGE
+            ),
+        })),
+    }
+}
@@ -0,0 +1,11 @@
+#[derive(thiserror::Error, Debug)]
+pub enum NinoverseCliError {
+    #[error("CLI: Unknown command. {additional_info}")]
+    UnknownCommand { additional_info: String },
+    #[error("CLI: Invalid argument. {additional_info}")]
+    InvalidArgument { additional_info: String },
+    #[error("CLI: Unsupported storage backend. {additional_info}")]
+    UnsupportedBackend { additional_info: String },
+    #[error("CLI: Invalid configuration. {additional_info}")]
+    InvalidConfiguration { additional_info: String },
+}
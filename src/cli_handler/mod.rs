@@ -0,0 +1,192 @@
+pub mod config;
+pub mod error;
+pub mod projects;
+pub mod replay;
+pub mod topics;
+
+use std::collections::HashMap;
+
+use error::NinoverseCliError;
+
+use sqlx::{Pool, migrate::Migrate};
+
+use crate::{
+    api_handler::tenant::is_valid_tenant_id,
+    configuration_handler::{self, StorageBackend},
+    db_handler::{
+        self,
+        migration::{self, MigratedDatabase, MigrationState},
+        repository::{DEFAULT_TENANT, Repositories},
+    },
+};
+
+const USAGE: &str = "Usage: ninoverse [serve | migrate | topics | config | projects | replay] ...";
+const MIGRATE_USAGE: &str = "Usage: ninoverse migrate [status | up | down | to <version>]";
+
+fn parse_version(argument: Option<&String>) -> Result<i64, NinoverseCliError> {
+    argument
+        .and_then(|version| version.parse::<i64>().ok())
+        .ok_or_else(|| NinoverseCliError::InvalidArgument {
+            additional_info: format!("A numeric target version is required. {}", MIGRATE_USAGE),
+        })
+}
+
+fn migration_state_label(state: MigrationState) -> &'static str {
+    match state {
+        MigrationState::Applied => "applied",
+        MigrationState::Pending => "pending",
+        MigrationState::Modified => "MODIFIED",
+        MigrationState::Missing => "MISSING",
+    }
+}
+
+async fn print_migration_status<DB>(pool: &Pool<DB>) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB: MigratedDatabase,
+    DB::Connection: Migrate,
+{
+    for status in migration::migration_status(pool).await? {
+        println!(
+            "{:>4}  {:<8}  {:<10}  {}",
+            status.version,
+            migration_state_label(status.state),
+            if status.reversible {
+                "reversible"
+            } else {
+                "forward"
+            },
+            status.description
+        );
+    }
+    Ok(())
+}
+
+async fn run_migrate_command_on<DB>(
+    pool: &Pool<DB>,
+    arguments: &[String],
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB: MigratedDatabase,
+    DB::Connection: Migrate,
+{
+    match arguments.first().map(String::as_str) {
+        None | Some("status") => print_migration_status(pool).await?,
+        Some("up") => {
+            migration::migrate_to(pool, None).await?;
+            print_migration_status(pool).await?;
+        }
+        Some("down") => match migration::revert_last_migration(pool).await? {
+            Some(version) => println!("CLI: Reverted migration {}.", version),
+            None => println!("CLI: No migration to revert."),
+        },
+        Some("to") => {
+            migration::migrate_to(pool, Some(parse_version(arguments.get(1))?)).await?;
+            print_migration_status(pool).await?;
+        }
+        Some(command) => {
+            return Err(Box::new(NinoverseCliError::UnknownCommand {
+                additional_info: format!("Unknown migrate command {}. {}", command, MIGRATE_USAGE),
+            }));
+        }
+    }
+    Ok(())
+}
+
+/// `ninoverse migrate ...`, manages the schema of the configured storage
+/// backend without starting the server.
+pub async fn run_migrate_command(arguments: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    configuration_handler::load_configuration();
+    match configuration_handler::get_storage_backend() {
+        StorageBackend::Postgres => {
+            let pool = db_handler::connect_db().await?;
+            run_migrate_command_on(&pool, arguments).await
+        }
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => {
+            let path = configuration_handler::get_sqlite_path();
+            let pool = db_handler::sqlite::get_sqlite_pool(&path).await?;
+            run_migrate_command_on(&pool, arguments).await
+        }
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => Err(Box::new(NinoverseCliError::UnsupportedBackend {
+            additional_info: "The sqlite backend requires building with the sqlite feature."
+                .to_string(),
+        })),
+        StorageBackend::InMemory => Err(Box::new(NinoverseCliError::UnsupportedBackend {
+            additional_info: "The in-memory backend has no schema to migrate.".to_string(),
+        })),
+    }
+}
+
+/// `ninoverse <command> ...`, every command but `serve`, which starts the
+/// server.
+pub async fn run_command(arguments: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match arguments.first().map(String::as_str) {
+        Some("migrate") => run_migrate_command(&arguments[1..]).await,
+        Some("topics") => topics::run_topics_command(&arguments[1..]).await,
+        Some("config") => config::run_config_command(&arguments[1..]),
+        Some("projects") => projects::run_projects_command(&arguments[1..]).await,
+        Some("replay") => replay::run_replay_command(&arguments[1..]).await,
+        command => Err(Box::new(NinoverseCliError::UnknownCommand {
+            additional_info: format!("Unknown command {}. {}", command.unwrap_or(""), USAGE),
+        })),
+    }
+}
+
+/// The arguments of a command, `--name value` options apart from the
+/// positional ones.
+struct CommandArguments<'a> {
+    positional: Vec<&'a str>,
+    options: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> CommandArguments<'a> {
+    fn parse(
+        arguments: &'a [String],
+        accepted_options: &[&str],
+        usage: &str,
+    ) -> Result<Self, NinoverseCliError> {
+        let mut parsed = CommandArguments {
+            positional: vec![],
+            options: HashMap::new(),
+        };
+        let mut arguments = arguments.iter().map(String::as_str);
+        while let Some(argument) = arguments.next() {
+            if !argument.starts_with("--") {
+                parsed.positional.push(argument);
+                continue;
+            }
+            if !accepted_options.contains(&argument) {
+                return Err(NinoverseCliError::InvalidArgument {
+                    additional_info: format!("Unknown option {}. {}", argument, usage),
+                });
+            }
+            let value = arguments
+                .next()
+                .ok_or_else(|| NinoverseCliError::InvalidArgument {
+                    additional_info: format!("Option {} requires a value. {}", argument, usage),
+                })?;
+            parsed.options.insert(argument, value);
+        }
+        Ok(parsed)
+    }
+
+    fn option(&self, name: &str) -> Option<&'a str> {
+        self.options.get(name).copied()
+    }
+}
+
+/// The repositories of the tenant named by `--tenant`, the default tenant
+/// without it.
+async fn tenant_repositories(
+    repositories: &Repositories,
+    tenant_id: Option<&str>,
+) -> Result<(String, Repositories), Box<dyn std::error::Error>> {
+    let tenant_id = tenant_id.unwrap_or(DEFAULT_TENANT);
+    if !is_valid_tenant_id(tenant_id) || repositories.tenants.get(tenant_id).await?.is_none() {
+        return Err(Box::new(NinoverseCliError::InvalidArgument {
+            additional_info: format!("Tenant {} does not exist.", tenant_id),
+        }));
+    }
+    Ok((tenant_id.to_string(), repositories.for_tenant(tenant_id)))
+}
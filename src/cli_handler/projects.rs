@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{CommandArguments, error::NinoverseCliError, tenant_repositories};
+use crate::{
+    configuration_handler, db_handler,
+    db_handler::{
+        repository::{Repositories, RepositoryResult},
+        structs::{NewProject, NewTask, Project, TASK_PRIORITIES, TASK_STATUSES, Task},
+    },
+};
+
+const PROJECTS_USAGE: &str = "Usage: ninoverse projects [export | import] <file> [--tenant <id>]";
+/// Length of the project names and task titles the database stores.
+const MAX_NAME_LENGTH: usize = 255;
+
+/// A project with its tasks, as written by `ninoverse projects export`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedProject {
+    #[serde(flatten)]
+    pub project: Project,
+    pub tasks: Vec<Task>,
+}
+
+/// The projects that are not deleted, with their tasks.
+pub async fn export_projects(
+    repositories: &Repositories,
+) -> RepositoryResult<Vec<ExportedProject>> {
+    let projects = repositories.projects.list(false).await?;
+    let project_ids: Vec<i32> = projects.iter().map(|project| project.id).collect();
+    let mut tasks: HashMap<i32, Vec<Task>> = HashMap::new();
+    for task in repositories.tasks.list_for_projects(&project_ids).await? {
+        tasks.entry(task.project_id).or_default().push(task);
+    }
+    Ok(projects
+        .into_iter()
+        .map(|project| ExportedProject {
+            tasks: tasks.remove(&project.id).unwrap_or_default(),
+            project,
+        })
+        .collect())
+}
+
+fn invalid_import(additional_info: String) -> Box<dyn std::error::Error> {
+    Box::new(NinoverseCliError::InvalidArgument { additional_info })
+}
+
+/// The tasks of a project, parents before their subtasks, `None` when their
+/// parents are cyclic.
+fn task_import_order(tasks: &[Task]) -> Option<Vec<&Task>> {
+    let mut ordered: Vec<&Task> = vec![];
+    let mut pending: Vec<&Task> = tasks.iter().collect();
+    while !pending.is_empty() {
+        let (ready, waiting): (Vec<&Task>, Vec<&Task>) = pending.into_iter().partition(|task| {
+            task.parent_id
+                .is_none_or(|parent_id| ordered.iter().any(|imported| imported.id == parent_id))
+        });
+        if ready.is_empty() {
+            return None;
+        }
+        ordered.extend(ready);
+        pending = waiting;
+    }
+    Some(ordered)
+}
+
+fn check_name(kind: &str, id: i32, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(invalid_import(format!(
+            "{} {} needs a name of 1 to {} characters.",
+            kind, id, MAX_NAME_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// Checks every project and task of the export before anything is inserted,
+/// an import either goes through or leaves the tenant untouched.
+async fn check_import(
+    repositories: &Repositories,
+    projects: &[ExportedProject],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let status_types = repositories.status_types.list().await?;
+    for exported in projects {
+        let project = &exported.project;
+        check_name("Project", project.id, &project.name)?;
+        if !status_types
+            .iter()
+            .any(|status_type| status_type.name == project.status)
+        {
+            return Err(invalid_import(format!(
+                "Project {} has the unknown status {}.",
+                project.id, project.status
+            )));
+        }
+        for task in &exported.tasks {
+            check_name("Task", task.id, &task.title)?;
+            let known_status = TASK_STATUSES.contains(&task.status.as_str())
+                || status_types
+                    .iter()
+                    .any(|status_type| status_type.completed && status_type.name == task.status);
+            if !known_status {
+                return Err(invalid_import(format!(
+                    "Task {} of project {} has the unknown status {}.",
+                    task.id, project.id, task.status
+                )));
+            }
+            if !TASK_PRIORITIES.contains(&task.priority.as_str()) {
+                return Err(invalid_import(format!(
+                    "Task {} of project {} has the unknown priority {}.",
+                    task.id, project.id, task.priority
+                )));
+            }
+            let parent_exported = task
+                .parent_id
+                .is_none_or(|parent_id| exported.tasks.iter().any(|parent| parent.id == parent_id));
+            if !parent_exported {
+                return Err(invalid_import(format!(
+                    "The parent of task {} of project {} is not part of the export.",
+                    task.id, project.id
+                )));
+            }
+        }
+        if task_import_order(&exported.tasks).is_none() {
+            return Err(invalid_import(format!(
+                "The tasks of project {} have cyclic parents.",
+                project.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Inserts the tasks of an imported project, checked by `check_import`.
+async fn import_tasks(
+    repositories: &Repositories,
+    project_id: i32,
+    tasks: &[Task],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut imported_ids: HashMap<i32, i32> = HashMap::new();
+    for task in task_import_order(tasks).unwrap_or_default() {
+        let imported = repositories
+            .tasks
+            .insert(
+                project_id,
+                &NewTask {
+                    title: task.title.clone(),
+                    description: task.description.clone(),
+                    parent_id: task.parent_id.map(|parent_id| imported_ids[&parent_id]),
+                    assignee_id: None,
+                    status: task.status.clone(),
+                    priority: task.priority.clone(),
+                    due_date: task.due_date,
+                },
+            )
+            .await?;
+        imported_ids.insert(task.id, imported.id);
+    }
+    Ok(())
+}
+
+/// Inserts the exported projects and their tasks under new ids. Owners,
+/// teams and assignees belong to the exporting tenant, they are left out.
+/// No event nor audit record is produced.
+pub async fn import_projects(
+    repositories: &Repositories,
+    projects: &[ExportedProject],
+) -> Result<Vec<Project>, Box<dyn std::error::Error>> {
+    check_import(repositories, projects).await?;
+    let mut imported_projects = vec![];
+    for exported in projects {
+        let project = repositories
+            .projects
+            .insert(
+                &NewProject {
+                    name: exported.project.name.clone(),
+                    description: exported.project.description.clone(),
+                    status: exported.project.status.clone(),
+                    team_id: None,
+                },
+                None,
+            )
+            .await?;
+        import_tasks(repositories, project.id, &exported.tasks).await?;
+        imported_projects.push(project);
+    }
+    Ok(imported_projects)
+}
+
+/// `ninoverse projects [export | import] <file>`, moves projects between
+/// deployments or tenants as JSON.
+pub async fn run_projects_command(arguments: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let arguments = CommandArguments::parse(arguments, &["--tenant"], PROJECTS_USAGE)?;
+    let (command, path) = match arguments.positional.as_slice() {
+        [command, path] => (*command, *path),
+        _ => return Err(invalid_import(PROJECTS_USAGE.to_string())),
+    };
+    if command != "export" && command != "import" {
+        return Err(Box::new(NinoverseCliError::UnknownCommand {
+            additional_info: format!("Unknown projects command {}. {}", command, PROJECTS_USAGE),
+        }));
+    }
+    configuration_handler::load_configuration();
+    let repositories = db_handler::init_repositories().await?;
+    let (tenant_id, repositories) =
+        tenant_repositories(&repositories, arguments.option("--tenant")).await?;
+    if command == "export" {
+        let projects = export_projects(&repositories).await?;
+        std::fs::write(path, serde_json::to_string_pretty(&projects)?)?;
+        println!(
+            "CLI: Exported {} projects of tenant {} to {}.",
+            projects.len(),
+            tenant_id,
+            path
+        );
+    } else {
+        let projects: Vec<ExportedProject> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let imported = import_projects(&repositories, &projects).await?;
+        println!(
+            "CLI: Imported {} projects into tenant {}.",
+            imported.len(),
+            tenant_id
+        );
+    }
+    Ok(())
+}
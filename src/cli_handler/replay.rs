@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use chrono::{NaiveDateTime, TimeDelta};
+
+use super::{CommandArguments, error::NinoverseCliError, tenant_repositories};
+use crate::{
+    configuration_handler, db_handler,
+    db_handler::{
+        audit::MAX_AUDIT_LIMIT,
+        repository::{Repositories, RepositoryResult},
+        structs::{AuditFilter, AuditRecord},
+    },
+    kafka_handler::{message_bus::init_message_bus, republish_audit_records},
+};
+
+const REPLAY_USAGE: &str =
+    "Usage: ninoverse replay [--tenant <id>] [--since <timestamp>] [--entity-type <type>]";
+
+/// The audit records matching `filter`, oldest first. Listing is capped, the
+/// records are read page by page going back in time.
+pub async fn audit_records_to_replay(
+    repositories: &Repositories,
+    filter: AuditFilter,
+) -> RepositoryResult<Vec<AuditRecord>> {
+    let mut filter = AuditFilter {
+        limit: Some(MAX_AUDIT_LIMIT),
+        ..filter
+    };
+    let mut records = vec![];
+    let mut seen_ids = HashSet::new();
+    loop {
+        let page = repositories.audit.list(&filter).await?;
+        let full_page = page.len() as i64 == MAX_AUDIT_LIMIT;
+        let oldest = page.iter().filter_map(|record| record.created_at).min();
+        let previous_count = records.len();
+        records.extend(page.into_iter().filter(|record| seen_ids.insert(record.id)));
+        match oldest {
+            // `until` is exclusive, the records of the oldest instant are read
+            // again so that none sharing it is skipped.
+            Some(oldest) if full_page && records.len() > previous_count => {
+                filter.until = Some(oldest + TimeDelta::microseconds(1));
+            }
+            _ => break,
+        }
+    }
+    records.sort_by_key(|record| record.id);
+    Ok(records)
+}
+
+/// `ninoverse replay`, publishes the stored audit records of a tenant to the
+/// audit topic again, for consumers that missed them.
+pub async fn run_replay_command(arguments: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let arguments = CommandArguments::parse(
+        arguments,
+        &["--tenant", "--since", "--entity-type"],
+        REPLAY_USAGE,
+    )?;
+    if let Some(argument) = arguments.positional.first() {
+        return Err(Box::new(NinoverseCliError::InvalidArgument {
+            additional_info: format!("Unexpected argument {}. {}", argument, REPLAY_USAGE),
+        }));
+    }
+    let since = arguments
+        .option("--since")
+        .map(|since| {
+            since
+                .parse::<NaiveDateTime>()
+                .map_err(|_| NinoverseCliError::InvalidArgument {
+                    additional_info: format!(
+                        "Invalid timestamp {}, expected 2024-01-31T12:00:00. {}",
+                        since, REPLAY_USAGE
+                    ),
+                })
+        })
+        .transpose()?;
+    configuration_handler::load_configuration();
+    let audit_topic = configuration_handler::get_kafka_audit_topic().ok_or_else(|| {
+        NinoverseCliError::InvalidConfiguration {
+            additional_info: "KAFKA_AUDIT_TOPIC is not set, there is no topic to replay to."
+                .to_string(),
+        }
+    })?;
+    let repositories = db_handler::init_repositories().await?;
+    let (tenant_id, repositories) =
+        tenant_repositories(&repositories, arguments.option("--tenant")).await?;
+    let records = audit_records_to_replay(
+        &repositories,
+        AuditFilter {
+            entity_type: arguments.option("--entity-type").map(String::from),
+            since,
+            ..AuditFilter::default()
+        },
+    )
+    .await?;
+    let message_bus = init_message_bus();
+    republish_audit_records(message_bus.as_ref(), &tenant_id, &audit_topic, &records).await?;
+    println!(
+        "CLI: Replayed {} audit records of tenant {} to {}.",
+        records.len(),
+        tenant_id,
+        audit_topic
+    );
+    Ok(())
+}
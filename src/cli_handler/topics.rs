@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use super::{CommandArguments, error::NinoverseCliError};
+use crate::{
+    configuration_handler, db_handler,
+    kafka_handler::{
+        init_kafka_topics,
+        message_bus::{TopicDescription, TopicSettings, ensure_topic, init_message_bus},
+        report_ensured_topic,
+    },
+};
+
+const TOPICS_USAGE: &str = "Usage: ninoverse topics [list | describe <topic> | create [<topic> [--partitions <count>] [--replication <factor>] [<config>=<value>...]] | alter <topic> <config>=<value>... | partitions <topic> <count> | delete <topic>...]";
+
+fn print_topic_description(topic: &TopicDescription) {
+    println!("{}", topic.name);
+    for partition in &topic.partitions {
+        println!(
+            "  partition {:>3}  leader {:>3}  replicas {:?}  in sync {:?}",
+            partition.id, partition.leader, partition.replicas, partition.in_sync_replicas
+        );
+    }
+    for (name, value) in &topic.configs {
+        println!("  {} = {}", name, value);
+    }
+}
+
+fn invalid_argument(additional_info: &str) -> NinoverseCliError {
+    NinoverseCliError::InvalidArgument {
+        additional_info: format!("{} {}", additional_info, TOPICS_USAGE),
+    }
+}
+
+fn topic_argument<'a>(arguments: &[&'a str]) -> Result<&'a str, NinoverseCliError> {
+    arguments
+        .first()
+        .copied()
+        .ok_or_else(|| invalid_argument("A topic name is required."))
+}
+
+fn count_argument(argument: Option<&str>, default: i32) -> Result<i32, NinoverseCliError> {
+    match argument {
+        Some(count) => count
+            .parse::<i32>()
+            .ok()
+            .filter(|count| *count > 0)
+            .ok_or_else(|| invalid_argument("Counts must be positive numbers.")),
+        None => Ok(default),
+    }
+}
+
+/// `<config>=<value>` arguments.
+fn config_arguments(arguments: &[&str]) -> Result<BTreeMap<String, String>, NinoverseCliError> {
+    arguments
+        .iter()
+        .map(|argument| {
+            argument
+                .split_once('=')
+                .filter(|(name, _)| !name.is_empty())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .ok_or_else(|| invalid_argument(&format!("Invalid config {}.", argument)))
+        })
+        .collect()
+}
+
+/// `ninoverse topics ...`, administers the topics of the configured message
+/// bus.
+pub async fn run_topics_command(arguments: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let command = arguments.first().map_or("list", String::as_str);
+    let arguments = CommandArguments::parse(
+        arguments.get(1..).unwrap_or_default(),
+        &["--partitions", "--replication"],
+        TOPICS_USAGE,
+    )?;
+    let positional = arguments.positional.as_slice();
+    configuration_handler::load_configuration();
+    let message_bus = init_message_bus();
+    match command {
+        "list" => {
+            for topic in message_bus.list_topics().await? {
+                println!(
+                    "{:<40}  {:>3} partitions",
+                    topic.name,
+                    topic.partitions.len()
+                );
+            }
+        }
+        "describe" => {
+            let topic = topic_argument(positional)?;
+            print_topic_description(&message_bus.describe_topic(topic).await?);
+        }
+        // The configured topics, those of every tenant included, as at startup.
+        "create" if positional.is_empty() => {
+            let repositories = db_handler::init_repositories().await?;
+            init_kafka_topics(&repositories, message_bus.as_ref()).await?;
+        }
+        "create" => {
+            let settings = TopicSettings {
+                partitions: count_argument(arguments.option("--partitions"), 1)?,
+                replication: count_argument(arguments.option("--replication"), 1)?,
+                configs: config_arguments(&positional[1..])?,
+            };
+            let ensured_topic =
+                ensure_topic(message_bus.as_ref(), topic_argument(positional)?, &settings).await?;
+            report_ensured_topic(&ensured_topic);
+        }
+        "alter" => {
+            let topic = topic_argument(positional)?;
+            let configs = config_arguments(&positional[1..])?;
+            if configs.is_empty() {
+                return Err(Box::new(invalid_argument(
+                    "At least one config is required.",
+                )));
+            }
+            message_bus.alter_topic_configs(topic, &configs).await?;
+            print_topic_description(&message_bus.describe_topic(topic).await?);
+        }
+        "partitions" => {
+            let topic = topic_argument(positional)?;
+            let partitions = positional
+                .get(1)
+                .ok_or_else(|| invalid_argument("A partition count is required."))?;
+            message_bus
+                .add_partitions(topic, count_argument(Some(partitions), 1)?)
+                .await?;
+            print_topic_description(&message_bus.describe_topic(topic).await?);
+        }
+        "delete" => {
+            topic_argument(positional)?;
+            let topics: Vec<String> = positional.iter().map(|topic| topic.to_string()).collect();
+            message_bus.delete_topics(&topics).await?;
+        }
+        command => {
+            return Err(Box::new(NinoverseCliError::UnknownCommand {
+                additional_info: format!("Unknown topics command {}. {}", command, TOPICS_USAGE),
+            }));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,6 @@
+#[derive(thiserror::Error, Debug)]
+pub enum NinoverseConfigurationError {
+    /// Every problem found, not only the first one.
+    #[error("CONFIGURATION: {} problems found.\n{}", .problems.len(), .problems.join("\n"))]
+    InvalidConfiguration { problems: Vec<String> },
+}
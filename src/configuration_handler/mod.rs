@@ -1,26 +1,149 @@
-use std::env::{self, VarError};
+pub mod error;
 
+use std::env;
+
+use error::NinoverseConfigurationError;
 use rdkafka::admin::{AdminOptions, TopicReplication};
 
 use crate::kafka_handler::structs::KafkaNinoverseTopic;
 
-pub fn test_configuration() -> Result<(), VarError> {
+pub fn load_configuration() {
     dotenv::dotenv().ok();
-    env::var("SELF_PORT").expect("SELF_PORT configuration value missing");
-    env::var("PG_HOST").expect("PG_HOST configuration value missing");
-    env::var("PG_PORT").expect("PG_PORT configuration value missing");
-    env::var("PG_USER").expect("PG_USER configuration value missing");
-    env::var("PG_PASSWORD").expect("PG_PASSWORD configuration value missing");
-    env::var("PG_DB").expect("PG_DB configuration value missing");
-    env::var("KAFKA_BROKER").expect("KAFKA_BROKER configuration value missing");
-    env::var("KAFKA_TOPIC").expect("KAFKA_TOPIC configuration value missing");
-    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Postgres,
+    Sqlite,
+    InMemory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBusBackend {
+    Kafka,
+    InMemory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobStoreBackend {
+    Local,
+    S3,
+}
+
+/// How events of different tenants are kept apart on the message bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KafkaTenantIsolation {
+    /// Shared topics, keys are prefixed with `<tenant>:`.
+    Key,
+    /// One `<topic>.<tenant>` topic per tenant.
+    Topic,
+}
+
+/// Loads the configuration and fails with all of its problems, for startup and
+/// `ninoverse config check` to report them at once.
+pub fn test_configuration() -> Result<(), NinoverseConfigurationError> {
+    load_configuration();
+    let problems = configuration_problems();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(NinoverseConfigurationError::InvalidConfiguration { problems })
+    }
+}
+
+/// Everything wrong with the configuration.
+fn configuration_problems() -> Vec<String> {
+    let mut required = vec!["SELF_PORT"];
+    if get_storage_backend() == StorageBackend::Postgres {
+        required.extend(["PG_HOST", "PG_PORT", "PG_USER", "PG_PASSWORD", "PG_DB"]);
+    }
+    if get_message_bus_backend() == MessageBusBackend::Kafka {
+        required.extend(["KAFKA_BROKER", "KAFKA_TOPIC"]);
+    }
+    if get_blob_store_backend() == BlobStoreBackend::S3 {
+        required.extend([
+            "S3_ENDPOINT",
+            "S3_BUCKET",
+            "S3_ACCESS_KEY_ID",
+            "S3_SECRET_ACCESS_KEY",
+        ]);
+    }
+    let mut problems: Vec<String> = required
+        .into_iter()
+        .filter(|name| env::var(name).is_err())
+        .map(|name| format!("{} configuration value missing", name))
+        .collect();
+    for (name, port) in [
+        ("SELF_PORT", get_self_port()),
+        ("GRPC_PORT", get_grpc_port()),
+    ] {
+        if port.parse::<u16>().is_err() {
+            problems.push(format!("{} is not a valid port: {}", name, port));
+        }
+    }
+    #[cfg(not(feature = "sqlite"))]
+    if get_storage_backend() == StorageBackend::Sqlite {
+        problems.push("STORAGE_BACKEND=sqlite requires building with the sqlite feature".into());
+    }
+    if let Some(bytes) = env::var("ATTACHMENT_MAX_BYTES")
+        .ok()
+        .and_then(|bytes| bytes.parse::<usize>().ok())
+        .filter(|bytes| *bytes > ATTACHMENT_MAX_BYTES_LIMIT)
+    {
+        problems.push(format!(
+            "ATTACHMENT_MAX_BYTES is above {}: {}",
+            ATTACHMENT_MAX_BYTES_LIMIT, bytes
+        ));
+    }
+    if let Some(attempts) = env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|attempts| attempts.parse::<u32>().ok())
+        .filter(|attempts| *attempts > WEBHOOK_MAX_ATTEMPTS_LIMIT)
+    {
+        problems.push(format!(
+            "WEBHOOK_MAX_ATTEMPTS is above {}: {}",
+            WEBHOOK_MAX_ATTEMPTS_LIMIT, attempts
+        ));
+    }
+    match get_jwt_algorithm().as_str() {
+        "HS256" => {}
+        "RS256" => {
+            if let Some(path) = get_jwt_public_key_path()
+                && std::fs::metadata(&path).is_err()
+            {
+                problems.push(format!("Can't read the JWT public key {}", path));
+            }
+        }
+        algorithm => problems.push(format!("Unsupported JWT_ALGORITHM {}", algorithm)),
+    }
+    problems
 }
 
 pub fn get_self_port() -> String {
     env::var("SELF_PORT").unwrap_or_else(|_| "7878".to_string())
 }
 
+/// Port of the gRPC server, next to the HTTP one.
+pub fn get_grpc_port() -> String {
+    env::var("GRPC_PORT").unwrap_or_else(|_| "50051".to_string())
+}
+
+pub fn get_storage_backend() -> StorageBackend {
+    match env::var("STORAGE_BACKEND")
+        .unwrap_or_else(|_| "postgres".to_string())
+        .as_str()
+    {
+        "sqlite" => StorageBackend::Sqlite,
+        "memory" => StorageBackend::InMemory,
+        _ => StorageBackend::Postgres,
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_sqlite_path() -> String {
+    env::var("SQLITE_PATH").unwrap_or_else(|_| "ninoverse.db".to_string())
+}
+
 pub fn get_pg_host() -> String {
     env::var("PG_HOST").unwrap_or_else(|_| "postgres".to_string())
 }
@@ -41,6 +164,184 @@ pub fn get_pg_db() -> String {
     env::var("PG_DB").unwrap_or_else(|_| "ninoverse".to_string())
 }
 
+pub fn get_pg_row_level_security() -> bool {
+    env::var("PG_ROW_LEVEL_SECURITY")
+        .map(|enabled| enabled == "true")
+        .unwrap_or(false)
+}
+
+pub fn get_projects_retention_days() -> i32 {
+    env::var("PROJECTS_RETENTION_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<i32>().ok())
+        .unwrap_or(30)
+}
+
+pub fn get_projects_purge_interval() -> std::time::Duration {
+    std::time::Duration::from_secs(
+        env::var("PROJECTS_PURGE_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .unwrap_or(3600),
+    )
+}
+
+pub fn get_blob_store_backend() -> BlobStoreBackend {
+    match env::var("BLOB_STORE")
+        .unwrap_or_else(|_| "local".to_string())
+        .as_str()
+    {
+        "s3" => BlobStoreBackend::S3,
+        _ => BlobStoreBackend::Local,
+    }
+}
+
+pub fn get_blob_local_path() -> String {
+    env::var("BLOB_LOCAL_PATH").unwrap_or_else(|_| "blobs".to_string())
+}
+
+/// Any S3 compatible service, addressed with path style URLs.
+pub fn get_s3_endpoint() -> String {
+    env::var("S3_ENDPOINT").unwrap_or_else(|_| "http://localhost:9000".to_string())
+}
+
+pub fn get_s3_bucket() -> String {
+    env::var("S3_BUCKET").unwrap_or_else(|_| "ninoverse".to_string())
+}
+
+pub fn get_s3_region() -> String {
+    env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string())
+}
+
+pub fn get_s3_access_key_id() -> String {
+    env::var("S3_ACCESS_KEY_ID").unwrap_or_default()
+}
+
+pub fn get_s3_secret_access_key() -> String {
+    env::var("S3_SECRET_ACCESS_KEY").unwrap_or_default()
+}
+
+/// Most bytes `ATTACHMENT_MAX_BYTES` may allow, uploads are held in memory
+/// whole before being stored.
+pub const ATTACHMENT_MAX_BYTES_LIMIT: usize = 100 * 1024 * 1024;
+
+pub fn get_attachment_max_bytes() -> usize {
+    env::var("ATTACHMENT_MAX_BYTES")
+        .ok()
+        .and_then(|bytes| bytes.parse::<usize>().ok())
+        .unwrap_or(10 * 1024 * 1024)
+}
+
+/// Accepted content types, comma separated in `ATTACHMENT_CONTENT_TYPES`.
+/// `image/*` accepts every image type.
+pub fn get_attachment_content_types() -> Vec<String> {
+    env::var("ATTACHMENT_CONTENT_TYPES")
+        .unwrap_or_else(|_| "application/pdf,image/*,text/plain,text/markdown".to_string())
+        .split(',')
+        .map(|content_type| content_type.trim().to_lowercase())
+        .filter(|content_type| !content_type.is_empty())
+        .collect()
+}
+
+/// The Postgres text search configuration stemming the indexed text, e.g.
+/// `english`, `german` or `simple` for no stemming.
+pub fn get_search_language() -> String {
+    env::var("SEARCH_LANGUAGE").unwrap_or_else(|_| "english".to_string())
+}
+
+/// How many of the latest events are kept for live clients reconnecting with
+/// the id of the last event they received.
+pub fn get_live_replay_size() -> usize {
+    env::var("LIVE_REPLAY_SIZE")
+        .ok()
+        .and_then(|size| size.parse::<usize>().ok())
+        .unwrap_or(1000)
+}
+
+/// Levels of nested selections a GraphQL query may have.
+pub fn get_graphql_max_depth() -> usize {
+    env::var("GRAPHQL_MAX_DEPTH")
+        .ok()
+        .and_then(|depth| depth.parse::<usize>().ok())
+        .unwrap_or(15)
+}
+
+/// Fields a GraphQL query may select, counted across its fragments.
+pub fn get_graphql_max_complexity() -> usize {
+    env::var("GRAPHQL_MAX_COMPLEXITY")
+        .ok()
+        .and_then(|complexity| complexity.parse::<usize>().ok())
+        .unwrap_or(500)
+}
+
+/// Nesting the GraphQL parser accepts, input values included, before the
+/// other limits are checked.
+pub fn get_graphql_max_recursion() -> usize {
+    env::var("GRAPHQL_MAX_RECURSION")
+        .ok()
+        .and_then(|depth| depth.parse::<usize>().ok())
+        .unwrap_or(32)
+}
+
+/// Most attempts at delivering an event `WEBHOOK_MAX_ATTEMPTS` may ask for.
+pub const WEBHOOK_MAX_ATTEMPTS_LIMIT: u32 = 20;
+
+/// Attempts at delivering an event to a webhook, the first one included.
+pub fn get_webhook_max_attempts() -> u32 {
+    env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|attempts| attempts.parse::<u32>().ok())
+        .filter(|attempts| *attempts > 0)
+        .unwrap_or(5)
+}
+
+/// Wait before the first retry of a delivery, doubled for every next one.
+pub fn get_webhook_retry_backoff() -> std::time::Duration {
+    std::time::Duration::from_millis(
+        env::var("WEBHOOK_RETRY_BACKOFF_MS")
+            .ok()
+            .and_then(|milliseconds| milliseconds.parse::<u64>().ok())
+            .unwrap_or(1000),
+    )
+}
+
+pub fn get_webhook_timeout() -> std::time::Duration {
+    std::time::Duration::from_secs(
+        env::var("WEBHOOK_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .unwrap_or(10),
+    )
+}
+
+/// Deliveries attempted at the same time, across every webhook.
+pub fn get_webhook_concurrency() -> usize {
+    env::var("WEBHOOK_CONCURRENCY")
+        .ok()
+        .and_then(|deliveries| deliveries.parse::<usize>().ok())
+        .filter(|deliveries| *deliveries > 0)
+        .unwrap_or(16)
+}
+
+/// Failed deliveries in a row after which a webhook is disabled.
+pub fn get_webhook_disable_after() -> i32 {
+    env::var("WEBHOOK_DISABLE_AFTER")
+        .ok()
+        .and_then(|deliveries| deliveries.parse::<i32>().ok())
+        .filter(|deliveries| *deliveries > 0)
+        .unwrap_or(10)
+}
+
+pub fn get_message_bus_backend() -> MessageBusBackend {
+    match env::var("MESSAGE_BUS")
+        .unwrap_or_else(|_| "kafka".to_string())
+        .as_str()
+    {
+        "memory" => MessageBusBackend::InMemory,
+        _ => MessageBusBackend::Kafka,
+    }
+}
+
 pub fn get_kafka_generic_broker() -> String {
     env::var("KAFKA_BROKER").unwrap_or_else(|_| "broker:9092".to_string())
 }
@@ -49,7 +350,7 @@ pub fn get_kafka_generic_topic<'a>() -> Vec<KafkaNinoverseTopic<'a>> {
     let topic_config = env::var("KAFKA_TOPIC").unwrap_or_else(|_| "ninoverse:1:1".to_string());
     let topic_config_array: Vec<&str> = topic_config.split(":").collect();
     let topic_name = topic_config_array
-        .get(0)
+        .first()
         .unwrap_or(&"ninoverse")
         .to_string();
     let partition = topic_config_array
@@ -71,6 +372,91 @@ pub fn get_kafka_generic_topic<'a>() -> Vec<KafkaNinoverseTopic<'a>> {
     }]
 }
 
+pub fn get_kafka_audit_topic() -> Option<String> {
+    env::var("KAFKA_AUDIT_TOPIC")
+        .ok()
+        .filter(|topic| !topic.is_empty())
+}
+
+/// Key the events of this service are signed with, shared by its instances.
+pub fn get_kafka_event_secret() -> Option<String> {
+    env::var("KAFKA_EVENT_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+}
+
+pub fn get_kafka_tenant_isolation() -> KafkaTenantIsolation {
+    match env::var("KAFKA_TENANT_ISOLATION")
+        .unwrap_or_else(|_| "key".to_string())
+        .as_str()
+    {
+        "topic" => KafkaTenantIsolation::Topic,
+        _ => KafkaTenantIsolation::Key,
+    }
+}
+
+/// Requests to `<tenant>.<TENANT_BASE_DOMAIN>` are resolved to `<tenant>`.
+pub fn get_tenant_base_domain() -> Option<String> {
+    env::var("TENANT_BASE_DOMAIN")
+        .ok()
+        .filter(|domain| !domain.is_empty())
+}
+
+pub fn get_auth_enabled() -> bool {
+    env::var("AUTH_ENABLED")
+        .map(|enabled| enabled != "false")
+        .unwrap_or(true)
+}
+
+pub fn get_jwt_algorithm() -> String {
+    env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string())
+}
+
+pub fn get_jwt_secret() -> Option<String> {
+    env::var("JWT_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+}
+
+pub fn get_jwt_public_key_path() -> Option<String> {
+    env::var("JWT_PUBLIC_KEY_PATH")
+        .ok()
+        .filter(|path| !path.is_empty())
+}
+
+/// A `redoc.standalone.js` served along with the docs, which otherwise load
+/// the pinned Redoc release from its CDN.
+pub fn get_docs_redoc_bundle_path() -> Option<String> {
+    env::var("DOCS_REDOC_BUNDLE_PATH")
+        .ok()
+        .filter(|path| !path.is_empty())
+}
+
+pub fn get_jwt_issuer() -> Option<String> {
+    env::var("JWT_ISSUER")
+        .ok()
+        .filter(|issuer| !issuer.is_empty())
+}
+
+pub fn get_jwt_audience() -> Option<String> {
+    env::var("JWT_AUDIENCE")
+        .ok()
+        .filter(|audience| !audience.is_empty())
+}
+
+/// Subjects granted every permission, comma separated in `ADMIN_SUBJECTS`.
+pub fn get_admin_subjects() -> Vec<String> {
+    env::var("ADMIN_SUBJECTS")
+        .map(|subjects| {
+            subjects
+                .split(',')
+                .map(|subject| subject.trim().to_string())
+                .filter(|subject| !subject.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn get_kafka_admin_options() -> AdminOptions {
     AdminOptions::new()
 }
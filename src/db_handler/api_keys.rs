@@ -0,0 +1,95 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{ApiKeyRepository, RepositoryResult},
+    structs::{ApiKey, NewApiKey},
+    tenants::TenantPool,
+};
+
+const API_KEY_COLUMNS: &str = "id, name, subject, prefix, created_at, expires_at, revoked_at";
+
+pub struct PostgresApiKeyRepository {
+    pool: TenantPool,
+}
+
+impl PostgresApiKeyRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresApiKeyRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl ApiKeyRepository for PostgresApiKeyRepository {
+    fn insert<'a>(&'a self, api_key: &'a NewApiKey) -> BoxFuture<'a, RepositoryResult<ApiKey>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "INSERT INTO api_keys (name, subject, prefix, key_hash, expires_at, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6) RETURNING {}",
+                API_KEY_COLUMNS
+            ))
+            .bind(&api_key.name)
+            .bind(&api_key.subject)
+            .bind(&api_key.prefix)
+            .bind(&api_key.key_hash)
+            .bind(api_key.expires_at)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn find_active<'a>(
+        &'a self,
+        key_hash: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "SELECT {} FROM api_keys \
+                 WHERE key_hash = $1 AND tenant_id = $2 AND revoked_at IS NULL \
+                    AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)",
+                API_KEY_COLUMNS
+            ))
+            .bind(key_hash)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list<'a>(&'a self, subject: &'a str) -> BoxFuture<'a, RepositoryResult<Vec<ApiKey>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "SELECT {} FROM api_keys WHERE subject = $1 AND tenant_id = $2 ORDER BY id",
+                API_KEY_COLUMNS
+            ))
+            .bind(subject)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn revoke<'a>(
+        &'a self,
+        id: i32,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP \
+                 WHERE id = $1 AND subject = $2 AND tenant_id = $3 AND revoked_at IS NULL \
+                 RETURNING {}",
+                API_KEY_COLUMNS
+            ))
+            .bind(id)
+            .bind(subject)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
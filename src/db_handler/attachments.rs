@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{AttachmentRepository, RepositoryResult},
+    structs::{Attachment, NewAttachment},
+    tenants::TenantPool,
+};
+
+const ATTACHMENT_COLUMNS: &str = "id, project_id, file_name, content_type, size, sha256, \
+     storage_key, uploaded_by, created_at";
+
+pub struct PostgresAttachmentRepository {
+    pool: TenantPool,
+}
+
+impl PostgresAttachmentRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresAttachmentRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl AttachmentRepository for PostgresAttachmentRepository {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Attachment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "SELECT {} FROM attachments WHERE project_id = $1 AND tenant_id = $2 ORDER BY id",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "SELECT {} FROM attachments WHERE id = $1 AND project_id = $2 AND tenant_id = $3",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        attachment: &'a NewAttachment,
+    ) -> BoxFuture<'a, RepositoryResult<Attachment>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "INSERT INTO attachments (project_id, file_name, content_type, size, sha256, \
+                 storage_key, uploaded_by, tenant_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+                 RETURNING {}",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&attachment.file_name)
+            .bind(&attachment.content_type)
+            .bind(attachment.size)
+            .bind(&attachment.sha256)
+            .bind(&attachment.storage_key)
+            .bind(&attachment.uploaded_by)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn delete(
+        &self,
+        project_id: i32,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "DELETE FROM attachments WHERE id = $1 AND project_id = $2 AND tenant_id = $3 \
+                 RETURNING {}",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn storage_keys(&self) -> BoxFuture<'_, RepositoryResult<Vec<String>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, String>(
+                "SELECT storage_key FROM attachments WHERE tenant_id = $1",
+            )
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde_json::{Map, Value};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{AuditRepository, RepositoryResult},
+    structs::{AuditFilter, AuditRecord, NewAuditRecord},
+    tenants::TenantPool,
+};
+
+const AUDIT_COLUMNS: &str =
+    "id, actor, action, entity_type, entity_id, before, after, diff, request_id, created_at";
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+pub const MAX_AUDIT_LIMIT: i64 = 1000;
+
+pub fn audit_limit(filter: &AuditFilter) -> i64 {
+    filter
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT)
+}
+
+/// Field by field diff of two JSON objects, every changed field is reported
+/// as `{"before": .., "after": ..}`. Missing sides are treated as `null`.
+pub fn json_diff(before: Option<&Value>, after: Option<&Value>) -> Value {
+    let empty = Map::new();
+    let before = before.and_then(Value::as_object).unwrap_or(&empty);
+    let after = after.and_then(Value::as_object).unwrap_or(&empty);
+    let mut diff = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let before_value = before.get(key).unwrap_or(&Value::Null);
+        let after_value = after.get(key).unwrap_or(&Value::Null);
+        if before_value != after_value && !diff.contains_key(key) {
+            diff.insert(
+                key.clone(),
+                serde_json::json!({ "before": before_value, "after": after_value }),
+            );
+        }
+    }
+    Value::Object(diff)
+}
+
+pub struct PostgresAuditRepository {
+    pool: TenantPool,
+}
+
+impl PostgresAuditRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresAuditRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl AuditRepository for PostgresAuditRepository {
+    fn insert<'a>(
+        &'a self,
+        record: &'a NewAuditRecord,
+    ) -> BoxFuture<'a, RepositoryResult<AuditRecord>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, AuditRecord>(&format!(
+                "INSERT INTO audit_log \
+                    (actor, action, entity_type, entity_id, before, after, diff, request_id, \
+                    tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+                 RETURNING {}",
+                AUDIT_COLUMNS
+            ))
+            .bind(&record.actor)
+            .bind(&record.action)
+            .bind(&record.entity_type)
+            .bind(record.entity_id)
+            .bind(&record.before)
+            .bind(&record.after)
+            .bind(json_diff(record.before.as_ref(), record.after.as_ref()))
+            .bind(&record.request_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list<'a>(
+        &'a self,
+        filter: &'a AuditFilter,
+    ) -> BoxFuture<'a, RepositoryResult<Vec<AuditRecord>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, AuditRecord>(&format!(
+                "SELECT {} FROM audit_log \
+                 WHERE tenant_id = $9 \
+                    AND ($1::VARCHAR IS NULL OR actor = $1) \
+                    AND ($2::VARCHAR IS NULL OR action = $2) \
+                    AND ($3::VARCHAR IS NULL OR entity_type = $3) \
+                    AND ($4::INTEGER IS NULL OR entity_id = $4) \
+                    AND ($5::VARCHAR IS NULL OR request_id = $5) \
+                    AND ($6::TIMESTAMP IS NULL OR created_at >= $6) \
+                    AND ($7::TIMESTAMP IS NULL OR created_at < $7) \
+                 ORDER BY id DESC \
+                 LIMIT $8",
+                AUDIT_COLUMNS
+            ))
+            .bind(&filter.actor)
+            .bind(&filter.action)
+            .bind(&filter.entity_type)
+            .bind(filter.entity_id)
+            .bind(&filter.request_id)
+            .bind(filter.since)
+            .bind(filter.until)
+            .bind(audit_limit(filter))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
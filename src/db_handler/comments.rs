@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{CommentRepository, RepositoryResult},
+    structs::{Comment, CommentRevision, NewComment},
+    tenants::TenantPool,
+};
+
+const COMMENT_COLUMNS: &str = "id, project_id, task_id, parent_id, author, body, created_at, \
+     updated_at, \
+     EXISTS (SELECT 1 FROM comment_revisions WHERE comment_revisions.comment_id = comments.id) \
+     AS edited";
+const REVISION_COLUMNS: &str = "id, comment_id, body, edited_by, created_at";
+
+pub struct PostgresCommentRepository {
+    pool: TenantPool,
+}
+
+impl PostgresCommentRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresCommentRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl CommentRepository for PostgresCommentRepository {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "SELECT {} FROM comments WHERE project_id = $1 AND tenant_id = $2 \
+                 ORDER BY created_at, id",
+                COMMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "SELECT {} FROM comments WHERE project_id = ANY($1) AND tenant_id = $2 \
+                 ORDER BY created_at, id",
+                COMMENT_COLUMNS
+            ))
+            .bind(project_ids)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "SELECT {} FROM comments WHERE id = $1 AND project_id = $2 AND tenant_id = $3",
+                COMMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task_id: Option<i32>,
+        author: &'a str,
+        comment: &'a NewComment,
+    ) -> BoxFuture<'a, RepositoryResult<Comment>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "INSERT INTO comments (project_id, task_id, parent_id, author, body, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6) RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(task_id)
+            .bind(comment.parent_id)
+            .bind(author)
+            .bind(&comment.body)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        body: &'a str,
+        edited_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Comment>>> {
+        Box::pin(async move {
+            let mut transaction = self.pool.begin().await?;
+            sqlx::query(
+                "INSERT INTO comment_revisions (comment_id, body, edited_by, tenant_id) \
+                 SELECT id, body, $3, tenant_id FROM comments \
+                 WHERE id = $1 AND project_id = $2 AND tenant_id = $4",
+            )
+            .bind(id)
+            .bind(project_id)
+            .bind(edited_by)
+            .bind(&self.pool.tenant_id)
+            .execute(&mut *transaction)
+            .await?;
+            let comment = sqlx::query_as::<_, Comment>(&format!(
+                "UPDATE comments SET body = $3, updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = $1 AND project_id = $2 AND tenant_id = $4 RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(body)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(comment)
+        })
+    }
+
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "DELETE FROM comments WHERE id = $1 AND project_id = $2 AND tenant_id = $3 \
+                 RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_revisions(
+        &self,
+        comment_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<CommentRevision>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, CommentRevision>(&format!(
+                "SELECT {} FROM comment_revisions WHERE comment_id = $1 AND tenant_id = $2 \
+                 ORDER BY id",
+                REVISION_COLUMNS
+            ))
+            .bind(comment_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
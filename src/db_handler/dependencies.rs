@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{DependencyRepository, RepositoryResult},
+    structs::{Dependency, DependencyInsertion, ItemKind},
+    tenants::TenantPool,
+};
+use crate::dependency_handler::DependencyGraph;
+
+const DEPENDENCY_COLUMNS: &str = "blocked_id, blocking_id, created_at";
+
+pub struct PostgresDependencyRepository {
+    pool: TenantPool,
+}
+
+impl PostgresDependencyRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresDependencyRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl DependencyRepository for PostgresDependencyRepository {
+    fn list(&self, kind: ItemKind) -> BoxFuture<'_, RepositoryResult<Vec<Dependency>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Dependency>(&format!(
+                "SELECT {} FROM {} WHERE tenant_id = $1 ORDER BY blocked_id, blocking_id",
+                DEPENDENCY_COLUMNS,
+                kind.dependency_table()
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<DependencyInsertion>> {
+        Box::pin(async move {
+            let table = kind.dependency_table();
+            let mut transaction = self.pool.begin().await?;
+            // Two insertions checked against the same graph could close a
+            // cycle together, they are serialized per tenant and table.
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+                .bind(format!("{}:{}", table, self.pool.tenant_id))
+                .execute(&mut *transaction)
+                .await?;
+            // Only what `blocking_id` waits for can lead back to `blocked_id`.
+            let upstream = sqlx::query_as::<_, Dependency>(&format!(
+                "WITH RECURSIVE upstream (id) AS ( \
+                    SELECT $1::INTEGER \
+                    UNION \
+                    SELECT dependencies.blocking_id FROM {table} AS dependencies \
+                    JOIN upstream ON dependencies.blocked_id = upstream.id \
+                    WHERE dependencies.tenant_id = $2 \
+                 ) \
+                 SELECT {columns} FROM {table} \
+                 WHERE tenant_id = $2 AND blocked_id IN (SELECT id FROM upstream)",
+                table = table,
+                columns = DEPENDENCY_COLUMNS
+            ))
+            .bind(blocking_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *transaction)
+            .await?;
+            if let Some(path) = DependencyGraph::new(&upstream).cycle_path(blocked_id, blocking_id)
+            {
+                transaction.rollback().await?;
+                return Ok(DependencyInsertion::Cycle(path));
+            }
+            let dependency = sqlx::query_as::<_, Dependency>(&format!(
+                "INSERT INTO {} (blocked_id, blocking_id, tenant_id) VALUES ($1, $2, $3) \
+                 ON CONFLICT (blocked_id, blocking_id) DO NOTHING RETURNING {}",
+                table, DEPENDENCY_COLUMNS
+            ))
+            .bind(blocked_id)
+            .bind(blocking_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(dependency.map_or(
+                DependencyInsertion::AlreadyExists,
+                DependencyInsertion::Inserted,
+            ))
+        })
+    }
+
+    fn delete(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Dependency>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Dependency>(&format!(
+                "DELETE FROM {} WHERE blocked_id = $1 AND blocking_id = $2 AND tenant_id = $3 \
+                 RETURNING {}",
+                kind.dependency_table(),
+                DEPENDENCY_COLUMNS
+            ))
+            .bind(blocked_id)
+            .bind(blocking_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
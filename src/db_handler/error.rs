@@ -0,0 +1,29 @@
+use sqlx::migrate::MigrateError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NinoverseDbError {
+    #[error("DB_HANDLER: Error querying the database.")]
+    QueryError { additional_info: String },
+    #[error("DB_HANDLER: Error running migrations.")]
+    MigrationError { additional_info: String },
+    #[error("DB_HANDLER: An applied migration was modified.")]
+    ModifiedMigration { additional_info: String },
+    #[error("DB_HANDLER: Migration can't be reverted.")]
+    IrreversibleMigration { additional_info: String },
+}
+
+impl From<sqlx::Error> for NinoverseDbError {
+    fn from(error: sqlx::Error) -> Self {
+        NinoverseDbError::QueryError {
+            additional_info: error.to_string(),
+        }
+    }
+}
+
+impl From<MigrateError> for NinoverseDbError {
+    fn from(error: MigrateError) -> Self {
+        NinoverseDbError::MigrationError {
+            additional_info: error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,1796 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use futures::future::{BoxFuture, ready};
+
+use super::{
+    audit::{audit_limit, json_diff},
+    repository::{
+        ApiKeyRepository, AttachmentRepository, AuditRepository, CommentRepository, DEFAULT_TENANT,
+        DependencyRepository, ProjectRepository, RepositoryResult, SearchRepository,
+        StatusTypeRepository, TagRepository, TaskRepository, TeamRepository, TenantRepository,
+        UserRepository, WebhookRepository,
+    },
+    structs::{
+        ApiKey, Attachment, AuditFilter, AuditRecord, Comment, CommentRevision, Dependency,
+        DependencyInsertion, ItemKind, Membership, NewApiKey, NewAttachment, NewAuditRecord,
+        NewComment, NewPendingWebhookDelivery, NewProject, NewStatusType, NewTag, NewTask, NewTeam,
+        NewTenant, NewWebhook, NewWebhookDelivery, PendingWebhookDelivery, Project,
+        ProjectStatusHistoryEntry, ProjectUpdate, SearchHit, StatusTransition, StatusType,
+        StatusTypeUpdate, Tag, TagAssignment, TagUpdate, Task, TaskUpdate, Team, Tenant, User,
+        Webhook, WebhookDelivery, WebhookUpdate,
+    },
+};
+use crate::dependency_handler::DependencyGraph;
+
+#[derive(Default)]
+struct InMemoryState {
+    projects: BTreeMap<i32, Project>,
+    last_project_id: i32,
+    status_history: Vec<ProjectStatusHistoryEntry>,
+    status_types: Vec<StatusType>,
+    status_transitions: Vec<StatusTransition>,
+    audit_log: Vec<AuditRecord>,
+    /// Keys with their hash, the hash is not part of `ApiKey`.
+    api_keys: Vec<(ApiKey, String)>,
+    users: Vec<User>,
+    teams: Vec<Team>,
+    memberships: Vec<Membership>,
+    tasks: BTreeMap<i32, Task>,
+    last_task_id: i32,
+    project_dependencies: Vec<Dependency>,
+    task_dependencies: Vec<Dependency>,
+    tags: BTreeMap<i32, Tag>,
+    last_tag_id: i32,
+    project_tags: Vec<TagAssignment>,
+    task_tags: Vec<TagAssignment>,
+    comments: BTreeMap<i32, Comment>,
+    last_comment_id: i32,
+    comment_revisions: Vec<CommentRevision>,
+    attachments: BTreeMap<i32, Attachment>,
+    last_attachment_id: i32,
+    webhooks: BTreeMap<i32, Webhook>,
+    last_webhook_id: i32,
+    webhook_deliveries: Vec<WebhookDelivery>,
+    last_webhook_delivery_id: i32,
+    pending_deliveries: BTreeMap<i32, PendingWebhookDelivery>,
+    last_pending_delivery_id: i32,
+}
+
+/// Keeps everything in process memory, mirroring the semantics of the
+/// Postgres schema (version bump on update, soft delete, seeded workflow).
+/// Meant for tests and running without a database. A store holds the data of
+/// a single tenant.
+pub struct InMemoryStore {
+    state: Arc<Mutex<InMemoryState>>,
+}
+
+/// The tenant registry and the stores of every tenant, each tenant gets its
+/// own copy of the seeded workflow.
+pub struct InMemoryTenants {
+    tenants: Mutex<Vec<Tenant>>,
+    states: Mutex<BTreeMap<String, Arc<Mutex<InMemoryState>>>>,
+}
+
+fn now() -> NaiveDateTime {
+    Utc::now().naive_utc()
+}
+
+/// Same as the `projects_touch_row` trigger.
+fn touch(project: &mut Project) {
+    project.updated_at = Some(now());
+    project.version += 1;
+}
+
+fn version_accepted(project: &Project, accepted_versions: &Option<Vec<i32>>) -> bool {
+    accepted_versions
+        .as_ref()
+        .is_none_or(|versions| versions.contains(&project.version))
+}
+
+fn seeded_transition(
+    from_status: &str,
+    to_status: &str,
+    required_fields: &[&str],
+) -> StatusTransition {
+    StatusTransition {
+        from_status: from_status.to_string(),
+        to_status: to_status.to_string(),
+        required_fields: required_fields
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+    }
+}
+
+/// The seeded status types, shared by every tenant in the Postgres schema.
+const SHARED_STATUS_TYPES: [&str; 3] = ["draft", "active", "done"];
+
+impl InMemoryState {
+    fn seeded() -> Self {
+        InMemoryState {
+            status_types: SHARED_STATUS_TYPES
+                .iter()
+                .enumerate()
+                .map(|(index, name)| StatusType {
+                    id: index as i32 + 1,
+                    name: name.to_string(),
+                    completed: *name == "done",
+                })
+                .collect(),
+            status_transitions: vec![
+                seeded_transition("draft", "active", &["description"]),
+                seeded_transition("active", "draft", &[]),
+                seeded_transition("active", "done", &[]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn dependencies(&self, kind: ItemKind) -> &Vec<Dependency> {
+        match kind {
+            ItemKind::Project => &self.project_dependencies,
+            ItemKind::Task => &self.task_dependencies,
+        }
+    }
+
+    fn dependencies_mut(&mut self, kind: ItemKind) -> &mut Vec<Dependency> {
+        match kind {
+            ItemKind::Project => &mut self.project_dependencies,
+            ItemKind::Task => &mut self.task_dependencies,
+        }
+    }
+
+    fn tag_assignments_mut(&mut self, kind: ItemKind) -> &mut Vec<TagAssignment> {
+        match kind {
+            ItemKind::Project => &mut self.project_tags,
+            ItemKind::Task => &mut self.task_tags,
+        }
+    }
+
+    /// Same as the ON DELETE CASCADE of the comment tables.
+    fn remove_comments(&mut self, mut removed_ids: Vec<i32>) {
+        while let Some(removed_id) = removed_ids.pop() {
+            self.comments.remove(&removed_id);
+            self.comment_revisions
+                .retain(|revision| revision.comment_id != removed_id);
+            removed_ids.extend(
+                self.comments
+                    .values()
+                    .filter(|reply| reply.parent_id == Some(removed_id))
+                    .map(|reply| reply.id),
+            );
+        }
+    }
+
+    /// Same as the `blocked` column of the SQL backends.
+    fn flag_project(&self, project: &Project) -> Project {
+        let mut project = project.clone();
+        project.blocked =
+            self.project_dependencies
+                .iter()
+                .filter(|dependency| dependency.blocked_id == project.id)
+                .filter_map(|dependency| self.projects.get(&dependency.blocking_id))
+                .filter(|blocking| blocking.deleted_at.is_none())
+                .any(|blocking| {
+                    !self.status_types.iter().any(|status_type| {
+                        status_type.completed && status_type.name == blocking.status
+                    })
+                });
+        project
+    }
+
+    fn flag_task(&self, task: &Task) -> Task {
+        let mut task = task.clone();
+        task.blocked =
+            self.task_dependencies
+                .iter()
+                .filter(|dependency| dependency.blocked_id == task.id)
+                .filter_map(|dependency| self.tasks.get(&dependency.blocking_id))
+                .any(|blocking| {
+                    !self.status_types.iter().any(|status_type| {
+                        status_type.completed && status_type.name == blocking.status
+                    })
+                });
+        task
+    }
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        InMemoryStore {
+            state: Arc::new(Mutex::new(InMemoryState::seeded())),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, InMemoryState> {
+        self.state.lock().expect("IN_MEMORY: State lock poisoned")
+    }
+}
+
+impl Default for InMemoryStore {
+    fn default() -> Self {
+        InMemoryStore::new()
+    }
+}
+
+impl InMemoryTenants {
+    pub fn new() -> Self {
+        InMemoryTenants {
+            tenants: Mutex::new(vec![Tenant {
+                id: DEFAULT_TENANT.to_string(),
+                name: "Default".to_string(),
+                created_at: Some(now()),
+            }]),
+            states: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// The store of a tenant, created empty on first use.
+    pub fn store(&self, tenant_id: &str) -> InMemoryStore {
+        let state = self
+            .states
+            .lock()
+            .expect("IN_MEMORY: State lock poisoned")
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(InMemoryState::seeded())))
+            .clone();
+        InMemoryStore { state }
+    }
+
+    fn tenants(&self) -> MutexGuard<'_, Vec<Tenant>> {
+        self.tenants.lock().expect("IN_MEMORY: State lock poisoned")
+    }
+}
+
+impl Default for InMemoryTenants {
+    fn default() -> Self {
+        InMemoryTenants::new()
+    }
+}
+
+impl TenantRepository for InMemoryTenants {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tenant>>> {
+        let mut tenants = self.tenants().clone();
+        tenants.sort_by(|left, right| left.id.cmp(&right.id));
+        Box::pin(ready(Ok(tenants)))
+    }
+
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>> {
+        let tenant = self
+            .tenants()
+            .iter()
+            .find(|tenant| tenant.id == id)
+            .cloned();
+        Box::pin(ready(Ok(tenant)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        tenant: &'a NewTenant,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>> {
+        let mut tenants = self.tenants();
+        if tenants.iter().any(|existing| existing.id == tenant.id) {
+            return Box::pin(ready(Ok(None)));
+        }
+        let tenant = Tenant {
+            id: tenant.id.clone(),
+            name: tenant.name.clone(),
+            created_at: Some(now()),
+        };
+        tenants.push(tenant.clone());
+        Box::pin(ready(Ok(Some(tenant))))
+    }
+}
+
+impl ProjectRepository for InMemoryStore {
+    fn list(&self, include_deleted: bool) -> BoxFuture<'_, RepositoryResult<Vec<Project>>> {
+        let state = self.state();
+        let projects = state
+            .projects
+            .values()
+            .filter(|project| include_deleted || project.deleted_at.is_none())
+            .map(|project| state.flag_project(project))
+            .collect();
+        Box::pin(ready(Ok(projects)))
+    }
+
+    fn get(
+        &self,
+        id: i32,
+        include_deleted: bool,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        let state = self.state();
+        let project = state
+            .projects
+            .get(&id)
+            .filter(|project| include_deleted || project.deleted_at.is_none())
+            .map(|project| state.flag_project(project));
+        Box::pin(ready(Ok(project)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project: &'a NewProject,
+        owner_id: Option<i32>,
+    ) -> BoxFuture<'a, RepositoryResult<Project>> {
+        let mut state = self.state();
+        state.last_project_id += 1;
+        let created_at = now();
+        let project = Project {
+            id: state.last_project_id,
+            name: project.name.clone(),
+            description: project.description.clone(),
+            status: project.status.clone(),
+            created_at: Some(created_at),
+            updated_at: Some(created_at),
+            version: 1,
+            deleted_at: None,
+            owner_id,
+            team_id: project.team_id,
+            blocked: false,
+        };
+        state.projects.insert(project.id, project.clone());
+        Box::pin(ready(Ok(project)))
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a ProjectUpdate,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Project>>> {
+        let mut state = self.state();
+        let project = state
+            .projects
+            .get_mut(&id)
+            .filter(|project| project.deleted_at.is_none())
+            .filter(|project| version_accepted(project, &accepted_versions))
+            .map(|project| {
+                if let Some(name) = &update.name {
+                    project.name = name.clone();
+                }
+                if let Some(description) = &update.description {
+                    project.description = Some(description.clone());
+                }
+                if let Some(status) = &update.status {
+                    project.status = status.clone();
+                }
+                if let Some(owner_id) = update.owner_id {
+                    project.owner_id = Some(owner_id);
+                }
+                if let Some(team_id) = update.team_id {
+                    project.team_id = Some(team_id);
+                }
+                touch(project);
+                project.clone()
+            });
+        let project = project.map(|project| state.flag_project(&project));
+        Box::pin(ready(Ok(project)))
+    }
+
+    fn soft_delete(
+        &self,
+        id: i32,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        let mut state = self.state();
+        let project = state
+            .projects
+            .get_mut(&id)
+            .filter(|project| project.deleted_at.is_none())
+            .filter(|project| version_accepted(project, &accepted_versions))
+            .map(|project| {
+                project.deleted_at = Some(now());
+                touch(project);
+                project.clone()
+            });
+        let project = project.map(|project| state.flag_project(&project));
+        Box::pin(ready(Ok(project)))
+    }
+
+    fn restore(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        let mut state = self.state();
+        let project = state
+            .projects
+            .get_mut(&id)
+            .filter(|project| project.deleted_at.is_some())
+            .map(|project| {
+                project.deleted_at = None;
+                touch(project);
+                project.clone()
+            });
+        let project = project.map(|project| state.flag_project(&project));
+        Box::pin(ready(Ok(project)))
+    }
+
+    fn purge_deleted(&self, retention_days: i32) -> BoxFuture<'_, RepositoryResult<Vec<i32>>> {
+        let threshold = now() - TimeDelta::days(retention_days as i64);
+        let mut state = self.state();
+        let purged_ids: Vec<i32> = state
+            .projects
+            .values()
+            .filter(|project| {
+                project
+                    .deleted_at
+                    .is_some_and(|deleted_at| deleted_at < threshold)
+            })
+            .map(|project| project.id)
+            .collect();
+        for id in &purged_ids {
+            state.projects.remove(id);
+        }
+        state
+            .status_history
+            .retain(|entry| !purged_ids.contains(&entry.project_id));
+        state
+            .tasks
+            .retain(|_, task| !purged_ids.contains(&task.project_id));
+        let task_ids: Vec<i32> = state.tasks.keys().copied().collect();
+        state.project_dependencies.retain(|dependency| {
+            !purged_ids.contains(&dependency.blocked_id)
+                && !purged_ids.contains(&dependency.blocking_id)
+        });
+        state.task_dependencies.retain(|dependency| {
+            task_ids.contains(&dependency.blocked_id) && task_ids.contains(&dependency.blocking_id)
+        });
+        state
+            .project_tags
+            .retain(|assignment| !purged_ids.contains(&assignment.item_id));
+        state
+            .task_tags
+            .retain(|assignment| task_ids.contains(&assignment.item_id));
+        let comment_ids = state
+            .comments
+            .values()
+            .filter(|comment| purged_ids.contains(&comment.project_id))
+            .map(|comment| comment.id)
+            .collect();
+        state.remove_comments(comment_ids);
+        state
+            .attachments
+            .retain(|_, attachment| !purged_ids.contains(&attachment.project_id));
+        Box::pin(ready(Ok(purged_ids)))
+    }
+
+    fn transition_status<'a>(
+        &'a self,
+        id: i32,
+        from_status: &'a str,
+        to_status: &'a str,
+        actor: &'a str,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<(Project, ProjectStatusHistoryEntry)>>> {
+        let mut state = self.state();
+        let project = state
+            .projects
+            .get_mut(&id)
+            .filter(|project| project.deleted_at.is_none() && project.status == from_status)
+            .filter(|project| version_accepted(project, &accepted_versions))
+            .map(|project| {
+                project.status = to_status.to_string();
+                touch(project);
+                project.clone()
+            });
+        let result = project.map(|project| {
+            let history_entry = ProjectStatusHistoryEntry {
+                id: state.status_history.last().map_or(1, |entry| entry.id + 1),
+                project_id: id,
+                from_status: from_status.to_string(),
+                to_status: to_status.to_string(),
+                actor: actor.to_string(),
+                created_at: Some(now()),
+            };
+            state.status_history.push(history_entry.clone());
+            (state.flag_project(&project), history_entry)
+        });
+        Box::pin(ready(Ok(result)))
+    }
+
+    fn list_status_history(
+        &self,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<ProjectStatusHistoryEntry>>> {
+        let history = self
+            .state()
+            .status_history
+            .iter()
+            .filter(|entry| entry.project_id == id)
+            .cloned()
+            .collect();
+        Box::pin(ready(Ok(history)))
+    }
+}
+
+impl StatusTypeRepository for InMemoryStore {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusType>>> {
+        let status_types = self.state().status_types.clone();
+        Box::pin(ready(Ok(status_types)))
+    }
+
+    fn exists<'a>(&'a self, name: &'a str) -> BoxFuture<'a, RepositoryResult<bool>> {
+        let exists = self
+            .state()
+            .status_types
+            .iter()
+            .any(|status_type| status_type.name == name);
+        Box::pin(ready(Ok(exists)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        status_type: &'a NewStatusType,
+    ) -> BoxFuture<'a, RepositoryResult<StatusType>> {
+        let mut state = self.state();
+        let status_type = StatusType {
+            id: state
+                .status_types
+                .last()
+                .map_or(1, |status_type| status_type.id + 1),
+            name: status_type.name.clone(),
+            completed: status_type.completed,
+        };
+        state.status_types.push(status_type.clone());
+        Box::pin(ready(Ok(status_type)))
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>> {
+        let status_type = self
+            .state()
+            .status_types
+            .iter()
+            .skip(SHARED_STATUS_TYPES.len())
+            .find(|status_type| status_type.id == id)
+            .cloned();
+        Box::pin(ready(Ok(status_type)))
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a StatusTypeUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusType>>> {
+        let status_type = self
+            .state()
+            .status_types
+            .iter_mut()
+            .skip(SHARED_STATUS_TYPES.len())
+            .find(|status_type| status_type.id == id)
+            .map(|status_type| {
+                if let Some(name) = &update.name {
+                    status_type.name = name.clone();
+                }
+                if let Some(completed) = update.completed {
+                    status_type.completed = completed;
+                }
+                status_type.clone()
+            });
+        Box::pin(ready(Ok(status_type)))
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>> {
+        let mut state = self.state();
+        let status_type = state
+            .status_types
+            .iter()
+            .skip(SHARED_STATUS_TYPES.len())
+            .position(|status_type| status_type.id == id)
+            .map(|index| state.status_types.remove(index + SHARED_STATUS_TYPES.len()));
+        Box::pin(ready(Ok(status_type)))
+    }
+
+    fn in_use<'a>(&'a self, status_type: &'a StatusType) -> BoxFuture<'a, RepositoryResult<bool>> {
+        let state = self.state();
+        let name = &status_type.name;
+        let in_use = state
+            .projects
+            .values()
+            .any(|project| &project.status == name)
+            || state.tasks.values().any(|task| &task.status == name)
+            || state
+                .status_history
+                .iter()
+                .any(|entry| &entry.from_status == name || &entry.to_status == name)
+            || state
+                .status_transitions
+                .iter()
+                .any(|transition| &transition.from_status == name || &transition.to_status == name);
+        Box::pin(ready(Ok(in_use)))
+    }
+
+    fn list_transitions(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusTransition>>> {
+        let transitions = self.state().status_transitions.clone();
+        Box::pin(ready(Ok(transitions)))
+    }
+
+    fn get_transition<'a>(
+        &'a self,
+        from_status: &'a str,
+        to_status: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusTransition>>> {
+        let transition = self
+            .state()
+            .status_transitions
+            .iter()
+            .find(|transition| {
+                transition.from_status == from_status && transition.to_status == to_status
+            })
+            .cloned();
+        Box::pin(ready(Ok(transition)))
+    }
+}
+
+impl AuditRepository for InMemoryStore {
+    fn insert<'a>(
+        &'a self,
+        record: &'a NewAuditRecord,
+    ) -> BoxFuture<'a, RepositoryResult<AuditRecord>> {
+        let mut state = self.state();
+        let record = AuditRecord {
+            id: state.audit_log.len() as i32 + 1,
+            actor: record.actor.clone(),
+            action: record.action.clone(),
+            entity_type: record.entity_type.clone(),
+            entity_id: record.entity_id,
+            before: record.before.clone(),
+            after: record.after.clone(),
+            diff: json_diff(record.before.as_ref(), record.after.as_ref()),
+            request_id: record.request_id.clone(),
+            created_at: Some(now()),
+        };
+        state.audit_log.push(record.clone());
+        Box::pin(ready(Ok(record)))
+    }
+
+    fn list<'a>(
+        &'a self,
+        filter: &'a AuditFilter,
+    ) -> BoxFuture<'a, RepositoryResult<Vec<AuditRecord>>> {
+        let records = self
+            .state()
+            .audit_log
+            .iter()
+            .rev()
+            .filter(|record| {
+                filter
+                    .actor
+                    .as_ref()
+                    .is_none_or(|actor| record.actor == *actor)
+            })
+            .filter(|record| {
+                filter
+                    .action
+                    .as_ref()
+                    .is_none_or(|action| record.action == *action)
+            })
+            .filter(|record| {
+                filter
+                    .entity_type
+                    .as_ref()
+                    .is_none_or(|entity_type| record.entity_type == *entity_type)
+            })
+            .filter(|record| {
+                filter
+                    .entity_id
+                    .is_none_or(|entity_id| record.entity_id == entity_id)
+            })
+            .filter(|record| {
+                filter
+                    .request_id
+                    .as_ref()
+                    .is_none_or(|request_id| record.request_id.as_ref() == Some(request_id))
+            })
+            .filter(|record| {
+                filter
+                    .since
+                    .is_none_or(|since| record.created_at.is_some_and(|at| at >= since))
+            })
+            .filter(|record| {
+                filter
+                    .until
+                    .is_none_or(|until| record.created_at.is_some_and(|at| at < until))
+            })
+            .take(audit_limit(filter) as usize)
+            .cloned()
+            .collect();
+        Box::pin(ready(Ok(records)))
+    }
+}
+
+impl ApiKeyRepository for InMemoryStore {
+    fn insert<'a>(&'a self, api_key: &'a NewApiKey) -> BoxFuture<'a, RepositoryResult<ApiKey>> {
+        let mut state = self.state();
+        let stored = ApiKey {
+            id: state.api_keys.len() as i32 + 1,
+            name: api_key.name.clone(),
+            subject: api_key.subject.clone(),
+            prefix: api_key.prefix.clone(),
+            created_at: Some(now()),
+            expires_at: api_key.expires_at,
+            revoked_at: None,
+        };
+        state
+            .api_keys
+            .push((stored.clone(), api_key.key_hash.clone()));
+        Box::pin(ready(Ok(stored)))
+    }
+
+    fn find_active<'a>(
+        &'a self,
+        key_hash: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>> {
+        let current_time = now();
+        let api_key = self
+            .state()
+            .api_keys
+            .iter()
+            .find(|(_, stored_hash)| stored_hash == key_hash)
+            .map(|(api_key, _)| api_key)
+            .filter(|api_key| api_key.revoked_at.is_none())
+            .filter(|api_key| {
+                api_key
+                    .expires_at
+                    .is_none_or(|expires_at| expires_at > current_time)
+            })
+            .cloned();
+        Box::pin(ready(Ok(api_key)))
+    }
+
+    fn list<'a>(&'a self, subject: &'a str) -> BoxFuture<'a, RepositoryResult<Vec<ApiKey>>> {
+        let api_keys = self
+            .state()
+            .api_keys
+            .iter()
+            .map(|(api_key, _)| api_key)
+            .filter(|api_key| api_key.subject == subject)
+            .cloned()
+            .collect();
+        Box::pin(ready(Ok(api_keys)))
+    }
+
+    fn revoke<'a>(
+        &'a self,
+        id: i32,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>> {
+        let api_key = self
+            .state()
+            .api_keys
+            .iter_mut()
+            .map(|(api_key, _)| api_key)
+            .find(|api_key| api_key.id == id && api_key.subject == subject)
+            .filter(|api_key| api_key.revoked_at.is_none())
+            .map(|api_key| {
+                api_key.revoked_at = Some(now());
+                api_key.clone()
+            });
+        Box::pin(ready(Ok(api_key)))
+    }
+}
+
+impl UserRepository for InMemoryStore {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<User>>> {
+        let users = self.state().users.clone();
+        Box::pin(ready(Ok(users)))
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<User>>> {
+        let user = self
+            .state()
+            .users
+            .iter()
+            .find(|user| user.id == id)
+            .cloned();
+        Box::pin(ready(Ok(user)))
+    }
+
+    fn find_by_subject<'a>(
+        &'a self,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<User>>> {
+        let user = self
+            .state()
+            .users
+            .iter()
+            .find(|user| user.subject == subject)
+            .cloned();
+        Box::pin(ready(Ok(user)))
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        subject: &'a str,
+        is_admin: bool,
+    ) -> BoxFuture<'a, RepositoryResult<User>> {
+        let mut state = self.state();
+        let user = match state.users.iter_mut().find(|user| user.subject == subject) {
+            Some(user) => {
+                user.is_admin = is_admin;
+                user.clone()
+            }
+            None => {
+                let user = User {
+                    id: state.users.len() as i32 + 1,
+                    subject: subject.to_string(),
+                    is_admin,
+                    created_at: Some(now()),
+                };
+                state.users.push(user.clone());
+                user
+            }
+        };
+        Box::pin(ready(Ok(user)))
+    }
+}
+
+impl TeamRepository for InMemoryStore {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Team>>> {
+        let teams = self.state().teams.clone();
+        Box::pin(ready(Ok(teams)))
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Team>>> {
+        let team = self
+            .state()
+            .teams
+            .iter()
+            .find(|team| team.id == id)
+            .cloned();
+        Box::pin(ready(Ok(team)))
+    }
+
+    fn insert<'a>(&'a self, team: &'a NewTeam) -> BoxFuture<'a, RepositoryResult<Option<Team>>> {
+        let mut state = self.state();
+        if state.teams.iter().any(|stored| stored.name == team.name) {
+            return Box::pin(ready(Ok(None)));
+        }
+        let team = Team {
+            id: state.teams.len() as i32 + 1,
+            name: team.name.clone(),
+            created_at: Some(now()),
+        };
+        state.teams.push(team.clone());
+        Box::pin(ready(Ok(Some(team))))
+    }
+
+    fn list_members(&self, team_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>> {
+        let mut members: Vec<Membership> = self
+            .state()
+            .memberships
+            .iter()
+            .filter(|membership| membership.team_id == team_id)
+            .cloned()
+            .collect();
+        members.sort_by_key(|membership| membership.user_id);
+        Box::pin(ready(Ok(members)))
+    }
+
+    fn list_memberships(&self, user_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>> {
+        let mut memberships: Vec<Membership> = self
+            .state()
+            .memberships
+            .iter()
+            .filter(|membership| membership.user_id == user_id)
+            .cloned()
+            .collect();
+        memberships.sort_by_key(|membership| membership.team_id);
+        Box::pin(ready(Ok(memberships)))
+    }
+
+    fn set_member<'a>(
+        &'a self,
+        team_id: i32,
+        user_id: i32,
+        role: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Membership>> {
+        let mut state = self.state();
+        let existing = state
+            .memberships
+            .iter_mut()
+            .find(|membership| membership.team_id == team_id && membership.user_id == user_id);
+        if let Some(membership) = existing {
+            membership.role = role.to_string();
+            let membership = membership.clone();
+            return Box::pin(ready(Ok(membership)));
+        }
+        let subject = state
+            .users
+            .iter()
+            .find(|user| user.id == user_id)
+            .map(|user| user.subject.clone())
+            .unwrap_or_default();
+        let membership = Membership {
+            team_id,
+            user_id,
+            subject,
+            role: role.to_string(),
+            created_at: Some(now()),
+        };
+        state.memberships.push(membership.clone());
+        Box::pin(ready(Ok(membership)))
+    }
+
+    fn remove_member(
+        &self,
+        team_id: i32,
+        user_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Membership>>> {
+        let mut state = self.state();
+        let membership = state
+            .memberships
+            .iter()
+            .position(|membership| membership.team_id == team_id && membership.user_id == user_id)
+            .map(|index| state.memberships.remove(index));
+        Box::pin(ready(Ok(membership)))
+    }
+}
+
+impl TaskRepository for InMemoryStore {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Task>>> {
+        let state = self.state();
+        let mut tasks: Vec<Task> = state
+            .tasks
+            .values()
+            .filter(|task| task.project_id == project_id)
+            .map(|task| state.flag_task(task))
+            .collect();
+        tasks.sort_by_key(|task| (task.position, task.id));
+        Box::pin(ready(Ok(tasks)))
+    }
+
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>> {
+        let state = self.state();
+        let mut tasks: Vec<Task> = state
+            .tasks
+            .values()
+            .filter(|task| project_ids.contains(&task.project_id))
+            .map(|task| state.flag_task(task))
+            .collect();
+        tasks.sort_by_key(|task| (task.position, task.id));
+        Box::pin(ready(Ok(tasks)))
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        let state = self.state();
+        let task = state
+            .tasks
+            .get(&id)
+            .filter(|task| task.project_id == project_id)
+            .map(|task| state.flag_task(task));
+        Box::pin(ready(Ok(task)))
+    }
+
+    fn find(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        let state = self.state();
+        let task = state.tasks.get(&id).map(|task| state.flag_task(task));
+        Box::pin(ready(Ok(task)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task: &'a NewTask,
+    ) -> BoxFuture<'a, RepositoryResult<Task>> {
+        let mut state = self.state();
+        state.last_task_id += 1;
+        let position = state
+            .tasks
+            .values()
+            .filter(|stored| stored.project_id == project_id)
+            .map(|stored| stored.position)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let created_at = now();
+        let task = Task {
+            id: state.last_task_id,
+            project_id,
+            parent_id: task.parent_id,
+            title: task.title.clone(),
+            description: task.description.clone(),
+            assignee_id: task.assignee_id,
+            status: task.status.clone(),
+            priority: task.priority.clone(),
+            due_date: task.due_date,
+            position,
+            created_at: Some(created_at),
+            updated_at: Some(created_at),
+            blocked: false,
+        };
+        state.tasks.insert(task.id, task.clone());
+        Box::pin(ready(Ok(task)))
+    }
+
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        update: &'a TaskUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Task>>> {
+        let mut state = self.state();
+        let task = state
+            .tasks
+            .get_mut(&id)
+            .filter(|task| task.project_id == project_id)
+            .map(|task| {
+                if let Some(title) = &update.title {
+                    task.title = title.clone();
+                }
+                if let Some(description) = &update.description {
+                    task.description = Some(description.clone());
+                }
+                if let Some(parent_id) = update.parent_id {
+                    task.parent_id = Some(parent_id);
+                }
+                if let Some(assignee_id) = update.assignee_id {
+                    task.assignee_id = Some(assignee_id);
+                }
+                if let Some(status) = &update.status {
+                    task.status = status.clone();
+                }
+                if let Some(priority) = &update.priority {
+                    task.priority = priority.clone();
+                }
+                if let Some(due_date) = update.due_date {
+                    task.due_date = Some(due_date);
+                }
+                task.updated_at = Some(now());
+                task.clone()
+            });
+        let task = task.map(|task| state.flag_task(&task));
+        Box::pin(ready(Ok(task)))
+    }
+
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        let mut state = self.state();
+        let task = state
+            .tasks
+            .get(&id)
+            .filter(|task| task.project_id == project_id)
+            .map(|task| state.flag_task(task));
+        if task.is_some() {
+            // Same as the ON DELETE CASCADE of `parent_id`.
+            let mut removed_ids = vec![id];
+            while let Some(removed_id) = removed_ids.pop() {
+                state.tasks.remove(&removed_id);
+                state.task_dependencies.retain(|dependency| {
+                    dependency.blocked_id != removed_id && dependency.blocking_id != removed_id
+                });
+                state
+                    .task_tags
+                    .retain(|assignment| assignment.item_id != removed_id);
+                let comment_ids = state
+                    .comments
+                    .values()
+                    .filter(|comment| comment.task_id == Some(removed_id))
+                    .map(|comment| comment.id)
+                    .collect();
+                state.remove_comments(comment_ids);
+                removed_ids.extend(
+                    state
+                        .tasks
+                        .values()
+                        .filter(|subtask| subtask.parent_id == Some(removed_id))
+                        .map(|subtask| subtask.id),
+                );
+            }
+        }
+        Box::pin(ready(Ok(task)))
+    }
+
+    fn reorder<'a>(
+        &'a self,
+        project_id: i32,
+        task_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>> {
+        {
+            let mut state = self.state();
+            let updated_at = now();
+            for (index, id) in task_ids.iter().enumerate() {
+                if let Some(task) = state
+                    .tasks
+                    .get_mut(id)
+                    .filter(|task| task.project_id == project_id)
+                {
+                    task.position = index as i32 + 1;
+                    task.updated_at = Some(updated_at);
+                }
+            }
+        }
+        TaskRepository::list(self, project_id)
+    }
+}
+
+impl DependencyRepository for InMemoryStore {
+    fn list(&self, kind: ItemKind) -> BoxFuture<'_, RepositoryResult<Vec<Dependency>>> {
+        let mut dependencies = self.state().dependencies(kind).clone();
+        dependencies.sort_by_key(|dependency| (dependency.blocked_id, dependency.blocking_id));
+        Box::pin(ready(Ok(dependencies)))
+    }
+
+    fn insert(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<DependencyInsertion>> {
+        let mut state = self.state();
+        let dependencies = state.dependencies_mut(kind);
+        if dependencies.iter().any(|dependency| {
+            dependency.blocked_id == blocked_id && dependency.blocking_id == blocking_id
+        }) {
+            return Box::pin(ready(Ok(DependencyInsertion::AlreadyExists)));
+        }
+        if let Some(path) = DependencyGraph::new(dependencies).cycle_path(blocked_id, blocking_id) {
+            return Box::pin(ready(Ok(DependencyInsertion::Cycle(path))));
+        }
+        let dependency = Dependency {
+            blocked_id,
+            blocking_id,
+            created_at: Some(now()),
+        };
+        dependencies.push(dependency.clone());
+        Box::pin(ready(Ok(DependencyInsertion::Inserted(dependency))))
+    }
+
+    fn delete(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Dependency>>> {
+        let mut state = self.state();
+        let dependencies = state.dependencies_mut(kind);
+        let dependency = dependencies
+            .iter()
+            .position(|dependency| {
+                dependency.blocked_id == blocked_id && dependency.blocking_id == blocking_id
+            })
+            .map(|index| dependencies.remove(index));
+        Box::pin(ready(Ok(dependency)))
+    }
+}
+
+impl TagRepository for InMemoryStore {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>> {
+        let mut tags: Vec<Tag> = self.state().tags.values().cloned().collect();
+        tags.sort_by(|left, right| left.name.cmp(&right.name));
+        Box::pin(ready(Ok(tags)))
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>> {
+        let tag = self.state().tags.get(&id).cloned();
+        Box::pin(ready(Ok(tag)))
+    }
+
+    fn insert<'a>(&'a self, tag: &'a NewTag) -> BoxFuture<'a, RepositoryResult<Option<Tag>>> {
+        let mut state = self.state();
+        if state.tags.values().any(|stored| stored.name == tag.name) {
+            return Box::pin(ready(Ok(None)));
+        }
+        state.last_tag_id += 1;
+        let tag = Tag {
+            id: state.last_tag_id,
+            name: tag.name.clone(),
+            color: tag.color.clone(),
+            created_at: Some(now()),
+        };
+        state.tags.insert(tag.id, tag.clone());
+        Box::pin(ready(Ok(Some(tag))))
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a TagUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tag>>> {
+        let tag = self.state().tags.get_mut(&id).map(|tag| {
+            if let Some(name) = &update.name {
+                tag.name = name.clone();
+            }
+            if let Some(color) = &update.color {
+                tag.color = color.clone();
+            }
+            tag.clone()
+        });
+        Box::pin(ready(Ok(tag)))
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>> {
+        let mut state = self.state();
+        let tag = state.tags.remove(&id);
+        state
+            .project_tags
+            .retain(|assignment| assignment.tag_id != id);
+        state.task_tags.retain(|assignment| assignment.tag_id != id);
+        Box::pin(ready(Ok(tag)))
+    }
+
+    fn list_assignments(
+        &self,
+        kind: ItemKind,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<TagAssignment>>> {
+        let mut assignments = self.state().tag_assignments_mut(kind).clone();
+        assignments.sort_by_key(|assignment| (assignment.item_id, assignment.tag_id));
+        Box::pin(ready(Ok(assignments)))
+    }
+
+    fn list_item_tags(
+        &self,
+        kind: ItemKind,
+        item_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>> {
+        let mut state = self.state();
+        let tag_ids: Vec<i32> = state
+            .tag_assignments_mut(kind)
+            .iter()
+            .filter(|assignment| assignment.item_id == item_id)
+            .map(|assignment| assignment.tag_id)
+            .collect();
+        let mut tags: Vec<Tag> = tag_ids
+            .iter()
+            .filter_map(|tag_id| state.tags.get(tag_id).cloned())
+            .collect();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Box::pin(ready(Ok(tags)))
+    }
+
+    fn assign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>> {
+        let mut state = self.state();
+        let assignments = state.tag_assignments_mut(kind);
+        for item_id in item_ids {
+            if !assignments
+                .iter()
+                .any(|assignment| assignment.tag_id == tag_id && assignment.item_id == *item_id)
+            {
+                assignments.push(TagAssignment {
+                    tag_id,
+                    item_id: *item_id,
+                });
+            }
+        }
+        Box::pin(ready(Ok(())))
+    }
+
+    fn unassign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>> {
+        self.state().tag_assignments_mut(kind).retain(|assignment| {
+            assignment.tag_id != tag_id || !item_ids.contains(&assignment.item_id)
+        });
+        Box::pin(ready(Ok(())))
+    }
+}
+
+impl CommentRepository for InMemoryStore {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Comment>>> {
+        let mut comments: Vec<Comment> = self
+            .state()
+            .comments
+            .values()
+            .filter(|comment| comment.project_id == project_id)
+            .cloned()
+            .collect();
+        comments.sort_by_key(|comment| (comment.created_at, comment.id));
+        Box::pin(ready(Ok(comments)))
+    }
+
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Comment>>> {
+        let mut comments: Vec<Comment> = self
+            .state()
+            .comments
+            .values()
+            .filter(|comment| project_ids.contains(&comment.project_id))
+            .cloned()
+            .collect();
+        comments.sort_by_key(|comment| (comment.created_at, comment.id));
+        Box::pin(ready(Ok(comments)))
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>> {
+        let comment = self
+            .state()
+            .comments
+            .get(&id)
+            .filter(|comment| comment.project_id == project_id)
+            .cloned();
+        Box::pin(ready(Ok(comment)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task_id: Option<i32>,
+        author: &'a str,
+        comment: &'a NewComment,
+    ) -> BoxFuture<'a, RepositoryResult<Comment>> {
+        let mut state = self.state();
+        state.last_comment_id += 1;
+        let created_at = now();
+        let comment = Comment {
+            id: state.last_comment_id,
+            project_id,
+            task_id,
+            parent_id: comment.parent_id,
+            author: author.to_string(),
+            body: comment.body.clone(),
+            created_at: Some(created_at),
+            updated_at: Some(created_at),
+            edited: false,
+        };
+        state.comments.insert(comment.id, comment.clone());
+        Box::pin(ready(Ok(comment)))
+    }
+
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        body: &'a str,
+        edited_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Comment>>> {
+        let mut state = self.state();
+        let revision_id = state.comment_revisions.len() as i32 + 1;
+        let Some(comment) = state
+            .comments
+            .get_mut(&id)
+            .filter(|comment| comment.project_id == project_id)
+        else {
+            return Box::pin(ready(Ok(None)));
+        };
+        let revision = CommentRevision {
+            id: revision_id,
+            comment_id: comment.id,
+            body: std::mem::replace(&mut comment.body, body.to_string()),
+            edited_by: edited_by.to_string(),
+            created_at: Some(now()),
+        };
+        comment.updated_at = revision.created_at;
+        comment.edited = true;
+        let comment = comment.clone();
+        state.comment_revisions.push(revision);
+        Box::pin(ready(Ok(Some(comment))))
+    }
+
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>> {
+        let mut state = self.state();
+        let comment = state
+            .comments
+            .get(&id)
+            .filter(|comment| comment.project_id == project_id)
+            .cloned();
+        if comment.is_some() {
+            state.remove_comments(vec![id]);
+        }
+        Box::pin(ready(Ok(comment)))
+    }
+
+    fn list_revisions(
+        &self,
+        comment_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<CommentRevision>>> {
+        let revisions = self
+            .state()
+            .comment_revisions
+            .iter()
+            .filter(|revision| revision.comment_id == comment_id)
+            .cloned()
+            .collect();
+        Box::pin(ready(Ok(revisions)))
+    }
+}
+
+impl AttachmentRepository for InMemoryStore {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Attachment>>> {
+        let attachments = self
+            .state()
+            .attachments
+            .values()
+            .filter(|attachment| attachment.project_id == project_id)
+            .cloned()
+            .collect();
+        Box::pin(ready(Ok(attachments)))
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>> {
+        let attachment = self
+            .state()
+            .attachments
+            .get(&id)
+            .filter(|attachment| attachment.project_id == project_id)
+            .cloned();
+        Box::pin(ready(Ok(attachment)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        attachment: &'a NewAttachment,
+    ) -> BoxFuture<'a, RepositoryResult<Attachment>> {
+        let mut state = self.state();
+        state.last_attachment_id += 1;
+        let attachment = Attachment {
+            id: state.last_attachment_id,
+            project_id,
+            file_name: attachment.file_name.clone(),
+            content_type: attachment.content_type.clone(),
+            size: attachment.size,
+            sha256: attachment.sha256.clone(),
+            storage_key: attachment.storage_key.clone(),
+            uploaded_by: attachment.uploaded_by.clone(),
+            created_at: Some(now()),
+        };
+        state.attachments.insert(attachment.id, attachment.clone());
+        Box::pin(ready(Ok(attachment)))
+    }
+
+    fn delete(
+        &self,
+        project_id: i32,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>> {
+        let mut state = self.state();
+        let exists = state
+            .attachments
+            .get(&id)
+            .is_some_and(|attachment| attachment.project_id == project_id);
+        let attachment = if exists {
+            state.attachments.remove(&id)
+        } else {
+            None
+        };
+        Box::pin(ready(Ok(attachment)))
+    }
+
+    fn storage_keys(&self) -> BoxFuture<'_, RepositoryResult<Vec<String>>> {
+        let storage_keys = self
+            .state()
+            .attachments
+            .values()
+            .map(|attachment| attachment.storage_key.clone())
+            .collect();
+        Box::pin(ready(Ok(storage_keys)))
+    }
+}
+
+impl WebhookRepository for InMemoryStore {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Webhook>>> {
+        let webhooks = self.state().webhooks.values().cloned().collect();
+        Box::pin(ready(Ok(webhooks)))
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        let webhook = self.state().webhooks.get(&id).cloned();
+        Box::pin(ready(Ok(webhook)))
+    }
+
+    fn insert<'a>(
+        &'a self,
+        webhook: &'a NewWebhook,
+        created_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Webhook>> {
+        let mut state = self.state();
+        state.last_webhook_id += 1;
+        let webhook = Webhook {
+            id: state.last_webhook_id,
+            url: webhook.url.clone(),
+            secret: webhook.secret.clone(),
+            event_types: webhook.event_types.clone(),
+            enabled: true,
+            consecutive_failures: 0,
+            disabled_at: None,
+            created_by: created_by.to_string(),
+            created_at: Some(now()),
+            updated_at: Some(now()),
+        };
+        state.webhooks.insert(webhook.id, webhook.clone());
+        Box::pin(ready(Ok(webhook)))
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a WebhookUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Webhook>>> {
+        let mut state = self.state();
+        let webhook = state.webhooks.get_mut(&id).map(|webhook| {
+            if let Some(url) = &update.url {
+                webhook.url = url.clone();
+            }
+            if let Some(secret) = &update.secret {
+                webhook.secret = secret.clone();
+            }
+            if let Some(event_types) = &update.event_types {
+                webhook.event_types = event_types.clone();
+            }
+            match update.enabled {
+                Some(true) => {
+                    webhook.consecutive_failures = 0;
+                    webhook.disabled_at = None;
+                }
+                Some(false) if webhook.enabled => webhook.disabled_at = Some(now()),
+                _ => {}
+            }
+            webhook.enabled = update.enabled.unwrap_or(webhook.enabled);
+            webhook.updated_at = Some(now());
+            webhook.clone()
+        });
+        Box::pin(ready(Ok(webhook)))
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        let mut state = self.state();
+        let webhook = state.webhooks.remove(&id);
+        state
+            .webhook_deliveries
+            .retain(|delivery| delivery.webhook_id != id);
+        state
+            .pending_deliveries
+            .retain(|_, delivery| delivery.webhook_id != id);
+        Box::pin(ready(Ok(webhook)))
+    }
+
+    fn record_outcome(
+        &self,
+        id: i32,
+        succeeded: bool,
+        disable_after: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        let mut state = self.state();
+        let webhook = state.webhooks.get_mut(&id).map(|webhook| {
+            if succeeded {
+                webhook.consecutive_failures = 0;
+            } else {
+                webhook.consecutive_failures += 1;
+                if webhook.enabled && webhook.consecutive_failures >= disable_after {
+                    webhook.enabled = false;
+                    webhook.disabled_at = Some(now());
+                }
+            }
+            webhook.clone()
+        });
+        Box::pin(ready(Ok(webhook)))
+    }
+
+    fn insert_delivery<'a>(
+        &'a self,
+        delivery: &'a NewWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<WebhookDelivery>> {
+        let mut state = self.state();
+        state.last_webhook_delivery_id += 1;
+        let delivery = WebhookDelivery {
+            id: state.last_webhook_delivery_id,
+            webhook_id: delivery.webhook_id,
+            event_id: delivery.event_id.clone(),
+            event_type: delivery.event_type.clone(),
+            attempt: delivery.attempt,
+            status_code: delivery.status_code,
+            error: delivery.error.clone(),
+            succeeded: delivery.succeeded,
+            duration_ms: delivery.duration_ms,
+            created_at: Some(now()),
+        };
+        state.webhook_deliveries.push(delivery.clone());
+        Box::pin(ready(Ok(delivery)))
+    }
+
+    fn list_deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<WebhookDelivery>>> {
+        let deliveries = self
+            .state()
+            .webhook_deliveries
+            .iter()
+            .rev()
+            .filter(|delivery| delivery.webhook_id == webhook_id)
+            .take(usize::try_from(limit).unwrap_or_default())
+            .cloned()
+            .collect();
+        Box::pin(ready(Ok(deliveries)))
+    }
+
+    fn insert_pending<'a>(
+        &'a self,
+        delivery: &'a NewPendingWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<PendingWebhookDelivery>> {
+        let mut state = self.state();
+        state.last_pending_delivery_id += 1;
+        let delivery = PendingWebhookDelivery {
+            id: state.last_pending_delivery_id,
+            webhook_id: delivery.webhook_id,
+            event_id: delivery.event_id.clone(),
+            event_type: delivery.event_type.clone(),
+            body: delivery.body.clone(),
+            attempt: 1,
+            next_attempt_at: now(),
+        };
+        state
+            .pending_deliveries
+            .insert(delivery.id, delivery.clone());
+        Box::pin(ready(Ok(delivery)))
+    }
+
+    fn claim_pending(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<PendingWebhookDelivery>>> {
+        let mut state = self.state();
+        let now = now();
+        let mut due: Vec<&mut PendingWebhookDelivery> = state
+            .pending_deliveries
+            .values_mut()
+            .filter(|delivery| delivery.next_attempt_at <= now)
+            .collect();
+        due.sort_by_key(|delivery| (delivery.next_attempt_at, delivery.id));
+        let claimed = due
+            .into_iter()
+            .take(usize::try_from(limit).unwrap_or_default())
+            .map(|delivery| {
+                delivery.next_attempt_at = now + TimeDelta::from_std(lease).unwrap_or_default();
+                delivery.clone()
+            })
+            .collect();
+        Box::pin(ready(Ok(claimed)))
+    }
+
+    fn reschedule_pending(
+        &self,
+        id: i32,
+        attempt: i32,
+        delay: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<()>> {
+        if let Some(delivery) = self.state().pending_deliveries.get_mut(&id) {
+            delivery.attempt = attempt;
+            delivery.next_attempt_at = now() + TimeDelta::from_std(delay).unwrap_or_default();
+        }
+        Box::pin(ready(Ok(())))
+    }
+
+    fn delete_pending(&self, id: i32) -> BoxFuture<'_, RepositoryResult<()>> {
+        self.state().pending_deliveries.remove(&id);
+        Box::pin(ready(Ok(())))
+    }
+}
+
+/// Splits `text` into the words the search terms can prefix, keeping what is
+/// in between so that the text can be put back together.
+fn split_words(text: &str) -> Vec<(bool, &str)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_word = false;
+    for (index, character) in text.char_indices() {
+        if character.is_alphanumeric() != in_word {
+            if index > start {
+                parts.push((in_word, &text[start..index]));
+            }
+            start = index;
+            in_word = !in_word;
+        }
+    }
+    if start < text.len() {
+        parts.push((in_word, &text[start..]));
+    }
+    parts
+}
+
+fn prefixed_words(text: &str, term: &str) -> usize {
+    split_words(text)
+        .into_iter()
+        .filter(|(is_word, word)| *is_word && word.to_lowercase().starts_with(term))
+        .count()
+}
+
+/// A stand-in for the Postgres ranking without stemming: every term must
+/// prefix a word, words of the title count more. The snippet is the whole
+/// text with the matched words marked.
+fn text_match(terms: &[String], title: Option<&str>, body: Option<&str>) -> Option<(f64, String)> {
+    let mut rank = 0.0;
+    for term in terms {
+        let title_matches = title.map_or(0, |title| prefixed_words(title, term));
+        let body_matches = body.map_or(0, |body| prefixed_words(body, term));
+        if title_matches + body_matches == 0 {
+            return None;
+        }
+        rank += title_matches as f64 + 0.4 * body_matches as f64;
+    }
+    let text = [title, body]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let snippet = split_words(&text)
+        .into_iter()
+        .map(|(is_word, part)| {
+            let lowercase = part.to_lowercase();
+            if is_word
+                && terms
+                    .iter()
+                    .any(|term| lowercase.starts_with(term.as_str()))
+            {
+                format!("<mark>{}</mark>", part)
+            } else {
+                part.to_string()
+            }
+        })
+        .collect();
+    Some((rank, snippet))
+}
+
+impl SearchRepository for InMemoryStore {
+    fn search<'a>(
+        &'a self,
+        terms: &'a [String],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<SearchHit>>> {
+        let state = self.state();
+        let projects = state.projects.values().filter_map(|project| {
+            let (rank, snippet) =
+                text_match(terms, Some(&project.name), project.description.as_deref())?;
+            Some(SearchHit {
+                entity_type: "project".to_string(),
+                id: project.id,
+                project_id: project.id,
+                task_id: None,
+                title: Some(project.name.clone()),
+                status: Some(project.status.clone()),
+                snippet,
+                rank,
+            })
+        });
+        let tasks = state.tasks.values().filter_map(|task| {
+            let (rank, snippet) =
+                text_match(terms, Some(&task.title), task.description.as_deref())?;
+            Some(SearchHit {
+                entity_type: "task".to_string(),
+                id: task.id,
+                project_id: task.project_id,
+                task_id: Some(task.id),
+                title: Some(task.title.clone()),
+                status: Some(task.status.clone()),
+                snippet,
+                rank,
+            })
+        });
+        let comments = state.comments.values().filter_map(|comment| {
+            let (rank, snippet) = text_match(terms, None, Some(&comment.body))?;
+            Some(SearchHit {
+                entity_type: "comment".to_string(),
+                id: comment.id,
+                project_id: comment.project_id,
+                task_id: comment.task_id,
+                title: None,
+                status: None,
+                snippet,
+                rank,
+            })
+        });
+        let mut hits: Vec<SearchHit> = projects.chain(tasks).chain(comments).collect();
+        hits.sort_by(|a, b| {
+            b.rank
+                .total_cmp(&a.rank)
+                .then_with(|| a.entity_type.cmp(&b.entity_type))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        Box::pin(ready(Ok(hits)))
+    }
+}
@@ -1,7 +1,232 @@
-use sqlx::{Pool, Postgres, migrate::Migrator};
+use std::collections::HashMap;
 
-pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
-    let migrator = Migrator::new(std::path::Path::new("./sql")).await?;
-    migrator.run(pool).await?;
+use sqlx::{
+    Database, Pool, Postgres,
+    migrate::{Migrate, MigrateError, Migration, Migrator},
+};
+
+use super::error::NinoverseDbError;
+
+/// Databases with a migration set, every backend keeps its own directory of
+/// `<version>_<description>.up.sql` / `.down.sql` pairs.
+pub trait MigratedDatabase: Database {
+    const MIGRATIONS_PATH: &'static str;
+}
+
+impl MigratedDatabase for Postgres {
+    const MIGRATIONS_PATH: &'static str = "./sql";
+}
+
+#[cfg(feature = "sqlite")]
+impl MigratedDatabase for sqlx::Sqlite {
+    const MIGRATIONS_PATH: &'static str = "./sql_sqlite";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the local file no longer matches the applied checksum.
+    Modified,
+    /// Applied, but the local file is gone.
+    Missing,
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub reversible: bool,
+}
+
+async fn get_migrator<DB: MigratedDatabase>() -> Result<Migrator, NinoverseDbError> {
+    Ok(Migrator::new(std::path::Path::new(DB::MIGRATIONS_PATH)).await?)
+}
+
+fn up_migrations(migrator: &Migrator) -> impl DoubleEndedIterator<Item = &Migration> {
+    migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+}
+
+fn down_migration(migrator: &Migrator, version: i64) -> Option<&Migration> {
+    migrator.iter().find(|migration| {
+        migration.version == version && migration.migration_type.is_down_migration()
+    })
+}
+
+async fn applied_checksums<C: Migrate>(
+    connection: &mut C,
+) -> Result<HashMap<i64, Vec<u8>>, NinoverseDbError> {
+    connection.ensure_migrations_table().await?;
+    Ok(connection
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|applied| (applied.version, applied.checksum.into_owned()))
+        .collect())
+}
+
+fn build_status(migrator: &Migrator, applied: &HashMap<i64, Vec<u8>>) -> Vec<MigrationStatus> {
+    let mut statuses: Vec<MigrationStatus> = up_migrations(migrator)
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            state: match applied.get(&migration.version) {
+                None => MigrationState::Pending,
+                Some(checksum) if *checksum != *migration.checksum => MigrationState::Modified,
+                Some(_) => MigrationState::Applied,
+            },
+            reversible: down_migration(migrator, migration.version).is_some(),
+        })
+        .collect();
+    for version in applied.keys() {
+        if !migrator.version_exists(*version) {
+            statuses.push(MigrationStatus {
+                version: *version,
+                description: String::new(),
+                state: MigrationState::Missing,
+                reversible: false,
+            });
+        }
+    }
+    statuses.sort_by_key(|status| status.version);
+    statuses
+}
+
+/// Refuses to go on when an already applied migration was edited or removed,
+/// the schema would silently diverge from the files otherwise.
+fn check_applied_migrations(
+    migrations_path: &str,
+    statuses: &[MigrationStatus],
+) -> Result<(), NinoverseDbError> {
+    let diverged: Vec<String> = statuses
+        .iter()
+        .filter(|status| {
+            matches!(
+                status.state,
+                MigrationState::Modified | MigrationState::Missing
+            )
+        })
+        .map(|status| format!("{} ({:?})", status.version, status.state))
+        .collect();
+    if diverged.is_empty() {
+        Ok(())
+    } else {
+        Err(NinoverseDbError::ModifiedMigration {
+            additional_info: format!(
+                "Applied migrations differ from {}: {}.",
+                migrations_path,
+                diverged.join(", ")
+            ),
+        })
+    }
+}
+
+pub async fn migration_status<DB>(pool: &Pool<DB>) -> Result<Vec<MigrationStatus>, NinoverseDbError>
+where
+    DB: MigratedDatabase,
+    DB::Connection: Migrate,
+{
+    let migrator = get_migrator::<DB>().await?;
+    let mut connection = pool.acquire().await?;
+    let applied = applied_checksums(&mut *connection).await?;
+    Ok(build_status(&migrator, &applied))
+}
+
+async fn migrate_to_locked<C: Migrate>(
+    migrations_path: &str,
+    migrator: &Migrator,
+    connection: &mut C,
+    target: Option<i64>,
+) -> Result<(), NinoverseDbError> {
+    let applied = applied_checksums(connection).await?;
+    if let Some(version) = connection.dirty_version().await? {
+        return Err(MigrateError::Dirty(version).into());
+    }
+    check_applied_migrations(migrations_path, &build_status(migrator, &applied))?;
+    if let Some(target) = target {
+        if target != 0 && !migrator.version_exists(target) {
+            return Err(MigrateError::VersionNotPresent(target).into());
+        }
+        let mut to_revert: Vec<i64> = applied
+            .keys()
+            .copied()
+            .filter(|version| *version > target)
+            .collect();
+        to_revert.sort_unstable_by(|a, b| b.cmp(a));
+        for version in to_revert {
+            let migration = down_migration(migrator, version).ok_or_else(|| {
+                NinoverseDbError::IrreversibleMigration {
+                    additional_info: format!("Migration {} has no down file.", version),
+                }
+            })?;
+            println!(
+                "DB_MIGRATION: Reverting {} {}",
+                version, migration.description
+            );
+            connection.revert(migration).await?;
+        }
+    }
+    for migration in up_migrations(migrator)
+        .filter(|migration| target.is_none_or(|target| migration.version <= target))
+        .filter(|migration| !applied.contains_key(&migration.version))
+    {
+        println!(
+            "DB_MIGRATION: Applying {} {}",
+            migration.version, migration.description
+        );
+        connection.apply(migration).await?;
+    }
     Ok(())
 }
+
+/// Brings the schema to `target`, applying or reverting migrations as
+/// needed. `None` applies every pending migration, `Some(0)` reverts all.
+pub async fn migrate_to<DB>(pool: &Pool<DB>, target: Option<i64>) -> Result<(), NinoverseDbError>
+where
+    DB: MigratedDatabase,
+    DB::Connection: Migrate,
+{
+    let migrator = get_migrator::<DB>().await?;
+    let mut connection = pool.acquire().await?;
+    connection.lock().await?;
+    let result = migrate_to_locked(DB::MIGRATIONS_PATH, &migrator, &mut *connection, target).await;
+    connection.unlock().await?;
+    result
+}
+
+pub async fn run_migrations<DB>(pool: &Pool<DB>) -> Result<(), NinoverseDbError>
+where
+    DB: MigratedDatabase,
+    DB::Connection: Migrate,
+{
+    migrate_to(pool, None).await
+}
+
+/// Reverts the latest applied migration, returning its version (`None` when
+/// nothing is applied).
+pub async fn revert_last_migration<DB>(pool: &Pool<DB>) -> Result<Option<i64>, NinoverseDbError>
+where
+    DB: MigratedDatabase,
+    DB::Connection: Migrate,
+{
+    let statuses = migration_status(pool).await?;
+    let Some(last_applied) = statuses
+        .iter()
+        .rev()
+        .find(|status| status.state != MigrationState::Pending)
+    else {
+        return Ok(None);
+    };
+    let target = statuses
+        .iter()
+        .rev()
+        .filter(|status| status.state != MigrationState::Pending)
+        .map(|status| status.version)
+        .find(|version| *version < last_applied.version)
+        .unwrap_or(0);
+    migrate_to(pool, Some(target)).await?;
+    Ok(Some(last_applied.version))
+}
@@ -1,9 +1,36 @@
-mod migration;
+pub mod api_keys;
+pub mod attachments;
+pub mod audit;
+pub mod comments;
+pub mod dependencies;
+pub mod error;
+pub mod in_memory;
+pub mod migration;
+pub mod projects;
+pub mod repository;
+pub mod search;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod status_types;
+pub mod structs;
+pub mod tags;
+pub mod tasks;
+pub mod teams;
+pub mod tenants;
+pub mod users;
+pub mod webhooks;
 
+use std::{collections::BTreeSet, sync::Arc};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use repository::Repositories;
 use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
 
+use super::blob_handler::BlobStore;
 use super::configuration_handler::{
-    get_pg_db, get_pg_host, get_pg_password, get_pg_port, get_pg_user,
+    StorageBackend, get_pg_db, get_pg_host, get_pg_password, get_pg_port,
+    get_pg_row_level_security, get_pg_user, get_projects_purge_interval,
+    get_projects_retention_days, get_search_language, get_storage_backend,
 };
 
 async fn get_pool() -> Result<Pool<Postgres>, sqlx::Error> {
@@ -22,11 +49,11 @@ async fn get_pool() -> Result<Pool<Postgres>, sqlx::Error> {
     Ok(pool)
 }
 
-async fn migrate_db(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
-    migration::run_migrations(&pool).await
+async fn migrate_db(pool: &Pool<Postgres>) -> Result<(), error::NinoverseDbError> {
+    migration::run_migrations(pool).await
 }
 
-pub async fn init_db() -> Result<Pool<Postgres>, sqlx::Error> {
+pub async fn connect_db() -> Result<Pool<Postgres>, sqlx::Error> {
     let mut pool: Result<Pool<Postgres>, sqlx::Error> = Err(sqlx::Error::PoolClosed);
     for _ in 0..5 {
         println!("DB_INIT: Trying to connect to the database...");
@@ -36,14 +63,181 @@ pub async fn init_db() -> Result<Pool<Postgres>, sqlx::Error> {
             break;
         }
     }
-    if let Ok(pool) = pool {
-        println!("DB_INIT: Running migrations");
-        migrate_db(&pool)
-            .await
-            .expect("DB_INIT: Error while running migrations");
-        println!("DB_INIT: Migrations run successfully");
-        Ok(pool)
-    } else {
-        pool
+    pool
+}
+
+pub async fn init_db() -> Result<Pool<Postgres>, sqlx::Error> {
+    let pool = connect_db().await?;
+    println!("DB_INIT: Running migrations");
+    migrate_db(&pool)
+        .await
+        .expect("DB_INIT: Error while running migrations");
+    println!("DB_INIT: Migrations run successfully");
+    let search_language = get_search_language();
+    if search::apply_search_language(&pool, &search_language).await? {
+        println!(
+            "DB_INIT: Rebuilt the search vectors with the {} configuration",
+            search_language
+        );
+    }
+    let row_level_security = get_pg_row_level_security();
+    tenants::set_row_level_security(&pool, row_level_security).await?;
+    println!(
+        "DB_INIT: Row level security {}",
+        if row_level_security {
+            "enforced"
+        } else {
+            "not enforced"
+        }
+    );
+    Ok(pool)
+}
+
+#[cfg(feature = "sqlite")]
+pub async fn init_sqlite_db() -> Result<Pool<sqlx::Sqlite>, sqlx::Error> {
+    let path = super::configuration_handler::get_sqlite_path();
+    println!("DB_INIT: Opening the SQLite database {}", path);
+    let pool = sqlite::get_sqlite_pool(&path).await?;
+    println!("DB_INIT: Running migrations");
+    migration::run_migrations(&pool)
+        .await
+        .expect("DB_INIT: Error while running migrations");
+    println!("DB_INIT: Migrations run successfully");
+    Ok(pool)
+}
+
+#[cfg(feature = "sqlite")]
+async fn init_sqlite_repositories() -> Result<Repositories, sqlx::Error> {
+    Ok(Repositories::sqlite(Arc::new(init_sqlite_db().await?)))
+}
+
+#[cfg(not(feature = "sqlite"))]
+async fn init_sqlite_repositories() -> Result<Repositories, sqlx::Error> {
+    Err(sqlx::Error::Configuration(
+        "DB_INIT: STORAGE_BACKEND=sqlite requires building with the sqlite feature".into(),
+    ))
+}
+
+pub async fn init_repositories() -> Result<Repositories, sqlx::Error> {
+    match get_storage_backend() {
+        StorageBackend::Postgres => Ok(Repositories::postgres(Arc::new(init_db().await?))),
+        StorageBackend::Sqlite => init_sqlite_repositories().await,
+        StorageBackend::InMemory => {
+            println!("DB_INIT: Using the in-memory storage backend");
+            Ok(Repositories::in_memory())
+        }
+    }
+}
+
+/// Blobs this recent may belong to an upload whose row isn't inserted yet.
+const ORPHANED_BLOB_GRACE: TimeDelta = TimeDelta::hours(1);
+
+/// Also deletes the attachment content of the purged projects, their
+/// metadata goes with the project rows, and the orphaned blobs.
+pub async fn run_purge_job(repositories: Repositories, blob_store: Arc<dyn BlobStore>) {
+    let retention_days = get_projects_retention_days();
+    let mut interval = tokio::time::interval(get_projects_purge_interval());
+    loop {
+        interval.tick().await;
+        let tenants = match repositories.tenants.list().await {
+            Ok(tenants) => tenants,
+            Err(list_error) => {
+                println!("PURGE_JOB: Error listing tenants: {:?}", list_error);
+                continue;
+            }
+        };
+        for tenant in tenants {
+            let tenant_repositories = repositories.for_tenant(&tenant.id);
+            match tenant_repositories
+                .projects
+                .purge_deleted(retention_days)
+                .await
+            {
+                Ok(purged_ids) if purged_ids.is_empty() => {}
+                Ok(purged_ids) => {
+                    println!(
+                        "PURGE_JOB: Purged {} projects of tenant {} deleted more than {} days ago: {:?}",
+                        purged_ids.len(),
+                        tenant.id,
+                        retention_days,
+                        purged_ids
+                    );
+                    for purged_id in purged_ids {
+                        let prefix = format!("{}/{}/", tenant.id, purged_id);
+                        if let Err(delete_error) = blob_store.delete_prefix(&prefix).await {
+                            println!(
+                                "PURGE_JOB: Error deleting the attachments of project {} of tenant {}: {:?}",
+                                purged_id, tenant.id, delete_error
+                            );
+                        }
+                    }
+                }
+                Err(purge_error) => println!(
+                    "PURGE_JOB: Error purging projects of tenant {}: {:?}",
+                    tenant.id, purge_error
+                ),
+            }
+            let swept_keys = sweep_orphaned_blobs(
+                &tenant_repositories,
+                blob_store.as_ref(),
+                Utc::now() - ORPHANED_BLOB_GRACE,
+            )
+            .await;
+            if !swept_keys.is_empty() {
+                println!(
+                    "PURGE_JOB: Deleted {} orphaned blobs of tenant {}: {:?}",
+                    swept_keys.len(),
+                    tenant.id,
+                    swept_keys
+                );
+            }
+        }
+    }
+}
+
+/// Deletes the blobs of the tenant no attachment refers to, such as those of
+/// deleted attachments whose content couldn't be deleted. Only blobs last
+/// modified before `older_than` are considered, the row of an upload is
+/// inserted after its content is stored. Returns the deleted keys.
+pub async fn sweep_orphaned_blobs(
+    repositories: &Repositories,
+    blob_store: &dyn BlobStore,
+    older_than: DateTime<Utc>,
+) -> Vec<String> {
+    let prefix = format!("{}/", repositories.tenant_id);
+    // Listed before the keys, an upload finishing in between is still known.
+    let blobs = match blob_store.list(&prefix).await {
+        Ok(blobs) => blobs,
+        Err(list_error) => {
+            println!(
+                "PURGE_JOB: Error listing the blobs of {}: {:?}",
+                prefix, list_error
+            );
+            return vec![];
+        }
+    };
+    let storage_keys: BTreeSet<String> = match repositories.attachments.storage_keys().await {
+        Ok(storage_keys) => storage_keys.into_iter().collect(),
+        Err(keys_error) => {
+            println!(
+                "PURGE_JOB: Error listing the attachments of tenant {}: {:?}",
+                repositories.tenant_id, keys_error
+            );
+            return vec![];
+        }
+    };
+    let mut swept_keys = vec![];
+    for blob in blobs {
+        if blob.modified >= older_than || storage_keys.contains(&blob.key) {
+            continue;
+        }
+        match blob_store.delete(&blob.key).await {
+            Ok(()) => swept_keys.push(blob.key),
+            Err(delete_error) => println!(
+                "PURGE_JOB: Error deleting the orphaned blob {}: {:?}",
+                blob.key, delete_error
+            ),
+        }
     }
+    swept_keys
 }
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{ProjectRepository, RepositoryResult},
+    structs::{NewProject, Project, ProjectStatusHistoryEntry, ProjectUpdate},
+    tenants::TenantPool,
+};
+
+/// `blocked` looks for a dependency that is neither deleted nor in a
+/// completed status of the tenant.
+const PROJECT_COLUMNS: &str = "id, name, description, status, created_at, updated_at, version, \
+     deleted_at, owner_id, team_id, \
+     EXISTS (SELECT 1 FROM project_dependencies \
+        JOIN projects AS blocking ON blocking.id = project_dependencies.blocking_id \
+        WHERE project_dependencies.blocked_id = projects.id \
+           AND blocking.deleted_at IS NULL \
+           AND blocking.status NOT IN (SELECT name FROM status_types_dictionary \
+              WHERE completed AND (tenant_id IS NULL OR tenant_id = projects.tenant_id))) \
+     AS blocked";
+
+pub struct PostgresProjectRepository {
+    pool: TenantPool,
+}
+
+impl PostgresProjectRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresProjectRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl ProjectRepository for PostgresProjectRepository {
+    fn list(&self, include_deleted: bool) -> BoxFuture<'_, RepositoryResult<Vec<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "SELECT {} FROM projects \
+                 WHERE tenant_id = $2 AND ($1 OR deleted_at IS NULL) ORDER BY id",
+                PROJECT_COLUMNS
+            ))
+            .bind(include_deleted)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(
+        &self,
+        id: i32,
+        include_deleted: bool,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "SELECT {} FROM projects \
+                 WHERE id = $1 AND tenant_id = $3 AND ($2 OR deleted_at IS NULL)",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(include_deleted)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project: &'a NewProject,
+        owner_id: Option<i32>,
+    ) -> BoxFuture<'a, RepositoryResult<Project>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "INSERT INTO projects (name, description, status, owner_id, team_id, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6) RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(&project.status)
+            .bind(owner_id)
+            .bind(project.team_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a ProjectUpdate,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET \
+                    name = COALESCE($2, name), \
+                    description = COALESCE($3, description), \
+                    status = COALESCE($4, status), \
+                    owner_id = COALESCE($6, owner_id), \
+                    team_id = COALESCE($7, team_id) \
+                 WHERE id = $1 AND tenant_id = $8 AND deleted_at IS NULL \
+                    AND ($5::INTEGER[] IS NULL OR version = ANY($5)) \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(&update.name)
+            .bind(&update.description)
+            .bind(&update.status)
+            .bind(accepted_versions)
+            .bind(update.owner_id)
+            .bind(update.team_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn soft_delete(
+        &self,
+        id: i32,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET deleted_at = CURRENT_TIMESTAMP \
+                 WHERE id = $1 AND tenant_id = $3 AND deleted_at IS NULL \
+                    AND ($2::INTEGER[] IS NULL OR version = ANY($2)) \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(accepted_versions)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn restore(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET deleted_at = NULL \
+                 WHERE id = $1 AND tenant_id = $2 AND deleted_at IS NOT NULL \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn purge_deleted(&self, retention_days: i32) -> BoxFuture<'_, RepositoryResult<Vec<i32>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, i32>(
+                "DELETE FROM projects \
+                 WHERE tenant_id = $2 AND deleted_at IS NOT NULL \
+                    AND deleted_at < CURRENT_TIMESTAMP - make_interval(days => $1) \
+                 RETURNING id",
+            )
+            .bind(retention_days)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn transition_status<'a>(
+        &'a self,
+        id: i32,
+        from_status: &'a str,
+        to_status: &'a str,
+        actor: &'a str,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<(Project, ProjectStatusHistoryEntry)>>> {
+        Box::pin(async move {
+            let mut transaction = self.pool.begin().await?;
+            let project = sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET status = $3 \
+                 WHERE id = $1 AND tenant_id = $5 AND status = $2 AND deleted_at IS NULL \
+                    AND ($4::INTEGER[] IS NULL OR version = ANY($4)) \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(from_status)
+            .bind(to_status)
+            .bind(accepted_versions)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            let Some(project) = project else {
+                transaction.rollback().await?;
+                return Ok(None);
+            };
+            let history_entry = sqlx::query_as::<_, ProjectStatusHistoryEntry>(
+                "INSERT INTO project_status_history \
+                    (project_id, from_status, to_status, actor, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5) \
+                 RETURNING id, project_id, from_status, to_status, actor, created_at",
+            )
+            .bind(id)
+            .bind(from_status)
+            .bind(to_status)
+            .bind(actor)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(Some((project, history_entry)))
+        })
+    }
+
+    fn list_status_history(
+        &self,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<ProjectStatusHistoryEntry>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ProjectStatusHistoryEntry>(
+                "SELECT id, project_id, from_status, to_status, actor, created_at \
+                 FROM project_status_history WHERE project_id = $1 AND tenant_id = $2 ORDER BY id",
+            )
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
@@ -0,0 +1,613 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+#[cfg(feature = "sqlite")]
+use super::sqlite::{
+    SqliteApiKeyRepository, SqliteAttachmentRepository, SqliteAuditRepository,
+    SqliteCommentRepository, SqliteDependencyRepository, SqliteProjectRepository,
+    SqliteSearchRepository, SqliteStatusTypeRepository, SqliteTagRepository, SqliteTaskRepository,
+    SqliteTeamRepository, SqliteTenantRepository, SqliteUserRepository, SqliteWebhookRepository,
+};
+use super::{
+    api_keys::PostgresApiKeyRepository,
+    attachments::PostgresAttachmentRepository,
+    audit::PostgresAuditRepository,
+    comments::PostgresCommentRepository,
+    dependencies::PostgresDependencyRepository,
+    error::NinoverseDbError,
+    in_memory::InMemoryTenants,
+    projects::PostgresProjectRepository,
+    search::PostgresSearchRepository,
+    status_types::PostgresStatusTypeRepository,
+    structs::{
+        ApiKey, Attachment, AuditFilter, AuditRecord, Comment, CommentRevision, Dependency,
+        DependencyInsertion, ItemKind, Membership, NewApiKey, NewAttachment, NewAuditRecord,
+        NewComment, NewPendingWebhookDelivery, NewProject, NewStatusType, NewTag, NewTask, NewTeam,
+        NewTenant, NewWebhook, NewWebhookDelivery, PendingWebhookDelivery, Project,
+        ProjectStatusHistoryEntry, ProjectUpdate, SearchHit, StatusTransition, StatusType,
+        StatusTypeUpdate, Tag, TagAssignment, TagUpdate, Task, TaskUpdate, Team, Tenant, User,
+        Webhook, WebhookDelivery, WebhookUpdate,
+    },
+    tags::PostgresTagRepository,
+    tasks::PostgresTaskRepository,
+    teams::PostgresTeamRepository,
+    tenants::PostgresTenantRepository,
+    users::PostgresUserRepository,
+    webhooks::PostgresWebhookRepository,
+};
+
+pub type RepositoryResult<T> = Result<T, NinoverseDbError>;
+
+/// The tenant every row belonged to before multi-tenancy, seeded by the
+/// migrations.
+pub const DEFAULT_TENANT: &str = "default";
+
+pub trait ProjectRepository: Send + Sync {
+    fn list(&self, include_deleted: bool) -> BoxFuture<'_, RepositoryResult<Vec<Project>>>;
+
+    fn get(
+        &self,
+        id: i32,
+        include_deleted: bool,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>>;
+
+    fn insert<'a>(
+        &'a self,
+        project: &'a NewProject,
+        owner_id: Option<i32>,
+    ) -> BoxFuture<'a, RepositoryResult<Project>>;
+
+    /// Applies the update only if the stored row version is one of
+    /// `accepted_versions` (any version when `None`). Returns `None` when no
+    /// row matched, the caller tells a missing project from a stale version by
+    /// reading it back.
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a ProjectUpdate,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Project>>>;
+
+    /// Marks the project as deleted, returns `None` when it doesn't exist or is
+    /// already deleted.
+    fn soft_delete(
+        &self,
+        id: i32,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>>;
+
+    /// Clears the deletion mark, returns `None` when the project doesn't exist
+    /// or isn't deleted.
+    fn restore(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Project>>>;
+
+    /// Hard deletes the projects soft deleted for longer than
+    /// `retention_days`, returning the purged ids.
+    fn purge_deleted(&self, retention_days: i32) -> BoxFuture<'_, RepositoryResult<Vec<i32>>>;
+
+    /// Moves the project from `from_status` to `to_status` and records it in
+    /// the status history atomically. Returns `None` when the project changed
+    /// in the meantime (different status or version).
+    fn transition_status<'a>(
+        &'a self,
+        id: i32,
+        from_status: &'a str,
+        to_status: &'a str,
+        actor: &'a str,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<(Project, ProjectStatusHistoryEntry)>>>;
+
+    fn list_status_history(
+        &self,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<ProjectStatusHistoryEntry>>>;
+}
+
+pub trait StatusTypeRepository: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusType>>>;
+
+    fn exists<'a>(&'a self, name: &'a str) -> BoxFuture<'a, RepositoryResult<bool>>;
+
+    fn insert<'a>(
+        &'a self,
+        status_type: &'a NewStatusType,
+    ) -> BoxFuture<'a, RepositoryResult<StatusType>>;
+
+    /// The tenant's own status type, the shared ones are never returned.
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>>;
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a StatusTypeUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusType>>>;
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>>;
+
+    /// Whether projects, deleted ones included, tasks, the status history or
+    /// the transitions refer to the status type.
+    fn in_use<'a>(&'a self, status_type: &'a StatusType) -> BoxFuture<'a, RepositoryResult<bool>>;
+
+    fn list_transitions(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusTransition>>>;
+
+    fn get_transition<'a>(
+        &'a self,
+        from_status: &'a str,
+        to_status: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusTransition>>>;
+}
+
+pub trait AuditRepository: Send + Sync {
+    fn insert<'a>(
+        &'a self,
+        record: &'a NewAuditRecord,
+    ) -> BoxFuture<'a, RepositoryResult<AuditRecord>>;
+
+    fn list<'a>(
+        &'a self,
+        filter: &'a AuditFilter,
+    ) -> BoxFuture<'a, RepositoryResult<Vec<AuditRecord>>>;
+}
+
+pub trait ApiKeyRepository: Send + Sync {
+    fn insert<'a>(&'a self, api_key: &'a NewApiKey) -> BoxFuture<'a, RepositoryResult<ApiKey>>;
+
+    /// The key with this hash, unless it is revoked or expired.
+    fn find_active<'a>(
+        &'a self,
+        key_hash: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>>;
+
+    fn list<'a>(&'a self, subject: &'a str) -> BoxFuture<'a, RepositoryResult<Vec<ApiKey>>>;
+
+    /// Revokes a key of `subject`, returns `None` when it doesn't exist or is
+    /// already revoked.
+    fn revoke<'a>(
+        &'a self,
+        id: i32,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>>;
+}
+
+pub trait UserRepository: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<User>>>;
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<User>>>;
+
+    fn find_by_subject<'a>(
+        &'a self,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<User>>>;
+
+    /// Creates the user on first sight, or overwrites its admin flag.
+    fn upsert<'a>(
+        &'a self,
+        subject: &'a str,
+        is_admin: bool,
+    ) -> BoxFuture<'a, RepositoryResult<User>>;
+}
+
+pub trait TeamRepository: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Team>>>;
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Team>>>;
+
+    /// Returns `None` when a team with this name already exists.
+    fn insert<'a>(&'a self, team: &'a NewTeam) -> BoxFuture<'a, RepositoryResult<Option<Team>>>;
+
+    fn list_members(&self, team_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>>;
+
+    /// Memberships of a user across every team.
+    fn list_memberships(&self, user_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>>;
+
+    /// Adds the user to the team or changes its role there.
+    fn set_member<'a>(
+        &'a self,
+        team_id: i32,
+        user_id: i32,
+        role: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Membership>>;
+
+    /// Returns `None` when the user is not a member of the team.
+    fn remove_member(
+        &self,
+        team_id: i32,
+        user_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Membership>>>;
+}
+
+pub trait TaskRepository: Send + Sync {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Task>>>;
+
+    /// The tasks of several projects in one query, for batched loading.
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>>;
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>>;
+
+    /// Looks a task up without knowing its project.
+    fn find(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>>;
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task: &'a NewTask,
+    ) -> BoxFuture<'a, RepositoryResult<Task>>;
+
+    /// Returns `None` when the task doesn't exist in the project.
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        update: &'a TaskUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Task>>>;
+
+    /// Deletes the task with its subtasks, returns `None` when it doesn't
+    /// exist in the project.
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>>;
+
+    /// Gives the tasks the positions of their index in `task_ids`
+    /// atomically, the caller checks they are the tasks of the project.
+    fn reorder<'a>(
+        &'a self,
+        project_id: i32,
+        task_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>>;
+}
+
+pub trait DependencyRepository: Send + Sync {
+    /// Every dependency of this kind in the tenant, graphs are walked in
+    /// memory.
+    fn list(&self, kind: ItemKind) -> BoxFuture<'_, RepositoryResult<Vec<Dependency>>>;
+
+    /// Inserts the dependency unless it exists or would close a cycle, the
+    /// check and the insert are atomic within the tenant. The caller checks
+    /// both ends exist.
+    fn insert(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<DependencyInsertion>>;
+
+    /// Returns `None` when the dependency doesn't exist.
+    fn delete(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Dependency>>>;
+}
+
+pub trait TagRepository: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>>;
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>>;
+
+    /// Returns `None` when a tag with this name already exists.
+    fn insert<'a>(&'a self, tag: &'a NewTag) -> BoxFuture<'a, RepositoryResult<Option<Tag>>>;
+
+    /// Returns `None` when the tag doesn't exist, the caller checks a new name
+    /// is not taken.
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a TagUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tag>>>;
+
+    /// Removes the tag from every item too.
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>>;
+
+    /// Every assignment to items of this kind in the tenant.
+    fn list_assignments(
+        &self,
+        kind: ItemKind,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<TagAssignment>>>;
+
+    /// The tags of one item, by name.
+    fn list_item_tags(
+        &self,
+        kind: ItemKind,
+        item_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>>;
+
+    /// Items already carrying the tag are skipped. The caller checks the items
+    /// exist.
+    fn assign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>>;
+
+    fn unassign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>>;
+}
+
+pub trait CommentRepository: Send + Sync {
+    /// The comments of the project and of its tasks, oldest first.
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Comment>>>;
+
+    /// `list` for several projects in one query, for batched loading.
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Comment>>>;
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>>;
+
+    /// The caller checks the task and the parent belong to the project.
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task_id: Option<i32>,
+        author: &'a str,
+        comment: &'a NewComment,
+    ) -> BoxFuture<'a, RepositoryResult<Comment>>;
+
+    /// Keeps the previous body as a revision. Returns `None` when the comment
+    /// doesn't exist in the project.
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        body: &'a str,
+        edited_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Comment>>>;
+
+    /// Deletes the comment with its replies, returns `None` when it doesn't
+    /// exist in the project.
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>>;
+
+    /// Oldest first.
+    fn list_revisions(
+        &self,
+        comment_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<CommentRevision>>>;
+}
+
+/// The registry of tenants, shared by all of them.
+pub trait TenantRepository: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tenant>>>;
+
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>>;
+
+    /// Returns `None` when a tenant with this id already exists.
+    fn insert<'a>(
+        &'a self,
+        tenant: &'a NewTenant,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>>;
+}
+
+#[derive(Clone)]
+enum Backend {
+    Postgres(Arc<Pool<Postgres>>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Arc<Pool<sqlx::Sqlite>>),
+    InMemory(Arc<InMemoryTenants>),
+}
+
+pub trait AttachmentRepository: Send + Sync {
+    /// Oldest first.
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Attachment>>>;
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>>;
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        attachment: &'a NewAttachment,
+    ) -> BoxFuture<'a, RepositoryResult<Attachment>>;
+
+    /// Only deletes the metadata, returns `None` when the attachment doesn't
+    /// exist in the project.
+    fn delete(
+        &self,
+        project_id: i32,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>>;
+
+    /// The storage key of every attachment of the tenant, whatever the
+    /// project.
+    fn storage_keys(&self) -> BoxFuture<'_, RepositoryResult<Vec<String>>>;
+}
+
+/// Hits of every kind, best first. Each term matches the words it prefixes,
+/// a hit matches all the terms.
+pub trait SearchRepository: Send + Sync {
+    fn search<'a>(&'a self, terms: &'a [String])
+    -> BoxFuture<'a, RepositoryResult<Vec<SearchHit>>>;
+}
+
+/// The webhooks of the tenant and the log of their deliveries.
+pub trait WebhookRepository: Send + Sync {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Webhook>>>;
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>>;
+
+    fn insert<'a>(
+        &'a self,
+        webhook: &'a NewWebhook,
+        created_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Webhook>>;
+
+    /// Returns `None` when the webhook doesn't exist.
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a WebhookUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Webhook>>>;
+
+    /// Deletes its deliveries along with it, returns `None` when the webhook
+    /// doesn't exist.
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>>;
+
+    /// Resets the failures of the webhook when the delivery succeeded, counts
+    /// one more otherwise and disables it once `disable_after` are reached.
+    fn record_outcome(
+        &self,
+        id: i32,
+        succeeded: bool,
+        disable_after: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>>;
+
+    fn insert_delivery<'a>(
+        &'a self,
+        delivery: &'a NewWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<WebhookDelivery>>;
+
+    /// Latest first, at most `limit`.
+    fn list_deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<WebhookDelivery>>>;
+
+    fn insert_pending<'a>(
+        &'a self,
+        delivery: &'a NewPendingWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<PendingWebhookDelivery>>;
+
+    /// Takes at most `limit` of the due deliveries, longest due first. They
+    /// are not due again before `lease` has passed, a delivery left
+    /// unfinished, by a restart for instance, is then taken again.
+    fn claim_pending(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<PendingWebhookDelivery>>>;
+
+    /// Makes the delivery due for `attempt` once `delay` has passed.
+    fn reschedule_pending(
+        &self,
+        id: i32,
+        attempt: i32,
+        delay: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<()>>;
+
+    fn delete_pending(&self, id: i32) -> BoxFuture<'_, RepositoryResult<()>>;
+}
+
+/// The persistence backend shared by the API and Kafka handlers. Every
+/// repository but `tenants` only sees the data of `tenant_id`.
+#[derive(Clone)]
+pub struct Repositories {
+    pub tenant_id: String,
+    pub projects: Arc<dyn ProjectRepository>,
+    pub status_types: Arc<dyn StatusTypeRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub api_keys: Arc<dyn ApiKeyRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub teams: Arc<dyn TeamRepository>,
+    pub tasks: Arc<dyn TaskRepository>,
+    pub dependencies: Arc<dyn DependencyRepository>,
+    pub tags: Arc<dyn TagRepository>,
+    pub comments: Arc<dyn CommentRepository>,
+    pub attachments: Arc<dyn AttachmentRepository>,
+    pub search: Arc<dyn SearchRepository>,
+    pub webhooks: Arc<dyn WebhookRepository>,
+    pub tenants: Arc<dyn TenantRepository>,
+    backend: Backend,
+}
+
+impl Repositories {
+    pub fn postgres(pool: Arc<Pool<Postgres>>) -> Self {
+        Repositories::postgres_for_tenant(pool, DEFAULT_TENANT)
+    }
+
+    fn postgres_for_tenant(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        Repositories {
+            tenant_id: tenant_id.to_string(),
+            projects: Arc::new(PostgresProjectRepository::new(pool.clone(), tenant_id)),
+            status_types: Arc::new(PostgresStatusTypeRepository::new(pool.clone(), tenant_id)),
+            audit: Arc::new(PostgresAuditRepository::new(pool.clone(), tenant_id)),
+            api_keys: Arc::new(PostgresApiKeyRepository::new(pool.clone(), tenant_id)),
+            users: Arc::new(PostgresUserRepository::new(pool.clone(), tenant_id)),
+            teams: Arc::new(PostgresTeamRepository::new(pool.clone(), tenant_id)),
+            tasks: Arc::new(PostgresTaskRepository::new(pool.clone(), tenant_id)),
+            dependencies: Arc::new(PostgresDependencyRepository::new(pool.clone(), tenant_id)),
+            tags: Arc::new(PostgresTagRepository::new(pool.clone(), tenant_id)),
+            comments: Arc::new(PostgresCommentRepository::new(pool.clone(), tenant_id)),
+            attachments: Arc::new(PostgresAttachmentRepository::new(pool.clone(), tenant_id)),
+            search: Arc::new(PostgresSearchRepository::new(pool.clone(), tenant_id)),
+            webhooks: Arc::new(PostgresWebhookRepository::new(pool.clone(), tenant_id)),
+            tenants: Arc::new(PostgresTenantRepository::new(pool.clone())),
+            backend: Backend::Postgres(pool),
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: Arc<Pool<sqlx::Sqlite>>) -> Self {
+        Repositories::sqlite_for_tenant(pool, DEFAULT_TENANT)
+    }
+
+    #[cfg(feature = "sqlite")]
+    fn sqlite_for_tenant(pool: Arc<Pool<sqlx::Sqlite>>, tenant_id: &str) -> Self {
+        Repositories {
+            tenant_id: tenant_id.to_string(),
+            projects: Arc::new(SqliteProjectRepository::new(pool.clone(), tenant_id)),
+            status_types: Arc::new(SqliteStatusTypeRepository::new(pool.clone(), tenant_id)),
+            audit: Arc::new(SqliteAuditRepository::new(pool.clone(), tenant_id)),
+            api_keys: Arc::new(SqliteApiKeyRepository::new(pool.clone(), tenant_id)),
+            users: Arc::new(SqliteUserRepository::new(pool.clone(), tenant_id)),
+            teams: Arc::new(SqliteTeamRepository::new(pool.clone(), tenant_id)),
+            tasks: Arc::new(SqliteTaskRepository::new(pool.clone(), tenant_id)),
+            dependencies: Arc::new(SqliteDependencyRepository::new(pool.clone(), tenant_id)),
+            tags: Arc::new(SqliteTagRepository::new(pool.clone(), tenant_id)),
+            comments: Arc::new(SqliteCommentRepository::new(pool.clone(), tenant_id)),
+            attachments: Arc::new(SqliteAttachmentRepository::new(pool.clone(), tenant_id)),
+            search: Arc::new(SqliteSearchRepository::new(pool.clone(), tenant_id)),
+            webhooks: Arc::new(SqliteWebhookRepository::new(pool.clone(), tenant_id)),
+            tenants: Arc::new(SqliteTenantRepository::new(pool.clone())),
+            backend: Backend::Sqlite(pool),
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Repositories::in_memory_for_tenant(Arc::new(InMemoryTenants::new()), DEFAULT_TENANT)
+    }
+
+    fn in_memory_for_tenant(tenants: Arc<InMemoryTenants>, tenant_id: &str) -> Self {
+        let store = Arc::new(tenants.store(tenant_id));
+        Repositories {
+            tenant_id: tenant_id.to_string(),
+            projects: store.clone(),
+            status_types: store.clone(),
+            audit: store.clone(),
+            api_keys: store.clone(),
+            users: store.clone(),
+            teams: store.clone(),
+            tasks: store.clone(),
+            dependencies: store.clone(),
+            tags: store.clone(),
+            comments: store.clone(),
+            attachments: store.clone(),
+            search: store.clone(),
+            webhooks: store,
+            tenants: tenants.clone(),
+            backend: Backend::InMemory(tenants),
+        }
+    }
+
+    /// The same backend, scoped to another tenant.
+    pub fn for_tenant(&self, tenant_id: &str) -> Repositories {
+        if tenant_id == self.tenant_id {
+            return self.clone();
+        }
+        match &self.backend {
+            Backend::Postgres(pool) => Repositories::postgres_for_tenant(pool.clone(), tenant_id),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite(pool) => Repositories::sqlite_for_tenant(pool.clone(), tenant_id),
+            Backend::InMemory(tenants) => {
+                Repositories::in_memory_for_tenant(tenants.clone(), tenant_id)
+            }
+        }
+    }
+}
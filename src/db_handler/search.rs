@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, SearchRepository},
+    structs::SearchHit,
+    tenants::TenantPool,
+};
+
+const HEADLINE_OPTIONS: &str =
+    "StartSel=<mark>, StopSel=</mark>, MinWords=10, MaxWords=30, MaxFragments=2";
+
+/// Sets the text search configuration of the `search_vector` columns and
+/// rebuilds them when it changed, tenant by tenant so that the row level
+/// security policies let the updates through. An unknown configuration is an
+/// error.
+pub async fn apply_search_language(
+    pool: &Pool<Postgres>,
+    language: &str,
+) -> Result<bool, sqlx::Error> {
+    let current: Option<String> = sqlx::query_scalar("SELECT language::TEXT FROM search_settings")
+        .fetch_one(pool)
+        .await?;
+    if current.as_deref() == Some(language) {
+        return Ok(false);
+    }
+    let mut transaction = pool.begin().await?;
+    sqlx::query("UPDATE search_settings SET language = $1::REGCONFIG")
+        .bind(language)
+        .execute(&mut *transaction)
+        .await?;
+    // Reindexing is not an edit of the projects.
+    sqlx::query("ALTER TABLE projects DISABLE TRIGGER projects_touch_row")
+        .execute(&mut *transaction)
+        .await?;
+    let tenant_ids: Vec<String> = sqlx::query_scalar("SELECT id FROM tenants")
+        .fetch_all(&mut *transaction)
+        .await?;
+    for tenant_id in tenant_ids {
+        sqlx::query("SELECT set_config('ninoverse.tenant_id', $1, true)")
+            .bind(&tenant_id)
+            .execute(&mut *transaction)
+            .await?;
+        for statement in [
+            "UPDATE projects SET search_vector = search_vector(name, description) \
+             WHERE tenant_id = $1",
+            "UPDATE tasks SET search_vector = search_vector(title, description) \
+             WHERE tenant_id = $1",
+            "UPDATE comments SET search_vector = search_vector(NULL, body) WHERE tenant_id = $1",
+        ] {
+            sqlx::query(statement)
+                .bind(&tenant_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+    }
+    sqlx::query("ALTER TABLE projects ENABLE TRIGGER projects_touch_row")
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(true)
+}
+
+/// Every term becomes a prefix match, all of them must match.
+fn ts_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("{}:*", term))
+        .collect::<Vec<String>>()
+        .join(" & ")
+}
+
+pub struct PostgresSearchRepository {
+    pool: TenantPool,
+}
+
+impl PostgresSearchRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresSearchRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl SearchRepository for PostgresSearchRepository {
+    fn search<'a>(
+        &'a self,
+        terms: &'a [String],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<SearchHit>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, SearchHit>(
+                "WITH search AS (SELECT to_tsquery(search_language(), $1) AS query) \
+                 SELECT 'project' AS entity_type, projects.id, projects.id AS project_id, \
+                 NULL::INTEGER AS task_id, projects.name::TEXT AS title, \
+                 projects.status::TEXT AS status, \
+                 ts_headline(search_language(), concat_ws(' ', projects.name, \
+                 projects.description), search.query, $3) AS snippet, \
+                 ts_rank(projects.search_vector, search.query)::FLOAT8 AS rank \
+                 FROM projects, search \
+                 WHERE projects.tenant_id = $2 AND projects.search_vector @@ search.query \
+                 UNION ALL \
+                 SELECT 'task', tasks.id, tasks.project_id, tasks.id, tasks.title::TEXT, \
+                 tasks.status::TEXT, \
+                 ts_headline(search_language(), concat_ws(' ', tasks.title, tasks.description), \
+                 search.query, $3), \
+                 ts_rank(tasks.search_vector, search.query)::FLOAT8 \
+                 FROM tasks, search \
+                 WHERE tasks.tenant_id = $2 AND tasks.search_vector @@ search.query \
+                 UNION ALL \
+                 SELECT 'comment', comments.id, comments.project_id, comments.task_id, NULL, \
+                 NULL, ts_headline(search_language(), comments.body, search.query, $3), \
+                 ts_rank(comments.search_vector, search.query)::FLOAT8 \
+                 FROM comments, search \
+                 WHERE comments.tenant_id = $2 AND comments.search_vector @@ search.query \
+                 ORDER BY rank DESC, entity_type, id",
+            )
+            .bind(ts_query(terms))
+            .bind(&self.pool.tenant_id)
+            .bind(HEADLINE_OPTIONS)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
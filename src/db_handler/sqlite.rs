@@ -0,0 +1,1991 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use chrono::NaiveDateTime;
+use futures::future::BoxFuture;
+use sqlx::{
+    Pool, Sqlite,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    types::Json,
+};
+
+use super::{
+    audit::{audit_limit, json_diff},
+    repository::{
+        ApiKeyRepository, AttachmentRepository, AuditRepository, CommentRepository,
+        DependencyRepository, ProjectRepository, RepositoryResult, SearchRepository,
+        StatusTypeRepository, TagRepository, TaskRepository, TeamRepository, TenantRepository,
+        UserRepository, WebhookRepository,
+    },
+    structs::{
+        ApiKey, Attachment, AuditFilter, AuditRecord, Comment, CommentRevision, Dependency,
+        DependencyInsertion, ItemKind, Membership, NewApiKey, NewAttachment, NewAuditRecord,
+        NewComment, NewPendingWebhookDelivery, NewProject, NewStatusType, NewTag, NewTask, NewTeam,
+        NewTenant, NewWebhook, NewWebhookDelivery, PendingWebhookDelivery, Project,
+        ProjectStatusHistoryEntry, ProjectUpdate, SearchHit, StatusTransition, StatusType,
+        StatusTypeUpdate, Tag, TagAssignment, TagUpdate, Task, TaskUpdate, Team, Tenant, User,
+        Webhook, WebhookDelivery, WebhookUpdate,
+    },
+};
+use crate::dependency_handler::DependencyGraph;
+
+/// `blocked` looks for a dependency that is neither deleted nor in a
+/// completed status of the tenant.
+const PROJECT_COLUMNS: &str = "id, name, description, status, created_at, updated_at, version, \
+     deleted_at, owner_id, team_id, \
+     EXISTS (SELECT 1 FROM project_dependencies \
+        JOIN projects AS blocking ON blocking.id = project_dependencies.blocking_id \
+        WHERE project_dependencies.blocked_id = projects.id \
+           AND blocking.deleted_at IS NULL \
+           AND blocking.status NOT IN (SELECT name FROM status_types_dictionary \
+              WHERE completed AND (tenant_id IS NULL OR tenant_id = projects.tenant_id))) \
+     AS blocked";
+const AUDIT_COLUMNS: &str =
+    "id, actor, action, entity_type, entity_id, before, after, diff, request_id, created_at";
+const API_KEY_COLUMNS: &str = "id, name, subject, prefix, created_at, expires_at, revoked_at";
+const USER_COLUMNS: &str = "id, subject, is_admin, created_at";
+const TEAM_COLUMNS: &str = "id, name, created_at";
+const TENANT_COLUMNS: &str = "id, name, created_at";
+const DEPENDENCY_COLUMNS: &str = "blocked_id, blocking_id, created_at";
+const TAG_COLUMNS: &str = "id, name, color, created_at";
+const COMMENT_COLUMNS: &str = "id, project_id, task_id, parent_id, author, body, created_at, \
+     updated_at, \
+     EXISTS (SELECT 1 FROM comment_revisions WHERE comment_revisions.comment_id = comments.id) \
+     AS edited";
+const REVISION_COLUMNS: &str = "id, comment_id, body, edited_by, created_at";
+const ATTACHMENT_COLUMNS: &str = "id, project_id, file_name, content_type, size, sha256, \
+     storage_key, uploaded_by, created_at";
+const WEBHOOK_COLUMNS: &str = "id, url, secret, event_types, enabled, consecutive_failures, \
+     disabled_at, created_by, created_at, updated_at";
+const WEBHOOK_DELIVERY_COLUMNS: &str = "id, webhook_id, event_id, event_type, attempt, \
+     status_code, error, succeeded, duration_ms, created_at";
+const PENDING_DELIVERY_COLUMNS: &str =
+    "id, webhook_id, event_id, event_type, body, attempt, next_attempt_at";
+/// The current time with milliseconds. `CURRENT_TIMESTAMP` stops at the
+/// second, the comments, transitions and edits of the activity feed made
+/// within the same second would come out of order.
+const NOW_MILLIS: &str = "strftime('%Y-%m-%d %H:%M:%f', 'now')";
+/// The current time with milliseconds, shifted by the milliseconds of `?2`.
+const PENDING_DELIVERY_DUE: &str =
+    "strftime('%Y-%m-%d %H:%M:%f', 'now', printf('+%.3f seconds', ?2 / 1000.0))";
+/// `blocked` looks for a dependency that is not in a completed status of the
+/// tenant, as for projects.
+const TASK_COLUMNS: &str = "id, project_id, parent_id, title, description, assignee_id, status, \
+     priority, due_date, position, created_at, updated_at, \
+     EXISTS (SELECT 1 FROM task_dependencies \
+        JOIN tasks AS blocking ON blocking.id = task_dependencies.blocking_id \
+        WHERE task_dependencies.blocked_id = tasks.id \
+           AND blocking.status NOT IN (SELECT name FROM status_types_dictionary \
+              WHERE completed AND (tenant_id IS NULL OR tenant_id = tasks.tenant_id))) \
+     AS blocked";
+const MEMBERSHIP_SELECT: &str = "SELECT memberships.team_id, memberships.user_id, users.subject, \
+        memberships.role, memberships.created_at \
+     FROM memberships JOIN users ON users.id = memberships.user_id";
+const STATUS_TRANSITION_SELECT: &str = "SELECT from_status.name AS from_status, \
+        to_status.name AS to_status, status_transitions.required_fields \
+     FROM status_transitions \
+     JOIN status_types_dictionary AS from_status ON from_status.id = status_transitions.from_status_id \
+     JOIN status_types_dictionary AS to_status ON to_status.id = status_transitions.to_status_id \
+     WHERE (status_transitions.tenant_id IS NULL OR status_transitions.tenant_id = ?1)";
+
+/// SQLite has no arrays, the accepted versions travel as a JSON array read
+/// back with `json_each`.
+fn versions_json(accepted_versions: Option<Vec<i32>>) -> Option<String> {
+    accepted_versions.map(|versions| serde_json::Value::from(versions).to_string())
+}
+
+#[derive(sqlx::FromRow)]
+struct StatusTransitionRow {
+    from_status: String,
+    to_status: String,
+    required_fields: Json<Vec<String>>,
+}
+
+impl From<StatusTransitionRow> for StatusTransition {
+    fn from(row: StatusTransitionRow) -> Self {
+        StatusTransition {
+            from_status: row.from_status,
+            to_status: row.to_status,
+            required_fields: row.required_fields.0,
+        }
+    }
+}
+
+/// The event types of webhooks are kept as a JSON array.
+#[derive(sqlx::FromRow)]
+struct WebhookRow {
+    id: i32,
+    url: String,
+    secret: String,
+    event_types: Json<Vec<String>>,
+    enabled: bool,
+    consecutive_failures: i32,
+    disabled_at: Option<NaiveDateTime>,
+    created_by: String,
+    created_at: Option<NaiveDateTime>,
+    updated_at: Option<NaiveDateTime>,
+}
+
+impl From<WebhookRow> for Webhook {
+    fn from(row: WebhookRow) -> Self {
+        Webhook {
+            id: row.id,
+            url: row.url,
+            secret: row.secret,
+            event_types: row.event_types.0,
+            enabled: row.enabled,
+            consecutive_failures: row.consecutive_failures,
+            disabled_at: row.disabled_at,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+pub async fn get_sqlite_pool(path: &str) -> Result<Pool<Sqlite>, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path))?
+        .create_if_missing(true)
+        .foreign_keys(true);
+    SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(options)
+        .await
+}
+
+pub struct SqliteProjectRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteProjectRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteProjectRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl ProjectRepository for SqliteProjectRepository {
+    fn list(&self, include_deleted: bool) -> BoxFuture<'_, RepositoryResult<Vec<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "SELECT {} FROM projects \
+                 WHERE tenant_id = ?2 AND (?1 OR deleted_at IS NULL) ORDER BY id",
+                PROJECT_COLUMNS
+            ))
+            .bind(include_deleted)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(
+        &self,
+        id: i32,
+        include_deleted: bool,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "SELECT {} FROM projects \
+                 WHERE id = ?1 AND tenant_id = ?3 AND (?2 OR deleted_at IS NULL)",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(include_deleted)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project: &'a NewProject,
+        owner_id: Option<i32>,
+    ) -> BoxFuture<'a, RepositoryResult<Project>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "INSERT INTO projects (name, description, status, owner_id, team_id, tenant_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6) RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(&project.name)
+            .bind(&project.description)
+            .bind(&project.status)
+            .bind(owner_id)
+            .bind(project.team_id)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a ProjectUpdate,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET \
+                    name = COALESCE(?2, name), \
+                    description = COALESCE(?3, description), \
+                    status = COALESCE(?4, status), \
+                    owner_id = COALESCE(?6, owner_id), \
+                    team_id = COALESCE(?7, team_id), \
+                    updated_at = CURRENT_TIMESTAMP, \
+                    version = version + 1 \
+                 WHERE id = ?1 AND tenant_id = ?8 AND deleted_at IS NULL \
+                    AND (?5 IS NULL OR version IN (SELECT value FROM json_each(?5))) \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(&update.name)
+            .bind(&update.description)
+            .bind(&update.status)
+            .bind(versions_json(accepted_versions))
+            .bind(update.owner_id)
+            .bind(update.team_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn soft_delete(
+        &self,
+        id: i32,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET \
+                    deleted_at = CURRENT_TIMESTAMP, \
+                    updated_at = CURRENT_TIMESTAMP, \
+                    version = version + 1 \
+                 WHERE id = ?1 AND tenant_id = ?3 AND deleted_at IS NULL \
+                    AND (?2 IS NULL OR version IN (SELECT value FROM json_each(?2))) \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(versions_json(accepted_versions))
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn restore(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Project>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET \
+                    deleted_at = NULL, \
+                    updated_at = CURRENT_TIMESTAMP, \
+                    version = version + 1 \
+                 WHERE id = ?1 AND tenant_id = ?2 AND deleted_at IS NOT NULL \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn purge_deleted(&self, retention_days: i32) -> BoxFuture<'_, RepositoryResult<Vec<i32>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, i32>(
+                "DELETE FROM projects \
+                 WHERE tenant_id = ?2 AND deleted_at IS NOT NULL \
+                    AND deleted_at < datetime('now', '-' || ?1 || ' days') \
+                 RETURNING id",
+            )
+            .bind(retention_days)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn transition_status<'a>(
+        &'a self,
+        id: i32,
+        from_status: &'a str,
+        to_status: &'a str,
+        actor: &'a str,
+        accepted_versions: Option<Vec<i32>>,
+    ) -> BoxFuture<'a, RepositoryResult<Option<(Project, ProjectStatusHistoryEntry)>>> {
+        Box::pin(async move {
+            let mut transaction = self.pool.begin().await?;
+            let project = sqlx::query_as::<_, Project>(&format!(
+                "UPDATE projects SET \
+                    status = ?3, \
+                    updated_at = CURRENT_TIMESTAMP, \
+                    version = version + 1 \
+                 WHERE id = ?1 AND tenant_id = ?5 AND status = ?2 AND deleted_at IS NULL \
+                    AND (?4 IS NULL OR version IN (SELECT value FROM json_each(?4))) \
+                 RETURNING {}",
+                PROJECT_COLUMNS
+            ))
+            .bind(id)
+            .bind(from_status)
+            .bind(to_status)
+            .bind(versions_json(accepted_versions))
+            .bind(&self.tenant_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            let Some(project) = project else {
+                transaction.rollback().await?;
+                return Ok(None);
+            };
+            let history_entry = sqlx::query_as::<_, ProjectStatusHistoryEntry>(&format!(
+                "INSERT INTO project_status_history \
+                    (project_id, from_status, to_status, actor, tenant_id, created_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, {}) \
+                 RETURNING id, project_id, from_status, to_status, actor, created_at",
+                NOW_MILLIS
+            ))
+            .bind(id)
+            .bind(from_status)
+            .bind(to_status)
+            .bind(actor)
+            .bind(&self.tenant_id)
+            .fetch_one(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(Some((project, history_entry)))
+        })
+    }
+
+    fn list_status_history(
+        &self,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<ProjectStatusHistoryEntry>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ProjectStatusHistoryEntry>(
+                "SELECT id, project_id, from_status, to_status, actor, created_at \
+                 FROM project_status_history WHERE project_id = ?1 AND tenant_id = ?2 ORDER BY id",
+            )
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteStatusTypeRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteStatusTypeRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteStatusTypeRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl StatusTypeRepository for SqliteStatusTypeRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "SELECT id, name, completed FROM status_types_dictionary \
+                 WHERE tenant_id IS NULL OR tenant_id = ?1 ORDER BY id",
+            )
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn exists<'a>(&'a self, name: &'a str) -> BoxFuture<'a, RepositoryResult<bool>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM status_types_dictionary \
+                    WHERE name = ?1 AND (tenant_id IS NULL OR tenant_id = ?2))",
+            )
+            .bind(name)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        status_type: &'a NewStatusType,
+    ) -> BoxFuture<'a, RepositoryResult<StatusType>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "INSERT INTO status_types_dictionary (name, completed, tenant_id) VALUES (?1, ?2, ?3) \
+                 RETURNING id, name, completed",
+            )
+            .bind(&status_type.name)
+            .bind(status_type.completed)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "SELECT id, name, completed FROM status_types_dictionary \
+                 WHERE id = ?1 AND tenant_id = ?2",
+            )
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a StatusTypeUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "UPDATE status_types_dictionary \
+                 SET name = COALESCE(?2, name), completed = COALESCE(?3, completed) \
+                 WHERE id = ?1 AND tenant_id = ?4 RETURNING id, name, completed",
+            )
+            .bind(id)
+            .bind(&update.name)
+            .bind(update.completed)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "DELETE FROM status_types_dictionary WHERE id = ?1 AND tenant_id = ?2 \
+                 RETURNING id, name, completed",
+            )
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn in_use<'a>(&'a self, status_type: &'a StatusType) -> BoxFuture<'a, RepositoryResult<bool>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM projects WHERE status = ?1 AND tenant_id = ?3) \
+                    OR EXISTS (SELECT 1 FROM tasks WHERE status = ?1 AND tenant_id = ?3) \
+                    OR EXISTS (SELECT 1 FROM project_status_history \
+                        WHERE (from_status = ?1 OR to_status = ?1) AND tenant_id = ?3) \
+                    OR EXISTS (SELECT 1 FROM status_transitions \
+                        WHERE from_status_id = ?2 OR to_status_id = ?2)",
+            )
+            .bind(&status_type.name)
+            .bind(status_type.id)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_transitions(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusTransition>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusTransitionRow>(&format!(
+                "{} ORDER BY status_transitions.id",
+                STATUS_TRANSITION_SELECT
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?
+            .into_iter()
+            .map(StatusTransition::from)
+            .collect())
+        })
+    }
+
+    fn get_transition<'a>(
+        &'a self,
+        from_status: &'a str,
+        to_status: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusTransition>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusTransitionRow>(&format!(
+                "{} AND from_status.name = ?2 AND to_status.name = ?3",
+                STATUS_TRANSITION_SELECT
+            ))
+            .bind(&self.tenant_id)
+            .bind(from_status)
+            .bind(to_status)
+            .fetch_optional(&*self.pool)
+            .await?
+            .map(StatusTransition::from))
+        })
+    }
+}
+
+pub struct SqliteAuditRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteAuditRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteAuditRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl AuditRepository for SqliteAuditRepository {
+    fn insert<'a>(
+        &'a self,
+        record: &'a NewAuditRecord,
+    ) -> BoxFuture<'a, RepositoryResult<AuditRecord>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, AuditRecord>(&format!(
+                "INSERT INTO audit_log \
+                    (actor, action, entity_type, entity_id, before, after, diff, request_id, \
+                    tenant_id, created_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, {}) \
+                 RETURNING {}",
+                NOW_MILLIS, AUDIT_COLUMNS
+            ))
+            .bind(&record.actor)
+            .bind(&record.action)
+            .bind(&record.entity_type)
+            .bind(record.entity_id)
+            .bind(&record.before)
+            .bind(&record.after)
+            .bind(json_diff(record.before.as_ref(), record.after.as_ref()))
+            .bind(&record.request_id)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list<'a>(
+        &'a self,
+        filter: &'a AuditFilter,
+    ) -> BoxFuture<'a, RepositoryResult<Vec<AuditRecord>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, AuditRecord>(&format!(
+                "SELECT {} FROM audit_log \
+                 WHERE tenant_id = ?9 \
+                    AND (?1 IS NULL OR actor = ?1) \
+                    AND (?2 IS NULL OR action = ?2) \
+                    AND (?3 IS NULL OR entity_type = ?3) \
+                    AND (?4 IS NULL OR entity_id = ?4) \
+                    AND (?5 IS NULL OR request_id = ?5) \
+                    AND (?6 IS NULL OR created_at >= ?6) \
+                    AND (?7 IS NULL OR created_at < ?7) \
+                 ORDER BY id DESC \
+                 LIMIT ?8",
+                AUDIT_COLUMNS
+            ))
+            .bind(&filter.actor)
+            .bind(&filter.action)
+            .bind(&filter.entity_type)
+            .bind(filter.entity_id)
+            .bind(&filter.request_id)
+            .bind(filter.since)
+            .bind(filter.until)
+            .bind(audit_limit(filter))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteApiKeyRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteApiKeyRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteApiKeyRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl ApiKeyRepository for SqliteApiKeyRepository {
+    fn insert<'a>(&'a self, api_key: &'a NewApiKey) -> BoxFuture<'a, RepositoryResult<ApiKey>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "INSERT INTO api_keys (name, subject, prefix, key_hash, expires_at, tenant_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6) RETURNING {}",
+                API_KEY_COLUMNS
+            ))
+            .bind(&api_key.name)
+            .bind(&api_key.subject)
+            .bind(&api_key.prefix)
+            .bind(&api_key.key_hash)
+            .bind(api_key.expires_at)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn find_active<'a>(
+        &'a self,
+        key_hash: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "SELECT {} FROM api_keys \
+                 WHERE key_hash = ?1 AND tenant_id = ?2 AND revoked_at IS NULL \
+                    AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)",
+                API_KEY_COLUMNS
+            ))
+            .bind(key_hash)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list<'a>(&'a self, subject: &'a str) -> BoxFuture<'a, RepositoryResult<Vec<ApiKey>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "SELECT {} FROM api_keys WHERE subject = ?1 AND tenant_id = ?2 ORDER BY id",
+                API_KEY_COLUMNS
+            ))
+            .bind(subject)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn revoke<'a>(
+        &'a self,
+        id: i32,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<ApiKey>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, ApiKey>(&format!(
+                "UPDATE api_keys SET revoked_at = CURRENT_TIMESTAMP \
+                 WHERE id = ?1 AND subject = ?2 AND tenant_id = ?3 AND revoked_at IS NULL \
+                 RETURNING {}",
+                API_KEY_COLUMNS
+            ))
+            .bind(id)
+            .bind(subject)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteUserRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteUserRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteUserRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl UserRepository for SqliteUserRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<User>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "SELECT {} FROM users WHERE tenant_id = ?1 ORDER BY id",
+                USER_COLUMNS
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<User>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "SELECT {} FROM users WHERE id = ?1 AND tenant_id = ?2",
+                USER_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn find_by_subject<'a>(
+        &'a self,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<User>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "SELECT {} FROM users WHERE subject = ?1 AND tenant_id = ?2",
+                USER_COLUMNS
+            ))
+            .bind(subject)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        subject: &'a str,
+        is_admin: bool,
+    ) -> BoxFuture<'a, RepositoryResult<User>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "INSERT INTO users (subject, is_admin, tenant_id) VALUES (?1, ?2, ?3) \
+                 ON CONFLICT (tenant_id, subject) DO UPDATE SET is_admin = excluded.is_admin \
+                 RETURNING {}",
+                USER_COLUMNS
+            ))
+            .bind(subject)
+            .bind(is_admin)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteTeamRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteTeamRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteTeamRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+
+    async fn find_member(
+        &self,
+        team_id: i32,
+        user_id: i32,
+    ) -> RepositoryResult<Option<Membership>> {
+        Ok(sqlx::query_as::<_, Membership>(&format!(
+            "{} WHERE memberships.team_id = ?1 AND memberships.user_id = ?2 \
+                AND memberships.tenant_id = ?3",
+            MEMBERSHIP_SELECT
+        ))
+        .bind(team_id)
+        .bind(user_id)
+        .bind(&self.tenant_id)
+        .fetch_optional(&*self.pool)
+        .await?)
+    }
+}
+
+impl TeamRepository for SqliteTeamRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Team>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Team>(&format!(
+                "SELECT {} FROM teams WHERE tenant_id = ?1 ORDER BY id",
+                TEAM_COLUMNS
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Team>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Team>(&format!(
+                "SELECT {} FROM teams WHERE id = ?1 AND tenant_id = ?2",
+                TEAM_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(&'a self, team: &'a NewTeam) -> BoxFuture<'a, RepositoryResult<Option<Team>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Team>(&format!(
+                "INSERT INTO teams (name, tenant_id) VALUES (?1, ?2) \
+                 ON CONFLICT (tenant_id, name) DO NOTHING RETURNING {}",
+                TEAM_COLUMNS
+            ))
+            .bind(&team.name)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_members(&self, team_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Membership>(&format!(
+                "{} WHERE memberships.team_id = ?1 AND memberships.tenant_id = ?2 \
+                 ORDER BY memberships.user_id",
+                MEMBERSHIP_SELECT
+            ))
+            .bind(team_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_memberships(&self, user_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Membership>(&format!(
+                "{} WHERE memberships.user_id = ?1 AND memberships.tenant_id = ?2 \
+                 ORDER BY memberships.team_id",
+                MEMBERSHIP_SELECT
+            ))
+            .bind(user_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    // SQLite can't join in a RETURNING clause, the membership is read back.
+    fn set_member<'a>(
+        &'a self,
+        team_id: i32,
+        user_id: i32,
+        role: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Membership>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO memberships (team_id, user_id, role, tenant_id) \
+                 VALUES (?1, ?2, ?3, ?4) \
+                 ON CONFLICT (team_id, user_id) DO UPDATE SET role = excluded.role",
+            )
+            .bind(team_id)
+            .bind(user_id)
+            .bind(role)
+            .bind(&self.tenant_id)
+            .execute(&*self.pool)
+            .await?;
+            Ok(self
+                .find_member(team_id, user_id)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?)
+        })
+    }
+
+    fn remove_member(
+        &self,
+        team_id: i32,
+        user_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Membership>>> {
+        Box::pin(async move {
+            let membership = self.find_member(team_id, user_id).await?;
+            sqlx::query(
+                "DELETE FROM memberships WHERE team_id = ?1 AND user_id = ?2 AND tenant_id = ?3",
+            )
+            .bind(team_id)
+            .bind(user_id)
+            .bind(&self.tenant_id)
+            .execute(&*self.pool)
+            .await?;
+            Ok(membership)
+        })
+    }
+}
+
+pub struct SqliteTaskRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteTaskRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteTaskRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl TaskRepository for SqliteTaskRepository {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE project_id = ?1 AND tenant_id = ?2 \
+                 ORDER BY position, id",
+                TASK_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks \
+                 WHERE project_id IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2 \
+                 ORDER BY position, id",
+                TASK_COLUMNS
+            ))
+            .bind(serde_json::Value::from(project_ids).to_string())
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?3",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn find(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE id = ?1 AND tenant_id = ?2",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task: &'a NewTask,
+    ) -> BoxFuture<'a, RepositoryResult<Task>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "INSERT INTO tasks (project_id, parent_id, title, description, assignee_id, \
+                    status, priority, due_date, position, tenant_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, \
+                    (SELECT COALESCE(MAX(position), 0) + 1 FROM tasks \
+                     WHERE project_id = ?1 AND tenant_id = ?9), ?9) \
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(task.parent_id)
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(task.assignee_id)
+            .bind(&task.status)
+            .bind(&task.priority)
+            .bind(task.due_date)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        update: &'a TaskUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "UPDATE tasks SET \
+                    title = COALESCE(?3, title), \
+                    description = COALESCE(?4, description), \
+                    parent_id = COALESCE(?5, parent_id), \
+                    assignee_id = COALESCE(?6, assignee_id), \
+                    status = COALESCE(?7, status), \
+                    priority = COALESCE(?8, priority), \
+                    due_date = COALESCE(?9, due_date), \
+                    updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?10 \
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&update.title)
+            .bind(&update.description)
+            .bind(update.parent_id)
+            .bind(update.assignee_id)
+            .bind(&update.status)
+            .bind(&update.priority)
+            .bind(update.due_date)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "DELETE FROM tasks WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?3 \
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn reorder<'a>(
+        &'a self,
+        project_id: i32,
+        task_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>> {
+        Box::pin(async move {
+            let mut transaction = self.pool.begin().await?;
+            sqlx::query(
+                "UPDATE tasks SET \
+                    position = (SELECT ordered.key + 1 FROM json_each(?1) AS ordered \
+                                WHERE ordered.value = tasks.id), \
+                    updated_at = CURRENT_TIMESTAMP \
+                 WHERE id IN (SELECT value FROM json_each(?1)) \
+                    AND project_id = ?2 AND tenant_id = ?3",
+            )
+            .bind(serde_json::Value::from(task_ids).to_string())
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .execute(&mut *transaction)
+            .await?;
+            let tasks = sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE project_id = ?1 AND tenant_id = ?2 \
+                 ORDER BY position, id",
+                TASK_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(tasks)
+        })
+    }
+}
+
+pub struct SqliteDependencyRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteDependencyRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteDependencyRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl DependencyRepository for SqliteDependencyRepository {
+    fn list(&self, kind: ItemKind) -> BoxFuture<'_, RepositoryResult<Vec<Dependency>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Dependency>(&format!(
+                "SELECT {} FROM {} WHERE tenant_id = ?1 ORDER BY blocked_id, blocking_id",
+                DEPENDENCY_COLUMNS,
+                kind.dependency_table()
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<DependencyInsertion>> {
+        Box::pin(async move {
+            let table = kind.dependency_table();
+            // Takes the write lock upfront, the check can't be raced.
+            let mut transaction = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+            let upstream = sqlx::query_as::<_, Dependency>(&format!(
+                "WITH RECURSIVE upstream (id) AS ( \
+                    SELECT ?1 \
+                    UNION \
+                    SELECT dependencies.blocking_id FROM {table} AS dependencies \
+                    JOIN upstream ON dependencies.blocked_id = upstream.id \
+                    WHERE dependencies.tenant_id = ?2 \
+                 ) \
+                 SELECT {columns} FROM {table} \
+                 WHERE tenant_id = ?2 AND blocked_id IN (SELECT id FROM upstream)",
+                table = table,
+                columns = DEPENDENCY_COLUMNS
+            ))
+            .bind(blocking_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&mut *transaction)
+            .await?;
+            if let Some(path) = DependencyGraph::new(&upstream).cycle_path(blocked_id, blocking_id)
+            {
+                transaction.rollback().await?;
+                return Ok(DependencyInsertion::Cycle(path));
+            }
+            let dependency = sqlx::query_as::<_, Dependency>(&format!(
+                "INSERT INTO {} (blocked_id, blocking_id, tenant_id) VALUES (?1, ?2, ?3) \
+                 ON CONFLICT (blocked_id, blocking_id) DO NOTHING RETURNING {}",
+                table, DEPENDENCY_COLUMNS
+            ))
+            .bind(blocked_id)
+            .bind(blocking_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(dependency.map_or(
+                DependencyInsertion::AlreadyExists,
+                DependencyInsertion::Inserted,
+            ))
+        })
+    }
+
+    fn delete(
+        &self,
+        kind: ItemKind,
+        blocked_id: i32,
+        blocking_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Dependency>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Dependency>(&format!(
+                "DELETE FROM {} WHERE blocked_id = ?1 AND blocking_id = ?2 AND tenant_id = ?3 \
+                 RETURNING {}",
+                kind.dependency_table(),
+                DEPENDENCY_COLUMNS
+            ))
+            .bind(blocked_id)
+            .bind(blocking_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteTagRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteTagRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteTagRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl TagRepository for SqliteTagRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "SELECT {} FROM tags WHERE tenant_id = ?1 ORDER BY name",
+                TAG_COLUMNS
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "SELECT {} FROM tags WHERE id = ?1 AND tenant_id = ?2",
+                TAG_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(&'a self, tag: &'a NewTag) -> BoxFuture<'a, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "INSERT INTO tags (name, color, tenant_id) VALUES (?1, ?2, ?3) \
+                 ON CONFLICT (tenant_id, name) DO NOTHING RETURNING {}",
+                TAG_COLUMNS
+            ))
+            .bind(&tag.name)
+            .bind(&tag.color)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a TagUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "UPDATE tags SET name = COALESCE(?2, name), color = COALESCE(?3, color) \
+                 WHERE id = ?1 AND tenant_id = ?4 RETURNING {}",
+                TAG_COLUMNS
+            ))
+            .bind(id)
+            .bind(&update.name)
+            .bind(&update.color)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "DELETE FROM tags WHERE id = ?1 AND tenant_id = ?2 RETURNING {}",
+                TAG_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_assignments(
+        &self,
+        kind: ItemKind,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<TagAssignment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, TagAssignment>(&format!(
+                "SELECT tag_id, {column} AS item_id FROM {table} WHERE tenant_id = ?1 \
+                 ORDER BY {column}, tag_id",
+                column = kind.tag_column(),
+                table = kind.tag_table()
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_item_tags(
+        &self,
+        kind: ItemKind,
+        item_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "SELECT {columns} FROM tags WHERE tenant_id = ?2 AND id IN \
+                 (SELECT tag_id FROM {table} WHERE {column} = ?1 AND tenant_id = ?2) \
+                 ORDER BY name",
+                columns = TAG_COLUMNS,
+                column = kind.tag_column(),
+                table = kind.tag_table()
+            ))
+            .bind(item_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn assign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>> {
+        Box::pin(async move {
+            // Without the WHERE, SQLite reads ON CONFLICT as a join constraint.
+            sqlx::query(&format!(
+                "INSERT INTO {} ({}, tag_id, tenant_id) \
+                 SELECT value, ?2, ?3 FROM json_each(?1) WHERE true ON CONFLICT DO NOTHING",
+                kind.tag_table(),
+                kind.tag_column()
+            ))
+            .bind(serde_json::Value::from(item_ids).to_string())
+            .bind(tag_id)
+            .bind(&self.tenant_id)
+            .execute(&*self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn unassign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE {} IN (SELECT value FROM json_each(?1)) \
+                 AND tag_id = ?2 AND tenant_id = ?3",
+                kind.tag_table(),
+                kind.tag_column()
+            ))
+            .bind(serde_json::Value::from(item_ids).to_string())
+            .bind(tag_id)
+            .bind(&self.tenant_id)
+            .execute(&*self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
+
+pub struct SqliteCommentRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteCommentRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteCommentRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl CommentRepository for SqliteCommentRepository {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "SELECT {} FROM comments WHERE project_id = ?1 AND tenant_id = ?2 \
+                 ORDER BY created_at, id",
+                COMMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "SELECT {} FROM comments \
+                 WHERE project_id IN (SELECT value FROM json_each(?1)) AND tenant_id = ?2 \
+                 ORDER BY created_at, id",
+                COMMENT_COLUMNS
+            ))
+            .bind(serde_json::Value::from(project_ids).to_string())
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "SELECT {} FROM comments WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?3",
+                COMMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task_id: Option<i32>,
+        author: &'a str,
+        comment: &'a NewComment,
+    ) -> BoxFuture<'a, RepositoryResult<Comment>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "INSERT INTO comments \
+                    (project_id, task_id, parent_id, author, body, tenant_id, created_at, \
+                    updated_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, {now}, {now}) RETURNING {}",
+                COMMENT_COLUMNS,
+                now = NOW_MILLIS
+            ))
+            .bind(project_id)
+            .bind(task_id)
+            .bind(comment.parent_id)
+            .bind(author)
+            .bind(&comment.body)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        body: &'a str,
+        edited_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Comment>>> {
+        Box::pin(async move {
+            let mut transaction = self.pool.begin().await?;
+            sqlx::query(
+                "INSERT INTO comment_revisions (comment_id, body, edited_by, tenant_id) \
+                 SELECT id, body, ?3, tenant_id FROM comments \
+                 WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?4",
+            )
+            .bind(id)
+            .bind(project_id)
+            .bind(edited_by)
+            .bind(&self.tenant_id)
+            .execute(&mut *transaction)
+            .await?;
+            let comment = sqlx::query_as::<_, Comment>(&format!(
+                "UPDATE comments SET body = ?3, updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?4 RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(body)
+            .bind(&self.tenant_id)
+            .fetch_optional(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(comment)
+        })
+    }
+
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Comment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Comment>(&format!(
+                "DELETE FROM comments WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?3 \
+                 RETURNING {}",
+                COMMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_revisions(
+        &self,
+        comment_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<CommentRevision>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, CommentRevision>(&format!(
+                "SELECT {} FROM comment_revisions WHERE comment_id = ?1 AND tenant_id = ?2 \
+                 ORDER BY id",
+                REVISION_COLUMNS
+            ))
+            .bind(comment_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteAttachmentRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteAttachmentRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteAttachmentRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl AttachmentRepository for SqliteAttachmentRepository {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Attachment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "SELECT {} FROM attachments WHERE project_id = ?1 AND tenant_id = ?2 ORDER BY id",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "SELECT {} FROM attachments WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?3",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        attachment: &'a NewAttachment,
+    ) -> BoxFuture<'a, RepositoryResult<Attachment>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "INSERT INTO attachments (project_id, file_name, content_type, size, sha256, \
+                 storage_key, uploaded_by, tenant_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+                 RETURNING {}",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&attachment.file_name)
+            .bind(&attachment.content_type)
+            .bind(attachment.size)
+            .bind(&attachment.sha256)
+            .bind(&attachment.storage_key)
+            .bind(&attachment.uploaded_by)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn delete(
+        &self,
+        project_id: i32,
+        id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Attachment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Attachment>(&format!(
+                "DELETE FROM attachments WHERE id = ?1 AND project_id = ?2 AND tenant_id = ?3 \
+                 RETURNING {}",
+                ATTACHMENT_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn storage_keys(&self) -> BoxFuture<'_, RepositoryResult<Vec<String>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, String>(
+                "SELECT storage_key FROM attachments WHERE tenant_id = ?1",
+            )
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+/// Every term becomes a quoted prefix query, all of them must match.
+fn fts_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"*", term))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub struct SqliteSearchRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteSearchRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteSearchRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+/// `bm25` is lower for better matches, matches in titles weigh more.
+impl SearchRepository for SqliteSearchRepository {
+    fn search<'a>(
+        &'a self,
+        terms: &'a [String],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<SearchHit>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, SearchHit>(
+                "SELECT entity_type, entity_id AS id, project_id, task_id, title, \
+                 CASE entity_type \
+                 WHEN 'project' THEN (SELECT status FROM projects WHERE id = entity_id) \
+                 WHEN 'task' THEN (SELECT status FROM tasks WHERE id = entity_id) \
+                 END AS status, \
+                 snippet(search_index, -1, '<mark>', '</mark>', '…', 24) AS snippet, \
+                 -bm25(search_index, 0, 0, 0, 0, 0, 4.0, 1.0) AS rank \
+                 FROM search_index WHERE search_index MATCH ?1 AND tenant_id = ?2 \
+                 ORDER BY rank DESC, entity_type, id",
+            )
+            .bind(fts_query(terms))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteTenantRepository {
+    pool: Arc<Pool<Sqlite>>,
+}
+
+impl SqliteTenantRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>) -> Self {
+        SqliteTenantRepository { pool }
+    }
+}
+
+impl TenantRepository for SqliteTenantRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tenant>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tenant>(&format!(
+                "SELECT {} FROM tenants ORDER BY id",
+                TENANT_COLUMNS
+            ))
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tenant>(&format!(
+                "SELECT {} FROM tenants WHERE id = ?1",
+                TENANT_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        tenant: &'a NewTenant,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tenant>(&format!(
+                "INSERT INTO tenants (id, name) VALUES (?1, ?2) \
+                 ON CONFLICT (id) DO NOTHING RETURNING {}",
+                TENANT_COLUMNS
+            ))
+            .bind(&tenant.id)
+            .bind(&tenant.name)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+}
+
+pub struct SqliteWebhookRepository {
+    pool: Arc<Pool<Sqlite>>,
+    tenant_id: String,
+}
+
+impl SqliteWebhookRepository {
+    pub fn new(pool: Arc<Pool<Sqlite>>, tenant_id: &str) -> Self {
+        SqliteWebhookRepository {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+}
+
+impl WebhookRepository for SqliteWebhookRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Webhook>>> {
+        Box::pin(async move {
+            let rows = sqlx::query_as::<_, WebhookRow>(&format!(
+                "SELECT {} FROM webhooks WHERE tenant_id = ?1 ORDER BY id",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(&self.tenant_id)
+            .fetch_all(&*self.pool)
+            .await?;
+            Ok(rows.into_iter().map(Webhook::from).collect())
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, WebhookRow>(&format!(
+                "SELECT {} FROM webhooks WHERE id = ?1 AND tenant_id = ?2",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+            Ok(row.map(Webhook::from))
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        webhook: &'a NewWebhook,
+        created_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Webhook>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, WebhookRow>(&format!(
+                "INSERT INTO webhooks (url, secret, event_types, created_by, tenant_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5) RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(&webhook.url)
+            .bind(&webhook.secret)
+            .bind(Json(&webhook.event_types))
+            .bind(created_by)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?;
+            Ok(row.into())
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a WebhookUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, WebhookRow>(&format!(
+                "UPDATE webhooks SET url = COALESCE(?2, url), secret = COALESCE(?3, secret), \
+                 event_types = COALESCE(?4, event_types), enabled = COALESCE(?5, enabled), \
+                 consecutive_failures = CASE WHEN ?5 THEN 0 ELSE consecutive_failures END, \
+                 disabled_at = CASE WHEN ?5 THEN NULL \
+                 WHEN NOT ?5 AND enabled THEN CURRENT_TIMESTAMP ELSE disabled_at END, \
+                 updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = ?1 AND tenant_id = ?6 RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&update.url)
+            .bind(&update.secret)
+            .bind(update.event_types.as_ref().map(Json))
+            .bind(update.enabled)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+            Ok(row.map(Webhook::from))
+        })
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, WebhookRow>(&format!(
+                "DELETE FROM webhooks WHERE id = ?1 AND tenant_id = ?2 RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+            Ok(row.map(Webhook::from))
+        })
+    }
+
+    fn record_outcome(
+        &self,
+        id: i32,
+        succeeded: bool,
+        disable_after: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, WebhookRow>(&format!(
+                "UPDATE webhooks SET \
+                 consecutive_failures = CASE WHEN ?2 THEN 0 ELSE consecutive_failures + 1 END, \
+                 enabled = enabled AND (?2 OR consecutive_failures + 1 < ?3), \
+                 disabled_at = CASE WHEN enabled AND NOT ?2 AND consecutive_failures + 1 >= ?3 \
+                 THEN CURRENT_TIMESTAMP ELSE disabled_at END \
+                 WHERE id = ?1 AND tenant_id = ?4 RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(succeeded)
+            .bind(disable_after)
+            .bind(&self.tenant_id)
+            .fetch_optional(&*self.pool)
+            .await?;
+            Ok(row.map(Webhook::from))
+        })
+    }
+
+    fn insert_delivery<'a>(
+        &'a self,
+        delivery: &'a NewWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<WebhookDelivery>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, WebhookDelivery>(&format!(
+                "INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, attempt, \
+                 status_code, error, succeeded, duration_ms, tenant_id) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) RETURNING {}",
+                WEBHOOK_DELIVERY_COLUMNS
+            ))
+            .bind(delivery.webhook_id)
+            .bind(&delivery.event_id)
+            .bind(&delivery.event_type)
+            .bind(delivery.attempt)
+            .bind(delivery.status_code)
+            .bind(&delivery.error)
+            .bind(delivery.succeeded)
+            .bind(delivery.duration_ms)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn list_deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<WebhookDelivery>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, WebhookDelivery>(&format!(
+                "SELECT {} FROM webhook_deliveries WHERE webhook_id = ?1 AND tenant_id = ?2 \
+                 ORDER BY id DESC LIMIT ?3",
+                WEBHOOK_DELIVERY_COLUMNS
+            ))
+            .bind(webhook_id)
+            .bind(&self.tenant_id)
+            .bind(limit)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert_pending<'a>(
+        &'a self,
+        delivery: &'a NewPendingWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<PendingWebhookDelivery>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, PendingWebhookDelivery>(&format!(
+                "INSERT INTO webhook_pending_deliveries (webhook_id, event_id, event_type, body, \
+                 tenant_id) VALUES (?1, ?2, ?3, ?4, ?5) RETURNING {}",
+                PENDING_DELIVERY_COLUMNS
+            ))
+            .bind(delivery.webhook_id)
+            .bind(&delivery.event_id)
+            .bind(&delivery.event_type)
+            .bind(&delivery.body)
+            .bind(&self.tenant_id)
+            .fetch_one(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn claim_pending(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<PendingWebhookDelivery>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, PendingWebhookDelivery>(&format!(
+                "UPDATE webhook_pending_deliveries SET next_attempt_at = {due} \
+                 WHERE tenant_id = ?1 AND id IN (SELECT id FROM webhook_pending_deliveries \
+                 WHERE tenant_id = ?1 \
+                 AND next_attempt_at <= {now} \
+                 ORDER BY next_attempt_at, id LIMIT ?3) RETURNING {columns}",
+                due = PENDING_DELIVERY_DUE,
+                now = NOW_MILLIS,
+                columns = PENDING_DELIVERY_COLUMNS
+            ))
+            .bind(&self.tenant_id)
+            .bind(lease.as_millis() as i64)
+            .bind(limit)
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn reschedule_pending(
+        &self,
+        id: i32,
+        attempt: i32,
+        delay: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query(&format!(
+                "UPDATE webhook_pending_deliveries SET attempt = ?3, next_attempt_at = {} \
+                 WHERE id = ?1 AND tenant_id = ?4",
+                PENDING_DELIVERY_DUE
+            ))
+            .bind(id)
+            .bind(delay.as_millis() as i64)
+            .bind(attempt)
+            .bind(&self.tenant_id)
+            .execute(&*self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn delete_pending(&self, id: i32) -> BoxFuture<'_, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM webhook_pending_deliveries WHERE id = ?1 AND tenant_id = ?2")
+                .bind(id)
+                .bind(&self.tenant_id)
+                .execute(&*self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
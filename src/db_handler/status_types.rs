@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, StatusTypeRepository},
+    structs::{NewStatusType, StatusTransition, StatusType, StatusTypeUpdate},
+    tenants::TenantPool,
+};
+
+/// Status types and transitions without tenant are shared by every tenant.
+const STATUS_TRANSITION_SELECT: &str = "SELECT from_status.name AS from_status, \
+        to_status.name AS to_status, status_transitions.required_fields \
+     FROM status_transitions \
+     JOIN status_types_dictionary AS from_status ON from_status.id = status_transitions.from_status_id \
+     JOIN status_types_dictionary AS to_status ON to_status.id = status_transitions.to_status_id \
+     WHERE (status_transitions.tenant_id IS NULL OR status_transitions.tenant_id = $1)";
+
+pub struct PostgresStatusTypeRepository {
+    pool: TenantPool,
+}
+
+impl PostgresStatusTypeRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresStatusTypeRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl StatusTypeRepository for PostgresStatusTypeRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "SELECT id, name, completed FROM status_types_dictionary \
+                 WHERE tenant_id IS NULL OR tenant_id = $1 ORDER BY id",
+            )
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn exists<'a>(&'a self, name: &'a str) -> BoxFuture<'a, RepositoryResult<bool>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM status_types_dictionary \
+                    WHERE name = $1 AND (tenant_id IS NULL OR tenant_id = $2))",
+            )
+            .bind(name)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        status_type: &'a NewStatusType,
+    ) -> BoxFuture<'a, RepositoryResult<StatusType>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "INSERT INTO status_types_dictionary (name, completed, tenant_id) VALUES ($1, $2, $3) \
+                 RETURNING id, name, completed",
+            )
+            .bind(&status_type.name)
+            .bind(status_type.completed)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "SELECT id, name, completed FROM status_types_dictionary \
+                 WHERE id = $1 AND tenant_id = $2",
+            )
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a StatusTypeUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "UPDATE status_types_dictionary \
+                 SET name = COALESCE($2, name), completed = COALESCE($3, completed) \
+                 WHERE id = $1 AND tenant_id = $4 RETURNING id, name, completed",
+            )
+            .bind(id)
+            .bind(&update.name)
+            .bind(update.completed)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<StatusType>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusType>(
+                "DELETE FROM status_types_dictionary WHERE id = $1 AND tenant_id = $2 \
+                 RETURNING id, name, completed",
+            )
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn in_use<'a>(&'a self, status_type: &'a StatusType) -> BoxFuture<'a, RepositoryResult<bool>> {
+        Box::pin(async move {
+            Ok(sqlx::query_scalar::<_, bool>(
+                "SELECT EXISTS (SELECT 1 FROM projects WHERE status = $1 AND tenant_id = $3) \
+                    OR EXISTS (SELECT 1 FROM tasks WHERE status = $1 AND tenant_id = $3) \
+                    OR EXISTS (SELECT 1 FROM project_status_history \
+                        WHERE (from_status = $1 OR to_status = $1) AND tenant_id = $3) \
+                    OR EXISTS (SELECT 1 FROM status_transitions \
+                        WHERE from_status_id = $2 OR to_status_id = $2)",
+            )
+            .bind(&status_type.name)
+            .bind(status_type.id)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_transitions(&self) -> BoxFuture<'_, RepositoryResult<Vec<StatusTransition>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusTransition>(&format!(
+                "{} ORDER BY status_transitions.id",
+                STATUS_TRANSITION_SELECT
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get_transition<'a>(
+        &'a self,
+        from_status: &'a str,
+        to_status: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<StatusTransition>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, StatusTransition>(&format!(
+                "{} AND from_status.name = $2 AND to_status.name = $3",
+                STATUS_TRANSITION_SELECT
+            ))
+            .bind(&self.pool.tenant_id)
+            .bind(from_status)
+            .bind(to_status)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
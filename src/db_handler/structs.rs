@@ -0,0 +1,665 @@
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
+pub struct Project {
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub version: i32,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub owner_id: Option<i32>,
+    pub team_id: Option<i32>,
+    /// One of its dependencies is not in a completed status, computed on read.
+    pub blocked: bool,
+}
+
+/// The creator becomes the owner, it is not part of the request.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, InputObject)]
+pub struct NewProject {
+    pub name: String,
+    pub description: Option<String>,
+    pub status: String,
+    #[serde(default)]
+    pub team_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct ProjectListQuery {
+    #[serde(default)]
+    pub include_deleted: bool,
+    /// Comma separated tag names.
+    pub tags: Option<String>,
+    #[serde(default)]
+    #[param(inline)]
+    pub tag_match: TagMatch,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, InputObject)]
+pub struct ProjectUpdate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub owner_id: Option<i32>,
+    #[serde(default)]
+    pub team_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, SimpleObject)]
+pub struct StatusType {
+    pub id: i32,
+    pub name: String,
+    /// Projects in a completed status don't block their dependents.
+    pub completed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, SimpleObject)]
+pub struct StatusTransition {
+    pub from_status: String,
+    pub to_status: String,
+    pub required_fields: Vec<String>,
+}
+
+impl StatusTransition {
+    /// Guard of the transition: lists the required project fields that are
+    /// still empty. A field the project doesn't have can never be filled, it
+    /// is always reported so a misconfigured transition can't be taken.
+    pub fn missing_fields(&self, project: &Project) -> Vec<String> {
+        self.required_fields
+            .iter()
+            .filter(|field| match field.as_str() {
+                "name" => project.name.trim().is_empty(),
+                "description" => project
+                    .description
+                    .as_deref()
+                    .is_none_or(|description| description.trim().is_empty()),
+                "owner_id" => project.owner_id.is_none(),
+                "team_id" => project.team_id.is_none(),
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransitionRequest {
+    pub to_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ProjectStatusHistoryEntry {
+    pub id: i32,
+    pub project_id: i32,
+    pub from_status: String,
+    pub to_status: String,
+    pub actor: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, InputObject)]
+pub struct NewStatusType {
+    pub name: String,
+    #[serde(default)]
+    #[graphql(default)]
+    pub completed: bool,
+}
+
+/// Fields left out keep their value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct StatusTypeUpdate {
+    pub name: Option<String>,
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct AuditRecord {
+    pub id: i32,
+    pub actor: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: i32,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub diff: serde_json::Value,
+    pub request_id: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAuditRecord {
+    pub actor: String,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: i32,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, IntoParams)]
+pub struct AuditFilter {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i32>,
+    pub request_id: Option<String>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    pub limit: Option<i64>,
+}
+
+/// A stored API key, the secret itself is only known to its holder.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ApiKey {
+    pub id: i32,
+    pub name: String,
+    pub subject: String,
+    pub prefix: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewApiKey {
+    pub name: String,
+    pub subject: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct User {
+    pub id: i32,
+    pub subject: String,
+    pub is_admin: bool,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Team {
+    pub id: i32,
+    pub name: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewTeam {
+    pub name: String,
+}
+
+/// Role of a user in a team, `role` is one of `viewer`, `editor`, `admin`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Membership {
+    pub team_id: i32,
+    pub user_id: i32,
+    pub subject: String,
+    pub role: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Tenant {
+    pub id: String,
+    pub name: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewTenant {
+    pub id: String,
+    pub name: String,
+}
+
+/// Tasks may also end in any other completed status type, those don't block
+/// their dependents.
+pub const TASK_STATUSES: [&str; 3] = ["todo", "in_progress", "done"];
+pub const TASK_PRIORITIES: [&str; 4] = ["low", "medium", "high", "urgent"];
+
+/// A task of a project, `parent_id` nests it under another task of the same
+/// project. Tasks are listed by `position`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, SimpleObject)]
+#[graphql(complex)]
+pub struct Task {
+    pub id: i32,
+    pub project_id: i32,
+    pub parent_id: Option<i32>,
+    pub title: String,
+    pub description: Option<String>,
+    pub assignee_id: Option<i32>,
+    pub status: String,
+    pub priority: String,
+    pub due_date: Option<NaiveDate>,
+    pub position: i32,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    /// One of its dependencies is not done, computed on read.
+    pub blocked: bool,
+}
+
+fn default_task_status() -> String {
+    "todo".to_string()
+}
+
+fn default_task_priority() -> String {
+    "medium".to_string()
+}
+
+/// New tasks are appended after the last task of the project.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, InputObject)]
+pub struct NewTask {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+    #[serde(default)]
+    pub assignee_id: Option<i32>,
+    #[serde(default = "default_task_status")]
+    #[graphql(default_with = "default_task_status()")]
+    pub status: String,
+    #[serde(default = "default_task_priority")]
+    #[graphql(default_with = "default_task_priority()")]
+    pub priority: String,
+    #[serde(default)]
+    pub due_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, InputObject)]
+pub struct TaskUpdate {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub parent_id: Option<i32>,
+    pub assignee_id: Option<i32>,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub due_date: Option<NaiveDate>,
+}
+
+/// Every task of the project, in their new order.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaskOrder {
+    pub task_ids: Vec<i32>,
+}
+
+/// The items dependencies and tags are attached to. Each kind has its own
+/// tables, projects depend on projects and tasks on tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Project,
+    Task,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Project => "project",
+            ItemKind::Task => "task",
+        }
+    }
+
+    pub fn dependency_table(&self) -> &'static str {
+        match self {
+            ItemKind::Project => "project_dependencies",
+            ItemKind::Task => "task_dependencies",
+        }
+    }
+
+    pub fn tag_table(&self) -> &'static str {
+        match self {
+            ItemKind::Project => "project_tags",
+            ItemKind::Task => "task_tags",
+        }
+    }
+
+    /// The column of the tag table referencing the item.
+    pub fn tag_column(&self) -> &'static str {
+        match self {
+            ItemKind::Project => "project_id",
+            ItemKind::Task => "task_id",
+        }
+    }
+}
+
+/// `blocked_id` is blocked by `blocking_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Dependency {
+    pub blocked_id: i32,
+    pub blocking_id: i32,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// What adding a dependency came to.
+#[derive(Debug, Clone)]
+pub enum DependencyInsertion {
+    Inserted(Dependency),
+    AlreadyExists,
+    /// The cycle it would have closed, as `DependencyGraph::cycle_path`.
+    Cycle(Vec<i32>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewDependency {
+    pub blocking_id: i32,
+}
+
+/// Tags are shared by the whole tenant, `color` is a `#rrggbb` hex code.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+    pub color: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+pub const DEFAULT_TAG_COLOR: &str = "#9e9e9e";
+
+fn default_tag_color() -> String {
+    DEFAULT_TAG_COLOR.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewTag {
+    pub name: String,
+    #[serde(default = "default_tag_color")]
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct TagUpdate {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+/// `item_id` is a project or task id depending on the table it was read from.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TagAssignment {
+    pub tag_id: i32,
+    pub item_id: i32,
+}
+
+/// Whether listings filtered by tags keep the items carrying any or all of
+/// them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct TagFilter {
+    /// Comma separated tag names.
+    pub tags: Option<String>,
+    #[serde(default)]
+    #[param(inline)]
+    pub tag_match: TagMatch,
+}
+
+/// The items a tag is assigned to or removed from in one request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct TagTargets {
+    #[serde(default)]
+    pub project_ids: Vec<i32>,
+    #[serde(default)]
+    pub task_ids: Vec<i32>,
+}
+
+/// `task_id` is set for comments on a task, `parent_id` for replies.
+/// Bodies are Markdown, rendered by the clients.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema, SimpleObject)]
+pub struct Comment {
+    pub id: i32,
+    pub project_id: i32,
+    pub task_id: Option<i32>,
+    pub parent_id: Option<i32>,
+    pub author: String,
+    pub body: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    /// The comment has revisions, computed on read.
+    pub edited: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewComment {
+    pub body: String,
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CommentUpdate {
+    pub body: String,
+}
+
+/// The body of a comment before an edit.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct CommentRevision {
+    pub id: i32,
+    pub comment_id: i32,
+    pub body: String,
+    pub edited_by: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+/// One entry of the activity feed of a project.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Activity {
+    Comment(Comment),
+    StatusTransition(ProjectStatusHistoryEntry),
+    /// An `update` audit record of the project or one of its tasks.
+    FieldEdit(AuditRecord),
+}
+
+impl Activity {
+    pub fn created_at(&self) -> Option<NaiveDateTime> {
+        match self {
+            Activity::Comment(comment) => comment.created_at,
+            Activity::StatusTransition(entry) => entry.created_at,
+            Activity::FieldEdit(record) => record.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct ActivityQuery {
+    /// The number of most recent entries, 100 by default.
+    pub limit: Option<usize>,
+}
+
+/// A file attached to a project, its content is kept by the blob store.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Attachment {
+    pub id: i32,
+    pub project_id: i32,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    /// Hex encoded SHA-256 of the content.
+    pub sha256: String,
+    #[serde(skip)]
+    pub storage_key: String,
+    pub uploaded_by: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub sha256: String,
+    pub storage_key: String,
+    pub uploaded_by: String,
+}
+
+/// The kinds of items `/search` looks through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchEntity {
+    Project,
+    Task,
+    Comment,
+}
+
+impl SearchEntity {
+    pub const ALL: [SearchEntity; 3] = [
+        SearchEntity::Project,
+        SearchEntity::Task,
+        SearchEntity::Comment,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchEntity::Project => "project",
+            SearchEntity::Task => "task",
+            SearchEntity::Comment => "comment",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<SearchEntity> {
+        SearchEntity::ALL
+            .into_iter()
+            .find(|entity| entity.as_str() == name)
+    }
+}
+
+/// One match of a search. `task_id` is set for tasks and comments on a task,
+/// `title` and `status` for projects and tasks. `snippet` wraps the matched
+/// words in `<mark>` tags, `rank` only compares hits of the same search.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct SearchHit {
+    pub entity_type: String,
+    pub id: i32,
+    pub project_id: i32,
+    pub task_id: Option<i32>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub snippet: String,
+    pub rank: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct SearchQuery {
+    pub q: String,
+    /// Comma separated `project`, `task` or `comment`, all of them by default.
+    #[serde(rename = "type")]
+    pub entity_types: Option<String>,
+    /// Comma separated statuses of projects and tasks.
+    pub status: Option<String>,
+    /// Comma separated tag names.
+    pub tags: Option<String>,
+    #[serde(default)]
+    #[param(inline)]
+    pub tag_match: TagMatch,
+    /// 20 hits by default, at most 100.
+    pub limit: Option<usize>,
+}
+
+/// An HTTP callback receiving the events of the tenant, signed with `secret`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Webhook {
+    pub id: i32,
+    pub url: String,
+    #[serde(skip)]
+    pub secret: String,
+    /// Names of the events sent, every event when empty.
+    pub event_types: Vec<String>,
+    pub enabled: bool,
+    /// Deliveries that failed in a row, reset by a successful one.
+    pub consecutive_failures: i32,
+    pub disabled_at: Option<NaiveDateTime>,
+    pub created_by: String,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl Webhook {
+    pub fn accepts(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.iter().any(|name| name == event_type)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+}
+
+/// Enabling a webhook again resets its failures.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct WebhookUpdate {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+/// One attempt at delivering an event to a webhook.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub webhook_id: i32,
+    /// The position of the Kafka message, `topic:partition:offset`.
+    pub event_id: String,
+    pub event_type: String,
+    /// 1 for the first attempt at delivering the event.
+    pub attempt: i32,
+    /// Missing when no response was received.
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub succeeded: bool,
+    pub duration_ms: i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWebhookDelivery {
+    pub webhook_id: i32,
+    pub event_id: String,
+    pub event_type: String,
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub error: Option<String>,
+    pub succeeded: bool,
+    pub duration_ms: i64,
+}
+
+/// An event waiting for its next attempt at a webhook. Kept until the webhook
+/// accepts it or the attempts run out, retries survive a restart.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PendingWebhookDelivery {
+    pub id: i32,
+    pub webhook_id: i32,
+    /// The position of the Kafka message, `topic:partition:offset`.
+    pub event_id: String,
+    pub event_type: String,
+    /// Posted as is on every attempt.
+    pub body: String,
+    /// The number of the next attempt, 1 for the first one.
+    pub attempt: i32,
+    pub next_attempt_at: NaiveDateTime,
+}
+
+/// Due right away, for its first attempt.
+#[derive(Debug, Clone)]
+pub struct NewPendingWebhookDelivery {
+    pub webhook_id: i32,
+    pub event_id: String,
+    pub event_type: String,
+    pub body: String,
+}
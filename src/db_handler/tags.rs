@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, TagRepository},
+    structs::{ItemKind, NewTag, Tag, TagAssignment, TagUpdate},
+    tenants::TenantPool,
+};
+
+const TAG_COLUMNS: &str = "id, name, color, created_at";
+
+pub struct PostgresTagRepository {
+    pool: TenantPool,
+}
+
+impl PostgresTagRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresTagRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl TagRepository for PostgresTagRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "SELECT {} FROM tags WHERE tenant_id = $1 ORDER BY name",
+                TAG_COLUMNS
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "SELECT {} FROM tags WHERE id = $1 AND tenant_id = $2",
+                TAG_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(&'a self, tag: &'a NewTag) -> BoxFuture<'a, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "INSERT INTO tags (name, color, tenant_id) VALUES ($1, $2, $3) \
+                 ON CONFLICT (tenant_id, name) DO NOTHING RETURNING {}",
+                TAG_COLUMNS
+            ))
+            .bind(&tag.name)
+            .bind(&tag.color)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a TagUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "UPDATE tags SET name = COALESCE($2, name), color = COALESCE($3, color) \
+                 WHERE id = $1 AND tenant_id = $4 RETURNING {}",
+                TAG_COLUMNS
+            ))
+            .bind(id)
+            .bind(&update.name)
+            .bind(&update.color)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "DELETE FROM tags WHERE id = $1 AND tenant_id = $2 RETURNING {}",
+                TAG_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_assignments(
+        &self,
+        kind: ItemKind,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<TagAssignment>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, TagAssignment>(&format!(
+                "SELECT tag_id, {column} AS item_id FROM {table} WHERE tenant_id = $1 \
+                 ORDER BY {column}, tag_id",
+                column = kind.tag_column(),
+                table = kind.tag_table()
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_item_tags(
+        &self,
+        kind: ItemKind,
+        item_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<Tag>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tag>(&format!(
+                "SELECT {columns} FROM tags WHERE tenant_id = $2 AND id IN \
+                 (SELECT tag_id FROM {table} WHERE {column} = $1 AND tenant_id = $2) \
+                 ORDER BY name",
+                columns = TAG_COLUMNS,
+                column = kind.tag_column(),
+                table = kind.tag_table()
+            ))
+            .bind(item_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn assign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query(&format!(
+                "INSERT INTO {} ({}, tag_id, tenant_id) \
+                 SELECT UNNEST($1::INTEGER[]), $2, $3 ON CONFLICT DO NOTHING",
+                kind.tag_table(),
+                kind.tag_column()
+            ))
+            .bind(item_ids)
+            .bind(tag_id)
+            .bind(&self.pool.tenant_id)
+            .execute(&mut *self.pool.acquire().await?)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn unassign<'a>(
+        &'a self,
+        kind: ItemKind,
+        tag_id: i32,
+        item_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE {} = ANY($1) AND tag_id = $2 AND tenant_id = $3",
+                kind.tag_table(),
+                kind.tag_column()
+            ))
+            .bind(item_ids)
+            .bind(tag_id)
+            .bind(&self.pool.tenant_id)
+            .execute(&mut *self.pool.acquire().await?)
+            .await?;
+            Ok(())
+        })
+    }
+}
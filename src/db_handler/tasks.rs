@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, TaskRepository},
+    structs::{NewTask, Task, TaskUpdate},
+    tenants::TenantPool,
+};
+
+/// `blocked` looks for a dependency that is not in a completed status of the
+/// tenant, as for projects.
+const TASK_COLUMNS: &str = "id, project_id, parent_id, title, description, assignee_id, status, \
+     priority, due_date, position, created_at, updated_at, \
+     EXISTS (SELECT 1 FROM task_dependencies \
+        JOIN tasks AS blocking ON blocking.id = task_dependencies.blocking_id \
+        WHERE task_dependencies.blocked_id = tasks.id \
+           AND blocking.status NOT IN (SELECT name FROM status_types_dictionary \
+              WHERE completed AND (tenant_id IS NULL OR tenant_id = tasks.tenant_id))) \
+     AS blocked";
+
+pub struct PostgresTaskRepository {
+    pool: TenantPool,
+}
+
+impl PostgresTaskRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresTaskRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl TaskRepository for PostgresTaskRepository {
+    fn list(&self, project_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE project_id = $1 AND tenant_id = $2 \
+                 ORDER BY position, id",
+                TASK_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_for_projects<'a>(
+        &'a self,
+        project_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE project_id = ANY($1) AND tenant_id = $2 \
+                 ORDER BY position, id",
+                TASK_COLUMNS
+            ))
+            .bind(project_ids)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE id = $1 AND project_id = $2 AND tenant_id = $3",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn find(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE id = $1 AND tenant_id = $2",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        project_id: i32,
+        task: &'a NewTask,
+    ) -> BoxFuture<'a, RepositoryResult<Task>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "INSERT INTO tasks (project_id, parent_id, title, description, assignee_id, \
+                    status, priority, due_date, position, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, \
+                    (SELECT COALESCE(MAX(position), 0) + 1 FROM tasks \
+                     WHERE project_id = $1 AND tenant_id = $9), $9) \
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(task.parent_id)
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(task.assignee_id)
+            .bind(&task.status)
+            .bind(&task.priority)
+            .bind(task.due_date)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        project_id: i32,
+        id: i32,
+        update: &'a TaskUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "UPDATE tasks SET \
+                    title = COALESCE($3, title), \
+                    description = COALESCE($4, description), \
+                    parent_id = COALESCE($5, parent_id), \
+                    assignee_id = COALESCE($6, assignee_id), \
+                    status = COALESCE($7, status), \
+                    priority = COALESCE($8, priority), \
+                    due_date = COALESCE($9, due_date), \
+                    updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = $1 AND project_id = $2 AND tenant_id = $10 \
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&update.title)
+            .bind(&update.description)
+            .bind(update.parent_id)
+            .bind(update.assignee_id)
+            .bind(&update.status)
+            .bind(&update.priority)
+            .bind(update.due_date)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn delete(&self, project_id: i32, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Task>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Task>(&format!(
+                "DELETE FROM tasks WHERE id = $1 AND project_id = $2 AND tenant_id = $3 \
+                 RETURNING {}",
+                TASK_COLUMNS
+            ))
+            .bind(id)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn reorder<'a>(
+        &'a self,
+        project_id: i32,
+        task_ids: &'a [i32],
+    ) -> BoxFuture<'a, RepositoryResult<Vec<Task>>> {
+        Box::pin(async move {
+            let mut transaction = self.pool.begin().await?;
+            sqlx::query(
+                "UPDATE tasks SET position = ordered.position, updated_at = CURRENT_TIMESTAMP \
+                 FROM UNNEST($1::INTEGER[]) WITH ORDINALITY AS ordered (id, position) \
+                 WHERE tasks.id = ordered.id AND tasks.project_id = $2 AND tasks.tenant_id = $3",
+            )
+            .bind(task_ids)
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .execute(&mut *transaction)
+            .await?;
+            let tasks = sqlx::query_as::<_, Task>(&format!(
+                "SELECT {} FROM tasks WHERE project_id = $1 AND tenant_id = $2 \
+                 ORDER BY position, id",
+                TASK_COLUMNS
+            ))
+            .bind(project_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *transaction)
+            .await?;
+            transaction.commit().await?;
+            Ok(tasks)
+        })
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, TeamRepository},
+    structs::{Membership, NewTeam, Team},
+    tenants::TenantPool,
+};
+
+const TEAM_COLUMNS: &str = "id, name, created_at";
+const MEMBERSHIP_SELECT: &str = "SELECT memberships.team_id, memberships.user_id, users.subject, \
+        memberships.role, memberships.created_at \
+     FROM memberships JOIN users ON users.id = memberships.user_id";
+
+pub struct PostgresTeamRepository {
+    pool: TenantPool,
+}
+
+impl PostgresTeamRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresTeamRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl TeamRepository for PostgresTeamRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Team>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Team>(&format!(
+                "SELECT {} FROM teams WHERE tenant_id = $1 ORDER BY id",
+                TEAM_COLUMNS
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Team>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Team>(&format!(
+                "SELECT {} FROM teams WHERE id = $1 AND tenant_id = $2",
+                TEAM_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(&'a self, team: &'a NewTeam) -> BoxFuture<'a, RepositoryResult<Option<Team>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Team>(&format!(
+                "INSERT INTO teams (name, tenant_id) VALUES ($1, $2) \
+                 ON CONFLICT (tenant_id, name) DO NOTHING RETURNING {}",
+                TEAM_COLUMNS
+            ))
+            .bind(&team.name)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_members(&self, team_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Membership>(&format!(
+                "{} WHERE memberships.team_id = $1 AND memberships.tenant_id = $2 \
+                 ORDER BY memberships.user_id",
+                MEMBERSHIP_SELECT
+            ))
+            .bind(team_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_memberships(&self, user_id: i32) -> BoxFuture<'_, RepositoryResult<Vec<Membership>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Membership>(&format!(
+                "{} WHERE memberships.user_id = $1 AND memberships.tenant_id = $2 \
+                 ORDER BY memberships.team_id",
+                MEMBERSHIP_SELECT
+            ))
+            .bind(user_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn set_member<'a>(
+        &'a self,
+        team_id: i32,
+        user_id: i32,
+        role: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Membership>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Membership>(
+                "WITH membership AS ( \
+                    INSERT INTO memberships (team_id, user_id, role, tenant_id) \
+                    VALUES ($1, $2, $3, $4) \
+                    ON CONFLICT (team_id, user_id) DO UPDATE SET role = EXCLUDED.role \
+                    RETURNING team_id, user_id, role, created_at \
+                 ) \
+                 SELECT membership.team_id, membership.user_id, users.subject, \
+                    membership.role, membership.created_at \
+                 FROM membership JOIN users ON users.id = membership.user_id",
+            )
+            .bind(team_id)
+            .bind(user_id)
+            .bind(role)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn remove_member(
+        &self,
+        team_id: i32,
+        user_id: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Membership>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Membership>(
+                "DELETE FROM memberships USING users \
+                 WHERE users.id = memberships.user_id \
+                    AND memberships.team_id = $1 AND memberships.user_id = $2 \
+                    AND memberships.tenant_id = $3 \
+                 RETURNING memberships.team_id, memberships.user_id, users.subject, \
+                    memberships.role, memberships.created_at",
+            )
+            .bind(team_id)
+            .bind(user_id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres, Transaction, pool::PoolConnection};
+
+use super::{
+    repository::{RepositoryResult, TenantRepository},
+    structs::{NewTenant, Tenant},
+};
+
+const TENANT_COLUMNS: &str = "id, name, created_at";
+
+/// Tables carrying a `tenant_id`, each with a `tenant_isolation` policy.
+const TENANT_TABLES: [&str; 21] = [
+    "projects",
+    "project_status_history",
+    "audit_log",
+    "api_keys",
+    "users",
+    "teams",
+    "memberships",
+    "status_types_dictionary",
+    "status_transitions",
+    "tasks",
+    "project_dependencies",
+    "task_dependencies",
+    "tags",
+    "project_tags",
+    "task_tags",
+    "comments",
+    "comment_revisions",
+    "attachments",
+    "webhooks",
+    "webhook_deliveries",
+    "webhook_pending_deliveries",
+];
+
+/// The pool as seen by one tenant. Connections are handed out with the
+/// `ninoverse.tenant_id` setting the row level security policies compare
+/// against, queries still filter on `tenant_id` themselves.
+#[derive(Clone)]
+pub struct TenantPool {
+    pool: Arc<Pool<Postgres>>,
+    pub tenant_id: String,
+}
+
+impl TenantPool {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        TenantPool {
+            pool,
+            tenant_id: tenant_id.to_string(),
+        }
+    }
+
+    pub async fn acquire(&self) -> Result<PoolConnection<Postgres>, sqlx::Error> {
+        let mut connection = self.pool.acquire().await?;
+        sqlx::query("SELECT set_config('ninoverse.tenant_id', $1, false)")
+            .bind(&self.tenant_id)
+            .execute(&mut *connection)
+            .await?;
+        Ok(connection)
+    }
+
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("SELECT set_config('ninoverse.tenant_id', $1, true)")
+            .bind(&self.tenant_id)
+            .execute(&mut *transaction)
+            .await?;
+        Ok(transaction)
+    }
+}
+
+/// Turns the enforcement of the `tenant_isolation` policies on or off. Forced
+/// so that they also apply to the owner of the tables, which the service
+/// usually connects as.
+pub async fn set_row_level_security(
+    pool: &Pool<Postgres>,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    let (enable, force) = if enabled {
+        ("ENABLE", "FORCE")
+    } else {
+        ("DISABLE", "NO FORCE")
+    };
+    for table in TENANT_TABLES {
+        sqlx::query(&format!(
+            "ALTER TABLE {} {} ROW LEVEL SECURITY, {} ROW LEVEL SECURITY",
+            table, enable, force
+        ))
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+pub struct PostgresTenantRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl PostgresTenantRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>) -> Self {
+        PostgresTenantRepository { pool }
+    }
+}
+
+impl TenantRepository for PostgresTenantRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Tenant>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tenant>(&format!(
+                "SELECT {} FROM tenants ORDER BY id",
+                TENANT_COLUMNS
+            ))
+            .fetch_all(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn get<'a>(&'a self, id: &'a str) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tenant>(&format!(
+                "SELECT {} FROM tenants WHERE id = $1",
+                TENANT_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        tenant: &'a NewTenant,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Tenant>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Tenant>(&format!(
+                "INSERT INTO tenants (id, name) VALUES ($1, $2) \
+                 ON CONFLICT (id) DO NOTHING RETURNING {}",
+                TENANT_COLUMNS
+            ))
+            .bind(&tenant.id)
+            .bind(&tenant.name)
+            .fetch_optional(&*self.pool)
+            .await?)
+        })
+    }
+}
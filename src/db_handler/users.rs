@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, UserRepository},
+    structs::User,
+    tenants::TenantPool,
+};
+
+const USER_COLUMNS: &str = "id, subject, is_admin, created_at";
+
+pub struct PostgresUserRepository {
+    pool: TenantPool,
+}
+
+impl PostgresUserRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresUserRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl UserRepository for PostgresUserRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<User>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "SELECT {} FROM users WHERE tenant_id = $1 ORDER BY id",
+                USER_COLUMNS
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<User>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "SELECT {} FROM users WHERE id = $1 AND tenant_id = $2",
+                USER_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn find_by_subject<'a>(
+        &'a self,
+        subject: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Option<User>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "SELECT {} FROM users WHERE subject = $1 AND tenant_id = $2",
+                USER_COLUMNS
+            ))
+            .bind(subject)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn upsert<'a>(
+        &'a self,
+        subject: &'a str,
+        is_admin: bool,
+    ) -> BoxFuture<'a, RepositoryResult<User>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, User>(&format!(
+                "INSERT INTO users (subject, is_admin, tenant_id) VALUES ($1, $2, $3) \
+                 ON CONFLICT (tenant_id, subject) DO UPDATE SET is_admin = EXCLUDED.is_admin \
+                 RETURNING {}",
+                USER_COLUMNS
+            ))
+            .bind(subject)
+            .bind(is_admin)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+}
@@ -0,0 +1,266 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::future::BoxFuture;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    repository::{RepositoryResult, WebhookRepository},
+    structs::{
+        NewPendingWebhookDelivery, NewWebhook, NewWebhookDelivery, PendingWebhookDelivery, Webhook,
+        WebhookDelivery, WebhookUpdate,
+    },
+    tenants::TenantPool,
+};
+
+const WEBHOOK_COLUMNS: &str = "id, url, secret, event_types, enabled, consecutive_failures, \
+     disabled_at, created_by, created_at, updated_at";
+const WEBHOOK_DELIVERY_COLUMNS: &str = "id, webhook_id, event_id, event_type, attempt, \
+     status_code, error, succeeded, duration_ms, created_at";
+const PENDING_DELIVERY_COLUMNS: &str =
+    "id, webhook_id, event_id, event_type, body, attempt, next_attempt_at";
+
+pub struct PostgresWebhookRepository {
+    pool: TenantPool,
+}
+
+impl PostgresWebhookRepository {
+    pub fn new(pool: Arc<Pool<Postgres>>, tenant_id: &str) -> Self {
+        PostgresWebhookRepository {
+            pool: TenantPool::new(pool, tenant_id),
+        }
+    }
+}
+
+impl WebhookRepository for PostgresWebhookRepository {
+    fn list(&self) -> BoxFuture<'_, RepositoryResult<Vec<Webhook>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Webhook>(&format!(
+                "SELECT {} FROM webhooks WHERE tenant_id = $1 ORDER BY id",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(&self.pool.tenant_id)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn get(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Webhook>(&format!(
+                "SELECT {} FROM webhooks WHERE id = $1 AND tenant_id = $2",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert<'a>(
+        &'a self,
+        webhook: &'a NewWebhook,
+        created_by: &'a str,
+    ) -> BoxFuture<'a, RepositoryResult<Webhook>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Webhook>(&format!(
+                "INSERT INTO webhooks (url, secret, event_types, created_by, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(&webhook.url)
+            .bind(&webhook.secret)
+            .bind(&webhook.event_types)
+            .bind(created_by)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        id: i32,
+        update: &'a WebhookUpdate,
+    ) -> BoxFuture<'a, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Webhook>(&format!(
+                "UPDATE webhooks SET url = COALESCE($2, url), secret = COALESCE($3, secret), \
+                 event_types = COALESCE($4, event_types), enabled = COALESCE($5, enabled), \
+                 consecutive_failures = CASE WHEN $5 THEN 0 ELSE consecutive_failures END, \
+                 disabled_at = CASE WHEN $5 THEN NULL \
+                 WHEN NOT $5 AND enabled THEN CURRENT_TIMESTAMP ELSE disabled_at END, \
+                 updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = $1 AND tenant_id = $6 RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&update.url)
+            .bind(&update.secret)
+            .bind(&update.event_types)
+            .bind(update.enabled)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn delete(&self, id: i32) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Webhook>(&format!(
+                "DELETE FROM webhooks WHERE id = $1 AND tenant_id = $2 RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn record_outcome(
+        &self,
+        id: i32,
+        succeeded: bool,
+        disable_after: i32,
+    ) -> BoxFuture<'_, RepositoryResult<Option<Webhook>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, Webhook>(&format!(
+                "UPDATE webhooks SET \
+                 consecutive_failures = CASE WHEN $2 THEN 0 ELSE consecutive_failures + 1 END, \
+                 enabled = enabled AND ($2 OR consecutive_failures + 1 < $3), \
+                 disabled_at = CASE WHEN enabled AND NOT $2 AND consecutive_failures + 1 >= $3 \
+                 THEN CURRENT_TIMESTAMP ELSE disabled_at END \
+                 WHERE id = $1 AND tenant_id = $4 RETURNING {}",
+                WEBHOOK_COLUMNS
+            ))
+            .bind(id)
+            .bind(succeeded)
+            .bind(disable_after)
+            .bind(&self.pool.tenant_id)
+            .fetch_optional(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert_delivery<'a>(
+        &'a self,
+        delivery: &'a NewWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<WebhookDelivery>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, WebhookDelivery>(&format!(
+                "INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, attempt, \
+                 status_code, error, succeeded, duration_ms, tenant_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING {}",
+                WEBHOOK_DELIVERY_COLUMNS
+            ))
+            .bind(delivery.webhook_id)
+            .bind(&delivery.event_id)
+            .bind(&delivery.event_type)
+            .bind(delivery.attempt)
+            .bind(delivery.status_code)
+            .bind(&delivery.error)
+            .bind(delivery.succeeded)
+            .bind(delivery.duration_ms)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn list_deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<WebhookDelivery>>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, WebhookDelivery>(&format!(
+                "SELECT {} FROM webhook_deliveries WHERE webhook_id = $1 AND tenant_id = $2 \
+                 ORDER BY id DESC LIMIT $3",
+                WEBHOOK_DELIVERY_COLUMNS
+            ))
+            .bind(webhook_id)
+            .bind(&self.pool.tenant_id)
+            .bind(limit)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn insert_pending<'a>(
+        &'a self,
+        delivery: &'a NewPendingWebhookDelivery,
+    ) -> BoxFuture<'a, RepositoryResult<PendingWebhookDelivery>> {
+        Box::pin(async move {
+            Ok(sqlx::query_as::<_, PendingWebhookDelivery>(&format!(
+                "INSERT INTO webhook_pending_deliveries (webhook_id, event_id, event_type, body, \
+                 tenant_id) VALUES ($1, $2, $3, $4, $5) RETURNING {}",
+                PENDING_DELIVERY_COLUMNS
+            ))
+            .bind(delivery.webhook_id)
+            .bind(&delivery.event_id)
+            .bind(&delivery.event_type)
+            .bind(&delivery.body)
+            .bind(&self.pool.tenant_id)
+            .fetch_one(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn claim_pending(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<Vec<PendingWebhookDelivery>>> {
+        Box::pin(async move {
+            // Skipping the locked rows, instances sharing the database never
+            // take the same delivery.
+            Ok(sqlx::query_as::<_, PendingWebhookDelivery>(&format!(
+                "UPDATE webhook_pending_deliveries \
+                 SET next_attempt_at = CURRENT_TIMESTAMP + $2 * INTERVAL '1 millisecond' \
+                 WHERE tenant_id = $1 AND id IN (SELECT id FROM webhook_pending_deliveries \
+                 WHERE tenant_id = $1 AND next_attempt_at <= CURRENT_TIMESTAMP \
+                 ORDER BY next_attempt_at, id LIMIT $3 FOR UPDATE SKIP LOCKED) RETURNING {}",
+                PENDING_DELIVERY_COLUMNS
+            ))
+            .bind(&self.pool.tenant_id)
+            .bind(lease.as_millis() as i64)
+            .bind(limit)
+            .fetch_all(&mut *self.pool.acquire().await?)
+            .await?)
+        })
+    }
+
+    fn reschedule_pending(
+        &self,
+        id: i32,
+        attempt: i32,
+        delay: Duration,
+    ) -> BoxFuture<'_, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "UPDATE webhook_pending_deliveries SET attempt = $2, \
+                 next_attempt_at = CURRENT_TIMESTAMP + $3 * INTERVAL '1 millisecond' \
+                 WHERE id = $1 AND tenant_id = $4",
+            )
+            .bind(id)
+            .bind(attempt)
+            .bind(delay.as_millis() as i64)
+            .bind(&self.pool.tenant_id)
+            .execute(&mut *self.pool.acquire().await?)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn delete_pending(&self, id: i32) -> BoxFuture<'_, RepositoryResult<()>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM webhook_pending_deliveries WHERE id = $1 AND tenant_id = $2")
+                .bind(id)
+                .bind(&self.pool.tenant_id)
+                .execute(&mut *self.pool.acquire().await?)
+                .await?;
+            Ok(())
+        })
+    }
+}
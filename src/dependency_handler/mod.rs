@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::db_handler::structs::Dependency;
+
+/// The dependencies of one kind, walked in both directions. Ids are project
+/// or task ids depending on the table the edges were read from.
+pub struct DependencyGraph {
+    blocking: BTreeMap<i32, BTreeSet<i32>>,
+    blocked: BTreeMap<i32, BTreeSet<i32>>,
+}
+
+impl DependencyGraph {
+    pub fn new(dependencies: &[Dependency]) -> Self {
+        let mut graph = DependencyGraph {
+            blocking: BTreeMap::new(),
+            blocked: BTreeMap::new(),
+        };
+        for dependency in dependencies {
+            graph
+                .blocking
+                .entry(dependency.blocked_id)
+                .or_default()
+                .insert(dependency.blocking_id);
+            graph
+                .blocked
+                .entry(dependency.blocking_id)
+                .or_default()
+                .insert(dependency.blocked_id);
+        }
+        graph
+    }
+
+    /// The cycle that making `blocked_id` wait for `blocking_id` would close,
+    /// starting and ending with `blocked_id`.
+    pub fn cycle_path(&self, blocked_id: i32, blocking_id: i32) -> Option<Vec<i32>> {
+        let mut reached_from = BTreeMap::from([(blocking_id, blocking_id)]);
+        let mut queue = VecDeque::from([blocking_id]);
+        while let Some(id) = queue.pop_front() {
+            if id == blocked_id {
+                let mut path = vec![id];
+                let mut current = id;
+                while current != blocking_id {
+                    current = reached_from[&current];
+                    path.push(current);
+                }
+                path.push(blocked_id);
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.blocking.get(&id).into_iter().flatten() {
+                if !reached_from.contains_key(next) {
+                    reached_from.insert(*next, id);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Everything `id` waits for, directly or not.
+    pub fn upstream(&self, id: i32) -> BTreeSet<i32> {
+        closure(&self.blocking, id)
+    }
+
+    /// Everything waiting for `id`, directly or not.
+    pub fn downstream(&self, id: i32) -> BTreeSet<i32> {
+        closure(&self.blocked, id)
+    }
+
+    /// The longest chain of pending items that has to be finished before `id`,
+    /// in the order they can be worked on and ending with `id` itself.
+    pub fn critical_path<F>(&self, id: i32, is_pending: F) -> Vec<i32>
+    where
+        F: Fn(i32) -> bool,
+    {
+        self.longest_chain(id, &is_pending, &mut BTreeMap::new())
+    }
+
+    fn longest_chain<F>(
+        &self,
+        id: i32,
+        is_pending: &F,
+        chains: &mut BTreeMap<i32, Vec<i32>>,
+    ) -> Vec<i32>
+    where
+        F: Fn(i32) -> bool,
+    {
+        if let Some(chain) = chains.get(&id) {
+            return chain.clone();
+        }
+        // Stops the walk should the stored edges ever contain a cycle.
+        chains.insert(id, vec![id]);
+        let mut longest = Vec::new();
+        for blocking_id in self.blocking.get(&id).into_iter().flatten() {
+            if is_pending(*blocking_id) {
+                let chain = self.longest_chain(*blocking_id, is_pending, chains);
+                if chain.len() > longest.len() {
+                    longest = chain;
+                }
+            }
+        }
+        longest.push(id);
+        chains.insert(id, longest.clone());
+        longest
+    }
+}
+
+fn closure(edges: &BTreeMap<i32, BTreeSet<i32>>, id: i32) -> BTreeSet<i32> {
+    let mut reached = BTreeSet::new();
+    let mut queue = VecDeque::from([id]);
+    while let Some(current) = queue.pop_front() {
+        for next in edges.get(&current).into_iter().flatten() {
+            if *next != id && reached.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+    reached
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use async_graphql::dataloader::Loader;
+
+use super::api_error;
+use crate::db_handler::{
+    repository::Repositories,
+    structs::{Comment, StatusType, Task},
+};
+
+/// The tasks of each project, by position.
+pub struct ProjectTasksLoader {
+    repositories: Repositories,
+}
+
+impl ProjectTasksLoader {
+    pub fn new(repositories: Repositories) -> Self {
+        ProjectTasksLoader { repositories }
+    }
+}
+
+impl Loader<i32> for ProjectTasksLoader {
+    type Value = Vec<Task>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, project_ids: &[i32]) -> Result<HashMap<i32, Vec<Task>>, Self::Error> {
+        let mut tasks: HashMap<i32, Vec<Task>> = HashMap::new();
+        for task in self
+            .repositories
+            .tasks
+            .list_for_projects(project_ids)
+            .await
+            .map_err(api_error)?
+        {
+            tasks.entry(task.project_id).or_default().push(task);
+        }
+        Ok(tasks)
+    }
+}
+
+/// The comments of each project and of its tasks, oldest first.
+pub struct ProjectCommentsLoader {
+    repositories: Repositories,
+}
+
+impl ProjectCommentsLoader {
+    pub fn new(repositories: Repositories) -> Self {
+        ProjectCommentsLoader { repositories }
+    }
+}
+
+impl Loader<i32> for ProjectCommentsLoader {
+    type Value = Vec<Comment>;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, project_ids: &[i32]) -> Result<HashMap<i32, Vec<Comment>>, Self::Error> {
+        let mut comments: HashMap<i32, Vec<Comment>> = HashMap::new();
+        for comment in self
+            .repositories
+            .comments
+            .list_for_projects(project_ids)
+            .await
+            .map_err(api_error)?
+        {
+            comments
+                .entry(comment.project_id)
+                .or_default()
+                .push(comment);
+        }
+        Ok(comments)
+    }
+}
+
+/// Status types by name, the dictionary is small enough to be read whole.
+pub struct StatusTypeLoader {
+    repositories: Repositories,
+}
+
+impl StatusTypeLoader {
+    pub fn new(repositories: Repositories) -> Self {
+        StatusTypeLoader { repositories }
+    }
+}
+
+impl Loader<String> for StatusTypeLoader {
+    type Value = StatusType;
+    type Error = async_graphql::Error;
+
+    async fn load(&self, names: &[String]) -> Result<HashMap<String, StatusType>, Self::Error> {
+        Ok(self
+            .repositories
+            .status_types
+            .list()
+            .await
+            .map_err(api_error)?
+            .into_iter()
+            .filter(|status_type| names.contains(&status_type.name))
+            .map(|status_type| (status_type.name.clone(), status_type))
+            .collect())
+    }
+}
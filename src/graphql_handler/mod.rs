@@ -0,0 +1,109 @@
+mod loaders;
+mod mutation;
+mod query;
+mod subscription;
+
+use std::sync::Arc;
+
+use actix_web::ResponseError;
+use async_graphql::{Data, ErrorExtensions, Schema, dataloader::DataLoader};
+use loaders::{ProjectCommentsLoader, ProjectTasksLoader, StatusTypeLoader};
+use mutation::MutationRoot;
+use query::QueryRoot;
+use subscription::SubscriptionRoot;
+use tokio::sync::mpsc::Sender;
+
+use crate::{
+    KafkaChannelMessage,
+    api_handler::{error::NinoverseApiError, request_context::RequestContext},
+    configuration_handler::{
+        get_graphql_max_complexity, get_graphql_max_depth, get_graphql_max_recursion,
+    },
+    db_handler::repository::Repositories,
+    kafka_handler::live::LiveFeed,
+    permission_handler::Caller,
+};
+
+pub type NinoverseSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
+
+/// What a query may ask for, queries over a limit are rejected before any
+/// resolver runs.
+pub struct GraphqlLimits {
+    pub max_depth: usize,
+    pub max_complexity: usize,
+    pub max_recursion: usize,
+}
+
+impl GraphqlLimits {
+    pub fn from_configuration() -> Self {
+        GraphqlLimits {
+            max_depth: get_graphql_max_depth(),
+            max_complexity: get_graphql_max_complexity(),
+            max_recursion: get_graphql_max_recursion(),
+        }
+    }
+}
+
+/// The live feed is shared, everything the resolvers act on comes with the
+/// request, see `RequestScope`.
+pub fn build_schema(live_feed: Arc<LiveFeed>, limits: &GraphqlLimits) -> NinoverseSchema {
+    Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .data(live_feed)
+        .limit_depth(limits.max_depth)
+        .limit_complexity(limits.max_complexity)
+        .limit_recursive_depth(limits.max_recursion)
+        .finish()
+}
+
+/// Who a GraphQL request or WebSocket connection acts as, in which tenant.
+/// The same checks, audit records and events as the REST handlers apply.
+pub struct RequestScope {
+    pub repositories: Repositories,
+    pub kafka_thread_sender: Sender<KafkaChannelMessage>,
+    pub context: RequestContext,
+    pub caller: Caller,
+}
+
+impl RequestScope {
+    /// The data of the request, with loaders batching the nested fields of
+    /// every project in one query instead of one per project.
+    pub fn into_data(self) -> Data {
+        let mut data = Data::default();
+        data.insert(DataLoader::new(
+            ProjectTasksLoader::new(self.repositories.clone()),
+            tokio::spawn,
+        ));
+        data.insert(DataLoader::new(
+            ProjectCommentsLoader::new(self.repositories.clone()),
+            tokio::spawn,
+        ));
+        data.insert(DataLoader::new(
+            StatusTypeLoader::new(self.repositories.clone()),
+            tokio::spawn,
+        ));
+        data.insert(self);
+        data
+    }
+}
+
+/// The message is the `additional_info` of the REST error body, the `code`
+/// extension its HTTP status, e.g. `NOT_FOUND`.
+impl ErrorExtensions for NinoverseApiError {
+    fn extend(&self) -> async_graphql::Error {
+        let code = self
+            .status_code()
+            .canonical_reason()
+            .unwrap_or_default()
+            .to_uppercase()
+            .replace(' ', "_");
+        async_graphql::Error::new(self.additional_info()).extend_with(|_, extensions| {
+            extensions.set("code", code);
+            extensions.set("error", self.to_string());
+        })
+    }
+}
+
+/// `?` alone would only keep the generic message of the error.
+fn api_error(error: impl Into<NinoverseApiError>) -> async_graphql::Error {
+    error.into().extend()
+}
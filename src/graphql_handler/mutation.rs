@@ -0,0 +1,147 @@
+use async_graphql::{Context, Object, Result};
+
+use super::{RequestScope, api_error};
+use crate::{
+    api_handler::{
+        projects::{delete_existing_project, insert_project, update_existing_project},
+        status_types::insert_status_type,
+        tasks::{delete_existing_task, insert_task, update_existing_task},
+    },
+    db_handler::structs::{
+        NewProject, NewStatusType, NewTask, Project, ProjectUpdate, StatusType, Task, TaskUpdate,
+    },
+};
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn create_project(&self, ctx: &Context<'_>, input: NewProject) -> Result<Project> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        insert_project(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            &input,
+        )
+        .await
+        .map_err(api_error)
+    }
+
+    /// Fails when `version` is given and the project was modified since.
+    /// The status only changes through the transitions of the REST API.
+    async fn update_project(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        input: ProjectUpdate,
+        version: Option<i32>,
+    ) -> Result<Project> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        update_existing_project(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+            version.map(|version| vec![version]),
+            &input,
+        )
+        .await
+        .map_err(api_error)
+    }
+
+    /// Soft deletes the project, returns it as deleted.
+    async fn delete_project(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        version: Option<i32>,
+    ) -> Result<Project> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        delete_existing_project(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+            version.map(|version| vec![version]),
+        )
+        .await
+        .map_err(api_error)
+    }
+
+    async fn create_task(
+        &self,
+        ctx: &Context<'_>,
+        project_id: i32,
+        input: NewTask,
+    ) -> Result<Task> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        insert_task(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            project_id,
+            &input,
+        )
+        .await
+        .map_err(api_error)
+    }
+
+    async fn update_task(
+        &self,
+        ctx: &Context<'_>,
+        project_id: i32,
+        id: i32,
+        input: TaskUpdate,
+    ) -> Result<Task> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        update_existing_task(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            project_id,
+            id,
+            &input,
+        )
+        .await
+        .map_err(api_error)
+    }
+
+    /// Deletes the task with its subtasks, returns the deleted task.
+    async fn delete_task(&self, ctx: &Context<'_>, project_id: i32, id: i32) -> Result<Task> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        delete_existing_task(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            project_id,
+            id,
+        )
+        .await
+        .map_err(api_error)
+    }
+
+    /// Administrators only.
+    async fn create_status_type(
+        &self,
+        ctx: &Context<'_>,
+        input: NewStatusType,
+    ) -> Result<StatusType> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        insert_status_type(
+            &scope.repositories,
+            &scope.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            &input,
+        )
+        .await
+        .map_err(api_error)
+    }
+}
@@ -0,0 +1,121 @@
+use async_graphql::{ComplexObject, Context, Object, Result, dataloader::DataLoader};
+
+use super::{
+    RequestScope, api_error,
+    loaders::{ProjectCommentsLoader, ProjectTasksLoader, StatusTypeLoader},
+};
+use crate::{
+    api_handler::tasks::{get_authorized_project, get_existing_task},
+    db_handler::structs::{Comment, Project, StatusTransition, StatusType, Task},
+    permission_handler::Permission,
+};
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The projects the caller can read.
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_deleted: bool,
+    ) -> Result<Vec<Project>> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        Ok(scope
+            .repositories
+            .projects
+            .list(include_deleted)
+            .await
+            .map_err(api_error)?
+            .into_iter()
+            .filter(|project| scope.caller.project_role(project).is_some())
+            .collect())
+    }
+
+    /// Deleted projects included.
+    async fn project(&self, ctx: &Context<'_>, id: i32) -> Result<Project> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        get_authorized_project(&scope.repositories, &scope.caller, id, Permission::Read)
+            .await
+            .map_err(api_error)
+    }
+
+    async fn task(&self, ctx: &Context<'_>, project_id: i32, id: i32) -> Result<Task> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        let project = get_authorized_project(
+            &scope.repositories,
+            &scope.caller,
+            project_id,
+            Permission::Read,
+        )
+        .await
+        .map_err(api_error)?;
+        get_existing_task(&scope.repositories, project.id, id)
+            .await
+            .map_err(api_error)
+    }
+
+    async fn status_types(&self, ctx: &Context<'_>) -> Result<Vec<StatusType>> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        scope
+            .repositories
+            .status_types
+            .list()
+            .await
+            .map_err(api_error)
+    }
+
+    async fn status_transitions(&self, ctx: &Context<'_>) -> Result<Vec<StatusTransition>> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        scope
+            .repositories
+            .status_types
+            .list_transitions()
+            .await
+            .map_err(api_error)
+    }
+}
+
+/// Projects are only resolved once the caller was checked to read them.
+#[ComplexObject]
+impl Project {
+    /// By position.
+    async fn tasks(&self, ctx: &Context<'_>) -> Result<Vec<Task>> {
+        let tasks = ctx
+            .data_unchecked::<DataLoader<ProjectTasksLoader>>()
+            .load_one(self.id)
+            .await?;
+        Ok(tasks.unwrap_or_default())
+    }
+
+    /// The comments of the project and of its tasks, oldest first.
+    async fn comments(&self, ctx: &Context<'_>) -> Result<Vec<Comment>> {
+        let comments = ctx
+            .data_unchecked::<DataLoader<ProjectCommentsLoader>>()
+            .load_one(self.id)
+            .await?;
+        Ok(comments.unwrap_or_default())
+    }
+
+    async fn status_type(&self, ctx: &Context<'_>) -> Result<Option<StatusType>> {
+        ctx.data_unchecked::<DataLoader<StatusTypeLoader>>()
+            .load_one(self.status.clone())
+            .await
+    }
+}
+
+#[ComplexObject]
+impl Task {
+    /// Oldest first, loaded with the comments of the project.
+    async fn comments(&self, ctx: &Context<'_>) -> Result<Vec<Comment>> {
+        let comments = ctx
+            .data_unchecked::<DataLoader<ProjectCommentsLoader>>()
+            .load_one(self.project_id)
+            .await?;
+        Ok(comments
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|comment| comment.task_id == Some(self.id))
+            .collect())
+    }
+}
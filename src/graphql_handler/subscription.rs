@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use async_graphql::{Context, Json, SimpleObject, Subscription};
+use futures::{Stream, stream};
+
+use super::RequestScope;
+use crate::kafka_handler::{
+    live::{LiveFeed, LiveMessage, LiveSubscription},
+    structs::KafkaNinoverseEvent,
+};
+
+/// An event of a followed project. `reset` events carry nothing else, events
+/// were missed and the client has to reload what it shows.
+#[derive(SimpleObject)]
+pub struct ProjectEvent {
+    /// The position of the Kafka message, to resume after with `lastEventId`.
+    id: Option<String>,
+    event: String,
+    project_id: Option<i32>,
+    data: Option<Json<KafkaNinoverseEvent>>,
+}
+
+impl From<LiveMessage> for ProjectEvent {
+    fn from(message: LiveMessage) -> Self {
+        match message {
+            LiveMessage::Event(live_event) => ProjectEvent {
+                id: Some(live_event.id.clone()),
+                event: live_event.event.name().to_string(),
+                project_id: Some(live_event.project.id),
+                data: Some(Json(live_event.event.clone())),
+            },
+            LiveMessage::Reset => ProjectEvent {
+                id: None,
+                event: "reset".to_string(),
+                project_id: None,
+                data: None,
+            },
+        }
+    }
+}
+
+pub struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// The events of `/events/stream`: of the projects the caller can read,
+    /// filtered by `projects` and `tags`, everything when both are empty.
+    async fn project_events(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] projects: Vec<i32>,
+        #[graphql(default)] tags: Vec<String>,
+        last_event_id: Option<String>,
+    ) -> impl Stream<Item = ProjectEvent> + use<> {
+        let scope = ctx.data_unchecked::<RequestScope>();
+        let receiver = ctx.data_unchecked::<Arc<LiveFeed>>().subscribe(
+            &scope.repositories.tenant_id,
+            scope.caller.clone(),
+            LiveSubscription {
+                project_ids: projects.into_iter().collect(),
+                tags: tags.into_iter().collect(),
+            },
+            last_event_id.as_deref(),
+        );
+        stream::unfold(receiver, |mut receiver| async move {
+            let message = receiver.next().await?;
+            Some((ProjectEvent::from(message), receiver))
+        })
+    }
+}
@@ -0,0 +1,67 @@
+mod service;
+
+pub mod proto {
+    tonic::include_proto!("ninoverse.v1");
+}
+
+use std::{net::TcpListener, sync::Arc};
+
+use proto::ninoverse_server::NinoverseServer;
+use service::NinoverseGrpcService;
+use tokio::sync::mpsc::Sender;
+use tonic::{
+    Code, Status,
+    transport::{Server, server::TcpIncoming},
+};
+
+use crate::{
+    KafkaChannelMessage,
+    api_handler::{auth::AuthSettings, error::NinoverseApiError},
+    db_handler::repository::Repositories,
+    kafka_handler::live::LiveFeed,
+};
+
+/// The gRPC server of `proto/ninoverse.proto`, serving until `shutdown`
+/// resolves. Event streams end when the live feed is closed.
+pub fn init_grpc_server(
+    repositories: Repositories,
+    kafka_thread_sender: Sender<KafkaChannelMessage>,
+    listener: TcpListener,
+    auth_settings: AuthSettings,
+    live_feed: Arc<LiveFeed>,
+    shutdown: impl Future<Output = ()>,
+) -> Result<impl Future<Output = Result<(), tonic::transport::Error>>, Box<dyn std::error::Error>> {
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    let service = NinoverseGrpcService {
+        repositories,
+        kafka_thread_sender,
+        auth_settings,
+        live_feed,
+    };
+    Ok(Server::builder()
+        .add_service(NinoverseServer::new(service))
+        .serve_with_incoming_shutdown(TcpIncoming::from(listener), shutdown))
+}
+
+/// The status closest to the HTTP one of the REST API, with the
+/// `additional_info` of the error body as message.
+impl From<NinoverseApiError> for Status {
+    fn from(api_error: NinoverseApiError) -> Self {
+        let code = match api_error {
+            NinoverseApiError::Unauthorized { .. } => Code::Unauthenticated,
+            NinoverseApiError::Forbidden { .. } => Code::PermissionDenied,
+            NinoverseApiError::NotFound { .. } => Code::NotFound,
+            NinoverseApiError::PreconditionFailed { .. } => Code::Aborted,
+            NinoverseApiError::InvalidTransition { .. } => Code::FailedPrecondition,
+            NinoverseApiError::ValidationError { .. }
+            | NinoverseApiError::UnsupportedMediaType { .. } => Code::InvalidArgument,
+            NinoverseApiError::PayloadTooLarge { .. }
+            | NinoverseApiError::RangeNotSatisfiable { .. } => Code::OutOfRange,
+            NinoverseApiError::DatabaseError { .. }
+            | NinoverseApiError::BlobStoreError { .. }
+            | NinoverseApiError::MessageBusError { .. } => Code::Internal,
+        };
+        Status::new(code, api_error.additional_info())
+    }
+}
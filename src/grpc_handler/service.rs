@@ -0,0 +1,454 @@
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use futures::{StreamExt, stream::BoxStream};
+use tokio::sync::mpsc::Sender;
+use tonic::{Request, Response, Status, metadata::MetadataMap};
+
+use super::proto::{
+    self, CreateProjectRequest, CreateStatusTypeRequest, DeleteProjectRequest,
+    DeleteStatusTypeRequest, GetProjectRequest, ListProjectsRequest, ListProjectsResponse,
+    ListStatusTransitionsRequest, ListStatusTransitionsResponse, ListStatusTypesRequest,
+    ListStatusTypesResponse, StreamProjectEventsRequest, TransitionProjectRequest,
+    UpdateProjectRequest, UpdateStatusTypeRequest, ninoverse_server::Ninoverse,
+};
+use crate::{
+    KafkaChannelMessage,
+    api_handler::{
+        access::load_caller,
+        auth::{API_KEY_HEADER, AuthSettings, authenticate_credentials},
+        error::NinoverseApiError,
+        projects::{delete_existing_project, insert_project, update_existing_project},
+        request_context::{ACTOR_HEADER, REQUEST_ID_HEADER, RequestContext, generate_request_id},
+        status_types::{
+            delete_existing_status_type, insert_status_type, update_existing_status_type,
+        },
+        tenant::{TENANT_HEADER, resolve_tenant_of},
+        transitions::transition_project,
+    },
+    db_handler::{
+        repository::Repositories,
+        structs::{
+            NewProject, NewStatusType, Project, ProjectStatusHistoryEntry, ProjectUpdate,
+            StatusTransition, StatusType, StatusTypeUpdate,
+        },
+    },
+    kafka_handler::live::{LiveFeed, LiveMessage, LiveSubscription},
+    permission_handler::{Caller, Permission},
+};
+
+pub struct NinoverseGrpcService {
+    pub repositories: Repositories,
+    pub kafka_thread_sender: Sender<KafkaChannelMessage>,
+    pub auth_settings: AuthSettings,
+    pub live_feed: Arc<LiveFeed>,
+}
+
+/// Who a call acts as, in which tenant, resolved from its metadata as the
+/// middlewares of the REST API do from the headers.
+struct CallScope {
+    tenant_id: String,
+    repositories: Repositories,
+    context: RequestContext,
+    caller: Caller,
+}
+
+fn metadata_value<'a>(metadata: &'a MetadataMap, key: &str) -> Option<&'a str> {
+    metadata
+        .get(key)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Same format as the REST API.
+fn timestamp(date_time: Option<NaiveDateTime>) -> Option<String> {
+    date_time.map(|date_time| date_time.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+}
+
+impl From<Project> for proto::Project {
+    fn from(project: Project) -> Self {
+        proto::Project {
+            id: project.id,
+            name: project.name,
+            description: project.description,
+            status: project.status,
+            created_at: timestamp(project.created_at),
+            updated_at: timestamp(project.updated_at),
+            version: project.version,
+            deleted_at: timestamp(project.deleted_at),
+            owner_id: project.owner_id,
+            team_id: project.team_id,
+            blocked: project.blocked,
+        }
+    }
+}
+
+impl From<(Project, ProjectStatusHistoryEntry)> for proto::ProjectTransition {
+    fn from((project, history_entry): (Project, ProjectStatusHistoryEntry)) -> Self {
+        proto::ProjectTransition {
+            project: Some(project.into()),
+            from_status: history_entry.from_status,
+            to_status: history_entry.to_status,
+            actor: history_entry.actor,
+            created_at: timestamp(history_entry.created_at),
+        }
+    }
+}
+
+impl From<StatusType> for proto::StatusType {
+    fn from(status_type: StatusType) -> Self {
+        proto::StatusType {
+            id: status_type.id,
+            name: status_type.name,
+            completed: status_type.completed,
+        }
+    }
+}
+
+impl From<StatusTransition> for proto::StatusTransition {
+    fn from(transition: StatusTransition) -> Self {
+        proto::StatusTransition {
+            from_status: transition.from_status,
+            to_status: transition.to_status,
+            required_fields: transition.required_fields,
+        }
+    }
+}
+
+impl From<LiveMessage> for proto::ProjectEvent {
+    fn from(message: LiveMessage) -> Self {
+        match message {
+            LiveMessage::Event(live_event) => proto::ProjectEvent {
+                id: Some(live_event.id.clone()),
+                event: live_event.event.name().to_string(),
+                project_id: Some(live_event.project.id),
+                data: serde_json::to_string(&live_event.event).ok(),
+            },
+            LiveMessage::Reset => proto::ProjectEvent {
+                id: None,
+                event: "reset".to_string(),
+                project_id: None,
+                data: None,
+            },
+        }
+    }
+}
+
+impl NinoverseGrpcService {
+    /// Authenticates the call with the `x-api-key` or `authorization`
+    /// metadata and resolves its tenant from the credentials or
+    /// `x-tenant-id`.
+    async fn scope(&self, metadata: &MetadataMap) -> Result<CallScope, NinoverseApiError> {
+        let requested_tenant = metadata_value(metadata, TENANT_HEADER).map(String::from);
+        let principal = if self.auth_settings.enabled {
+            Some(
+                authenticate_credentials(
+                    &self.auth_settings,
+                    &self.repositories,
+                    metadata_value(metadata, API_KEY_HEADER),
+                    metadata_value(metadata, "authorization"),
+                    requested_tenant.clone(),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        let tenant_id = resolve_tenant_of(
+            &self.repositories,
+            &self.auth_settings,
+            requested_tenant,
+            principal.as_ref(),
+        )
+        .await?;
+        let repositories = self.repositories.for_tenant(&tenant_id);
+        // The authenticated subject wins over the self-declared actor.
+        let context = RequestContext {
+            actor: principal
+                .as_ref()
+                .map(|principal| principal.subject.as_str())
+                .or_else(|| metadata_value(metadata, ACTOR_HEADER))
+                .unwrap_or("anonymous")
+                .to_string(),
+            request_id: Some(
+                metadata_value(metadata, REQUEST_ID_HEADER)
+                    .map(String::from)
+                    .unwrap_or_else(generate_request_id),
+            ),
+        };
+        let caller = load_caller(&repositories, &self.auth_settings, principal).await?;
+        Ok(CallScope {
+            tenant_id,
+            repositories,
+            context,
+            caller,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl Ninoverse for NinoverseGrpcService {
+    async fn list_projects(
+        &self,
+        request: Request<ListProjectsRequest>,
+    ) -> Result<Response<ListProjectsResponse>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let projects = scope
+            .repositories
+            .projects
+            .list(request.get_ref().include_deleted)
+            .await
+            .map_err(NinoverseApiError::from)?
+            .into_iter()
+            .filter(|project| scope.caller.project_role(project).is_some())
+            .map(proto::Project::from)
+            .collect();
+        Ok(Response::new(ListProjectsResponse { projects }))
+    }
+
+    async fn get_project(
+        &self,
+        request: Request<GetProjectRequest>,
+    ) -> Result<Response<proto::Project>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let GetProjectRequest {
+            id,
+            include_deleted,
+        } = request.into_inner();
+        let project = scope
+            .repositories
+            .projects
+            .get(id, include_deleted)
+            .await
+            .map_err(NinoverseApiError::from)?
+            .ok_or_else(|| NinoverseApiError::NotFound {
+                additional_info: format!("Project {} does not exist.", id),
+            })?;
+        scope
+            .caller
+            .authorize(&project, Permission::Read)
+            .map_err(NinoverseApiError::from)?;
+        Ok(Response::new(project.into()))
+    }
+
+    async fn create_project(
+        &self,
+        request: Request<CreateProjectRequest>,
+    ) -> Result<Response<proto::Project>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let CreateProjectRequest {
+            name,
+            description,
+            status,
+            team_id,
+        } = request.into_inner();
+        let project = insert_project(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            &NewProject {
+                name,
+                description,
+                status,
+                team_id,
+            },
+        )
+        .await?;
+        Ok(Response::new(project.into()))
+    }
+
+    async fn update_project(
+        &self,
+        request: Request<UpdateProjectRequest>,
+    ) -> Result<Response<proto::Project>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let UpdateProjectRequest {
+            id,
+            version,
+            name,
+            description,
+            owner_id,
+            team_id,
+        } = request.into_inner();
+        let project = update_existing_project(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+            version.map(|version| vec![version]),
+            &ProjectUpdate {
+                name,
+                description,
+                status: None,
+                owner_id,
+                team_id,
+            },
+        )
+        .await?;
+        Ok(Response::new(project.into()))
+    }
+
+    async fn delete_project(
+        &self,
+        request: Request<DeleteProjectRequest>,
+    ) -> Result<Response<proto::Project>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let DeleteProjectRequest { id, version } = request.into_inner();
+        let project = delete_existing_project(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+            version.map(|version| vec![version]),
+        )
+        .await?;
+        Ok(Response::new(project.into()))
+    }
+
+    async fn transition_project(
+        &self,
+        request: Request<TransitionProjectRequest>,
+    ) -> Result<Response<proto::ProjectTransition>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let TransitionProjectRequest {
+            id,
+            version,
+            to_status,
+        } = request.into_inner();
+        let transition = transition_project(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+            version.map(|version| vec![version]),
+            &to_status,
+        )
+        .await?;
+        Ok(Response::new(transition.into()))
+    }
+
+    async fn list_status_types(
+        &self,
+        request: Request<ListStatusTypesRequest>,
+    ) -> Result<Response<ListStatusTypesResponse>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let status_types = scope
+            .repositories
+            .status_types
+            .list()
+            .await
+            .map_err(NinoverseApiError::from)?
+            .into_iter()
+            .map(proto::StatusType::from)
+            .collect();
+        Ok(Response::new(ListStatusTypesResponse { status_types }))
+    }
+
+    async fn create_status_type(
+        &self,
+        request: Request<CreateStatusTypeRequest>,
+    ) -> Result<Response<proto::StatusType>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let CreateStatusTypeRequest { name, completed } = request.into_inner();
+        let status_type = insert_status_type(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            &NewStatusType { name, completed },
+        )
+        .await?;
+        Ok(Response::new(status_type.into()))
+    }
+
+    async fn update_status_type(
+        &self,
+        request: Request<UpdateStatusTypeRequest>,
+    ) -> Result<Response<proto::StatusType>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let UpdateStatusTypeRequest {
+            id,
+            name,
+            completed,
+        } = request.into_inner();
+        let status_type = update_existing_status_type(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+            &StatusTypeUpdate { name, completed },
+        )
+        .await?;
+        Ok(Response::new(status_type.into()))
+    }
+
+    async fn delete_status_type(
+        &self,
+        request: Request<DeleteStatusTypeRequest>,
+    ) -> Result<Response<proto::StatusType>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let DeleteStatusTypeRequest { id } = request.into_inner();
+        let status_type = delete_existing_status_type(
+            &scope.repositories,
+            &self.kafka_thread_sender,
+            &scope.context,
+            &scope.caller,
+            id,
+        )
+        .await?;
+        Ok(Response::new(status_type.into()))
+    }
+
+    async fn list_status_transitions(
+        &self,
+        request: Request<ListStatusTransitionsRequest>,
+    ) -> Result<Response<ListStatusTransitionsResponse>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let status_transitions = scope
+            .repositories
+            .status_types
+            .list_transitions()
+            .await
+            .map_err(NinoverseApiError::from)?
+            .into_iter()
+            .map(proto::StatusTransition::from)
+            .collect();
+        Ok(Response::new(ListStatusTransitionsResponse {
+            status_transitions,
+        }))
+    }
+
+    type StreamProjectEventsStream = BoxStream<'static, Result<proto::ProjectEvent, Status>>;
+
+    async fn stream_project_events(
+        &self,
+        request: Request<StreamProjectEventsRequest>,
+    ) -> Result<Response<Self::StreamProjectEventsStream>, Status> {
+        let scope = self.scope(request.metadata()).await?;
+        let StreamProjectEventsRequest {
+            projects,
+            tags,
+            last_event_id,
+        } = request.into_inner();
+        let receiver = self.live_feed.subscribe(
+            &scope.tenant_id,
+            scope.caller,
+            LiveSubscription {
+                project_ids: projects.into_iter().collect(),
+                tags: tags.into_iter().collect(),
+            },
+            last_event_id.as_deref(),
+        );
+        let events = futures::stream::unfold(receiver, |mut receiver| async move {
+            let message = receiver.next().await?;
+            Some((Ok(proto::ProjectEvent::from(message)), receiver))
+        });
+        Ok(Response::new(events.boxed()))
+    }
+}
@@ -0,0 +1,34 @@
+use rdkafka::error::KafkaError;
+
+use crate::db_handler::error::NinoverseDbError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NinoverseKafkaError {
+    #[error("KAFKA_HANDLER: Error talking to the broker.")]
+    BrokerError { additional_info: String },
+    #[error("KAFKA_HANDLER: Unknown topic.")]
+    UnknownTopic { additional_info: String },
+    #[error("KAFKA_HANDLER: Invalid topic request.")]
+    InvalidRequest { additional_info: String },
+    #[error("KAFKA_HANDLER: Error querying the database.")]
+    DatabaseError { additional_info: String },
+}
+
+impl From<NinoverseDbError> for NinoverseKafkaError {
+    fn from(error: NinoverseDbError) -> Self {
+        NinoverseKafkaError::DatabaseError {
+            additional_info: match error {
+                NinoverseDbError::QueryError { additional_info } => additional_info,
+                other => other.to_string(),
+            },
+        }
+    }
+}
+
+impl From<KafkaError> for NinoverseKafkaError {
+    fn from(error: KafkaError) -> Self {
+        NinoverseKafkaError::BrokerError {
+            additional_info: error.to_string(),
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::{BTreeMap, HashMap, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use chrono::Utc;
+use futures::{
+    StreamExt,
+    future::{BoxFuture, ready},
+};
+use regex::Regex;
+use tokio::sync::Notify;
+
+use super::{
+    error::NinoverseKafkaError,
+    message_bus::{
+        BusMessage, BusMessageStream, MessageBus, MessageBusResult, PartitionDescription,
+        TopicDescription, TopicSettings,
+    },
+};
+
+/// A topic a consumer reads by name or, starting with `^`, by a pattern that
+/// also matches the topics created after subscribing, as in Kafka.
+enum TopicSelector {
+    Name(String),
+    Pattern(Regex),
+}
+
+impl TopicSelector {
+    fn parse(topic: &str) -> MessageBusResult<TopicSelector> {
+        if !topic.starts_with('^') {
+            return Ok(TopicSelector::Name(topic.to_string()));
+        }
+        Regex::new(topic)
+            .map(TopicSelector::Pattern)
+            .map_err(|regex_error| NinoverseKafkaError::InvalidRequest {
+                additional_info: format!("Invalid topic pattern {}: {}", topic, regex_error),
+            })
+    }
+}
+
+#[derive(Default)]
+struct InMemoryBusState {
+    /// Topic name to partitions, each partition is its log of messages.
+    topics: HashMap<String, Vec<Vec<BusMessage>>>,
+    /// Configs set on the topics, they have no effect.
+    topic_configs: HashMap<String, BTreeMap<String, String>>,
+    /// Next offset to deliver per consumer group, topic and partition.
+    group_offsets: HashMap<(String, String, i32), i64>,
+}
+
+impl InMemoryBusState {
+    fn ensure_topic(&mut self, topic: &str, partitions: i32) -> &mut Vec<Vec<BusMessage>> {
+        self.topics
+            .entry(topic.to_string())
+            .or_insert_with(|| vec![Vec::new(); partitions.max(1) as usize])
+    }
+
+    /// Takes the next undelivered message of the group, committing its offset.
+    fn next_message(&mut self, group_id: &str, selectors: &[TopicSelector]) -> Option<BusMessage> {
+        let mut topics = vec![];
+        for selector in selectors {
+            match selector {
+                TopicSelector::Name(topic) => topics.push(topic.clone()),
+                TopicSelector::Pattern(pattern) => {
+                    let mut matching: Vec<String> = self
+                        .topics
+                        .keys()
+                        .filter(|topic| pattern.is_match(topic))
+                        .cloned()
+                        .collect();
+                    matching.sort();
+                    topics.extend(matching);
+                }
+            }
+        }
+        for topic in &topics {
+            let Some(partitions) = self.topics.get(topic) else {
+                continue;
+            };
+            for (partition, log) in partitions.iter().enumerate() {
+                let offset_key = (group_id.to_string(), topic.clone(), partition as i32);
+                let offset = self.group_offsets.get(&offset_key).copied().unwrap_or(0);
+                if let Some(message) = log.get(offset as usize) {
+                    let message = message.clone();
+                    self.group_offsets.insert(offset_key, offset + 1);
+                    return Some(message);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[derive(Default)]
+struct InMemoryBusShared {
+    state: Mutex<InMemoryBusState>,
+    published: Notify,
+}
+
+impl InMemoryBusShared {
+    fn state(&self) -> MutexGuard<'_, InMemoryBusState> {
+        self.state
+            .lock()
+            .expect("IN_MEMORY_BUS: State lock poisoned")
+    }
+}
+
+/// Keeps topics in process memory with Kafka-like semantics: messages are
+/// spread over partitions by key, every consumer group tracks its own offsets
+/// and consumers of the same group share the messages. Meant for tests and
+/// running without a broker, nothing survives a restart.
+#[derive(Clone, Default)]
+pub struct InMemoryMessageBus {
+    shared: Arc<InMemoryBusShared>,
+}
+
+impl InMemoryMessageBus {
+    pub fn new() -> Self {
+        InMemoryMessageBus::default()
+    }
+
+    /// Messages of `topic` not yet delivered to `group_id`.
+    #[cfg(test)]
+    pub fn pending_messages(&self, group_id: &str, topic: &str) -> usize {
+        let state = self.shared.state();
+        state.topics.get(topic).map_or(0, |partitions| {
+            partitions
+                .iter()
+                .enumerate()
+                .map(|(partition, log)| {
+                    let offset_key = (group_id.to_string(), topic.to_string(), partition as i32);
+                    let offset = state.group_offsets.get(&offset_key).copied().unwrap_or(0);
+                    log.len() - offset as usize
+                })
+                .sum()
+        })
+    }
+}
+
+/// A single broker, `0`, leads every partition.
+fn topic_description(
+    name: &str,
+    partitions: &[Vec<BusMessage>],
+    configs: BTreeMap<String, String>,
+) -> TopicDescription {
+    TopicDescription {
+        name: name.to_string(),
+        partitions: (0..partitions.len() as i32)
+            .map(|id| PartitionDescription {
+                id,
+                leader: 0,
+                replicas: vec![0],
+                in_sync_replicas: vec![0],
+            })
+            .collect(),
+        configs,
+    }
+}
+
+fn invalid_request(additional_info: String) -> NinoverseKafkaError {
+    NinoverseKafkaError::InvalidRequest { additional_info }
+}
+
+fn unknown_topic(topic: &str) -> NinoverseKafkaError {
+    NinoverseKafkaError::UnknownTopic {
+        additional_info: format!("Topic {} does not exist.", topic),
+    }
+}
+
+fn partition_for_key(key: &str, partitions: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % partitions as u64) as usize
+}
+
+impl MessageBus for InMemoryMessageBus {
+    fn create_topic<'a>(
+        &'a self,
+        topic: &'a str,
+        settings: &'a TopicSettings,
+    ) -> BoxFuture<'a, MessageBusResult<bool>> {
+        if settings.partitions < 1 {
+            return Box::pin(ready(Err(invalid_request(format!(
+                "Topic {} needs at least one partition.",
+                topic
+            )))));
+        }
+        // Like a cluster of a single broker.
+        if settings.replication != 1 {
+            return Box::pin(ready(Err(invalid_request(format!(
+                "Topic {} can only have a replication factor of 1.",
+                topic
+            )))));
+        }
+        let mut state = self.shared.state();
+        if state.topics.contains_key(topic) {
+            return Box::pin(ready(Ok(false)));
+        }
+        state.ensure_topic(topic, settings.partitions);
+        state
+            .topic_configs
+            .insert(topic.to_string(), settings.configs.clone());
+        println!("TOPIC_CREATION: Created in-memory topic {}", topic);
+        Box::pin(ready(Ok(true)))
+    }
+
+    fn list_topics(&self) -> BoxFuture<'_, MessageBusResult<Vec<TopicDescription>>> {
+        let mut topics: Vec<TopicDescription> = self
+            .shared
+            .state()
+            .topics
+            .iter()
+            .map(|(name, partitions)| topic_description(name, partitions, BTreeMap::new()))
+            .collect();
+        topics.sort_by(|first, second| first.name.cmp(&second.name));
+        Box::pin(ready(Ok(topics)))
+    }
+
+    fn describe_topic<'a>(
+        &'a self,
+        topic: &'a str,
+    ) -> BoxFuture<'a, MessageBusResult<TopicDescription>> {
+        let state = self.shared.state();
+        let description = state
+            .topics
+            .get(topic)
+            .map(|partitions| {
+                let configs = state.topic_configs.get(topic).cloned().unwrap_or_default();
+                topic_description(topic, partitions, configs)
+            })
+            .ok_or_else(|| unknown_topic(topic));
+        Box::pin(ready(description))
+    }
+
+    fn alter_topic_configs<'a>(
+        &'a self,
+        topic: &'a str,
+        configs: &'a BTreeMap<String, String>,
+    ) -> BoxFuture<'a, MessageBusResult<()>> {
+        let mut state = self.shared.state();
+        if !state.topics.contains_key(topic) {
+            return Box::pin(ready(Err(unknown_topic(topic))));
+        }
+        state
+            .topic_configs
+            .entry(topic.to_string())
+            .or_default()
+            .extend(configs.clone());
+        Box::pin(ready(Ok(())))
+    }
+
+    fn add_partitions<'a>(
+        &'a self,
+        topic: &'a str,
+        partitions: i32,
+    ) -> BoxFuture<'a, MessageBusResult<()>> {
+        let mut state = self.shared.state();
+        let Some(logs) = state.topics.get_mut(topic) else {
+            return Box::pin(ready(Err(unknown_topic(topic))));
+        };
+        if partitions <= logs.len() as i32 {
+            return Box::pin(ready(Err(invalid_request(format!(
+                "Topic {} already has {} partitions.",
+                topic,
+                logs.len()
+            )))));
+        }
+        logs.resize(partitions as usize, Vec::new());
+        Box::pin(ready(Ok(())))
+    }
+
+    fn delete_topics<'a>(&'a self, topics: &'a [String]) -> BoxFuture<'a, MessageBusResult<()>> {
+        let mut state = self.shared.state();
+        let mut deletion_error = None;
+        for topic in topics {
+            if state.topics.remove(topic).is_some() {
+                state.topic_configs.remove(topic);
+                state
+                    .group_offsets
+                    .retain(|(_, offset_topic, _), _| offset_topic != topic);
+                println!("TOPIC_DELETION: Deleted in-memory topic {}", topic);
+            } else {
+                deletion_error.get_or_insert(unknown_topic(topic));
+            }
+        }
+        Box::pin(ready(deletion_error.map_or(Ok(()), Err)))
+    }
+
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        key: &'a str,
+        payload: &'a str,
+    ) -> BoxFuture<'a, MessageBusResult<()>> {
+        {
+            let mut state = self.shared.state();
+            // Same as a broker with auto.create.topics.enable.
+            let partitions = state.ensure_topic(topic, 1);
+            let partition = partition_for_key(key, partitions.len());
+            let log = &mut partitions[partition];
+            log.push(BusMessage {
+                topic: topic.to_string(),
+                partition: partition as i32,
+                offset: log.len() as i64,
+                key: Some(key.to_string()),
+                payload: Some(payload.to_string()),
+                timestamp: Some(Utc::now().timestamp_millis()),
+            });
+        }
+        self.shared.published.notify_waiters();
+        Box::pin(ready(Ok(())))
+    }
+
+    fn subscribe(&self, group_id: &str, topics: &[&str]) -> MessageBusResult<BusMessageStream> {
+        if topics.is_empty() {
+            return Err(NinoverseKafkaError::UnknownTopic {
+                additional_info: "No topic to subscribe to.".to_string(),
+            });
+        }
+        let shared = self.shared.clone();
+        let group_id = group_id.to_string();
+        let topics = topics
+            .iter()
+            .map(|topic| TopicSelector::parse(topic))
+            .collect::<MessageBusResult<Vec<_>>>()?;
+        Ok(futures::stream::unfold(
+            (shared, group_id, topics),
+            |(shared, group_id, topics)| async move {
+                loop {
+                    let message = {
+                        // Registered before looking at the logs so a publish
+                        // in between is not missed.
+                        let published = shared.published.notified();
+                        tokio::pin!(published);
+                        published.as_mut().enable();
+                        let message = shared.state().next_message(&group_id, &topics);
+                        if message.is_none() {
+                            published.await;
+                        }
+                        message
+                    };
+                    if let Some(message) = message {
+                        return Some((Ok(message), (shared, group_id, topics)));
+                    }
+                }
+            },
+        )
+        .boxed())
+    }
+
+    fn read_after<'a>(
+        &'a self,
+        topic: &'a str,
+        partition: i32,
+        offset: i64,
+        limit: usize,
+    ) -> BoxFuture<'a, MessageBusResult<Vec<BusMessage>>> {
+        let state = self.shared.state();
+        let Some(partitions) = state.topics.get(topic) else {
+            return Box::pin(ready(Err(unknown_topic(topic))));
+        };
+        let Some(position) = partitions
+            .get(partition as usize)
+            .and_then(|log| log.get(offset as usize))
+        else {
+            return Box::pin(ready(Err(invalid_request(format!(
+                "Message {}:{}:{} does not exist.",
+                topic, partition, offset
+            )))));
+        };
+        let mut messages: Vec<BusMessage> = partitions
+            .iter()
+            .flatten()
+            .filter(|message| {
+                if message.partition == partition {
+                    message.offset > offset
+                } else {
+                    message.timestamp >= position.timestamp
+                }
+            })
+            .cloned()
+            .collect();
+        messages.sort_by_key(|message| message.timestamp);
+        messages.truncate(limit);
+        Box::pin(ready(Ok(messages)))
+    }
+}
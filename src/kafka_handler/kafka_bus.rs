@@ -0,0 +1,487 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use futures::{StreamExt, future::BoxFuture};
+use rdkafka::{
+    ClientConfig, Message, Offset, TopicPartitionList,
+    admin::{
+        AdminClient, AlterConfig, ConfigSource, NewPartitions, NewTopic, ResourceSpecifier,
+        TopicReplication,
+    },
+    consumer::{BaseConsumer, Consumer, StreamConsumer},
+    error::KafkaError,
+    message::OwnedMessage,
+    metadata::MetadataTopic,
+    producer::{FutureProducer, FutureRecord},
+    types::RDKafkaErrorCode,
+};
+
+use super::{
+    error::NinoverseKafkaError,
+    message_bus::{
+        BusMessage, BusMessageStream, MessageBus, MessageBusResult, PartitionDescription,
+        TopicDescription, TopicSettings,
+    },
+    structs::KafkaNinoverseBrokerContext,
+};
+use crate::configuration_handler::{get_kafka_admin_options, get_kafka_generic_broker};
+
+/// How long metadata requests wait for the broker.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long `read_after` may take to read up to the end of the partitions.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn create_kafka_consumer(
+    group_id: &str,
+    topics: &[&str],
+) -> Result<StreamConsumer, rdkafka::error::KafkaError> {
+    println!("CONSUMER_CREATION: Creating consumer.");
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("group.id", group_id)
+        .set("bootstrap.servers", get_kafka_generic_broker())
+        .set("enable.partition.eof", "false")
+        .set("session.timeout.ms", "6000")
+        .set("enable.auto.commit", "false")
+        // Topics matching a pattern subscription are found on metadata refresh.
+        .set("topic.metadata.refresh.interval.ms", "10000")
+        // .set("auto.offset.reset", "earliest")
+        .create()?;
+    println!(
+        "CONSUMER_CREATION: Created, subscribing to topics {}.",
+        topics.join(", ")
+    );
+    consumer.subscribe(topics)?;
+    println!(
+        "CONSUMER_CREATION: Subscribed to topics {}.",
+        topics.join(", ")
+    );
+    Ok(consumer)
+}
+
+/// A consumer assigned to partitions by hand, it never commits and its group
+/// is only there because the broker requires one.
+fn create_kafka_reader() -> Result<BaseConsumer, KafkaError> {
+    ClientConfig::new()
+        .set("group.id", "ninoverse-reader")
+        .set("bootstrap.servers", get_kafka_generic_broker())
+        .set("enable.partition.eof", "false")
+        .set("enable.auto.commit", "false")
+        .create()
+}
+
+fn read_timeout(topic: &str) -> NinoverseKafkaError {
+    NinoverseKafkaError::BrokerError {
+        additional_info: format!("Timed out reading topic {}.", topic),
+    }
+}
+
+/// See `MessageBus::read_after`. The consumer blocks, it runs off the async
+/// threads.
+fn read_partitions_after(
+    topic: &str,
+    partition: i32,
+    offset: i64,
+    limit: usize,
+) -> MessageBusResult<Vec<BusMessage>> {
+    let reader = create_kafka_reader()?;
+    let metadata = reader.fetch_metadata(Some(topic), METADATA_TIMEOUT)?;
+    let description = metadata
+        .topics()
+        .first()
+        .map(topic_description)
+        .transpose()?
+        .ok_or_else(|| topic_error(topic, RDKafkaErrorCode::UnknownTopic))?;
+
+    let mut position = TopicPartitionList::new();
+    position.add_partition_offset(topic, partition, Offset::Offset(offset))?;
+    reader.assign(&position)?;
+    let timestamp = match reader.poll(READ_TIMEOUT) {
+        Some(Ok(message)) if message.offset() == offset => message.timestamp().to_millis(),
+        Some(Err(kafka_error)) => return Err(kafka_error.into()),
+        _ => {
+            return Err(NinoverseKafkaError::InvalidRequest {
+                additional_info: format!(
+                    "Message {}:{}:{} does not exist.",
+                    topic, partition, offset
+                ),
+            });
+        }
+    };
+
+    let mut from_timestamp = TopicPartitionList::new();
+    for other in &description.partitions {
+        if other.id != partition {
+            from_timestamp.add_partition_offset(
+                topic,
+                other.id,
+                Offset::Offset(timestamp.unwrap_or_default()),
+            )?;
+        }
+    }
+    let mut start = if from_timestamp.count() > 0 {
+        reader.offsets_for_times(from_timestamp, METADATA_TIMEOUT)?
+    } else {
+        from_timestamp
+    };
+    start.add_partition_offset(topic, partition, Offset::Offset(offset + 1))?;
+
+    // The end of every partition with something left to read.
+    let mut ends = HashMap::new();
+    for element in start.elements() {
+        let (_, high) = reader.fetch_watermarks(topic, element.partition(), METADATA_TIMEOUT)?;
+        if let Offset::Offset(first) = element.offset()
+            && first < high
+        {
+            ends.insert(element.partition(), high);
+        }
+    }
+    reader.assign(&start)?;
+    let deadline = Instant::now() + READ_TIMEOUT;
+    let mut messages = vec![];
+    while !ends.is_empty() && messages.len() < limit {
+        let remaining = deadline
+            .checked_duration_since(Instant::now())
+            .ok_or_else(|| read_timeout(topic))?;
+        let message = reader
+            .poll(remaining)
+            .ok_or_else(|| read_timeout(topic))??;
+        let Some(end) = ends.get(&message.partition()).copied() else {
+            continue;
+        };
+        if message.offset() + 1 >= end {
+            ends.remove(&message.partition());
+        }
+        if message.offset() < end {
+            messages.push(BusMessage::from(message.detach()));
+        }
+    }
+    messages.sort_by_key(|message| message.timestamp);
+    Ok(messages)
+}
+
+fn create_kafka_producer() -> FutureProducer {
+    println!("PRODUCER_CREATION: Creating producer.");
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", get_kafka_generic_broker())
+        .set("message.timeout.ms", "5000")
+        .create()
+        .expect("PRODUCER_CREATION: Producer creation error");
+    println!("PRODUCER_CREATION: Producer created.");
+    producer
+}
+
+fn create_kafka_admin_client() -> Result<AdminClient<KafkaNinoverseBrokerContext>, KafkaError> {
+    println!("ADMIN_CLIENT_CREATION: Creating admin client.");
+    let admin_client: AdminClient<KafkaNinoverseBrokerContext> = ClientConfig::new()
+        .set("bootstrap.servers", get_kafka_generic_broker())
+        .create_with_context(KafkaNinoverseBrokerContext {})?;
+    println!("ADMIN_CLIENT_CREATION: Created Admin client.");
+    Ok(admin_client)
+}
+
+fn topic_error(topic: &str, code: RDKafkaErrorCode) -> NinoverseKafkaError {
+    match code {
+        RDKafkaErrorCode::UnknownTopicOrPartition | RDKafkaErrorCode::UnknownTopic => {
+            NinoverseKafkaError::UnknownTopic {
+                additional_info: format!("Topic {} does not exist.", topic),
+            }
+        }
+        RDKafkaErrorCode::InvalidTopic
+        | RDKafkaErrorCode::InvalidPartitions
+        | RDKafkaErrorCode::InvalidReplicationFactor
+        | RDKafkaErrorCode::InvalidConfig
+        | RDKafkaErrorCode::InvalidRequest
+        | RDKafkaErrorCode::PolicyViolation => NinoverseKafkaError::InvalidRequest {
+            additional_info: format!("Topic {}: {}.", topic, code),
+        },
+        code => NinoverseKafkaError::BrokerError {
+            additional_info: format!("Topic {}: {}.", topic, code),
+        },
+    }
+}
+
+fn topic_description(topic: &MetadataTopic) -> MessageBusResult<TopicDescription> {
+    if let Some(error) = topic.error() {
+        return Err(topic_error(topic.name(), error.into()));
+    }
+    Ok(TopicDescription {
+        name: topic.name().to_string(),
+        partitions: topic
+            .partitions()
+            .iter()
+            .map(|partition| PartitionDescription {
+                id: partition.id(),
+                leader: partition.leader(),
+                replicas: partition.replicas().to_vec(),
+                in_sync_replicas: partition.isr().to_vec(),
+            })
+            .collect(),
+        configs: BTreeMap::new(),
+    })
+}
+
+/// Every topic, or only `topic`. `fetch_metadata` blocks, it runs off the
+/// async threads.
+async fn fetch_topics(topic: Option<String>) -> MessageBusResult<Vec<TopicDescription>> {
+    let admin_client = create_kafka_admin_client()?;
+    tokio::task::spawn_blocking(move || {
+        admin_client
+            .inner()
+            .fetch_metadata(topic.as_deref(), METADATA_TIMEOUT)?
+            .topics()
+            .iter()
+            .filter(|topic| !topic.name().starts_with("__"))
+            .map(topic_description)
+            .collect()
+    })
+    .await
+    .map_err(|join_error| NinoverseKafkaError::BrokerError {
+        additional_info: join_error.to_string(),
+    })?
+}
+
+fn bytes_to_string(bytes: Option<&[u8]>, field: &str) -> Option<String> {
+    bytes.map(|bytes| {
+        String::from_utf8(bytes.to_vec())
+            .unwrap_or_else(|_| format!("MESSAGE: Invalid UTF-8 array for {} value.", field))
+    })
+}
+
+impl From<OwnedMessage> for BusMessage {
+    fn from(message: OwnedMessage) -> Self {
+        BusMessage {
+            topic: message.topic().to_string(),
+            partition: message.partition(),
+            offset: message.offset(),
+            key: bytes_to_string(message.key(), "key"),
+            payload: bytes_to_string(message.payload(), "payload"),
+            timestamp: message.timestamp().to_millis(),
+        }
+    }
+}
+
+/// The real broker at `get_kafka_generic_broker()`.
+pub struct KafkaMessageBus {
+    producer: FutureProducer,
+}
+
+impl KafkaMessageBus {
+    pub fn new() -> Self {
+        KafkaMessageBus {
+            producer: create_kafka_producer(),
+        }
+    }
+}
+
+impl Default for KafkaMessageBus {
+    fn default() -> Self {
+        KafkaMessageBus::new()
+    }
+}
+
+impl MessageBus for KafkaMessageBus {
+    fn create_topic<'a>(
+        &'a self,
+        topic: &'a str,
+        settings: &'a TopicSettings,
+    ) -> BoxFuture<'a, MessageBusResult<bool>> {
+        Box::pin(async move {
+            let admin_client = create_kafka_admin_client()?;
+            let new_topic = NewTopic {
+                name: topic,
+                num_partitions: settings.partitions,
+                replication: TopicReplication::Fixed(settings.replication),
+                config: settings
+                    .configs
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+                    .collect(),
+            };
+            println!("TOPIC_CREATION: Sending request to Kafka Admin Client");
+            let creation_result = admin_client
+                .create_topics(&[new_topic], &get_kafka_admin_options())
+                .await?
+                .pop();
+            match creation_result {
+                Some(Ok(_)) => Ok(true),
+                Some(Err((_, RDKafkaErrorCode::TopicAlreadyExists))) => Ok(false),
+                Some(Err((_, code))) => Err(topic_error(topic, code)),
+                None => Err(NinoverseKafkaError::BrokerError {
+                    additional_info: format!("No creation result for topic {}.", topic),
+                }),
+            }
+        })
+    }
+
+    fn list_topics(&self) -> BoxFuture<'_, MessageBusResult<Vec<TopicDescription>>> {
+        Box::pin(fetch_topics(None))
+    }
+
+    fn describe_topic<'a>(
+        &'a self,
+        topic: &'a str,
+    ) -> BoxFuture<'a, MessageBusResult<TopicDescription>> {
+        Box::pin(async move {
+            let mut description = fetch_topics(Some(topic.to_string()))
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| topic_error(topic, RDKafkaErrorCode::UnknownTopicOrPartition))?;
+            let admin_client = create_kafka_admin_client()?;
+            for resource in admin_client
+                .describe_configs(
+                    &[ResourceSpecifier::Topic(topic)],
+                    &get_kafka_admin_options(),
+                )
+                .await?
+            {
+                let resource = resource.map_err(|code| topic_error(topic, code))?;
+                description.configs.extend(
+                    resource
+                        .entries
+                        .into_iter()
+                        .filter(|entry| !entry.is_sensitive)
+                        .filter_map(|entry| Some((entry.name, entry.value?))),
+                );
+            }
+            Ok(description)
+        })
+    }
+
+    /// Kafka replaces every config of the topic on alter, the configs it
+    /// already set are sent along. Sensitive ones can't be read back and are
+    /// reset.
+    fn alter_topic_configs<'a>(
+        &'a self,
+        topic: &'a str,
+        configs: &'a BTreeMap<String, String>,
+    ) -> BoxFuture<'a, MessageBusResult<()>> {
+        Box::pin(async move {
+            let admin_client = create_kafka_admin_client()?;
+            let options = get_kafka_admin_options();
+            let mut merged_configs = BTreeMap::new();
+            for resource in admin_client
+                .describe_configs(&[ResourceSpecifier::Topic(topic)], &options)
+                .await?
+            {
+                let resource = resource.map_err(|code| topic_error(topic, code))?;
+                merged_configs.extend(
+                    resource
+                        .entries
+                        .into_iter()
+                        .filter(|entry| entry.source == ConfigSource::DynamicTopic)
+                        .filter_map(|entry| Some((entry.name, entry.value?))),
+                );
+            }
+            merged_configs.extend(configs.clone());
+            let alter_config = merged_configs.iter().fold(
+                AlterConfig::new(ResourceSpecifier::Topic(topic)),
+                |alter_config, (name, value)| alter_config.set(name, value),
+            );
+            for alter_result in admin_client
+                .alter_configs(&[alter_config], &options)
+                .await?
+            {
+                alter_result.map_err(|(_, code)| topic_error(topic, code))?;
+            }
+            Ok(())
+        })
+    }
+
+    fn add_partitions<'a>(
+        &'a self,
+        topic: &'a str,
+        partitions: i32,
+    ) -> BoxFuture<'a, MessageBusResult<()>> {
+        Box::pin(async move {
+            let partitions =
+                usize::try_from(partitions).map_err(|_| NinoverseKafkaError::InvalidRequest {
+                    additional_info: format!("Invalid partition count {}.", partitions),
+                })?;
+            let admin_client = create_kafka_admin_client()?;
+            for partitions_result in admin_client
+                .create_partitions(
+                    &[NewPartitions::new(topic, partitions)],
+                    &get_kafka_admin_options(),
+                )
+                .await?
+            {
+                partitions_result.map_err(|(_, code)| topic_error(topic, code))?;
+            }
+            Ok(())
+        })
+    }
+
+    fn delete_topics<'a>(&'a self, topics: &'a [String]) -> BoxFuture<'a, MessageBusResult<()>> {
+        Box::pin(async move {
+            let admin_client = create_kafka_admin_client()?;
+            let names: Vec<&str> = topics.iter().map(String::as_str).collect();
+            let mut deletion_error = None;
+            for deletion_result in admin_client
+                .delete_topics(&names, &get_kafka_admin_options())
+                .await?
+            {
+                match deletion_result {
+                    Ok(topic) => println!("TOPIC_DELETION: Deleted {}", topic),
+                    Err((topic, code)) => {
+                        println!("TOPIC_DELETION: Error deleting {}: {}", topic, code);
+                        deletion_error.get_or_insert(topic_error(&topic, code));
+                    }
+                }
+            }
+            deletion_error.map_or(Ok(()), Err)
+        })
+    }
+
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        key: &'a str,
+        payload: &'a str,
+    ) -> BoxFuture<'a, MessageBusResult<()>> {
+        Box::pin(async move {
+            let queue_timeout = std::time::Duration::from_secs(1);
+            let record = FutureRecord::to(topic).payload(payload).key(key);
+            self.producer
+                .send(record, queue_timeout)
+                .await
+                .map_err(|(kafka_error, _)| kafka_error)?;
+            Ok(())
+        })
+    }
+
+    fn subscribe(&self, group_id: &str, topics: &[&str]) -> MessageBusResult<BusMessageStream> {
+        let consumer = create_kafka_consumer(group_id, topics)?;
+        // The stream has to own the consumer, `StreamConsumer::stream` borrows it.
+        Ok(futures::stream::unfold(consumer, |consumer| async move {
+            let message = consumer
+                .recv()
+                .await
+                .map(|message| BusMessage::from(message.detach()))
+                .map_err(Into::into);
+            Some((message, consumer))
+        })
+        .boxed())
+    }
+
+    fn read_after<'a>(
+        &'a self,
+        topic: &'a str,
+        partition: i32,
+        offset: i64,
+        limit: usize,
+    ) -> BoxFuture<'a, MessageBusResult<Vec<BusMessage>>> {
+        let topic = topic.to_string();
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || {
+                read_partitions_after(&topic, partition, offset, limit)
+            })
+            .await
+            .map_err(|join_error| NinoverseKafkaError::BrokerError {
+                additional_info: join_error.to_string(),
+            })?
+        })
+    }
+}
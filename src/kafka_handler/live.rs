@@ -0,0 +1,335 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use serde::Deserialize;
+use tokio::sync::{broadcast, watch};
+use utoipa::IntoParams;
+
+use super::{
+    events_topic, message_bus::MessageBus, message_live_event, structs::KafkaNinoverseEvent,
+};
+use crate::{
+    db_handler::{repository::Repositories, structs::Project},
+    permission_handler::Caller,
+};
+
+/// How many events a slow client may fall behind before it is sent a reset.
+const CHANNEL_CAPACITY: usize = 256;
+/// Most messages read back from the history for a reconnecting client, one
+/// further behind is sent a reset instead.
+const MAX_REPLAYED_MESSAGES: usize = 10_000;
+
+/// An event accepted by the consumer, with what the subscriptions and the
+/// access checks need to know about its project.
+#[derive(Debug, Clone)]
+pub struct LiveEvent {
+    /// The position of the Kafka message, `topic:partition:offset`.
+    pub id: String,
+    pub tenant_id: String,
+    pub project: Project,
+    /// Names of the tags of the project and, for task events, of the task.
+    pub tags: BTreeSet<String>,
+    pub event: KafkaNinoverseEvent,
+}
+
+/// The topic, partition and offset of an event id, `None` for ids not
+/// issued by the consumer.
+fn event_position(id: &str) -> Option<(String, i32, i64)> {
+    let (rest, offset) = id.rsplit_once(':')?;
+    let (topic, partition) = rest.rsplit_once(':')?;
+    Some((
+        topic.to_string(),
+        partition.parse().ok()?,
+        offset.parse().ok()?,
+    ))
+}
+
+/// Where the events that are no longer among the recent ones are read back
+/// from, as the consumer would process them.
+#[derive(Clone)]
+pub struct LiveHistory {
+    pub repositories: Repositories,
+    pub message_bus: Arc<dyn MessageBus>,
+    pub auth_enabled: bool,
+}
+
+/// What a client of the live endpoints receives next.
+#[derive(Debug, Clone)]
+pub enum LiveMessage {
+    Event(Arc<LiveEvent>),
+    /// Events were missed, the client has to reload what it shows.
+    Reset,
+}
+
+/// The projects and tags a client follows, everything it can read when both
+/// are empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveSubscription {
+    pub project_ids: BTreeSet<i32>,
+    pub tags: BTreeSet<String>,
+}
+
+impl LiveSubscription {
+    fn matches(&self, event: &LiveEvent) -> bool {
+        (self.project_ids.is_empty() && self.tags.is_empty())
+            || self.project_ids.contains(&event.project.id)
+            || !self.tags.is_disjoint(&event.tags)
+    }
+
+    pub fn add(&mut self, other: LiveSubscription) {
+        self.project_ids.extend(other.project_ids);
+        self.tags.extend(other.tags);
+    }
+
+    pub fn remove(&mut self, other: &LiveSubscription) {
+        self.project_ids
+            .retain(|id| !other.project_ids.contains(id));
+        self.tags.retain(|tag| !other.tags.contains(tag));
+    }
+}
+
+/// The query of the live endpoints: comma separated project ids and tag
+/// names, and the id of the last event received for clients that can't send
+/// a `Last-Event-ID` header.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+pub struct LiveQuery {
+    pub projects: Option<String>,
+    pub tags: Option<String>,
+    pub last_event_id: Option<String>,
+}
+
+impl LiveQuery {
+    /// Ids that are not numbers are ignored.
+    pub fn subscription(&self) -> LiveSubscription {
+        let values = |list: &Option<String>| -> Vec<String> {
+            list.as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        LiveSubscription {
+            project_ids: values(&self.projects)
+                .iter()
+                .filter_map(|id| id.parse().ok())
+                .collect(),
+            tags: values(&self.tags).into_iter().collect(),
+        }
+    }
+}
+
+/// One client of the feed. Events of other tenants, of projects the caller
+/// can't read or outside of the subscription are skipped.
+pub struct LiveReceiver {
+    replay: VecDeque<LiveMessage>,
+    /// Read on the first `next`, when the client resumes from an event the
+    /// feed no longer holds.
+    history: Option<(LiveHistory, String, i32, i64)>,
+    /// Last offset read from the history per topic and partition, the
+    /// channel delivers these events as well.
+    replayed: HashMap<(String, i32), i64>,
+    receiver: broadcast::Receiver<Arc<LiveEvent>>,
+    closed: watch::Receiver<bool>,
+    tenant_id: String,
+    caller: Caller,
+    pub subscription: LiveSubscription,
+}
+
+impl LiveReceiver {
+    fn visible(&self, event: &LiveEvent) -> bool {
+        event.tenant_id == self.tenant_id
+            && self.caller.project_role(&event.project).is_some()
+            && self.subscription.matches(event)
+    }
+
+    fn replayed(&self, event: &LiveEvent) -> bool {
+        !self.replayed.is_empty()
+            && event_position(&event.id).is_some_and(|(topic, partition, offset)| {
+                self.replayed
+                    .get(&(topic, partition))
+                    .is_some_and(|replayed| offset <= *replayed)
+            })
+    }
+
+    /// The events after the position, a reset when they can't be read or
+    /// there are too many of them.
+    async fn read_history(
+        &mut self,
+        history: &LiveHistory,
+        topic: &str,
+        partition: i32,
+        offset: i64,
+    ) -> VecDeque<LiveMessage> {
+        let messages = match history
+            .message_bus
+            .read_after(topic, partition, offset, MAX_REPLAYED_MESSAGES + 1)
+            .await
+        {
+            Ok(messages) if messages.len() > MAX_REPLAYED_MESSAGES => {
+                println!(
+                    "LIVE_FEED: Events after {}:{}:{} not replayed, more than {}",
+                    topic, partition, offset, MAX_REPLAYED_MESSAGES
+                );
+                return VecDeque::from([LiveMessage::Reset]);
+            }
+            Ok(messages) => messages,
+            Err(bus_error) => {
+                println!(
+                    "LIVE_FEED: Events after {}:{}:{} not replayed: {:?}",
+                    topic, partition, offset, bus_error
+                );
+                return VecDeque::from([LiveMessage::Reset]);
+            }
+        };
+        let mut replay = VecDeque::new();
+        for message in messages {
+            let replayed = self
+                .replayed
+                .entry((message.topic.clone(), message.partition))
+                .or_insert(message.offset);
+            *replayed = (*replayed).max(message.offset);
+            if let Some(event) =
+                message_live_event(&history.repositories, history.auth_enabled, &message).await
+            {
+                replay.push_back(LiveMessage::Event(Arc::new(event)));
+            }
+        }
+        replay
+    }
+
+    /// `None` once the feed is closed.
+    pub async fn next(&mut self) -> Option<LiveMessage> {
+        if let Some((history, topic, partition, offset)) = self.history.take() {
+            self.replay = self.read_history(&history, &topic, partition, offset).await;
+        }
+        while let Some(message) = self.replay.pop_front() {
+            match &message {
+                LiveMessage::Event(event) if !self.visible(event) => continue,
+                _ => return Some(message),
+            }
+        }
+        loop {
+            if *self.closed.borrow() {
+                return None;
+            }
+            tokio::select! {
+                received = self.receiver.recv() => match received {
+                    Ok(event) if self.replayed(&event) => continue,
+                    Ok(event) if self.visible(&event) => return Some(LiveMessage::Event(event)),
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(_)) => return Some(LiveMessage::Reset),
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                _ = self.closed.changed() => return None,
+            }
+        }
+    }
+}
+
+/// Fans the events processed by the consumer out to the live endpoints and
+/// keeps the most recent ones for clients reconnecting with the id of the
+/// last event they received. Older events are read back from the history,
+/// when there is one.
+pub struct LiveFeed {
+    sender: broadcast::Sender<Arc<LiveEvent>>,
+    recent: Mutex<VecDeque<Arc<LiveEvent>>>,
+    replay_size: usize,
+    history: Option<LiveHistory>,
+    closed: watch::Sender<bool>,
+}
+
+impl LiveFeed {
+    pub fn new(replay_size: usize) -> Self {
+        LiveFeed {
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+            recent: Mutex::new(VecDeque::with_capacity(replay_size)),
+            replay_size,
+            history: None,
+            closed: watch::channel(false).0,
+        }
+    }
+
+    pub fn with_history(mut self, history: LiveHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    fn recent(&self) -> MutexGuard<'_, VecDeque<Arc<LiveEvent>>> {
+        self.recent.lock().expect("LIVE_FEED: Lock poisoned")
+    }
+
+    pub fn publish(&self, event: LiveEvent) {
+        let event = Arc::new(event);
+        // Sent with the lock held, a client subscribing meanwhile gets the
+        // event either in its replay or from the channel, never both.
+        let mut recent = self.recent();
+        if self.replay_size > 0 {
+            if recent.len() == self.replay_size {
+                recent.pop_front();
+            }
+            recent.push_back(event.clone());
+        }
+        let _ = self.sender.send(event);
+    }
+
+    /// Starts after `last_event_id`, from the recent events when it is still
+    /// among them and from the history otherwise, only from the events topic
+    /// of the tenant. Starts with a reset when neither has it.
+    pub fn subscribe(
+        &self,
+        tenant_id: &str,
+        caller: Caller,
+        subscription: LiveSubscription,
+        last_event_id: Option<&str>,
+    ) -> LiveReceiver {
+        let recent = self.recent();
+        let mut history = None;
+        let replay = match last_event_id {
+            None => VecDeque::new(),
+            Some(last_event_id) => {
+                match recent.iter().position(|event| event.id == last_event_id) {
+                    Some(position) => recent
+                        .iter()
+                        .skip(position + 1)
+                        .cloned()
+                        .map(LiveMessage::Event)
+                        .collect(),
+                    None => match (&self.history, event_position(last_event_id)) {
+                        (Some(feed_history), Some((topic, partition, offset)))
+                            if topic == events_topic(tenant_id) =>
+                        {
+                            history = Some((feed_history.clone(), topic, partition, offset));
+                            VecDeque::new()
+                        }
+                        _ => VecDeque::from([LiveMessage::Reset]),
+                    },
+                }
+            }
+        };
+        LiveReceiver {
+            replay,
+            history,
+            replayed: HashMap::new(),
+            receiver: self.sender.subscribe(),
+            closed: self.closed.subscribe(),
+            tenant_id: tenant_id.to_string(),
+            caller,
+            subscription,
+        }
+    }
+
+    /// Ends every live connection, for the server to shut down.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Resolves once the feed is closed, for connections that outlive their
+    /// subscriptions.
+    pub async fn closed(&self) {
+        let _ = self.closed.subscribe().wait_for(|closed| *closed).await;
+    }
+}
@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use futures::{future::BoxFuture, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    error::NinoverseKafkaError, in_memory::InMemoryMessageBus, kafka_bus::KafkaMessageBus,
+};
+use crate::configuration_handler::{MessageBusBackend, get_message_bus_backend};
+
+pub type MessageBusResult<T> = Result<T, NinoverseKafkaError>;
+
+/// A consumed message, detached from the backend that delivered it.
+#[derive(Debug, Clone)]
+pub struct BusMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<String>,
+    pub payload: Option<String>,
+    /// Milliseconds since the epoch.
+    pub timestamp: Option<i64>,
+}
+
+pub type BusMessageStream = BoxStream<'static, MessageBusResult<BusMessage>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartitionDescription {
+    pub id: i32,
+    pub leader: i32,
+    pub replicas: Vec<i32>,
+    pub in_sync_replicas: Vec<i32>,
+}
+
+/// A topic as the broker reports it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TopicDescription {
+    pub name: String,
+    pub partitions: Vec<PartitionDescription>,
+    /// Only read by `describe_topic`, sensitive values are left out.
+    pub configs: BTreeMap<String, String>,
+}
+
+fn default_topic_count() -> i32 {
+    1
+}
+
+/// What a topic is created with, or checked against when it already exists.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TopicSettings {
+    #[serde(default = "default_topic_count")]
+    pub partitions: i32,
+    #[serde(default = "default_topic_count")]
+    pub replication: i32,
+    /// Topic configs such as `retention.ms`, the others keep the broker
+    /// defaults.
+    #[serde(default)]
+    pub configs: BTreeMap<String, String>,
+}
+
+/// The new partition count of a topic, partitions can only be added.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartitionCount {
+    pub partitions: i32,
+}
+
+/// A setting of an existing topic that differs from the requested one.
+/// `setting` is `partitions`, `replication` or a config name.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TopicDrift {
+    pub setting: String,
+    pub expected: String,
+    /// `None` for a config the topic doesn't report.
+    pub actual: Option<String>,
+}
+
+/// The outcome of `ensure_topic`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EnsuredTopic {
+    /// `false` when the topic already existed.
+    pub created: bool,
+    pub topic: TopicDescription,
+    /// Empty when the topic matches the requested settings. Drift is only
+    /// reported, the topic is left as it is.
+    pub drift: Vec<TopicDrift>,
+}
+
+pub trait MessageBus: Send + Sync {
+    /// Creates the topic, `false` when it already exists.
+    fn create_topic<'a>(
+        &'a self,
+        topic: &'a str,
+        settings: &'a TopicSettings,
+    ) -> BoxFuture<'a, MessageBusResult<bool>>;
+
+    /// Every topic but the internal ones of the broker, without their
+    /// configuration.
+    fn list_topics(&self) -> BoxFuture<'_, MessageBusResult<Vec<TopicDescription>>>;
+
+    fn describe_topic<'a>(
+        &'a self,
+        topic: &'a str,
+    ) -> BoxFuture<'a, MessageBusResult<TopicDescription>>;
+
+    /// Sets `configs` on the topic, its other configs keep their values.
+    fn alter_topic_configs<'a>(
+        &'a self,
+        topic: &'a str,
+        configs: &'a BTreeMap<String, String>,
+    ) -> BoxFuture<'a, MessageBusResult<()>>;
+
+    /// Grows the topic to `partitions` partitions.
+    fn add_partitions<'a>(
+        &'a self,
+        topic: &'a str,
+        partitions: i32,
+    ) -> BoxFuture<'a, MessageBusResult<()>>;
+
+    /// Fails when one of the topics couldn't be deleted, the others are
+    /// deleted anyway.
+    fn delete_topics<'a>(&'a self, topics: &'a [String]) -> BoxFuture<'a, MessageBusResult<()>>;
+
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        key: &'a str,
+        payload: &'a str,
+    ) -> BoxFuture<'a, MessageBusResult<()>>;
+
+    /// Joins `group_id` on `topics`, every message is delivered to a single
+    /// consumer of the group. Topics starting with `^` are patterns, matching
+    /// the topics created later as well.
+    fn subscribe(&self, group_id: &str, topics: &[&str]) -> MessageBusResult<BusMessageStream>;
+
+    /// The messages of `topic` published after the one at `offset` of
+    /// `partition`: the rest of that partition and, for the other partitions,
+    /// those from its timestamp on. Reads up to the end of the partitions at
+    /// the time of the call, without committing anything, and stops after
+    /// `limit` messages. Fails when the message is gone.
+    fn read_after<'a>(
+        &'a self,
+        topic: &'a str,
+        partition: i32,
+        offset: i64,
+        limit: usize,
+    ) -> BoxFuture<'a, MessageBusResult<Vec<BusMessage>>>;
+}
+
+fn topic_drift(settings: &TopicSettings, topic: &TopicDescription) -> Vec<TopicDrift> {
+    let mut drift = vec![];
+    if topic.partitions.len() as i32 != settings.partitions {
+        drift.push(TopicDrift {
+            setting: "partitions".to_string(),
+            expected: settings.partitions.to_string(),
+            actual: Some(topic.partitions.len().to_string()),
+        });
+    }
+    if let Some(partition) = topic.partitions.first()
+        && partition.replicas.len() as i32 != settings.replication
+    {
+        drift.push(TopicDrift {
+            setting: "replication".to_string(),
+            expected: settings.replication.to_string(),
+            actual: Some(partition.replicas.len().to_string()),
+        });
+    }
+    for (name, expected) in &settings.configs {
+        let actual = topic.configs.get(name);
+        if actual != Some(expected) {
+            drift.push(TopicDrift {
+                setting: name.clone(),
+                expected: expected.clone(),
+                actual: actual.cloned(),
+            });
+        }
+    }
+    drift
+}
+
+/// Creates the topic unless it exists, in which case it is compared with
+/// `settings` instead. Safe to repeat.
+pub async fn ensure_topic(
+    message_bus: &dyn MessageBus,
+    topic: &str,
+    settings: &TopicSettings,
+) -> MessageBusResult<EnsuredTopic> {
+    let created = message_bus.create_topic(topic, settings).await?;
+    let description = message_bus.describe_topic(topic).await?;
+    Ok(EnsuredTopic {
+        created,
+        drift: topic_drift(settings, &description),
+        topic: description,
+    })
+}
+
+pub fn init_message_bus() -> Arc<dyn MessageBus> {
+    match get_message_bus_backend() {
+        MessageBusBackend::Kafka => Arc::new(KafkaMessageBus::new()),
+        MessageBusBackend::InMemory => {
+            println!("MESSAGE_BUS: Using the in-memory message bus");
+            Arc::new(InMemoryMessageBus::new())
+        }
+    }
+}
@@ -1,186 +1,573 @@
+pub mod error;
+pub mod in_memory;
+pub mod kafka_bus;
+pub mod live;
+pub mod message_bus;
 pub mod structs;
 
-use chrono::DateTime;
-
-use rdkafka::{
-    ClientConfig, Message,
-    admin::{AdminClient, NewTopic, TopicReplication},
-    consumer::{Consumer, StreamConsumer},
-    error::KafkaError,
-    producer::{FutureProducer, FutureRecord},
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
 };
 
-use futures::TryStreamExt;
+use chrono::DateTime;
 
-use structs::KafkaNinoverseBrokerContext;
-use tokio::sync::mpsc::{Receiver, Sender};
+use futures::{StreamExt, TryStreamExt};
+
+use error::NinoverseKafkaError;
+use live::{LiveEvent, LiveFeed};
+use message_bus::{
+    BusMessage, EnsuredTopic, MessageBus, MessageBusResult, TopicSettings, ensure_topic,
+};
+use structs::{EVENT_PERMISSION, KafkaNinoverseEnvelope, KafkaNinoverseEvent};
+use tokio::sync::{
+    Notify,
+    mpsc::{Receiver, Sender},
+};
 
 use crate::{
     KafkaChannelMessage,
     configuration_handler::{
-        get_kafka_admin_options, get_kafka_generic_broker, get_kafka_generic_topic,
+        KafkaTenantIsolation, get_kafka_audit_topic, get_kafka_generic_topic,
+        get_kafka_tenant_isolation,
+    },
+    db_handler::{
+        repository::{DEFAULT_TENANT, Repositories},
+        structs::{AuditRecord, ItemKind, NewPendingWebhookDelivery, Webhook},
     },
+    permission_handler::{check_permission, error::NinoversePermissionError},
+    webhook_handler::{EventDelivery, WebhookSettings, run_deliveries},
 };
 
-async fn create_kafka_consumer() -> Result<StreamConsumer, rdkafka::error::KafkaError> {
-    println!("CONSUMER_CREATION: Creating consumer.");
-    let consumer: StreamConsumer = ClientConfig::new()
-        .set("group.id", "ninoverse")
-        .set("bootstrap.servers", get_kafka_generic_broker())
-        .set("enable.partition.eof", "false")
-        .set("session.timeout.ms", "6000")
-        .set("enable.auto.commit", "false")
-        // .set("auto.offset.reset", "earliest")
-        .create()
-        .expect("CONSUMER_CREATION: creation failed");
-    println!("CONSUMER_CREATION: Created, subscribing to topic ninoverse.");
-    consumer
-        .subscribe(&["ninoverse"])
-        .expect("CONSUMER_CREATION: Can't subscribe to specified topic");
-    println!("CONSUMER_CREATION: Subscribed to topic ninoverse.");
-    Ok(consumer)
-}
-
-async fn create_kafka_producer() -> Result<FutureProducer, rdkafka::error::KafkaError> {
-    println!("PRODUCER_CREATION: Creating producer.");
-    let producer: FutureProducer = ClientConfig::new()
-        .set("bootstrap.servers", get_kafka_generic_broker())
-        .set("message.timeout.ms", "5000")
-        .create()
-        .expect("PRODUCER_CREATION: Producer creation error");
-    println!("PRODUCER_CREATION: Producer created.");
-    Ok(producer)
-}
-
-async fn handle_kafka_message(message: rdkafka::message::OwnedMessage) -> Result<(), KafkaError> {
-    let mut key = String::new();
-    if let Some(key_bytes) = message.key() {
-        key = String::from_utf8(key_bytes.to_vec())
-            .unwrap_or_else(|_| "MESSAGE: Invalid UTF-8 array for key value.".to_string());
+/// The topic and key a message of `tenant_id` is published with.
+fn tenant_route(tenant_id: &str, topic: String, key: String) -> (String, String) {
+    match get_kafka_tenant_isolation() {
+        KafkaTenantIsolation::Key => (topic, format!("{}:{}", tenant_id, key)),
+        KafkaTenantIsolation::Topic => (format!("{}.{}", topic, tenant_id), key),
+    }
+}
+
+/// The topic the events of `tenant_id` are published to.
+fn events_topic(tenant_id: &str) -> String {
+    tenant_route(tenant_id, String::from("ninoverse"), String::new()).0
+}
+
+/// The tenant a consumed message belongs to, the default tenant for messages
+/// published without one.
+fn message_tenant<'a>(topic: &'a str, key: &'a str) -> &'a str {
+    let tenant_id = match get_kafka_tenant_isolation() {
+        KafkaTenantIsolation::Key => key.split_once(':').map(|(tenant_id, _)| tenant_id),
+        KafkaTenantIsolation::Topic => topic.strip_prefix("ninoverse."),
+    };
+    tenant_id.unwrap_or(DEFAULT_TENANT)
+}
+
+/// The pattern of the topics of every tenant, those created after startup
+/// included, when they are isolated by topic.
+fn tenant_topic_pattern(topic: &str) -> String {
+    format!("^{}\\.[a-z0-9][a-z0-9_-]*$", regex::escape(topic))
+}
+
+/// Events may come from other services, the actor they carry must hold the
+/// permission the change requires. The events signed by this service were
+/// checked by the API that published them, and nothing is checked when
+/// authentication is disabled.
+async fn check_event_actor(
+    repositories: &Repositories,
+    auth_enabled: bool,
+    envelope: &KafkaNinoverseEnvelope,
+) -> Result<(), NinoversePermissionError> {
+    if !auth_enabled || envelope.is_trusted(&repositories.tenant_id) {
+        return Ok(());
+    }
+    check_permission(
+        repositories,
+        envelope.event.actor(),
+        envelope.event.project_id(),
+        EVENT_PERMISSION,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// The event as shown to the live endpoints, `None` when its project is gone.
+async fn live_event(
+    repositories: &Repositories,
+    id: String,
+    event: KafkaNinoverseEvent,
+) -> Result<Option<LiveEvent>, NinoversePermissionError> {
+    let Some(project) = repositories.projects.get(event.project_id(), false).await? else {
+        return Ok(None);
     };
+    let mut tags = BTreeSet::new();
+    for (kind, item_id) in [
+        (ItemKind::Project, Some(project.id)),
+        (ItemKind::Task, event.task_id()),
+    ] {
+        let Some(item_id) = item_id else {
+            continue;
+        };
+        tags.extend(
+            repositories
+                .tags
+                .list_item_tags(kind, item_id)
+                .await?
+                .into_iter()
+                .map(|tag| tag.name),
+        );
+    }
+    Ok(Some(LiveEvent {
+        id,
+        tenant_id: repositories.tenant_id.clone(),
+        project,
+        tags,
+        event,
+    }))
+}
+
+/// The id of the event a message carries, its position in Kafka.
+fn event_id(message: &BusMessage) -> String {
+    format!("{}:{}:{}", message.topic, message.partition, message.offset)
+}
+
+/// The event of a message as shown to the live endpoints, `None` for messages
+/// that are not events, rejected events and events of deleted projects.
+async fn message_live_event(
+    repositories: &Repositories,
+    auth_enabled: bool,
+    message: &BusMessage,
+) -> Option<LiveEvent> {
+    let key = message.key.as_deref().unwrap_or_default();
+    let tenant_repositories = repositories.for_tenant(message_tenant(&message.topic, key));
+    let envelope =
+        serde_json::from_str::<KafkaNinoverseEnvelope>(message.payload.as_deref()?).ok()?;
+    if let Err(permission_error) =
+        check_event_actor(&tenant_repositories, auth_enabled, &envelope).await
+    {
+        println!("MESSAGE: Rejected event {}: {:?}", key, permission_error);
+        return None;
+    }
+    match live_event(&tenant_repositories, event_id(message), envelope.event).await {
+        Ok(live_event) => live_event,
+        Err(live_error) => {
+            println!("MESSAGE: Event {} not sent live: {:?}", key, live_error);
+            None
+        }
+    }
+}
+
+async fn handle_kafka_message(
+    repositories: &Repositories,
+    auth_enabled: bool,
+    live_feed: &LiveFeed,
+    message: BusMessage,
+) -> Result<(), error::NinoverseKafkaError> {
     let timestamp = DateTime::from_timestamp(
         message
-            .timestamp()
-            .to_millis()
+            .timestamp
             .expect("MESSAGE: Incorrect timestamp format.")
             / 1000,
         0,
     )
     .expect("MESSAGE: Error creating date string.");
-    let mut payload = String::new();
-    if let Some(payload_bytes) = message.payload() {
-        payload = String::from_utf8(payload_bytes.to_vec())
-            .unwrap_or_else(|_| "MESSAGE: Invalid UTF-8 array for payload value.".to_string());
-    };
     println!(
         "MESSAGE: {}:{}/{}/{}/{}: {}",
-        message.topic(),
-        message.partition(),
-        message.offset(),
-        key.as_str(),
+        message.topic,
+        message.partition,
+        message.offset,
+        message.key.as_deref().unwrap_or_default(),
         timestamp.to_string().as_str(),
-        payload
+        message.payload.as_deref().unwrap_or_default()
     );
+    if let Some(live_event) = message_live_event(repositories, auth_enabled, &message).await {
+        live_feed.publish(live_event);
+    }
     Ok(())
 }
 
-async fn init_kafka_consumer(kafka_thread_channel_sender: Sender<KafkaChannelMessage>) {
-    let consumer = create_kafka_consumer()
+/// The events topic and, when tenants are isolated by topic, theirs.
+fn event_topics() -> Vec<String> {
+    let mut topics = vec![String::from("ninoverse")];
+    if get_kafka_tenant_isolation() == KafkaTenantIsolation::Topic {
+        topics.push(tenant_topic_pattern("ninoverse"));
+    }
+    topics
+}
+
+/// The webhooks an event goes to, with the event ready to be posted. Events
+/// rejected by the consumer are not sent either.
+async fn webhook_event(
+    repositories: &Repositories,
+    auth_enabled: bool,
+    message: BusMessage,
+) -> Option<(Repositories, Vec<Webhook>, EventDelivery)> {
+    let key = message.key.as_deref().unwrap_or_default();
+    let tenant_repositories = repositories.for_tenant(message_tenant(&message.topic, key));
+    let envelope =
+        serde_json::from_str::<KafkaNinoverseEnvelope>(message.payload.as_deref()?).ok()?;
+    check_event_actor(&tenant_repositories, auth_enabled, &envelope)
         .await
+        .ok()?;
+    let event = envelope.event;
+    let webhooks: Vec<Webhook> = match tenant_repositories.webhooks.list().await {
+        Ok(webhooks) => webhooks
+            .into_iter()
+            .filter(|webhook| webhook.enabled && webhook.accepts(event.name()))
+            .collect(),
+        Err(db_error) => {
+            println!("WEBHOOKS: Error listing webhooks: {:?}", db_error);
+            return None;
+        }
+    };
+    if webhooks.is_empty() {
+        return None;
+    }
+    match EventDelivery::new(event_id(&message), &tenant_repositories.tenant_id, &event) {
+        Ok(delivery) => Some((tenant_repositories, webhooks, delivery)),
+        Err(serialization_error) => {
+            println!("WEBHOOKS: Error serializing event: {}", serialization_error);
+            None
+        }
+    }
+}
+
+/// Consumes the events on a group of its own, queueing them for the webhooks
+/// of their tenant. `run_deliveries` posts them.
+async fn init_webhook_consumer(
+    repositories: Repositories,
+    message_bus: Arc<dyn MessageBus>,
+    queued: Arc<Notify>,
+    auth_enabled: bool,
+) {
+    let topics = event_topics();
+    let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+    let mut stream = match message_bus.subscribe("ninoverse-webhooks", &topics) {
+        Ok(stream) => stream,
+        Err(bus_error) => {
+            println!("WEBHOOKS: Error in creating consumer: {:?}", bus_error);
+            return;
+        }
+    };
+    println!("WEBHOOKS: Thread started, consuming the stream.");
+    while let Some(message) = stream.next().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(bus_error) => {
+                println!("WEBHOOKS: Error in consuming stream: {:?}", bus_error);
+                continue;
+            }
+        };
+        let Some((tenant_repositories, webhooks, delivery)) =
+            webhook_event(&repositories, auth_enabled, message).await
+        else {
+            continue;
+        };
+        for webhook in webhooks {
+            let pending = tenant_repositories
+                .webhooks
+                .insert_pending(&NewPendingWebhookDelivery {
+                    webhook_id: webhook.id,
+                    event_id: delivery.id.clone(),
+                    event_type: delivery.event_type.to_string(),
+                    body: delivery.body.clone(),
+                })
+                .await;
+            if let Err(db_error) = pending {
+                println!(
+                    "WEBHOOKS: Error queueing event {} for webhook {}: {:?}",
+                    delivery.id, webhook.id, db_error
+                );
+            }
+        }
+        queued.notify_one();
+    }
+}
+
+async fn init_kafka_consumer(
+    repositories: Repositories,
+    message_bus: Arc<dyn MessageBus>,
+    live_feed: Arc<LiveFeed>,
+    auth_enabled: bool,
+    kafka_thread_channel_sender: Sender<KafkaChannelMessage>,
+) {
+    let topics = event_topics();
+    let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+    let stream = message_bus
+        .subscribe("ninoverse", &topics)
         .expect("Error in creating consumer");
-    println!("CONSUMER: Thread started, creating stream and consuming it.");
+    println!("CONSUMER: Thread started, consuming the stream.");
     kafka_thread_channel_sender
         .send(KafkaChannelMessage::KafkaConsumerStarted)
         .await
         .expect("CONSUMER: Error in sending message to producer thread");
-    consumer
-        .stream()
-        .try_for_each(|borrowed_message| handle_kafka_message(borrowed_message.detach()))
+    stream
+        .try_for_each(|message| {
+            handle_kafka_message(&repositories, auth_enabled, &live_feed, message)
+        })
         .await
         .expect("CONSUMER: Error in consuming stream");
 }
 
-async fn init_kafka_producer(mut kafka_thread_receiver: Receiver<KafkaChannelMessage>) {
-    let producer = create_kafka_producer()
-        .await
-        .expect("Error in creating producer");
-    loop {
-        println!("PRODUCER: Thread waiting for consumer to start.");
-        kafka_thread_receiver.recv().await.inspect(|message| {
-            if let KafkaChannelMessage::KafkaConsumerError = message {
-                println!("PRODUCER: Received an error in consumer start.")
-            }
-        });
-        break;
-    }
+async fn init_kafka_producer(
+    message_bus: Arc<dyn MessageBus>,
+    mut kafka_thread_receiver: Receiver<KafkaChannelMessage>,
+) {
+    println!("PRODUCER: Thread waiting for consumer to start.");
+    kafka_thread_receiver.recv().await.inspect(|message| {
+        if let KafkaChannelMessage::KafkaConsumerError = message {
+            println!("PRODUCER: Received an error in consumer start.")
+        }
+    });
     println!("PRODUCER: Thread started, sending messages.");
     while let Some(received) = kafka_thread_receiver.recv().await {
-        if let KafkaChannelMessage::Message { sender, content } = received {
-            let queue_timeout = std::time::Duration::from_secs(1);
-            let key = sender;
-            let payload = format!("Message: {}", content);
-            let record = FutureRecord::to("ninoverse")
-                .payload(payload.as_str())
-                .key(key.as_str());
-            producer
-                .send(record, queue_timeout)
-                .await
-                .expect("Error in sending message");
+        let (topic, key, payload) = match received {
+            KafkaChannelMessage::Message { sender, content } => (
+                String::from("ninoverse"),
+                sender,
+                format!("Message: {}", content),
+            ),
+            KafkaChannelMessage::Event {
+                topic,
+                key,
+                payload,
+            } => (topic, key, payload),
+            _ => continue,
+        };
+        message_bus
+            .publish(topic.as_str(), key.as_str(), payload.as_str())
+            .await
+            .expect("Error in sending message");
+    }
+}
+
+async fn send_event(
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    topic: String,
+    key: String,
+    payload: Result<String, serde_json::Error>,
+) {
+    let payload = match payload {
+        Ok(payload) => payload,
+        Err(serialization_error) => {
+            println!(
+                "PUBLISH_EVENT: Error serializing event: {}",
+                serialization_error
+            );
+            return;
         }
+    };
+    let kafka_write_result = kafka_thread_sender
+        .send(KafkaChannelMessage::Event {
+            topic,
+            key,
+            payload,
+        })
+        .await;
+    if let Err(kafka_write_error) = kafka_write_result {
+        println!("PUBLISH_EVENT: {}", kafka_write_error);
+    }
+}
+
+pub async fn publish_event(
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    tenant_id: &str,
+    event: KafkaNinoverseEvent,
+) {
+    let (topic, key) = tenant_route(tenant_id, String::from("ninoverse"), event.key());
+    let envelope = KafkaNinoverseEnvelope::signed(tenant_id, event);
+    send_event(
+        kafka_thread_sender,
+        topic,
+        key,
+        serde_json::to_string(&envelope),
+    )
+    .await;
+}
+
+/// Mirrors an audit record to the audit topic, no-op when `KAFKA_AUDIT_TOPIC`
+/// is not configured.
+pub async fn publish_audit_record(
+    kafka_thread_sender: &Sender<KafkaChannelMessage>,
+    tenant_id: &str,
+    record: &AuditRecord,
+) {
+    if let Some(topic) = get_kafka_audit_topic() {
+        let (topic, key) = audit_route(tenant_id, topic, record);
+        send_event(
+            kafka_thread_sender,
+            topic,
+            key,
+            serde_json::to_string(record),
+        )
+        .await;
     }
 }
 
-async fn create_kafka_admin_client() -> Result<AdminClient<KafkaNinoverseBrokerContext>, KafkaError>
-{
-    println!("ADMIN_CLIENT_CREATION: Creating admin client.");
-    let admin_client: AdminClient<KafkaNinoverseBrokerContext> = ClientConfig::new()
-        .set("bootstrap.servers", get_kafka_generic_broker())
-        .create_with_context(KafkaNinoverseBrokerContext {})
-        .expect("Failed to create AdminClient with custom context");
-    println!("ADMIN_CLIENT_CREATION: Created Admin client.");
-    Ok(admin_client)
+fn audit_route(tenant_id: &str, topic: String, record: &AuditRecord) -> (String, String) {
+    tenant_route(
+        tenant_id,
+        topic,
+        format!("{}-{}", record.entity_type, record.entity_id),
+    )
+}
+
+/// Publishes stored audit records to `audit_topic` again, in the given order,
+/// straight to the message bus instead of through the producer thread.
+pub async fn republish_audit_records(
+    message_bus: &dyn MessageBus,
+    tenant_id: &str,
+    audit_topic: &str,
+    records: &[AuditRecord],
+) -> MessageBusResult<()> {
+    for record in records {
+        let (topic, key) = audit_route(tenant_id, audit_topic.to_string(), record);
+        let payload =
+            serde_json::to_string(record).map_err(|error| NinoverseKafkaError::BrokerError {
+                additional_info: format!("Audit record {}: {}", record.id, error),
+            })?;
+        message_bus.publish(&topic, &key, &payload).await?;
+    }
+    Ok(())
 }
 
-async fn init_kafka_topics(admin_client: AdminClient<KafkaNinoverseBrokerContext>) {
-    println!("TOPIC_CREATION: Creating topics object.");
-    let kafka_topics = get_kafka_generic_topic();
-    let kafka_new_topics: Vec<NewTopic<'_>> = kafka_topics
-        .iter()
-        .map(|element| NewTopic {
-            name: &element.topic,
-            num_partitions: element.partition,
-            replication: TopicReplication::Fixed(1),
-            config: vec![],
+/// The configured topics with their settings.
+fn configured_topics() -> Vec<(String, TopicSettings)> {
+    let mut topics: Vec<(String, TopicSettings)> = get_kafka_generic_topic()
+        .into_iter()
+        .map(|kafka_topic| {
+            let settings = TopicSettings {
+                partitions: kafka_topic.partition,
+                replication: 1,
+                configs: kafka_topic
+                    .config
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            };
+            (kafka_topic.topic, settings)
         })
         .collect();
-    if kafka_new_topics.iter().len() == 0 {
+    if let Some(audit_topic) = get_kafka_audit_topic() {
+        let settings = TopicSettings {
+            partitions: 1,
+            replication: 1,
+            configs: BTreeMap::new(),
+        };
+        topics.push((audit_topic, settings));
+    }
+    topics
+}
+
+/// Whether the service publishes to or consumes the topic: the events topic,
+/// the configured topics and their copies per tenant.
+pub fn is_service_topic(topic: &str) -> bool {
+    configured_topics()
+        .into_iter()
+        .map(|(name, _)| name)
+        .chain([String::from("ninoverse")])
+        .any(|name| {
+            topic == name
+                || regex::Regex::new(&tenant_topic_pattern(&name))
+                    .is_ok_and(|pattern| pattern.is_match(topic))
+        })
+}
+
+/// The copies of the configured topics a tenant gets when tenants are
+/// isolated by topic.
+fn tenant_configured_topics(tenant_id: &str) -> Vec<(String, TopicSettings)> {
+    if get_kafka_tenant_isolation() == KafkaTenantIsolation::Key {
+        return vec![];
+    }
+    configured_topics()
+        .into_iter()
+        .map(|(topic, settings)| (format!("{}.{}", topic, tenant_id), settings))
+        .collect()
+}
+
+async fn ensure_topics(
+    message_bus: &dyn MessageBus,
+    topics: &[(String, TopicSettings)],
+) -> MessageBusResult<()> {
+    for (topic, settings) in topics {
+        report_ensured_topic(&ensure_topic(message_bus, topic, settings).await?);
+    }
+    Ok(())
+}
+
+/// Creates the configured topics, with those of every tenant when they are
+/// isolated by topic.
+pub async fn init_kafka_topics(
+    repositories: &Repositories,
+    message_bus: &dyn MessageBus,
+) -> MessageBusResult<()> {
+    println!("TOPIC_CREATION: Creating topics object.");
+    let mut topics = configured_topics();
+    for tenant in repositories.tenants.list().await? {
+        topics.extend(tenant_configured_topics(&tenant.id));
+    }
+    if topics.is_empty() {
         println!("TOPIC_CREATION: No topic created (no topic creation requested).");
+    }
+    ensure_topics(message_bus, &topics).await
+}
+
+/// Creates the topics of a tenant created after startup, consumers find them
+/// through their pattern.
+pub async fn init_tenant_topics(
+    message_bus: &dyn MessageBus,
+    tenant_id: &str,
+) -> MessageBusResult<()> {
+    ensure_topics(message_bus, &tenant_configured_topics(tenant_id)).await
+}
+
+/// Prints whether the topic was created and how it drifted from its settings.
+pub fn report_ensured_topic(ensured_topic: &EnsuredTopic) {
+    let name = &ensured_topic.topic.name;
+    if ensured_topic.created {
+        println!("TOPIC_CREATION: Created {}", name);
     } else {
-        let options = get_kafka_admin_options();
-        println!("TOPIC_CREATION: Sending request to Kafka Admin Client");
-        let topic_creation_result_list = admin_client
-            .create_topics(&kafka_new_topics, &options)
-            .await
-            .expect("TOPIC_CREATION: Topic creation failed");
-        for topic_creation_result in topic_creation_result_list {
-            println!("TOPIC_CREATION: {:?}", topic_creation_result);
-        }
+        println!("TOPIC_CREATION: {} already exists", name);
+    }
+    for drift in &ensured_topic.drift {
+        println!(
+            "TOPIC_CREATION: {} drifted, {} is {} instead of {}",
+            name,
+            drift.setting,
+            drift.actual.as_deref().unwrap_or("unset"),
+            drift.expected
+        );
     }
 }
 
 pub async fn init_kafka(
+    repositories: Repositories,
+    message_bus: Arc<dyn MessageBus>,
+    live_feed: Arc<LiveFeed>,
+    webhook_settings: WebhookSettings,
+    auth_enabled: bool,
     kafka_thread_sender: Sender<KafkaChannelMessage>,
     kafka_thread_receiver: Receiver<KafkaChannelMessage>,
-) {
-    let admin_client = create_kafka_admin_client()
-        .await
-        .expect("INIT_KAFKA: Admin client creation failed");
-    init_kafka_topics(admin_client).await;
-    let consumer_handle = tokio::spawn(async {
-        init_kafka_consumer(kafka_thread_sender).await;
-    });
-    let producer_handle = tokio::spawn(async {
-        init_kafka_producer(kafka_thread_receiver).await;
-    });
-    tokio::try_join!(producer_handle, consumer_handle).expect("Error in Kafka threads");
+) -> MessageBusResult<()> {
+    init_kafka_topics(&repositories, message_bus.as_ref()).await?;
+    // Joined in place instead of spawned, aborting the Kafka thread stops them
+    // all.
+    let queued = Arc::new(Notify::new());
+    tokio::join!(
+        init_kafka_producer(message_bus.clone(), kafka_thread_receiver),
+        init_webhook_consumer(
+            repositories.clone(),
+            message_bus.clone(),
+            queued.clone(),
+            auth_enabled
+        ),
+        run_deliveries(repositories.clone(), Arc::new(webhook_settings), queued),
+        init_kafka_consumer(
+            repositories,
+            message_bus,
+            live_feed,
+            auth_enabled,
+            kafka_thread_sender
+        )
+    );
+    Ok(())
 }
@@ -1,4 +1,46 @@
+use std::sync::OnceLock;
+
+use chrono::NaiveDateTime;
+use hmac::{Hmac, Mac};
 use rdkafka::{admin::TopicReplication, ClientContext};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{
+    configuration_handler::get_kafka_event_secret,
+    db_handler::structs::{Comment, Task},
+    permission_handler::Permission,
+};
+
+/// The `origin` of the events published by this service.
+pub const EVENT_ORIGIN: &str = "ninoverse";
+
+/// The permission on the project the actor of an event of another service
+/// must hold, every change an event describes needs it through the API.
+pub const EVENT_PERMISSION: Permission = Permission::Write;
+
+/// `KAFKA_EVENT_SECRET`, or a random key when it is not configured. The
+/// events of other instances are then checked like those of other services.
+fn event_secret() -> &'static [u8] {
+    static EVENT_SECRET: OnceLock<Vec<u8>> = OnceLock::new();
+    EVENT_SECRET.get_or_init(|| match get_kafka_event_secret() {
+        Some(secret) => secret.into_bytes(),
+        None => {
+            println!(
+                "EVENTS: KAFKA_EVENT_SECRET not set, only the events of this instance are trusted"
+            );
+            rand::random::<[u8; 32]>().to_vec()
+        }
+    })
+}
+
+fn event_mac(tenant_id: &str, event: &KafkaNinoverseEvent) -> Option<Hmac<Sha256>> {
+    let body = serde_json::to_string(event).ok()?;
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(event_secret()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{}.{}", tenant_id, body).as_bytes());
+    Some(mac)
+}
 
 #[allow(dead_code)]
 pub struct KafkaNinoverseTopic<'a> {
@@ -24,3 +66,146 @@ impl ClientContext for KafkaNinoverseBrokerContext {
     //     _oauthbearer_config: Option<&str>,
     // ) -> Result<OAuthToken, Box<dyn Error>> {  }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum KafkaNinoverseEvent {
+    ProjectStatusChanged {
+        project_id: i32,
+        from_status: String,
+        to_status: String,
+        actor: String,
+        changed_at: Option<NaiveDateTime>,
+    },
+    TaskCreated {
+        task: Task,
+        actor: String,
+    },
+    TaskUpdated {
+        task: Task,
+        actor: String,
+    },
+    /// Its subtasks are deleted along with it, without events of their own.
+    TaskDeleted {
+        task: Task,
+        actor: String,
+    },
+    TasksReordered {
+        project_id: i32,
+        task_ids: Vec<i32>,
+        actor: String,
+    },
+    /// Sent once per mentioned user, when the comment is posted or when an
+    /// edit adds the mention.
+    UserMentioned {
+        comment: Comment,
+        subject: String,
+        actor: String,
+    },
+}
+
+impl KafkaNinoverseEvent {
+    /// The project the event is about.
+    pub fn project_id(&self) -> i32 {
+        match self {
+            KafkaNinoverseEvent::ProjectStatusChanged { project_id, .. }
+            | KafkaNinoverseEvent::TasksReordered { project_id, .. } => *project_id,
+            KafkaNinoverseEvent::TaskCreated { task, .. }
+            | KafkaNinoverseEvent::TaskUpdated { task, .. }
+            | KafkaNinoverseEvent::TaskDeleted { task, .. } => task.project_id,
+            KafkaNinoverseEvent::UserMentioned { comment, .. } => comment.project_id,
+        }
+    }
+
+    /// The task the event is about, if any.
+    pub fn task_id(&self) -> Option<i32> {
+        match self {
+            KafkaNinoverseEvent::ProjectStatusChanged { .. }
+            | KafkaNinoverseEvent::TasksReordered { .. } => None,
+            KafkaNinoverseEvent::TaskCreated { task, .. }
+            | KafkaNinoverseEvent::TaskUpdated { task, .. }
+            | KafkaNinoverseEvent::TaskDeleted { task, .. } => Some(task.id),
+            KafkaNinoverseEvent::UserMentioned { comment, .. } => comment.task_id,
+        }
+    }
+
+    /// Every value of `name`.
+    pub const NAMES: [&'static str; 6] = [
+        "project_status_changed",
+        "task_created",
+        "task_updated",
+        "task_deleted",
+        "tasks_reordered",
+        "user_mentioned",
+    ];
+
+    /// The `event` field of the serialized event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            KafkaNinoverseEvent::ProjectStatusChanged { .. } => "project_status_changed",
+            KafkaNinoverseEvent::TaskCreated { .. } => "task_created",
+            KafkaNinoverseEvent::TaskUpdated { .. } => "task_updated",
+            KafkaNinoverseEvent::TaskDeleted { .. } => "task_deleted",
+            KafkaNinoverseEvent::TasksReordered { .. } => "tasks_reordered",
+            KafkaNinoverseEvent::UserMentioned { .. } => "user_mentioned",
+        }
+    }
+
+    pub fn actor(&self) -> &str {
+        match self {
+            KafkaNinoverseEvent::ProjectStatusChanged { actor, .. }
+            | KafkaNinoverseEvent::TaskCreated { actor, .. }
+            | KafkaNinoverseEvent::TaskUpdated { actor, .. }
+            | KafkaNinoverseEvent::TaskDeleted { actor, .. }
+            | KafkaNinoverseEvent::TasksReordered { actor, .. }
+            | KafkaNinoverseEvent::UserMentioned { actor, .. } => actor,
+        }
+    }
+
+    /// Task events share the key of their project so that they are consumed
+    /// in order with the project events.
+    pub fn key(&self) -> String {
+        format!("project-{}", self.project_id())
+    }
+}
+
+/// An event as published on the events topic. Events of other services have
+/// no `origin`, or one of their own, and no `signature`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaNinoverseEnvelope {
+    #[serde(flatten)]
+    pub event: KafkaNinoverseEvent,
+    /// Informative, anyone can write it.
+    #[serde(default)]
+    pub origin: Option<String>,
+    /// HMAC-SHA256 of `<tenant>.<event>` with the event secret, in hex.
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+impl KafkaNinoverseEnvelope {
+    /// An event of this service for `tenant_id`.
+    pub fn signed(tenant_id: &str, event: KafkaNinoverseEvent) -> Self {
+        let signature =
+            event_mac(tenant_id, &event).map(|mac| hex::encode(mac.finalize().into_bytes()));
+        KafkaNinoverseEnvelope {
+            event,
+            origin: Some(EVENT_ORIGIN.to_string()),
+            signature,
+        }
+    }
+
+    /// Events signed by this service were authorized by the API that
+    /// published them. The tenant is signed along so that they can't be
+    /// replayed for another one.
+    pub fn is_trusted(&self, tenant_id: &str) -> bool {
+        let Some(signature) = self
+            .signature
+            .as_deref()
+            .and_then(|signature| hex::decode(signature).ok())
+        else {
+            return false;
+        };
+        event_mac(tenant_id, &self.event).is_some_and(|mac| mac.verify_slice(&signature).is_ok())
+    }
+}
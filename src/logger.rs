@@ -1,15 +1,17 @@
-pub enum LogLevel {
-    Debug,
-    Info,
-    Warning,
-    Danger,
-}
-
-pub fn log(msg: String, level: LogLevel) {
-    match level {
-        LogLevel::Debug => println!("{}", msg),
-        LogLevel::Info => println!("{}", msg),
-        LogLevel::Warning => println!("{}", msg),
-        LogLevel::Danger => println!("{}", msg),
-    }
-}
+#[allow(dead_code)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Danger,
+}
+
+#[allow(dead_code)]
+pub fn log(msg: String, level: LogLevel) {
+    match level {
+        LogLevel::Debug => println!("{}", msg),
+        LogLevel::Info => println!("{}", msg),
+        LogLevel::Warning => println!("{}", msg),
+        LogLevel::Danger => println!("{}", msg),
+    }
+}
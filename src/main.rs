@@ -1,19 +1,36 @@
 mod api_handler;
+mod blob_handler;
+mod cli_handler;
 mod configuration_handler;
 mod db_handler;
+mod dependency_handler;
+mod graphql_handler;
+mod grpc_handler;
 // mod http_handler;
 mod kafka_handler;
 mod logger;
+mod permission_handler;
+#[cfg(test)]
+mod tests;
+mod webhook_handler;
 
 // use logger::{log, LogLevel};
 
-use std::sync::Arc;
+use std::{net::TcpListener, sync::Arc};
 
-use api_handler::init_request_handler;
+use api_handler::{attachments::AttachmentSettings, auth::AuthSettings, init_request_handler};
 
-use kafka_handler::init_kafka;
+use db_handler::repository::Repositories;
 
-use sqlx::{Pool, Postgres};
+use grpc_handler::init_grpc_server;
+
+use kafka_handler::{
+    init_kafka,
+    live::{LiveFeed, LiveHistory},
+    message_bus::{MessageBus, init_message_bus},
+};
+
+use webhook_handler::WebhookSettings;
 
 pub enum KafkaChannelMessage {
     KafkaProducerStarted,
@@ -23,39 +40,187 @@ pub enum KafkaChannelMessage {
     Message {
         sender: String,
         content: String,
-    }
+    },
+    Event {
+        topic: String,
+        key: String,
+        payload: String,
+    },
+}
+
+/// The sockets of the HTTP and gRPC servers, bound before the threads start.
+pub struct Listeners {
+    pub api: TcpListener,
+    pub grpc: TcpListener,
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let result = match arguments.first().map(String::as_str) {
+        None | Some("serve") => serve().await,
+        Some(_) => cli_handler::run_command(&arguments).await,
+    };
+    // Reported with their message rather than their debug output.
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+/// Starts every server and thread, until the shutdown signal.
+async fn serve() -> Result<(), Box<dyn std::error::Error>> {
     println!("MAIN: Program started.");
     println!("MAIN: Testing configuration.");
     configuration_handler::test_configuration()?;
     println!("MAIN: Configuration loaded.");
-    println!("MAIN: Initializing DB pool.");
-    let pool = db_handler::init_db().await?;
+    println!("MAIN: Initializing storage.");
+    let repositories = db_handler::init_repositories().await?;
+    println!("MAIN: Initializing message bus.");
+    let message_bus = init_message_bus();
+    let api_listener = TcpListener::bind((
+        "0.0.0.0",
+        configuration_handler::get_self_port().parse::<u16>()?,
+    ))?;
+    let grpc_listener = TcpListener::bind((
+        "0.0.0.0",
+        configuration_handler::get_grpc_port().parse::<u16>()?,
+    ))?;
     println!("MAIN: Starting threads");
-    run_threads(Arc::new(pool)).await?;
+    run_threads(
+        repositories,
+        message_bus,
+        Listeners {
+            api: api_listener,
+            grpc: grpc_listener,
+        },
+        AuthSettings::from_configuration(),
+        AttachmentSettings::from_configuration(),
+        WebhookSettings::from_configuration(),
+        shutdown_signal(),
+    )
+    .await?;
     Ok(())
 
     // ThreadPool
 }
 
-async fn run_threads(pool: Arc<Pool<Postgres>>) -> Result<(), Box<dyn std::error::Error>> {
-    let pool_tcp_clone = pool.clone();
+async fn shutdown_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("MAIN: Error listening for the shutdown signal.");
+    println!("MAIN: Shutdown signal received.");
+}
+
+/// Runs the API, gRPC, Kafka and purge threads until one of them stops or
+/// `shutdown` resolves, in which case the API drains its pending requests and
+/// the other threads are stopped.
+async fn run_threads(
+    repositories: Repositories,
+    message_bus: Arc<dyn MessageBus>,
+    listeners: Listeners,
+    auth_settings: AuthSettings,
+    attachment_settings: AttachmentSettings,
+    webhook_settings: WebhookSettings,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let repositories_tcp_clone = repositories.clone();
+    let repositories_grpc_clone = repositories.clone();
+    let repositories_purge_clone = repositories.clone();
+    let repositories_kafka_clone = repositories.clone();
+    let blob_store_purge_clone = attachment_settings.blob_store.clone();
     let (kafka_thread_sender, kafka_thread_receiver) = tokio::sync::mpsc::channel(100);
     let kafka_thread_sender_tcp = kafka_thread_sender.clone();
-    let api_listener_thread_handler = tokio::spawn(async move {
+    let kafka_thread_sender_grpc = kafka_thread_sender.clone();
+    let live_feed = Arc::new(
+        LiveFeed::new(configuration_handler::get_live_replay_size()).with_history(LiveHistory {
+            repositories: repositories.clone(),
+            message_bus: message_bus.clone(),
+            auth_enabled: auth_settings.enabled,
+        }),
+    );
+    let live_feed_kafka_clone = live_feed.clone();
+    let auth_enabled = auth_settings.enabled;
+    let (grpc_shutdown_sender, grpc_shutdown_receiver) = tokio::sync::oneshot::channel::<()>();
+    let grpc_server = init_grpc_server(
+        repositories_grpc_clone,
+        kafka_thread_sender_grpc,
+        listeners.grpc,
+        auth_settings.clone(),
+        live_feed.clone(),
+        async {
+            let _ = grpc_shutdown_receiver.await;
+        },
+    )?;
+    let api_server = init_request_handler(
+        repositories_tcp_clone,
+        kafka_thread_sender_tcp,
+        message_bus.clone(),
+        listeners.api,
+        auth_settings,
+        attachment_settings,
+        live_feed.clone(),
+    )?;
+    let api_server_handle = api_server.handle();
+    let mut api_listener_thread_handler = tokio::spawn(async move {
         println!("RUN_THREADS: Starting API listener thread.");
-        let _ = init_request_handler(pool_tcp_clone, kafka_thread_sender_tcp)
-            .expect("RUN_THREADS: Error in the HTTP Server.")
-            .await;
+        let _ = api_server.await;
     });
-    let kafka_thread_handler = tokio::spawn(async move {
+    let mut grpc_thread_handler = tokio::spawn(async move {
+        println!("RUN_THREADS: Starting gRPC thread.");
+        if let Err(grpc_error) = grpc_server.await {
+            println!("RUN_THREADS: gRPC server error: {}", grpc_error);
+        }
+    });
+    let mut kafka_thread_handler = tokio::spawn(async move {
         println!("RUN_THREADS: Starting KAFKA thread.");
-        init_kafka(kafka_thread_sender, kafka_thread_receiver).await;
+        if let Err(kafka_error) = init_kafka(
+            repositories_kafka_clone,
+            message_bus,
+            live_feed_kafka_clone,
+            webhook_settings,
+            auth_enabled,
+            kafka_thread_sender,
+            kafka_thread_receiver,
+        )
+        .await
+        {
+            println!("RUN_THREADS: Kafka error: {:?}", kafka_error);
+        }
+    });
+    let mut purge_thread_handler = tokio::spawn(async move {
+        println!("RUN_THREADS: Starting PURGE thread.");
+        db_handler::run_purge_job(repositories_purge_clone, blob_store_purge_clone).await;
     });
-    tokio::try_join!(api_listener_thread_handler, kafka_thread_handler)
-        .expect("RUN_THREADS: Some error occured in the thread.");
+    let shutdown_requested = tokio::select! {
+        threads_result = async {
+            tokio::try_join!(
+                &mut api_listener_thread_handler,
+                &mut grpc_thread_handler,
+                &mut kafka_thread_handler,
+                &mut purge_thread_handler
+            )
+        } => {
+            threads_result.expect("RUN_THREADS: Some error occured in the thread.");
+            false
+        }
+        _ = shutdown => true,
+    };
+    if shutdown_requested {
+        println!("RUN_THREADS: Stopping threads.");
+        // Live connections never end on their own, the server would wait for them.
+        live_feed.close();
+        let _ = grpc_shutdown_sender.send(());
+        api_server_handle.stop(true).await;
+        kafka_thread_handler.abort();
+        purge_thread_handler.abort();
+        let _ = tokio::join!(
+            api_listener_thread_handler,
+            grpc_thread_handler,
+            kafka_thread_handler,
+            purge_thread_handler
+        );
+        println!("RUN_THREADS: Threads stopped.");
+    }
     Ok(())
 }
@@ -0,0 +1,22 @@
+use crate::db_handler::error::NinoverseDbError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NinoversePermissionError {
+    #[error("PERMISSION_HANDLER: Permission denied.")]
+    Forbidden { additional_info: String },
+    #[error("PERMISSION_HANDLER: Resource not found.")]
+    NotFound { additional_info: String },
+    #[error("PERMISSION_HANDLER: Error querying the database.")]
+    DatabaseError { additional_info: String },
+}
+
+impl From<NinoverseDbError> for NinoversePermissionError {
+    fn from(error: NinoverseDbError) -> Self {
+        NinoversePermissionError::DatabaseError {
+            additional_info: match error {
+                NinoverseDbError::QueryError { additional_info } => additional_info,
+                other => other.to_string(),
+            },
+        }
+    }
+}
@@ -0,0 +1,220 @@
+pub mod error;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::db_handler::{
+    error::NinoverseDbError,
+    repository::Repositories,
+    structs::{Membership, Project, User},
+};
+use error::NinoversePermissionError;
+
+/// Roles are ordered, a role has every permission of the roles below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Reads a role stored in the `memberships` table.
+    pub fn parse(role: &str) -> Option<Role> {
+        match role {
+            "viewer" => Some(Role::Viewer),
+            "editor" => Some(Role::Editor),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn permissions(&self) -> Vec<Permission> {
+        Permission::ALL
+            .into_iter()
+            .filter(|permission| permission.required_role() <= *self)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Read,
+    Write,
+    Delete,
+    ManageAccess,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 4] = [
+        Permission::Read,
+        Permission::Write,
+        Permission::Delete,
+        Permission::ManageAccess,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Read => "read",
+            Permission::Write => "write",
+            Permission::Delete => "delete",
+            Permission::ManageAccess => "manage_access",
+        }
+    }
+
+    pub fn required_role(&self) -> Role {
+        match self {
+            Permission::Read => Role::Viewer,
+            Permission::Write => Role::Editor,
+            Permission::Delete | Permission::ManageAccess => Role::Admin,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProjectPermissions {
+    pub project_id: i32,
+    pub role: Option<Role>,
+    pub permissions: Vec<Permission>,
+}
+
+/// Whose permissions are checked, with the team memberships loaded once.
+#[derive(Debug, Clone)]
+pub enum Caller {
+    /// Authentication is disabled, nothing is enforced.
+    Unrestricted,
+    User {
+        user: User,
+        memberships: Vec<Membership>,
+    },
+}
+
+impl Caller {
+    pub async fn load(repositories: &Repositories, user: User) -> Result<Caller, NinoverseDbError> {
+        let memberships = repositories.teams.list_memberships(user.id).await?;
+        Ok(Caller::User { user, memberships })
+    }
+
+    /// Creates the user on its first request. The admin flag comes from the
+    /// configuration and overwrites the stored one.
+    pub async fn provision(
+        repositories: &Repositories,
+        subject: &str,
+        is_admin: bool,
+    ) -> Result<Caller, NinoverseDbError> {
+        let user = match repositories.users.find_by_subject(subject).await? {
+            Some(user) if user.is_admin == is_admin => user,
+            _ => repositories.users.upsert(subject, is_admin).await?,
+        };
+        Caller::load(repositories, user).await
+    }
+
+    pub fn user(&self) -> Option<&User> {
+        match self {
+            Caller::Unrestricted => None,
+            Caller::User { user, .. } => Some(user),
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.user().is_none_or(|user| user.is_admin)
+    }
+
+    pub fn team_role(&self, team_id: i32) -> Option<Role> {
+        match self {
+            Caller::User { user, memberships } if !user.is_admin => memberships
+                .iter()
+                .find(|membership| membership.team_id == team_id)
+                .and_then(|membership| Role::parse(&membership.role)),
+            _ => Some(Role::Admin),
+        }
+    }
+
+    /// Owners and admins hold every permission, team members their role in
+    /// the project team. Projects with neither owner nor team predate access
+    /// control and are readable by everyone.
+    pub fn project_role(&self, project: &Project) -> Option<Role> {
+        let Some(user) = self.user() else {
+            return Some(Role::Admin);
+        };
+        if user.is_admin || project.owner_id == Some(user.id) {
+            return Some(Role::Admin);
+        }
+        match (project.owner_id, project.team_id) {
+            (_, Some(team_id)) => self.team_role(team_id),
+            (None, None) => Some(Role::Viewer),
+            (Some(_), None) => None,
+        }
+    }
+
+    /// Projects the caller can't read are reported as missing rather than
+    /// forbidden, their existence is not disclosed.
+    pub fn authorize(
+        &self,
+        project: &Project,
+        permission: Permission,
+    ) -> Result<Role, NinoversePermissionError> {
+        let role = self
+            .project_role(project)
+            .filter(|role| *role >= Permission::Read.required_role())
+            .ok_or_else(|| NinoversePermissionError::NotFound {
+                additional_info: format!("Project {} does not exist.", project.id),
+            })?;
+        if role < permission.required_role() {
+            return Err(NinoversePermissionError::Forbidden {
+                additional_info: format!(
+                    "The {} permission on project {} is required.",
+                    permission.as_str(),
+                    project.id
+                ),
+            });
+        }
+        Ok(role)
+    }
+
+    pub fn project_permissions(&self, project: &Project) -> ProjectPermissions {
+        let role = self.project_role(project);
+        ProjectPermissions {
+            project_id: project.id,
+            role,
+            permissions: role.map(|role| role.permissions()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Checks a permission outside of an HTTP request, e.g. for events consumed
+/// from other services. Subjects never seen by the API have no permission.
+pub async fn check_permission(
+    repositories: &Repositories,
+    subject: &str,
+    project_id: i32,
+    permission: Permission,
+) -> Result<Role, NinoversePermissionError> {
+    let user = repositories
+        .users
+        .find_by_subject(subject)
+        .await?
+        .ok_or_else(|| NinoversePermissionError::Forbidden {
+            additional_info: format!("Unknown user {}.", subject),
+        })?;
+    let project = repositories
+        .projects
+        .get(project_id, true)
+        .await?
+        .ok_or_else(|| NinoversePermissionError::NotFound {
+            additional_info: format!("Project {} does not exist.", project_id),
+        })?;
+    Caller::load(repositories, user)
+        .await?
+        .authorize(&project, permission)
+}
@@ -0,0 +1,173 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    ADMIN_SUBJECT, Credentials, TEST_SUBJECT, TestService, issue_token, new_project,
+};
+use crate::{
+    api_handler::teams::MembershipRequest,
+    db_handler::structs::{NewTeam, ProjectUpdate},
+    permission_handler::{Permission, Role, check_permission, error::NinoversePermissionError},
+};
+
+const OUTSIDER_SUBJECT: &str = "outsider";
+
+fn rename(name: &str) -> ProjectUpdate {
+    ProjectUpdate {
+        name: Some(name.to_string()),
+        description: None,
+        status: None,
+        owner_id: None,
+        team_id: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn team_roles_grant_project_permissions() {
+    let service = TestService::start().await;
+    let owner = &service.client;
+    let outsider = owner.with_credentials(Credentials::Bearer(issue_token(OUTSIDER_SUBJECT)));
+    let project = owner
+        .create_project(&new_project("Private"))
+        .await
+        .into_body();
+
+    // Unreadable projects are not disclosed.
+    assert_eq!(
+        outsider.get_project(project.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert!(outsider.list_projects(false).await.into_body().is_empty());
+    assert_eq!(
+        outsider.get_project_permissions(project.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    let team = owner
+        .create_team(&NewTeam {
+            name: "core".to_string(),
+        })
+        .await
+        .into_body();
+    let viewer = owner
+        .set_team_member(
+            team.id,
+            OUTSIDER_SUBJECT,
+            &MembershipRequest { role: Role::Viewer },
+        )
+        .await
+        .into_body();
+    assert_eq!(viewer.subject, OUTSIDER_SUBJECT);
+    let assignment = ProjectUpdate {
+        team_id: Some(team.id),
+        ..rename("Private")
+    };
+    let project = owner
+        .update_project(project.id, &assignment, None)
+        .await
+        .into_body();
+    assert_eq!(project.team_id, Some(team.id));
+
+    assert_eq!(
+        outsider.get_project(project.id).await.status,
+        StatusCode::OK
+    );
+    assert_eq!(
+        outsider
+            .update_project(project.id, &rename("Hijacked"), None)
+            .await
+            .status,
+        StatusCode::FORBIDDEN
+    );
+    let permissions = outsider
+        .get_project_permissions(project.id)
+        .await
+        .into_body();
+    assert_eq!(permissions.role, Some(Role::Viewer));
+    assert_eq!(permissions.permissions, vec![Permission::Read]);
+
+    owner
+        .set_team_member(
+            team.id,
+            OUTSIDER_SUBJECT,
+            &MembershipRequest { role: Role::Editor },
+        )
+        .await
+        .into_body();
+    assert_eq!(
+        outsider
+            .update_project(project.id, &rename("Edited"), None)
+            .await
+            .status,
+        StatusCode::OK
+    );
+    assert_eq!(
+        outsider.delete_project(project.id, None).await.status,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        outsider
+            .set_team_member(team.id, "someone", &MembershipRequest { role: Role::Admin })
+            .await
+            .status,
+        StatusCode::FORBIDDEN
+    );
+
+    let removed = owner.remove_team_member(team.id, OUTSIDER_SUBJECT).await;
+    assert_eq!(removed.status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        outsider.get_project(project.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+    let members = owner.list_team_members(team.id).await.into_body();
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].subject, TEST_SUBJECT);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn administrators_hold_every_permission() {
+    let service = TestService::start().await;
+    let project = service
+        .client
+        .create_project(&new_project("Administered"))
+        .await
+        .into_body();
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+
+    assert!(admin.current_user().await.into_body().user.is_admin);
+    let permissions = admin.get_project_permissions(project.id).await.into_body();
+    assert_eq!(permissions.role, Some(Role::Admin));
+    assert_eq!(permissions.permissions, Permission::ALL.to_vec());
+    assert_eq!(
+        admin.delete_project(project.id, None).await.status,
+        StatusCode::NO_CONTENT
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn permissions_are_checked_outside_of_requests() {
+    let service = TestService::start().await;
+    let project = service
+        .client
+        .create_project(&new_project("Evented"))
+        .await
+        .into_body();
+    let repositories = &service.repositories;
+
+    let role = check_permission(repositories, TEST_SUBJECT, project.id, Permission::Write)
+        .await
+        .expect("The owner can write");
+    assert_eq!(role, Role::Admin);
+    assert!(matches!(
+        check_permission(repositories, OUTSIDER_SUBJECT, project.id, Permission::Read).await,
+        Err(NinoversePermissionError::Forbidden { .. })
+    ));
+    assert!(matches!(
+        check_permission(repositories, TEST_SUBJECT, project.id + 1, Permission::Read).await,
+        Err(NinoversePermissionError::NotFound { .. })
+    ));
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,246 @@
+use bytes::Bytes;
+use chrono::{TimeDelta, Utc};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+
+use super::harness::{
+    ATTACHMENT_MAX_BYTES, Credentials, TestService, create_project, issue_token, new_project,
+};
+use crate::{
+    blob_handler::{BlobStore, local::LocalBlobStore},
+    db_handler::{repository::DEFAULT_TENANT, sweep_orphaned_blobs},
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn attachments_are_streamed_back_with_ranges() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Specs")).await;
+    let content: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    let sha256 = hex::encode(Sha256::digest(&content));
+
+    let response = client
+        .upload_attachment(
+            project.id,
+            "drafts/spec.pdf",
+            "application/pdf",
+            content.clone(),
+            Some(&sha256.to_uppercase()),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    let attachment = response.into_body();
+    assert_eq!(attachment.file_name, "spec.pdf");
+    assert_eq!(attachment.content_type, "application/pdf");
+    assert_eq!(attachment.size, 1000);
+    assert_eq!(attachment.sha256, sha256);
+    let listed = client.list_attachments(project.id).await.into_body();
+    assert_eq!(
+        listed.iter().map(|listed| listed.id).collect::<Vec<i32>>(),
+        vec![attachment.id]
+    );
+
+    let download = client
+        .download_attachment(project.id, attachment.id, None)
+        .await;
+    assert_eq!(download.status, StatusCode::OK);
+    assert_eq!(download.body, content);
+    assert_eq!(download.header("accept-ranges"), Some("bytes"));
+    assert_eq!(download.header("content-type"), Some("application/pdf"));
+    assert_eq!(
+        download.header("etag"),
+        Some(format!("\"{}\"", sha256).as_str())
+    );
+
+    for (range, start, end) in [
+        ("bytes=0-9", 0, 9),
+        ("bytes=990-5000", 990, 999),
+        ("bytes=-5", 995, 999),
+        ("bytes=500-", 500, 999),
+    ] {
+        let partial = client
+            .download_attachment(project.id, attachment.id, Some(range))
+            .await;
+        assert_eq!(partial.status, StatusCode::PARTIAL_CONTENT, "{}", range);
+        assert_eq!(partial.body, content[start..=end], "{}", range);
+        assert_eq!(
+            partial.header("content-range"),
+            Some(format!("bytes {}-{}/1000", start, end).as_str())
+        );
+    }
+    let outside = client
+        .download_attachment(project.id, attachment.id, Some("bytes=1000-"))
+        .await;
+    assert_eq!(outside.status, StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(outside.header("content-range"), Some("bytes */1000"));
+    // Several ranges are not supported, the whole content is sent instead.
+    let several = client
+        .download_attachment(project.id, attachment.id, Some("bytes=0-1,5-6"))
+        .await;
+    assert_eq!(several.status, StatusCode::OK);
+    assert_eq!(several.body.len(), 1000);
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn uploads_are_checked_before_being_stored() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Limits")).await;
+
+    assert_eq!(
+        client
+            .upload_attachment(
+                project.id,
+                "notes.txt",
+                "text/plain",
+                b"hello".to_vec(),
+                Some(&"0".repeat(64)),
+            )
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        client
+            .upload_attachment(
+                project.id,
+                "large.txt",
+                "text/plain",
+                vec![b'a'; ATTACHMENT_MAX_BYTES + 1],
+                None,
+            )
+            .await
+            .status,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        client
+            .upload_attachment(
+                project.id,
+                "archive.zip",
+                "application/zip",
+                b"PK".to_vec(),
+                None,
+            )
+            .await
+            .status,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+    assert!(
+        client
+            .list_attachments(project.id)
+            .await
+            .into_body()
+            .is_empty()
+    );
+    // Nothing was written for the rejected uploads.
+    assert!(
+        !service
+            .blob_root
+            .join(DEFAULT_TENANT)
+            .join(project.id.to_string())
+            .exists()
+    );
+
+    let image = client
+        .upload_attachment(project.id, "logo.png", "image/png", vec![1, 2, 3], None)
+        .await;
+    assert_eq!(image.status, StatusCode::CREATED);
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn deleting_attachments_removes_their_content() {
+    let service = TestService::start().await;
+    let owner = &service.client;
+    let outsider = owner.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let project = create_project(owner, &new_project("Cleanup")).await;
+    let attachment = owner
+        .upload_attachment(project.id, "a.txt", "text/plain", b"a".to_vec(), None)
+        .await
+        .into_body();
+
+    assert_eq!(
+        outsider
+            .download_attachment(project.id, attachment.id, None)
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        outsider
+            .delete_attachment(project.id, attachment.id)
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        owner
+            .delete_attachment(project.id, attachment.id)
+            .await
+            .status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        owner
+            .download_attachment(project.id, attachment.id, None)
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    assert!(
+        owner
+            .list_attachments(project.id)
+            .await
+            .into_body()
+            .is_empty()
+    );
+    let blobs = service
+        .blob_root
+        .join(DEFAULT_TENANT)
+        .join(project.id.to_string());
+    assert_eq!(std::fs::read_dir(blobs).unwrap().count(), 0);
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn orphaned_blobs_are_swept_once_old_enough() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Sweep")).await;
+    let attachment = client
+        .upload_attachment(project.id, "kept.txt", "text/plain", b"kept".to_vec(), None)
+        .await
+        .into_body();
+    let blob_store = LocalBlobStore::new(service.blob_root.clone());
+    let orphan_key = format!("{}/{}/orphan", DEFAULT_TENANT, project.id);
+    blob_store
+        .put(&orphan_key, Bytes::from_static(b"orphan"))
+        .await
+        .expect("Put failed");
+    let repositories = service.repositories.for_tenant(DEFAULT_TENANT);
+
+    // Too recent, it could be an upload whose row isn't there yet.
+    let swept =
+        sweep_orphaned_blobs(&repositories, &blob_store, Utc::now() - TimeDelta::hours(1)).await;
+    assert!(swept.is_empty());
+    let swept = sweep_orphaned_blobs(
+        &repositories,
+        &blob_store,
+        Utc::now() + TimeDelta::minutes(1),
+    )
+    .await;
+    assert_eq!(swept, [orphan_key]);
+    assert_eq!(
+        client
+            .download_attachment(project.id, attachment.id, None)
+            .await
+            .body,
+        b"kept"
+    );
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,65 @@
+use reqwest::StatusCode;
+
+use super::harness::{Credentials, TEST_SUBJECT, TestService, issue_token};
+use crate::api_handler::api_keys::ApiKeyRequest;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn missing_credentials_are_rejected() {
+    let service = TestService::start().await;
+    let anonymous = service.client.with_credentials(Credentials::Anonymous);
+    let response = anonymous.list_projects(false).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert!(response.request_id.is_some());
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn invalid_bearer_token_is_rejected() {
+    let service = TestService::start().await;
+    let forged = format!("{}x", issue_token(TEST_SUBJECT));
+    let response = service
+        .client
+        .with_credentials(Credentials::Bearer(forged))
+        .list_projects(false)
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn api_key_lifecycle() {
+    let service = TestService::start().await;
+    let issued = service
+        .client
+        .create_api_key(&ApiKeyRequest {
+            name: "ci".to_string(),
+            expires_at: None,
+        })
+        .await
+        .into_body();
+    assert_eq!(issued.api_key.subject, TEST_SUBJECT);
+    assert!(issued.key.starts_with(&issued.api_key.prefix));
+
+    let with_key = service
+        .client
+        .with_credentials(Credentials::ApiKey(issued.key.clone()));
+    let listed = with_key.list_api_keys().await.into_body();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, issued.api_key.id);
+
+    let revoked = with_key.revoke_api_key(issued.api_key.id).await;
+    assert_eq!(revoked.status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        with_key.list_projects(false).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        service
+            .client
+            .revoke_api_key(issued.api_key.id)
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use futures::StreamExt;
+
+use super::harness::new_task;
+use crate::{
+    cli_handler::{
+        projects::{export_projects, import_projects},
+        replay::audit_records_to_replay,
+        run_command,
+    },
+    db_handler::{
+        audit::MAX_AUDIT_LIMIT,
+        repository::Repositories,
+        structs::{AuditFilter, AuditRecord, NewAuditRecord, NewProject, NewTenant, Task},
+    },
+    kafka_handler::{
+        error::NinoverseKafkaError,
+        in_memory::InMemoryMessageBus,
+        message_bus::{MessageBus, TopicSettings},
+        republish_audit_records,
+    },
+};
+
+fn audit_record(entity_type: &str, entity_id: i32) -> NewAuditRecord {
+    NewAuditRecord {
+        actor: "tester".to_string(),
+        action: "update".to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id,
+        before: None,
+        after: None,
+        request_id: None,
+    }
+}
+
+#[tokio::test]
+async fn projects_are_exported_and_imported_into_another_tenant() {
+    let repositories = Repositories::in_memory();
+    repositories
+        .tenants
+        .insert(&NewTenant {
+            id: "acme".to_string(),
+            name: "Acme".to_string(),
+        })
+        .await
+        .unwrap()
+        .expect("Tenant not created");
+    let project = repositories
+        .projects
+        .insert(
+            &NewProject {
+                name: "Exported".to_string(),
+                description: Some("With subtasks".to_string()),
+                status: "draft".to_string(),
+                team_id: None,
+            },
+            Some(1),
+        )
+        .await
+        .unwrap();
+    let parent = repositories
+        .tasks
+        .insert(project.id, &new_task("Parent", None))
+        .await
+        .unwrap();
+    repositories
+        .tasks
+        .insert(project.id, &new_task("Child", Some(parent.id)))
+        .await
+        .unwrap();
+
+    let exported = export_projects(&repositories).await.unwrap();
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0].tasks.len(), 2);
+    // The file format round-trips.
+    let exported: Vec<_> =
+        serde_json::from_str(&serde_json::to_string(&exported).unwrap()).unwrap();
+
+    let acme = repositories.for_tenant("acme");
+    let imported = import_projects(&acme, &exported).await.unwrap();
+    assert_eq!(imported.len(), 1);
+    assert_eq!(imported[0].name, "Exported");
+    assert_eq!(imported[0].owner_id, None);
+    let tasks = acme.tasks.list(imported[0].id).await.unwrap();
+    let imported_parent = tasks.iter().find(|task| task.title == "Parent").unwrap();
+    let imported_child = tasks.iter().find(|task| task.title == "Child").unwrap();
+    assert_eq!(imported_child.parent_id, Some(imported_parent.id));
+    assert!(
+        acme.audit
+            .list(&AuditFilter::default())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn imports_with_unknown_statuses_insert_nothing() {
+    let repositories = Repositories::in_memory();
+    for status in ["draft", "unknown"] {
+        repositories
+            .projects
+            .insert(
+                &NewProject {
+                    name: status.to_string(),
+                    description: None,
+                    status: "draft".to_string(),
+                    team_id: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    let mut exported = export_projects(&repositories).await.unwrap();
+    exported[1].project.status = "unknown".to_string();
+
+    let target = Repositories::in_memory();
+    assert!(import_projects(&target, &exported).await.is_err());
+    assert!(target.projects.list(true).await.unwrap().is_empty());
+
+    // Tasks are checked as well, before the first project is inserted.
+    exported[1].project.status = "draft".to_string();
+    let task = repositories
+        .tasks
+        .insert(exported[1].project.id, &new_task("Someday", None))
+        .await
+        .unwrap();
+    for (status, priority) in [("shelved", "medium"), ("todo", "someday")] {
+        exported[1].tasks = vec![Task {
+            status: status.to_string(),
+            priority: priority.to_string(),
+            ..task.clone()
+        }];
+        let error = import_projects(&target, &exported).await.unwrap_err();
+        assert!(error.to_string().contains("unknown"), "{}", error);
+        assert!(target.projects.list(true).await.unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn errors_explain_what_is_wrong() {
+    let error = run_command(&["frobnicate".to_string()]).await.unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("CLI: Unknown command. Unknown command frobnicate. Usage:")
+    );
+}
+
+#[tokio::test]
+async fn topics_are_administered_on_the_message_bus() {
+    let message_bus = InMemoryMessageBus::new();
+    message_bus
+        .create_topic(
+            "replayed",
+            &TopicSettings {
+                partitions: 3,
+                replication: 1,
+                configs: BTreeMap::new(),
+            },
+        )
+        .await
+        .unwrap();
+    message_bus
+        .publish("events", "key", "payload")
+        .await
+        .unwrap();
+
+    let topics = message_bus.list_topics().await.unwrap();
+    let names: Vec<&str> = topics.iter().map(|topic| topic.name.as_str()).collect();
+    assert_eq!(names, ["events", "replayed"]);
+    let replayed = message_bus.describe_topic("replayed").await.unwrap();
+    assert_eq!(replayed.partitions.len(), 3);
+
+    let deletion = message_bus
+        .delete_topics(&["replayed".to_string(), "missing".to_string()])
+        .await;
+    assert!(matches!(
+        deletion,
+        Err(NinoverseKafkaError::UnknownTopic { .. })
+    ));
+    assert!(matches!(
+        message_bus.describe_topic("replayed").await,
+        Err(NinoverseKafkaError::UnknownTopic { .. })
+    ));
+}
+
+#[tokio::test]
+async fn audit_records_are_replayed_oldest_first() {
+    let repositories = Repositories::in_memory();
+    // More than a page of the audit list.
+    let record_count = MAX_AUDIT_LIMIT as i32 + 5;
+    for entity_id in 0..record_count {
+        repositories
+            .audit
+            .insert(&audit_record("project", entity_id))
+            .await
+            .unwrap();
+    }
+    repositories
+        .audit
+        .insert(&audit_record("task", 0))
+        .await
+        .unwrap();
+
+    let records = audit_records_to_replay(
+        &repositories,
+        AuditFilter {
+            entity_type: Some("project".to_string()),
+            ..AuditFilter::default()
+        },
+    )
+    .await
+    .unwrap();
+    let entity_ids: Vec<i32> = records.iter().map(|record| record.entity_id).collect();
+    assert_eq!(entity_ids, (0..record_count).collect::<Vec<_>>());
+
+    let message_bus = InMemoryMessageBus::new();
+    republish_audit_records(&message_bus, "default", "ninoverse.audit", &records[..2])
+        .await
+        .unwrap();
+    let replayed: Vec<AuditRecord> = message_bus
+        .subscribe("replay-test", &["ninoverse.audit"])
+        .unwrap()
+        .take(2)
+        .map(|message| serde_json::from_str(&message.unwrap().payload.unwrap()).unwrap())
+        .collect()
+        .await;
+    assert_eq!(replayed[0].id, records[0].id);
+    assert_eq!(replayed[1].id, records[1].id);
+}
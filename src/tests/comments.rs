@@ -0,0 +1,222 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    ADMIN_SUBJECT, ApiClient, Credentials, TestService, create_project, issue_token, new_project,
+};
+use crate::{
+    db_handler::structs::{
+        Activity, Comment, CommentUpdate, NewComment, NewTask, ProjectUpdate, TransitionRequest,
+    },
+    kafka_handler::structs::KafkaNinoverseEvent,
+};
+
+async fn comment(client: &ApiClient, item: &str, body: &str, parent_id: Option<i32>) -> Comment {
+    let response = client
+        .create_comment(
+            item,
+            &NewComment {
+                body: body.to_string(),
+                parent_id,
+            },
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    response.into_body()
+}
+
+fn bodies(comments: &[Comment]) -> Vec<&str> {
+    comments
+        .iter()
+        .map(|comment| comment.body.as_str())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn comment_threads_keep_their_edit_history() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let project = create_project(client, &new_project("Docs")).await;
+    let project_path = format!("/projects/{}", project.id);
+    let task = client
+        .create_task(
+            project.id,
+            &NewTask {
+                title: "Outline".to_string(),
+                description: None,
+                parent_id: None,
+                assignee_id: None,
+                status: "todo".to_string(),
+                priority: "medium".to_string(),
+                due_date: None,
+            },
+        )
+        .await
+        .into_body();
+    let task_path = format!("{}/tasks/{}", project_path, task.id);
+
+    let question = comment(client, &project_path, "Which **format**?", None).await;
+    let answer = comment(&admin, &project_path, "Markdown.", Some(question.id)).await;
+    comment(client, &task_path, "Started the outline.", None).await;
+    // A reply stays on the item of the comment it answers.
+    assert_eq!(
+        client
+            .create_comment(
+                &task_path,
+                &NewComment {
+                    body: "Off topic".to_string(),
+                    parent_id: Some(question.id),
+                },
+            )
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        bodies(&client.list_comments(&project_path).await.into_body()),
+        vec!["Which **format**?", "Markdown."]
+    );
+    assert_eq!(
+        bodies(&client.list_comments(&task_path).await.into_body()),
+        vec!["Started the outline."]
+    );
+
+    // Only the author edits, the previous bodies are kept.
+    let edit = CommentUpdate {
+        body: "Which format, Markdown or HTML?".to_string(),
+    };
+    assert_eq!(
+        admin
+            .update_comment(project.id, question.id, &edit)
+            .await
+            .status,
+        StatusCode::FORBIDDEN
+    );
+    let edited = client
+        .update_comment(project.id, question.id, &edit)
+        .await
+        .into_body();
+    assert!(edited.edited);
+    assert!(!answer.edited);
+    let revisions = client
+        .list_comment_revisions(project.id, question.id)
+        .await
+        .into_body();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].body, "Which **format**?");
+
+    // Deleting a comment deletes its replies.
+    assert_eq!(
+        client.delete_comment(project.id, question.id).await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert!(
+        client
+            .list_comments(&project_path)
+            .await
+            .into_body()
+            .is_empty()
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mentions_notify_known_users_once() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let outsider = client.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    // Users are known from their first request on.
+    outsider.current_user().await;
+    admin.current_user().await;
+    let mut probe = service.kafka_probe(&["ninoverse"]);
+    let project = create_project(client, &new_project("Launch")).await;
+
+    let posted = comment(
+        client,
+        &format!("/projects/{}", project.id),
+        "Thanks @outsider, ask @nobody or mail admin@example.com.",
+        None,
+    )
+    .await;
+    client
+        .update_comment(
+            project.id,
+            posted.id,
+            &CommentUpdate {
+                body: "Thanks @outsider and @admin.".to_string(),
+            },
+        )
+        .await;
+
+    let mut mentioned = Vec::new();
+    for _ in 0..2 {
+        let event: KafkaNinoverseEvent = probe
+            .wait_for_json(|message| {
+                message
+                    .payload
+                    .as_deref()
+                    .is_some_and(|payload| payload.contains("\"user_mentioned\""))
+            })
+            .await;
+        let KafkaNinoverseEvent::UserMentioned {
+            comment, subject, ..
+        } = event
+        else {
+            panic!("Expected a user_mentioned event");
+        };
+        assert_eq!(comment.id, posted.id);
+        mentioned.push(subject);
+    }
+    assert_eq!(mentioned, vec!["outsider", ADMIN_SUBJECT]);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn activity_feed_merges_comments_transitions_and_edits() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Release")).await;
+    let project_path = format!("/projects/{}", project.id);
+
+    comment(client, &project_path, "Kick-off", None).await;
+    client
+        .update_project(
+            project.id,
+            &ProjectUpdate {
+                name: None,
+                description: Some("Version 2".to_string()),
+                status: None,
+                owner_id: None,
+                team_id: None,
+            },
+            None,
+        )
+        .await;
+    client
+        .transition_project(
+            project.id,
+            &TransitionRequest {
+                to_status: "active".to_string(),
+            },
+        )
+        .await;
+    comment(client, &project_path, "Under way", None).await;
+
+    let activity = client.project_activity(project.id, 100).await.into_body();
+    assert_eq!(activity.len(), 4);
+    assert!(matches!(&activity[0], Activity::Comment(comment) if comment.body == "Kick-off"));
+    assert!(matches!(&activity[1], Activity::FieldEdit(record) if record.entity_type == "project"));
+    assert!(
+        matches!(&activity[2], Activity::StatusTransition(entry) if entry.to_status == "active")
+    );
+    assert!(matches!(&activity[3], Activity::Comment(comment) if comment.body == "Under way"));
+
+    // The limit keeps the most recent entries.
+    let latest = client.project_activity(project.id, 2).await.into_body();
+    assert!(matches!(
+        &latest[..],
+        [Activity::StatusTransition(_), Activity::Comment(_)]
+    ));
+    service.shutdown().await.expect("Service failed");
+}
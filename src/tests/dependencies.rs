@@ -0,0 +1,322 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    ADMIN_SUBJECT, Credentials, TestService, create_project, create_task, issue_token, new_project,
+    new_task,
+};
+use crate::db_handler::structs::{
+    NewProject, NewStatusType, Project, Task, TaskUpdate, TransitionRequest,
+};
+
+fn active_project(name: &str) -> NewProject {
+    NewProject {
+        status: "active".to_string(),
+        ..new_project(name)
+    }
+}
+
+fn project_path(project: &Project) -> String {
+    format!("/projects/{}", project.id)
+}
+
+fn task_path(task: &Task) -> String {
+    format!("/projects/{}/tasks/{}", task.project_id, task.id)
+}
+
+fn project_names(projects: &[Project]) -> Vec<&str> {
+    projects
+        .iter()
+        .map(|project| project.name.as_str())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn project_dependencies_reject_cycles_and_flag_blocked_projects() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let design = create_project(client, &active_project("Design")).await;
+    let build = create_project(client, &new_project("Build")).await;
+    let launch = create_project(client, &new_project("Launch")).await;
+
+    let added = client
+        .add_dependency(&project_path(&build), design.id)
+        .await;
+    assert_eq!(added.status, StatusCode::CREATED);
+    assert_eq!(
+        client
+            .add_dependency(&project_path(&launch), build.id)
+            .await
+            .status,
+        StatusCode::CREATED
+    );
+    assert!(client.get_project(build.id).await.into_body().blocked);
+    assert!(!client.get_project(design.id).await.into_body().blocked);
+
+    let cycle = client
+        .add_dependency(&project_path(&design), launch.id)
+        .await;
+    assert_eq!(cycle.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(cycle.into_error().additional_info.contains(&format!(
+        "{} -> {} -> {} -> {}",
+        design.id, launch.id, build.id, design.id
+    )));
+    for blocking_id in [build.id, design.id, 9999] {
+        assert_eq!(
+            client
+                .add_dependency(&project_path(&build), blocking_id)
+                .await
+                .status,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    // Completing the blocking project unblocks its dependents.
+    client
+        .transition_project(
+            design.id,
+            &TransitionRequest {
+                to_status: "done".to_string(),
+            },
+        )
+        .await;
+    assert!(!client.get_project(build.id).await.into_body().blocked);
+
+    assert_eq!(
+        client
+            .remove_dependency(&project_path(&launch), build.id)
+            .await
+            .status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        client
+            .remove_dependency(&project_path(&launch), build.id)
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    assert!(
+        client
+            .list_dependencies(&project_path(&launch))
+            .await
+            .into_body()
+            .is_empty()
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_dependencies_never_close_a_cycle() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let projects = [
+        create_project(client, &new_project("Front")).await,
+        create_project(client, &new_project("Back")).await,
+        create_project(client, &new_project("Infra")).await,
+    ];
+
+    // Each one alone is fine, together they would close a cycle.
+    let statuses = futures::future::join_all((0..projects.len()).map(|index| {
+        let blocked = &projects[index];
+        let blocking = &projects[(index + 1) % projects.len()];
+        async move {
+            client
+                .add_dependency(&project_path(blocked), blocking.id)
+                .await
+                .status
+        }
+    }))
+    .await;
+    assert_eq!(
+        statuses
+            .iter()
+            .filter(|status| **status == StatusCode::CREATED)
+            .count(),
+        projects.len() - 1
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn project_closures_skip_completed_and_unreadable_projects() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let outsider = client.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let design = create_project(client, &active_project("Design")).await;
+    let build = create_project(client, &new_project("Build")).await;
+    let test = create_project(client, &new_project("Test")).await;
+    let launch = create_project(client, &new_project("Launch")).await;
+    let hidden = create_project(&outsider, &new_project("Hidden")).await;
+    for (blocked, blocking) in [
+        (&build, &design),
+        (&test, &build),
+        (&launch, &design),
+        (&launch, &test),
+    ] {
+        client
+            .add_dependency(&project_path(blocked), blocking.id)
+            .await;
+    }
+    // Only someone who can read both projects can link them.
+    assert_eq!(
+        outsider
+            .add_dependency(&project_path(&hidden), launch.id)
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    admin
+        .add_dependency(&project_path(&hidden), launch.id)
+        .await;
+
+    let upstream: Vec<Project> = client
+        .related_items(&project_path(&launch), "upstream")
+        .await
+        .into_body();
+    assert_eq!(project_names(&upstream), vec!["Design", "Build", "Test"]);
+    let downstream: Vec<Project> = client
+        .related_items(&project_path(&design), "downstream")
+        .await
+        .into_body();
+    assert_eq!(project_names(&downstream), vec!["Build", "Test", "Launch"]);
+    let critical_path: Vec<Project> = client
+        .related_items(&project_path(&launch), "critical_path")
+        .await
+        .into_body();
+    assert_eq!(
+        project_names(&critical_path),
+        vec!["Design", "Build", "Test", "Launch"]
+    );
+
+    client
+        .transition_project(
+            design.id,
+            &TransitionRequest {
+                to_status: "done".to_string(),
+            },
+        )
+        .await;
+    let critical_path: Vec<Project> = client
+        .related_items(&project_path(&launch), "critical_path")
+        .await
+        .into_body();
+    assert_eq!(
+        project_names(&critical_path),
+        vec!["Build", "Test", "Launch"]
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn task_dependencies_stay_within_their_project() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Planned")).await;
+    let other_project = create_project(client, &new_project("Elsewhere")).await;
+    let write = create_task(client, project.id, &new_task("Write", None)).await;
+    let review = create_task(client, project.id, &new_task("Review", None)).await;
+    let foreign = create_task(client, other_project.id, &new_task("Foreign", None)).await;
+
+    assert_eq!(
+        client
+            .add_dependency(&task_path(&review), foreign.id)
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    client.add_dependency(&task_path(&review), write.id).await;
+    assert_eq!(
+        client
+            .add_dependency(&task_path(&write), review.id)
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert!(
+        client
+            .get_task(project.id, review.id)
+            .await
+            .into_body()
+            .blocked
+    );
+    let upstream: Vec<Task> = client
+        .related_items(&task_path(&review), "upstream")
+        .await
+        .into_body();
+    assert_eq!(
+        upstream.iter().map(|task| task.id).collect::<Vec<i32>>(),
+        vec![write.id]
+    );
+
+    client
+        .update_task(
+            project.id,
+            write.id,
+            &TaskUpdate {
+                status: Some("done".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert!(
+        !client
+            .get_task(project.id, review.id)
+            .await
+            .into_body()
+            .blocked
+    );
+
+    // Any completed status type unblocks, as for projects.
+    let set_status = |status: &str| TaskUpdate {
+        status: Some(status.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        client
+            .update_task(project.id, write.id, &set_status("shipped"))
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    admin
+        .create_status_type(&NewStatusType {
+            name: "shipped".to_string(),
+            completed: true,
+        })
+        .await
+        .into_body();
+    client
+        .update_task(project.id, write.id, &set_status("in_progress"))
+        .await;
+    assert!(
+        client
+            .get_task(project.id, review.id)
+            .await
+            .into_body()
+            .blocked
+    );
+    client
+        .update_task(project.id, write.id, &set_status("shipped"))
+        .await
+        .into_body();
+    assert!(
+        !client
+            .get_task(project.id, review.id)
+            .await
+            .into_body()
+            .blocked
+    );
+
+    // Deleting a task drops its dependencies.
+    client.delete_task(project.id, write.id).await;
+    assert!(
+        client
+            .list_dependencies(&task_path(&review))
+            .await
+            .into_body()
+            .is_empty()
+    );
+    service.shutdown().await.expect("Service failed");
+}
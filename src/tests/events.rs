@@ -0,0 +1,163 @@
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use reqwest::StatusCode;
+
+use super::harness::{TEST_SUBJECT, TestBackends, TestService};
+use crate::{
+    db_handler::{
+        repository::Repositories,
+        structs::{NewProject, TransitionRequest},
+    },
+    kafka_handler::{
+        in_memory::InMemoryMessageBus, message_bus::MessageBus, structs::KafkaNinoverseEvent,
+    },
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_transition_emits_an_event() {
+    let service = TestService::start().await;
+    let mut probe = service.kafka_probe(&["ninoverse"]);
+    let project = service
+        .client
+        .create_project(&NewProject {
+            name: "Evented".to_string(),
+            description: Some("Ready to start".to_string()),
+            status: "draft".to_string(),
+            team_id: None,
+        })
+        .await
+        .into_body();
+
+    let transition = service
+        .client
+        .transition_project(
+            project.id,
+            &TransitionRequest {
+                to_status: "active".to_string(),
+            },
+        )
+        .await;
+    assert_eq!(transition.status, StatusCode::CREATED);
+
+    let expected_key = format!("default:project-{}", project.id);
+    let event: KafkaNinoverseEvent = probe
+        .wait_for_json(|message| message.key.as_deref() == Some(expected_key.as_str()))
+        .await;
+    let KafkaNinoverseEvent::ProjectStatusChanged {
+        project_id,
+        from_status,
+        to_status,
+        actor,
+        ..
+    } = event
+    else {
+        panic!("Expected a project_status_changed event");
+    };
+    assert_eq!(project_id, project.id);
+    assert_eq!(from_status, "draft");
+    assert_eq!(to_status, "active");
+    assert_eq!(actor, TEST_SUBJECT);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_transition_emits_nothing() {
+    let service = TestService::start().await;
+    let mut probe = service.kafka_probe(&["ninoverse"]);
+    let project = service
+        .client
+        .create_project(&NewProject {
+            name: "Undescribed".to_string(),
+            description: None,
+            status: "draft".to_string(),
+            team_id: None,
+        })
+        .await
+        .into_body();
+
+    let transition = service
+        .client
+        .transition_project(
+            project.id,
+            &TransitionRequest {
+                to_status: "active".to_string(),
+            },
+        )
+        .await;
+    assert_eq!(transition.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // The greeting goes through the same producer, once it arrives anything
+    // published before it would have arrived too.
+    assert_eq!(service.client.send_greeting().await, StatusCode::OK);
+    let message = probe
+        .wait_for(|message| message.key.as_deref() == Some("base_endpoint"))
+        .await;
+    assert_eq!(message.topic, "ninoverse");
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn consumer_handles_published_messages() {
+    let message_bus = InMemoryMessageBus::new();
+    let service = TestService::start_with(TestBackends {
+        repositories: Repositories::in_memory(),
+        message_bus: Arc::new(message_bus.clone()),
+        database_dir: None,
+    })
+    .await;
+
+    for index in 0..3 {
+        message_bus
+            .publish("ninoverse", "external", &format!("Message {}", index))
+            .await
+            .expect("Publish failed");
+    }
+    let started = tokio::time::Instant::now();
+    while message_bus.pending_messages("ninoverse", "ninoverse") > 0 {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "The service consumer did not catch up"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pattern_subscriptions_follow_topics_created_later() {
+    let message_bus = InMemoryMessageBus::new();
+    let mut messages = message_bus
+        .subscribe(
+            "pattern-test",
+            &["ninoverse", "^ninoverse\\.[a-z0-9][a-z0-9_-]*$"],
+        )
+        .expect("Subscribe failed");
+
+    for topic in ["ninoverse.audit.acme", "ninoverse.acme", "ninoverse"] {
+        message_bus
+            .publish(topic, "key", topic)
+            .await
+            .expect("Publish failed");
+    }
+    let mut received = vec![];
+    for _ in 0..2 {
+        let message = tokio::time::timeout(Duration::from_secs(5), messages.next())
+            .await
+            .expect("No message received")
+            .expect("Stream ended")
+            .expect("Stream failed");
+        received.push(message.topic);
+    }
+    received.sort();
+    assert_eq!(received, ["ninoverse", "ninoverse.acme"]);
+    assert_eq!(
+        message_bus.pending_messages("pattern-test", "ninoverse.audit.acme"),
+        1
+    );
+    assert!(
+        message_bus
+            .subscribe("pattern-test", &["^ninoverse.(unclosed"])
+            .is_err()
+    );
+}
@@ -0,0 +1,368 @@
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+use super::harness::{
+    ADMIN_SUBJECT, ApiClient, Credentials, TestService, create_project, create_task, issue_token,
+    new_project, new_task,
+};
+use crate::db_handler::structs::{AuditFilter, NewComment};
+
+async fn comment(client: &ApiClient, item: &str, body: &str) {
+    client
+        .create_comment(
+            item,
+            &NewComment {
+                body: body.to_string(),
+                parent_id: None,
+            },
+        )
+        .await
+        .into_body();
+}
+
+/// The `data` of the response, panics with its errors.
+async fn data(client: &ApiClient, query: &str, variables: Value) -> Value {
+    let mut response = client.graphql(query, variables).await.into_body();
+    assert!(response["errors"].is_null(), "{}", response["errors"]);
+    response["data"].take()
+}
+
+/// The `code` extension of the only error of the response.
+async fn error_code(client: &ApiClient, query: &str, variables: Value) -> String {
+    let response = client.graphql(query, variables).await.into_body();
+    let errors = response["errors"].as_array().expect("Expected errors");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    errors[0]["extensions"]["code"]
+        .as_str()
+        .unwrap_or_else(|| panic!("No code in {:?}", errors))
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queries_resolve_nested_tasks_and_comments() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let outsider = client.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let launch = create_project(client, &new_project("Launch")).await;
+    let write = create_task(client, launch.id, &new_task("Write", None)).await;
+    let ship = create_task(client, launch.id, &new_task("Ship", None)).await;
+    let empty = create_project(client, &new_project("Empty")).await;
+    comment(client, &format!("/projects/{}", launch.id), "Kick-off").await;
+    comment(
+        client,
+        &format!("/projects/{}/tasks/{}", launch.id, write.id),
+        "Draft ready",
+    )
+    .await;
+
+    let projects = data(
+        client,
+        "{ projects { id name statusType { name completed } \
+           tasks { id title comments { body } } comments { body taskId } } }",
+        json!({}),
+    )
+    .await;
+    assert_eq!(
+        projects,
+        json!({ "projects": [
+            {
+                "id": launch.id,
+                "name": "Launch",
+                "statusType": { "name": "draft", "completed": false },
+                "tasks": [
+                    { "id": write.id, "title": "Write", "comments": [{ "body": "Draft ready" }] },
+                    { "id": ship.id, "title": "Ship", "comments": [] },
+                ],
+                "comments": [
+                    { "body": "Kick-off", "taskId": null },
+                    { "body": "Draft ready", "taskId": write.id },
+                ],
+            },
+            {
+                "id": empty.id,
+                "name": "Empty",
+                "statusType": { "name": "draft", "completed": false },
+                "tasks": [],
+                "comments": [],
+            },
+        ]})
+    );
+
+    let task = data(
+        client,
+        "query Task($projectId: Int!, $id: Int!) { task(projectId: $projectId, id: $id) { title blocked } }",
+        json!({ "projectId": launch.id, "id": ship.id }),
+    )
+    .await;
+    assert_eq!(
+        task,
+        json!({ "task": { "title": "Ship", "blocked": false } })
+    );
+    let status_types = data(
+        client,
+        "{ statusTypes { name } statusTransitions { fromStatus } }",
+        json!({}),
+    )
+    .await;
+    assert!(
+        status_types["statusTypes"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({ "name": "draft" }))
+    );
+
+    // Projects the caller can't read are left out or refused as in REST.
+    assert_eq!(
+        data(&outsider, "{ projects { id } }", json!({})).await,
+        json!({ "projects": [] })
+    );
+    let project_query = "query Project($id: Int!) { project(id: $id) { name } }";
+    assert_eq!(
+        error_code(&outsider, project_query, json!({ "id": launch.id })).await,
+        "NOT_FOUND"
+    );
+    assert_eq!(
+        error_code(client, project_query, json!({ "id": 4242 })).await,
+        "NOT_FOUND"
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mutations_apply_the_rest_checks() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+
+    let created = data(
+        client,
+        "mutation Create($input: NewProject!) { createProject(input: $input) { id version } }",
+        json!({ "input": { "name": "Graph", "status": "draft" } }),
+    )
+    .await;
+    let project_id = created["createProject"]["id"].as_i64().unwrap() as i32;
+    let version = created["createProject"]["version"].as_i64().unwrap();
+    let update = "mutation Update($id: Int!, $version: Int, $input: ProjectUpdate!) { \
+                  updateProject(id: $id, version: $version, input: $input) { name version } }";
+    let updated = data(
+        client,
+        update,
+        json!({ "id": project_id, "version": version, "input": { "name": "Graphs" } }),
+    )
+    .await;
+    assert_eq!(updated["updateProject"]["name"], "Graphs");
+    assert_eq!(
+        error_code(
+            client,
+            update,
+            json!({ "id": project_id, "version": version, "input": { "name": "Stale" } }),
+        )
+        .await,
+        "PRECONDITION_FAILED"
+    );
+    assert_eq!(
+        error_code(
+            client,
+            update,
+            json!({ "id": project_id, "input": { "status": "done" } }),
+        )
+        .await,
+        "CONFLICT"
+    );
+
+    let create_task = "mutation CreateTask($projectId: Int!, $input: NewTask!) { \
+                       createTask(projectId: $projectId, input: $input) { id status priority } }";
+    assert_eq!(
+        error_code(
+            client,
+            create_task,
+            json!({ "projectId": project_id, "input": { "title": " " } }),
+        )
+        .await,
+        "UNPROCESSABLE_ENTITY"
+    );
+    let task = data(
+        client,
+        create_task,
+        json!({ "projectId": project_id, "input": { "title": "Resolve" } }),
+    )
+    .await;
+    // The defaults of the REST API.
+    assert_eq!(task["createTask"]["status"], "todo");
+    assert_eq!(task["createTask"]["priority"], "medium");
+    let task_id = task["createTask"]["id"].as_i64().unwrap() as i32;
+    data(
+        client,
+        "mutation UpdateTask($projectId: Int!, $id: Int!) { \
+         updateTask(projectId: $projectId, id: $id, input: { status: \"done\" }) { status } }",
+        json!({ "projectId": project_id, "id": task_id }),
+    )
+    .await;
+    assert_eq!(
+        client
+            .get_task(project_id, task_id)
+            .await
+            .into_body()
+            .status,
+        "done"
+    );
+    let deleted = data(
+        client,
+        "mutation DeleteTask($projectId: Int!, $id: Int!) { \
+         deleteTask(projectId: $projectId, id: $id) { id } }",
+        json!({ "projectId": project_id, "id": task_id }),
+    )
+    .await;
+    assert_eq!(deleted["deleteTask"]["id"], task_id);
+    assert_eq!(
+        client.get_task(project_id, task_id).await.status,
+        StatusCode::NOT_FOUND
+    );
+    let actions: Vec<String> = admin
+        .list_audit_records(&AuditFilter {
+            entity_type: Some("task".to_string()),
+            ..AuditFilter::default()
+        })
+        .await
+        .into_body()
+        .into_iter()
+        .map(|record| record.action)
+        .collect();
+    assert_eq!(actions, vec!["delete", "update", "create"]);
+
+    let create_status_type =
+        "mutation { createStatusType(input: { name: \"review\" }) { name completed } }";
+    assert_eq!(
+        error_code(client, create_status_type, json!({})).await,
+        "FORBIDDEN"
+    );
+    assert_eq!(
+        data(&admin, create_status_type, json!({})).await,
+        json!({ "createStatusType": { "name": "review", "completed": false } })
+    );
+    data(
+        client,
+        "mutation Delete($id: Int!) { deleteProject(id: $id) { deletedAt } }",
+        json!({ "id": project_id }),
+    )
+    .await;
+    assert_eq!(
+        client.get_project(project_id).await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscriptions_receive_project_events() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let followed = create_project(client, &new_project("Followed")).await;
+    let other = create_project(client, &new_project("Other")).await;
+    let mut socket = client.graphql_socket().await;
+    socket
+        .send_json(&json!({
+            "id": "1",
+            "type": "subscribe",
+            "payload": {
+                "query": "subscription Events($projects: [Int!]!) { \
+                          projectEvents(projects: $projects) { id event projectId data } }",
+                "variables": { "projects": [followed.id] },
+            },
+        }))
+        .await;
+    // Subscribed once the server answers a ping sent after the subscription.
+    socket.send_json(&json!({ "type": "ping" })).await;
+    assert_eq!(socket.next_frame().await["type"], "pong");
+
+    // Queries work over the socket too.
+    socket
+        .send_json(&json!({
+            "id": "2",
+            "type": "subscribe",
+            "payload": { "query": "{ projects { name } }" },
+        }))
+        .await;
+    let frame = socket.next_frame().await;
+    assert_eq!(frame["id"], "2");
+    assert_eq!(
+        frame["payload"]["data"]["projects"],
+        json!([{ "name": "Followed" }, { "name": "Other" }])
+    );
+    assert_eq!(socket.next_frame().await["type"], "complete");
+
+    create_task(client, other.id, &new_task("Ignored", None)).await;
+    let task = create_task(client, followed.id, &new_task("Followed", None)).await;
+    let frame = socket.next_frame().await;
+    assert_eq!(frame["type"], "next");
+    assert_eq!(frame["id"], "1");
+    let event = &frame["payload"]["data"]["projectEvents"];
+    assert!(event["id"].is_string());
+    assert_eq!(event["event"], "task_created");
+    assert_eq!(event["projectId"], followed.id);
+    assert_eq!(event["data"]["task"]["id"], task.id);
+
+    drop(socket);
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schema_is_public_but_queries_are_not() {
+    let service = TestService::start().await;
+    let anonymous = service.client.with_credentials(Credentials::Anonymous);
+
+    let schema = anonymous.get_graphql_schema().await;
+    assert_eq!(schema.status, StatusCode::OK);
+    let schema = String::from_utf8(schema.body).unwrap();
+    for definition in [
+        "type Query",
+        "type Mutation",
+        "type Subscription",
+        "input NewTask",
+        "projectEvents(",
+    ] {
+        assert!(schema.contains(definition), "{} missing", definition);
+    }
+    assert_eq!(
+        anonymous
+            .graphql("{ projects { id } }", json!({}))
+            .await
+            .status,
+        StatusCode::UNAUTHORIZED
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
+
+/// `__type(name: "Project")` down to `levels` nested `ofType`.
+fn nested_type_query(levels: usize) -> String {
+    format!(
+        "{{ __type(name: \"Project\") {{ fields {{ type {{ {} name {} }} }} }} }}",
+        "ofType { ".repeat(levels),
+        "} ".repeat(levels)
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn queries_nested_too_deep_are_rejected() {
+    let service = TestService::start().await;
+    let client = &service.client;
+
+    let fields = data(client, &nested_type_query(3), json!({})).await;
+    assert!(fields["__type"]["fields"].is_array());
+
+    let response = client
+        .graphql(&nested_type_query(20), json!({}))
+        .await
+        .into_body();
+    assert!(response["data"].is_null());
+    assert_eq!(
+        response["errors"][0]["message"],
+        "Query is nested too deep."
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
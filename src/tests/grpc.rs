@@ -0,0 +1,323 @@
+use serde_json::Value;
+use tonic::Code;
+
+use super::harness::{ADMIN_SUBJECT, Credentials, TestService, issue_token};
+use crate::{
+    db_handler::structs::NewTask,
+    grpc_handler::proto::{
+        CreateProjectRequest, CreateStatusTypeRequest, DeleteProjectRequest,
+        DeleteStatusTypeRequest, GetProjectRequest, ListProjectsRequest, ListStatusTypesRequest,
+        StreamProjectEventsRequest, TransitionProjectRequest, UpdateProjectRequest,
+        UpdateStatusTypeRequest,
+    },
+};
+
+fn new_project(name: &str) -> CreateProjectRequest {
+    CreateProjectRequest {
+        name: name.to_string(),
+        description: None,
+        status: "draft".to_string(),
+        team_id: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn projects_are_managed_with_the_rest_checks() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let outsider = client.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let mut grpc = service.grpc_client().await;
+
+    let project = grpc
+        .create_project(client.grpc_request(new_project("Remote")))
+        .await
+        .expect("Create failed")
+        .into_inner();
+    let rest_project = client.get_project(project.id).await.into_body();
+    assert_eq!(project.name, rest_project.name);
+    assert_eq!(project.version, rest_project.version);
+    assert!(project.created_at.is_some());
+
+    let stale = grpc
+        .update_project(client.grpc_request(UpdateProjectRequest {
+            id: project.id,
+            version: Some(project.version + 1),
+            name: Some("Stale".to_string()),
+            ..UpdateProjectRequest::default()
+        }))
+        .await
+        .expect_err("Stale version accepted");
+    assert_eq!(stale.code(), Code::Aborted);
+    let updated = grpc
+        .update_project(client.grpc_request(UpdateProjectRequest {
+            id: project.id,
+            version: Some(project.version),
+            name: Some("Renamed".to_string()),
+            ..UpdateProjectRequest::default()
+        }))
+        .await
+        .expect("Update failed")
+        .into_inner();
+    assert_eq!(updated.name, "Renamed");
+    assert_eq!(
+        client.get_project(project.id).await.into_body().name,
+        "Renamed"
+    );
+
+    let invalid = grpc
+        .create_project(client.grpc_request(CreateProjectRequest {
+            status: "unknown".to_string(),
+            ..new_project("Invalid")
+        }))
+        .await
+        .expect_err("Unknown status accepted");
+    assert_eq!(invalid.code(), Code::InvalidArgument);
+
+    // Projects the caller can't read are left out or refused as in REST.
+    let listed = grpc
+        .list_projects(outsider.grpc_request(ListProjectsRequest::default()))
+        .await
+        .expect("List failed")
+        .into_inner();
+    assert!(listed.projects.is_empty());
+    let hidden = grpc
+        .get_project(outsider.grpc_request(GetProjectRequest {
+            id: project.id,
+            include_deleted: false,
+        }))
+        .await
+        .expect_err("Outsider read the project");
+    assert_eq!(hidden.code(), Code::NotFound);
+    let anonymous = grpc
+        .list_projects(
+            client
+                .with_credentials(Credentials::Anonymous)
+                .grpc_request(ListProjectsRequest::default()),
+        )
+        .await
+        .expect_err("Anonymous call accepted");
+    assert_eq!(anonymous.code(), Code::Unauthenticated);
+
+    let deleted = grpc
+        .delete_project(client.grpc_request(DeleteProjectRequest {
+            id: project.id,
+            version: None,
+        }))
+        .await
+        .expect("Delete failed")
+        .into_inner();
+    assert!(deleted.deleted_at.is_some());
+    let missing = grpc
+        .get_project(client.grpc_request(GetProjectRequest {
+            id: project.id,
+            include_deleted: false,
+        }))
+        .await
+        .expect_err("Deleted project found");
+    assert_eq!(missing.code(), Code::NotFound);
+
+    drop(grpc);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_types_are_created_by_administrators() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let mut grpc = service.grpc_client().await;
+    let review = CreateStatusTypeRequest {
+        name: "review".to_string(),
+        completed: false,
+    };
+
+    let forbidden = grpc
+        .create_status_type(client.grpc_request(review.clone()))
+        .await
+        .expect_err("Non administrator created a status type");
+    assert_eq!(forbidden.code(), Code::PermissionDenied);
+    let created = grpc
+        .create_status_type(admin.grpc_request(review))
+        .await
+        .expect("Create failed")
+        .into_inner();
+    assert_eq!(created.name, "review");
+    let status_types = grpc
+        .list_status_types(client.grpc_request(ListStatusTypesRequest {}))
+        .await
+        .expect("List failed")
+        .into_inner()
+        .status_types;
+    assert!(
+        status_types
+            .iter()
+            .any(|status_type| status_type.name == "review")
+    );
+
+    let rename = |id: i32, name: &str| UpdateStatusTypeRequest {
+        id,
+        name: Some(name.to_string()),
+        completed: None,
+    };
+    let forbidden = grpc
+        .update_status_type(client.grpc_request(rename(created.id, "audit")))
+        .await
+        .expect_err("Non administrator updated a status type");
+    assert_eq!(forbidden.code(), Code::PermissionDenied);
+    let shared = grpc
+        .update_status_type(admin.grpc_request(rename(1, "sketch")))
+        .await
+        .expect_err("Shared status type updated");
+    assert_eq!(shared.code(), Code::NotFound);
+    let renamed = grpc
+        .update_status_type(admin.grpc_request(rename(created.id, "audit")))
+        .await
+        .expect("Update failed")
+        .into_inner();
+    assert_eq!(renamed.name, "audit");
+    grpc.create_project(client.grpc_request(CreateProjectRequest {
+        status: "audit".to_string(),
+        ..new_project("Audited")
+    }))
+    .await
+    .expect("Create failed");
+    let in_use = grpc
+        .delete_status_type(admin.grpc_request(DeleteStatusTypeRequest { id: created.id }))
+        .await
+        .expect_err("Status type in use deleted");
+    assert_eq!(in_use.code(), Code::InvalidArgument);
+    let parked = grpc
+        .create_status_type(admin.grpc_request(CreateStatusTypeRequest {
+            name: "parked".to_string(),
+            completed: false,
+        }))
+        .await
+        .expect("Create failed")
+        .into_inner();
+    let deleted = grpc
+        .delete_status_type(admin.grpc_request(DeleteStatusTypeRequest { id: parked.id }))
+        .await
+        .expect("Delete failed")
+        .into_inner();
+    assert_eq!(deleted.name, "parked");
+
+    drop(grpc);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn projects_move_through_the_transitions() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let mut grpc = service.grpc_client().await;
+    let project = grpc
+        .create_project(client.grpc_request(new_project("Moving")))
+        .await
+        .expect("Create failed")
+        .into_inner();
+    let to_status = |status: &str, version: Option<i32>| TransitionProjectRequest {
+        id: project.id,
+        version,
+        to_status: status.to_string(),
+    };
+
+    let not_allowed = grpc
+        .transition_project(client.grpc_request(to_status("done", None)))
+        .await
+        .expect_err("Draft project moved to done");
+    assert_eq!(not_allowed.code(), Code::FailedPrecondition);
+    let missing_description = grpc
+        .transition_project(client.grpc_request(to_status("active", None)))
+        .await
+        .expect_err("Project without description activated");
+    assert_eq!(missing_description.code(), Code::InvalidArgument);
+
+    let described = grpc
+        .update_project(client.grpc_request(UpdateProjectRequest {
+            id: project.id,
+            description: Some("Described".to_string()),
+            ..UpdateProjectRequest::default()
+        }))
+        .await
+        .expect("Update failed")
+        .into_inner();
+    let stale = grpc
+        .transition_project(client.grpc_request(to_status("active", Some(project.version))))
+        .await
+        .expect_err("Stale version accepted");
+    assert_eq!(stale.code(), Code::Aborted);
+    let transition = grpc
+        .transition_project(client.grpc_request(to_status("active", Some(described.version))))
+        .await
+        .expect("Transition failed")
+        .into_inner();
+    assert_eq!(transition.from_status, "draft");
+    assert_eq!(transition.to_status, "active");
+    let moved = transition.project.expect("No project");
+    assert_eq!(moved.status, "active");
+    assert_eq!(moved.version, described.version + 1);
+    let history = client
+        .list_project_transitions(project.id)
+        .await
+        .into_body();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].actor, transition.actor);
+
+    drop(grpc);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn project_events_are_streamed_until_shutdown() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let mut grpc = service.grpc_client().await;
+    let followed = grpc
+        .create_project(client.grpc_request(new_project("Followed")))
+        .await
+        .expect("Create failed")
+        .into_inner();
+    let other = grpc
+        .create_project(client.grpc_request(new_project("Other")))
+        .await
+        .expect("Create failed")
+        .into_inner();
+
+    let mut events = grpc
+        .stream_project_events(client.grpc_request(StreamProjectEventsRequest {
+            projects: vec![followed.id],
+            ..StreamProjectEventsRequest::default()
+        }))
+        .await
+        .expect("Subscribe failed")
+        .into_inner();
+    let new_task = |title: &str| NewTask {
+        title: title.to_string(),
+        description: None,
+        parent_id: None,
+        assignee_id: None,
+        status: "todo".to_string(),
+        priority: "medium".to_string(),
+        due_date: None,
+    };
+    client.create_task(other.id, &new_task("Ignored")).await;
+    let task = client
+        .create_task(followed.id, &new_task("Followed"))
+        .await
+        .into_body();
+
+    let event = events
+        .message()
+        .await
+        .expect("Stream failed")
+        .expect("Stream ended");
+    assert_eq!(event.event, "task_created");
+    assert_eq!(event.project_id, Some(followed.id));
+    assert!(event.id.is_some());
+    let data: Value = serde_json::from_str(&event.data.expect("No data")).unwrap();
+    assert_eq!(data["task"]["id"], task.id);
+
+    // The stream ends when the service stops instead of holding it up.
+    service.shutdown().await.expect("Service failed");
+    assert!(matches!(events.message().await, Ok(None) | Err(_)));
+}
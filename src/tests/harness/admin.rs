@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use super::{ApiClient, ApiResponse};
+use crate::{
+    api_handler::{
+        api_keys::{ApiKeyRequest, IssuedApiKey},
+        teams::MembershipRequest,
+        users::CurrentUser,
+    },
+    db_handler::structs::{
+        ApiKey, AuditFilter, AuditRecord, Membership, NewStatusType, NewTeam, NewTenant,
+        NewWebhook, StatusType, StatusTypeUpdate, Team, Tenant, Webhook, WebhookDelivery,
+        WebhookUpdate,
+    },
+    kafka_handler::message_bus::{EnsuredTopic, PartitionCount, TopicDescription, TopicSettings},
+};
+
+impl ApiClient {
+    pub async fn current_user(&self) -> ApiResponse<CurrentUser> {
+        self.send(self.http.get(self.url("/users/me"))).await
+    }
+
+    pub async fn create_team(&self, team: &NewTeam) -> ApiResponse<Team> {
+        self.send(self.http.post(self.url("/teams")).json(team))
+            .await
+    }
+
+    pub async fn list_team_members(&self, team_id: i32) -> ApiResponse<Vec<Membership>> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/teams/{}/members", team_id))),
+        )
+        .await
+    }
+
+    pub async fn set_team_member(
+        &self,
+        team_id: i32,
+        subject: &str,
+        request: &MembershipRequest,
+    ) -> ApiResponse<Membership> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/teams/{}/members/{}", team_id, subject)))
+                .json(request),
+        )
+        .await
+    }
+
+    pub async fn remove_team_member(&self, team_id: i32, subject: &str) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .delete(self.url(&format!("/teams/{}/members/{}", team_id, subject))),
+        )
+        .await
+    }
+
+    pub async fn list_tenants(&self) -> ApiResponse<Vec<Tenant>> {
+        self.send(self.http.get(self.url("/tenants"))).await
+    }
+
+    pub async fn create_tenant(&self, tenant: &NewTenant) -> ApiResponse<Tenant> {
+        self.send(self.http.post(self.url("/tenants")).json(tenant))
+            .await
+    }
+
+    pub async fn list_topics(&self) -> ApiResponse<Vec<TopicDescription>> {
+        self.send(self.http.get(self.url("/topics"))).await
+    }
+
+    pub async fn get_topic(&self, name: &str) -> ApiResponse<TopicDescription> {
+        self.send(self.http.get(self.url(&format!("/topics/{}", name))))
+            .await
+    }
+
+    pub async fn ensure_topic(
+        &self,
+        name: &str,
+        settings: &TopicSettings,
+    ) -> ApiResponse<EnsuredTopic> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/topics/{}", name)))
+                .json(settings),
+        )
+        .await
+    }
+
+    pub async fn update_topic_configs(
+        &self,
+        name: &str,
+        configs: &BTreeMap<String, String>,
+    ) -> ApiResponse<TopicDescription> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/topics/{}/configs", name)))
+                .json(configs),
+        )
+        .await
+    }
+
+    pub async fn add_topic_partitions(
+        &self,
+        name: &str,
+        partitions: i32,
+    ) -> ApiResponse<TopicDescription> {
+        self.send(
+            self.http
+                .post(self.url(&format!("/topics/{}/partitions", name)))
+                .json(&PartitionCount { partitions }),
+        )
+        .await
+    }
+
+    pub async fn delete_topic(&self, name: &str) -> ApiResponse<()> {
+        self.send(self.http.delete(self.url(&format!("/topics/{}", name))))
+            .await
+    }
+
+    pub async fn list_api_keys(&self) -> ApiResponse<Vec<ApiKey>> {
+        self.send(self.http.get(self.url("/api_keys"))).await
+    }
+
+    pub async fn create_api_key(&self, request: &ApiKeyRequest) -> ApiResponse<IssuedApiKey> {
+        self.send(self.http.post(self.url("/api_keys")).json(request))
+            .await
+    }
+
+    pub async fn revoke_api_key(&self, id: i32) -> ApiResponse<()> {
+        self.send(self.http.delete(self.url(&format!("/api_keys/{}", id))))
+            .await
+    }
+
+    pub async fn create_status_type(&self, status_type: &NewStatusType) -> ApiResponse<StatusType> {
+        self.send(self.http.post(self.url("/status_types")).json(status_type))
+            .await
+    }
+
+    pub async fn update_status_type(
+        &self,
+        id: i32,
+        update: &StatusTypeUpdate,
+    ) -> ApiResponse<StatusType> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/status_types/{}", id)))
+                .json(update),
+        )
+        .await
+    }
+
+    pub async fn delete_status_type(&self, id: i32) -> ApiResponse<()> {
+        self.send(self.http.delete(self.url(&format!("/status_types/{}", id))))
+            .await
+    }
+
+    pub async fn list_audit_records(&self, filter: &AuditFilter) -> ApiResponse<Vec<AuditRecord>> {
+        self.send(self.http.get(self.url("/audit")).query(filter))
+            .await
+    }
+
+    pub async fn list_webhooks(&self) -> ApiResponse<Vec<Webhook>> {
+        self.send(self.http.get(self.url("/webhooks"))).await
+    }
+
+    pub async fn get_webhook(&self, id: i32) -> ApiResponse<Webhook> {
+        self.send(self.http.get(self.url(&format!("/webhooks/{}", id))))
+            .await
+    }
+
+    pub async fn create_webhook(&self, webhook: &NewWebhook) -> ApiResponse<Webhook> {
+        self.send(self.http.post(self.url("/webhooks")).json(webhook))
+            .await
+    }
+
+    pub async fn update_webhook(&self, id: i32, update: &WebhookUpdate) -> ApiResponse<Webhook> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/webhooks/{}", id)))
+                .json(update),
+        )
+        .await
+    }
+
+    pub async fn delete_webhook(&self, id: i32) -> ApiResponse<()> {
+        self.send(self.http.delete(self.url(&format!("/webhooks/{}", id))))
+            .await
+    }
+
+    pub async fn list_webhook_deliveries(&self, id: i32) -> ApiResponse<Vec<WebhookDelivery>> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/webhooks/{}/deliveries", id))),
+        )
+        .await
+    }
+}
@@ -0,0 +1,259 @@
+use std::time::Duration;
+
+use reqwest::{
+    Client, RequestBuilder, StatusCode,
+    header::{self, HeaderMap},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use super::{Credentials, TEST_SUBJECT, WAIT_TIMEOUT, issue_token};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiErrorBody {
+    pub error: String,
+    pub additional_info: String,
+}
+
+#[derive(Debug)]
+pub struct ApiResponse<T> {
+    pub status: StatusCode,
+    pub etag: Option<String>,
+    pub request_id: Option<String>,
+    pub body: Result<T, ApiErrorBody>,
+}
+
+impl<T> ApiResponse<T> {
+    /// The body of a successful response, panics with the error otherwise.
+    pub fn into_body(self) -> T {
+        match self.body {
+            Ok(body) => body,
+            Err(error_body) => panic!(
+                "TEST_HARNESS: Expected a success, got {}: {:?}",
+                self.status, error_body
+            ),
+        }
+    }
+
+    pub fn into_error(self) -> ApiErrorBody {
+        match self.body {
+            Ok(_) => panic!("TEST_HARNESS: Expected an error, got {}", self.status),
+            Err(error_body) => error_body,
+        }
+    }
+}
+
+/// A response that is not JSON, such as a file download.
+pub struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl RawResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+
+/// Typed client for the HTTP API of a `TestService`.
+pub struct ApiClient {
+    base_url: String,
+    pub(super) http: Client,
+    pub(super) credentials: Credentials,
+    /// Sent as `X-Tenant-Id`, the service resolves the tenant otherwise.
+    pub(super) tenant: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new(base_url: String, credentials: Credentials) -> Self {
+        ApiClient {
+            base_url,
+            // No idle keep-alive connections, the graceful stop of the API
+            // would wait for them.
+            http: Client::builder()
+                .pool_max_idle_per_host(0)
+                .build()
+                .expect("TEST_HARNESS: Can't build the HTTP client"),
+            credentials,
+            tenant: None,
+        }
+    }
+
+    /// A client for the same service authenticating differently.
+    pub fn with_credentials(&self, credentials: Credentials) -> Self {
+        ApiClient {
+            base_url: self.base_url.clone(),
+            http: self.http.clone(),
+            credentials,
+            tenant: self.tenant.clone(),
+        }
+    }
+
+    /// A client for the same service acting on another tenant.
+    pub fn with_tenant(&self, tenant: &str) -> Self {
+        ApiClient {
+            base_url: self.base_url.clone(),
+            http: self.http.clone(),
+            credentials: self.credentials.clone(),
+            tenant: Some(tenant.to_string()),
+        }
+    }
+
+    /// A gRPC request with the credentials and tenant of the client.
+    pub fn grpc_request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        let metadata = request.metadata_mut();
+        match &self.credentials {
+            Credentials::Anonymous => {}
+            Credentials::Bearer(token) => {
+                metadata.insert(
+                    "authorization",
+                    format!("Bearer {}", token).parse().unwrap(),
+                );
+            }
+            Credentials::ApiKey(key) => {
+                metadata.insert("x-api-key", key.parse().unwrap());
+            }
+        }
+        if let Some(tenant) = &self.tenant {
+            metadata.insert("x-tenant-id", tenant.parse().unwrap());
+        }
+        request
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub(super) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub(super) async fn wait_until_ready(&self) {
+        let started = tokio::time::Instant::now();
+        while self.http.get(self.url("/hey")).send().await.is_err() {
+            assert!(
+                started.elapsed() < WAIT_TIMEOUT,
+                "TEST_HARNESS: Service did not start in time"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    pub(super) fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        let request = match &self.credentials {
+            Credentials::Anonymous => request,
+            Credentials::Bearer(token) => request.bearer_auth(token),
+            Credentials::ApiKey(key) => request.header("x-api-key", key),
+        };
+        match &self.tenant {
+            Some(tenant) => request.header("x-tenant-id", tenant),
+            None => request,
+        }
+    }
+
+    pub(super) async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> ApiResponse<T> {
+        let response = self
+            .authenticated(request)
+            .send()
+            .await
+            .expect("TEST_HARNESS: Request failed");
+        let status = response.status();
+        let header_value = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header_value(header::ETAG.as_str());
+        let request_id = header_value("x-request-id");
+        let bytes = response
+            .bytes()
+            .await
+            .expect("TEST_HARNESS: Can't read the response body");
+        let body = if status.is_success() {
+            // Empty bodies (204) deserialize as `()`.
+            let bytes: &[u8] = if bytes.is_empty() { b"null" } else { &bytes };
+            Ok(serde_json::from_slice(bytes).expect("TEST_HARNESS: Unexpected response body"))
+        } else {
+            Err(serde_json::from_slice(&bytes).expect("TEST_HARNESS: Unexpected error body"))
+        };
+        ApiResponse {
+            status,
+            etag,
+            request_id,
+            body,
+        }
+    }
+
+    pub(super) async fn send_raw(&self, request: RequestBuilder) -> RawResponse {
+        let response = self
+            .authenticated(request)
+            .send()
+            .await
+            .expect("TEST_HARNESS: Request failed");
+        RawResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response
+                .bytes()
+                .await
+                .expect("TEST_HARNESS: Can't read the response body")
+                .to_vec(),
+        }
+    }
+
+    pub(super) fn with_if_match(request: RequestBuilder, if_match: Option<&str>) -> RequestBuilder {
+        match if_match {
+            Some(etag) => request.header(header::IF_MATCH, etag),
+            None => request,
+        }
+    }
+
+    /// `GET /`, publishes a plain message through the Kafka producer.
+    pub async fn send_greeting(&self) -> StatusCode {
+        self.http
+            .get(self.url("/"))
+            .bearer_auth(issue_token(TEST_SUBJECT))
+            .send()
+            .await
+            .expect("TEST_HARNESS: Request failed")
+            .status()
+    }
+
+    pub async fn get_openapi(&self) -> ApiResponse<serde_json::Value> {
+        self.send(self.http.get(self.url("/openapi.json"))).await
+    }
+
+    pub async fn get_docs(&self) -> RawResponse {
+        self.send_raw(self.http.get(self.url("/docs"))).await
+    }
+
+    pub async fn get_redoc_bundle(&self) -> RawResponse {
+        self.send_raw(self.http.get(self.url("/docs/redoc.standalone.js")))
+            .await
+    }
+
+    /// Errors of the query are in the body, only malformed requests fail.
+    pub async fn graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> ApiResponse<serde_json::Value> {
+        self.send(
+            self.http
+                .post(self.url("/graphql"))
+                .json(&serde_json::json!({ "query": query, "variables": variables })),
+        )
+        .await
+    }
+
+    pub async fn get_graphql_schema(&self) -> RawResponse {
+        self.send_raw(self.http.get(self.url("/graphql/schema")))
+            .await
+    }
+}
@@ -0,0 +1,41 @@
+use reqwest::StatusCode;
+
+use super::ApiClient;
+use crate::db_handler::structs::{NewProject, NewTask, Project, Task};
+
+/// A draft project without description nor team.
+pub fn new_project(name: &str) -> NewProject {
+    NewProject {
+        name: name.to_string(),
+        description: None,
+        status: "draft".to_string(),
+        team_id: None,
+    }
+}
+
+/// A `todo` task of medium priority, unassigned and without due date.
+pub fn new_task(title: &str, parent_id: Option<i32>) -> NewTask {
+    NewTask {
+        title: title.to_string(),
+        description: None,
+        parent_id,
+        assignee_id: None,
+        status: "todo".to_string(),
+        priority: "medium".to_string(),
+        due_date: None,
+    }
+}
+
+/// Creates `project` as `client`, panics unless it is created.
+pub async fn create_project(client: &ApiClient, project: &NewProject) -> Project {
+    let response = client.create_project(project).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    response.into_body()
+}
+
+/// Creates `task` in the project as `client`, panics unless it is created.
+pub async fn create_task(client: &ApiClient, project_id: i32, task: &NewTask) -> Task {
+    let response = client.create_task(project_id, task).await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    response.into_body()
+}
@@ -0,0 +1,383 @@
+use reqwest::{
+    header,
+    multipart::{Form, Part},
+};
+use serde::de::DeserializeOwned;
+
+use super::{ApiClient, ApiResponse, RawResponse};
+use crate::{
+    db_handler::structs::{
+        Activity, Attachment, Comment, CommentRevision, CommentUpdate, Dependency, NewComment,
+        NewDependency, NewProject, NewTag, NewTask, Project, ProjectStatusHistoryEntry,
+        ProjectUpdate, SearchHit, Tag, TagTargets, TagUpdate, Task, TaskOrder, TaskUpdate,
+        TransitionRequest,
+    },
+    permission_handler::ProjectPermissions,
+};
+
+impl ApiClient {
+    pub async fn list_projects(&self, include_deleted: bool) -> ApiResponse<Vec<Project>> {
+        self.send(
+            self.http
+                .get(self.url("/projects"))
+                .query(&[("include_deleted", include_deleted)]),
+        )
+        .await
+    }
+
+    /// `tag_match` is `any` or `all`.
+    pub async fn list_tagged_projects(
+        &self,
+        tags: &str,
+        tag_match: &str,
+    ) -> ApiResponse<Vec<Project>> {
+        self.send(
+            self.http
+                .get(self.url("/projects"))
+                .query(&[("tags", tags), ("tag_match", tag_match)]),
+        )
+        .await
+    }
+
+    pub async fn get_project(&self, id: i32) -> ApiResponse<Project> {
+        self.send(self.http.get(self.url(&format!("/projects/{}", id))))
+            .await
+    }
+
+    pub async fn create_project(&self, project: &NewProject) -> ApiResponse<Project> {
+        self.send(self.http.post(self.url("/projects")).json(project))
+            .await
+    }
+
+    pub async fn update_project(
+        &self,
+        id: i32,
+        update: &ProjectUpdate,
+        if_match: Option<&str>,
+    ) -> ApiResponse<Project> {
+        let request = self
+            .http
+            .put(self.url(&format!("/projects/{}", id)))
+            .json(update);
+        self.send(ApiClient::with_if_match(request, if_match)).await
+    }
+
+    pub async fn delete_project(&self, id: i32, if_match: Option<&str>) -> ApiResponse<()> {
+        let request = self.http.delete(self.url(&format!("/projects/{}", id)));
+        self.send(ApiClient::with_if_match(request, if_match)).await
+    }
+
+    pub async fn restore_project(&self, id: i32) -> ApiResponse<Project> {
+        self.send(
+            self.http
+                .post(self.url(&format!("/projects/{}/restore", id))),
+        )
+        .await
+    }
+
+    pub async fn list_project_transitions(
+        &self,
+        id: i32,
+    ) -> ApiResponse<Vec<ProjectStatusHistoryEntry>> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/projects/{}/transitions", id))),
+        )
+        .await
+    }
+
+    pub async fn transition_project(
+        &self,
+        id: i32,
+        transition: &TransitionRequest,
+    ) -> ApiResponse<ProjectStatusHistoryEntry> {
+        self.send(
+            self.http
+                .post(self.url(&format!("/projects/{}/transitions", id)))
+                .json(transition),
+        )
+        .await
+    }
+
+    pub async fn get_project_permissions(&self, id: i32) -> ApiResponse<ProjectPermissions> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/projects/{}/permissions", id))),
+        )
+        .await
+    }
+
+    pub async fn list_tasks(&self, project_id: i32) -> ApiResponse<Vec<Task>> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/projects/{}/tasks", project_id))),
+        )
+        .await
+    }
+
+    pub async fn get_task(&self, project_id: i32, id: i32) -> ApiResponse<Task> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/projects/{}/tasks/{}", project_id, id))),
+        )
+        .await
+    }
+
+    pub async fn create_task(&self, project_id: i32, task: &NewTask) -> ApiResponse<Task> {
+        self.send(
+            self.http
+                .post(self.url(&format!("/projects/{}/tasks", project_id)))
+                .json(task),
+        )
+        .await
+    }
+
+    pub async fn update_task(
+        &self,
+        project_id: i32,
+        id: i32,
+        update: &TaskUpdate,
+    ) -> ApiResponse<Task> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/projects/{}/tasks/{}", project_id, id)))
+                .json(update),
+        )
+        .await
+    }
+
+    pub async fn delete_task(&self, project_id: i32, id: i32) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .delete(self.url(&format!("/projects/{}/tasks/{}", project_id, id))),
+        )
+        .await
+    }
+
+    pub async fn reorder_tasks(
+        &self,
+        project_id: i32,
+        order: &TaskOrder,
+    ) -> ApiResponse<Vec<Task>> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/projects/{}/tasks/order", project_id)))
+                .json(order),
+        )
+        .await
+    }
+
+    /// `item` is the path of the blocked item, `/projects/{id}` or
+    /// `/projects/{id}/tasks/{task_id}`.
+    pub async fn list_dependencies(&self, item: &str) -> ApiResponse<Vec<Dependency>> {
+        self.send(self.http.get(self.url(&format!("{}/dependencies", item))))
+            .await
+    }
+
+    pub async fn add_dependency(&self, item: &str, blocking_id: i32) -> ApiResponse<Dependency> {
+        self.send(
+            self.http
+                .post(self.url(&format!("{}/dependencies", item)))
+                .json(&NewDependency { blocking_id }),
+        )
+        .await
+    }
+
+    pub async fn remove_dependency(&self, item: &str, blocking_id: i32) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .delete(self.url(&format!("{}/dependencies/{}", item, blocking_id))),
+        )
+        .await
+    }
+
+    /// `relation` is `upstream`, `downstream` or `critical_path`.
+    pub async fn related_items<T: DeserializeOwned>(
+        &self,
+        item: &str,
+        relation: &str,
+    ) -> ApiResponse<Vec<T>> {
+        self.send(
+            self.http
+                .get(self.url(&format!("{}/dependencies/{}", item, relation))),
+        )
+        .await
+    }
+
+    pub async fn list_tags(&self) -> ApiResponse<Vec<Tag>> {
+        self.send(self.http.get(self.url("/tags"))).await
+    }
+
+    pub async fn create_tag(&self, tag: &NewTag) -> ApiResponse<Tag> {
+        self.send(self.http.post(self.url("/tags")).json(tag)).await
+    }
+
+    pub async fn update_tag(&self, id: i32, update: &TagUpdate) -> ApiResponse<Tag> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/tags/{}", id)))
+                .json(update),
+        )
+        .await
+    }
+
+    pub async fn delete_tag(&self, id: i32) -> ApiResponse<()> {
+        self.send(self.http.delete(self.url(&format!("/tags/{}", id))))
+            .await
+    }
+
+    pub async fn assign_tag(&self, id: i32, targets: &TagTargets) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .post(self.url(&format!("/tags/{}/assign", id)))
+                .json(targets),
+        )
+        .await
+    }
+
+    pub async fn unassign_tag(&self, id: i32, targets: &TagTargets) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .post(self.url(&format!("/tags/{}/unassign", id)))
+                .json(targets),
+        )
+        .await
+    }
+
+    /// `item` is the path of a project or task, like for dependencies.
+    pub async fn list_item_tags(&self, item: &str) -> ApiResponse<Vec<Tag>> {
+        self.send(self.http.get(self.url(&format!("{}/tags", item))))
+            .await
+    }
+
+    /// `item` is the path of a project or of a task.
+    pub async fn list_comments(&self, item: &str) -> ApiResponse<Vec<Comment>> {
+        self.send(self.http.get(self.url(&format!("{}/comments", item))))
+            .await
+    }
+
+    pub async fn create_comment(&self, item: &str, comment: &NewComment) -> ApiResponse<Comment> {
+        self.send(
+            self.http
+                .post(self.url(&format!("{}/comments", item)))
+                .json(comment),
+        )
+        .await
+    }
+
+    pub async fn update_comment(
+        &self,
+        project_id: i32,
+        id: i32,
+        update: &CommentUpdate,
+    ) -> ApiResponse<Comment> {
+        self.send(
+            self.http
+                .put(self.url(&format!("/projects/{}/comments/{}", project_id, id)))
+                .json(update),
+        )
+        .await
+    }
+
+    pub async fn delete_comment(&self, project_id: i32, id: i32) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .delete(self.url(&format!("/projects/{}/comments/{}", project_id, id))),
+        )
+        .await
+    }
+
+    pub async fn list_comment_revisions(
+        &self,
+        project_id: i32,
+        id: i32,
+    ) -> ApiResponse<Vec<CommentRevision>> {
+        self.send(self.http.get(self.url(&format!(
+            "/projects/{}/comments/{}/revisions",
+            project_id, id
+        ))))
+        .await
+    }
+
+    pub async fn project_activity(
+        &self,
+        project_id: i32,
+        limit: usize,
+    ) -> ApiResponse<Vec<Activity>> {
+        self.send(self.http.get(self.url(&format!(
+            "/projects/{}/activity?limit={}",
+            project_id, limit
+        ))))
+        .await
+    }
+
+    /// `filters` are extra query parameters such as `type`, `status` or `tags`.
+    pub async fn search(&self, q: &str, filters: &[(&str, &str)]) -> ApiResponse<Vec<SearchHit>> {
+        self.send(
+            self.http
+                .get(self.url("/search"))
+                .query(&[("q", q)])
+                .query(filters),
+        )
+        .await
+    }
+
+    /// Uploads `content` as the `file` field, with the `sha256` field when
+    /// given.
+    pub async fn upload_attachment(
+        &self,
+        project_id: i32,
+        file_name: &str,
+        content_type: &str,
+        content: Vec<u8>,
+        sha256: Option<&str>,
+    ) -> ApiResponse<Attachment> {
+        let file = Part::bytes(content)
+            .file_name(file_name.to_string())
+            .mime_str(content_type)
+            .expect("TEST_HARNESS: Invalid content type");
+        let mut form = Form::new().part("file", file);
+        if let Some(sha256) = sha256 {
+            form = form.text("sha256", sha256.to_string());
+        }
+        self.send(
+            self.http
+                .post(self.url(&format!("/projects/{}/attachments", project_id)))
+                .multipart(form),
+        )
+        .await
+    }
+
+    pub async fn list_attachments(&self, project_id: i32) -> ApiResponse<Vec<Attachment>> {
+        self.send(
+            self.http
+                .get(self.url(&format!("/projects/{}/attachments", project_id))),
+        )
+        .await
+    }
+
+    pub async fn delete_attachment(&self, project_id: i32, id: i32) -> ApiResponse<()> {
+        self.send(
+            self.http
+                .delete(self.url(&format!("/projects/{}/attachments/{}", project_id, id))),
+        )
+        .await
+    }
+
+    /// `range` is sent as the `Range` header.
+    pub async fn download_attachment(
+        &self,
+        project_id: i32,
+        id: i32,
+        range: Option<&str>,
+    ) -> RawResponse {
+        let mut request = self.http.get(self.url(&format!(
+            "/projects/{}/attachments/{}/content",
+            project_id, id
+        )));
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        self.send_raw(request).await
+    }
+}
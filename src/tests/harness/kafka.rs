@@ -0,0 +1,48 @@
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+
+use super::WAIT_TIMEOUT;
+use crate::kafka_handler::message_bus::{BusMessage, BusMessageStream, MessageBus};
+
+/// A consumer on its own group, for asserting on what the service publishes.
+pub struct KafkaProbe {
+    stream: BusMessageStream,
+}
+
+impl KafkaProbe {
+    pub fn new(message_bus: &dyn MessageBus, topics: &[&str]) -> Self {
+        let group_id = format!("test-probe-{:032x}", rand::random::<u128>());
+        let stream = message_bus
+            .subscribe(&group_id, topics)
+            .expect("TEST_HARNESS: Can't subscribe the probe");
+        KafkaProbe { stream }
+    }
+
+    pub async fn next_message(&mut self) -> BusMessage {
+        tokio::time::timeout(WAIT_TIMEOUT, self.stream.next())
+            .await
+            .expect("TEST_HARNESS: No message received in time")
+            .expect("TEST_HARNESS: Probe stream ended")
+            .expect("TEST_HARNESS: Probe stream failed")
+    }
+
+    /// Skips messages until one matches `predicate`.
+    pub async fn wait_for(&mut self, predicate: impl Fn(&BusMessage) -> bool) -> BusMessage {
+        loop {
+            let message = self.next_message().await;
+            if predicate(&message) {
+                return message;
+            }
+        }
+    }
+
+    /// The payload of the next message matching `predicate`, parsed as JSON.
+    pub async fn wait_for_json<T: DeserializeOwned>(
+        &mut self,
+        predicate: impl Fn(&BusMessage) -> bool,
+    ) -> T {
+        let message = self.wait_for(predicate).await;
+        serde_json::from_str(message.payload.as_deref().unwrap_or("null"))
+            .expect("TEST_HARNESS: Unexpected message payload")
+    }
+}
@@ -0,0 +1,273 @@
+//! The test service and what talks to it: `client` and its per-concern
+//! `admin`, `items` and `streams` endpoints, the Kafka probe, the webhook
+//! receiver, and the fixtures shared by the scenarios.
+
+mod admin;
+mod client;
+mod fixtures;
+mod items;
+mod kafka;
+mod streams;
+mod webhooks;
+
+use std::{net::TcpListener, path::PathBuf, sync::Arc, time::Duration};
+
+use tokio::{sync::oneshot, task::JoinHandle};
+use tonic::transport::Channel;
+
+pub use client::{ApiClient, ApiResponse, RawResponse};
+pub use fixtures::{create_project, create_task, new_project, new_task};
+pub use kafka::KafkaProbe;
+pub use webhooks::{ReceivedWebhook, WebhookReceiver};
+
+use crate::{
+    Listeners,
+    api_handler::{
+        attachments::AttachmentSettings,
+        auth::{AuthSettings, JwtClaims, JwtSettings},
+    },
+    blob_handler::local::LocalBlobStore,
+    configuration_handler,
+    db_handler::{self, repository::Repositories},
+    grpc_handler::proto::ninoverse_client::NinoverseClient,
+    kafka_handler::{
+        in_memory::InMemoryMessageBus, kafka_bus::KafkaMessageBus, message_bus::MessageBus,
+    },
+    run_threads,
+    webhook_handler::WebhookSettings,
+};
+
+/// How long the harness waits for the service or a Kafka message before
+/// failing the test.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+const JWT_SECRET: &str = "ninoverse-test-secret";
+/// Subject of the token the default client authenticates with.
+pub const TEST_SUBJECT: &str = "tester";
+/// Subject configured as administrator of the test service.
+pub const ADMIN_SUBJECT: &str = "admin";
+/// Size limit of the attachments of the test service.
+pub const ATTACHMENT_MAX_BYTES: usize = 100_000;
+/// Attempts at delivering an event to a webhook of the test service.
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 3;
+/// Failed deliveries in a row disabling a webhook of the test service.
+pub const WEBHOOK_DISABLE_AFTER: i32 = 2;
+
+/// Signs a token the test service accepts.
+pub fn issue_token(subject: &str) -> String {
+    issue_tenant_token(subject, None)
+}
+
+/// Signs a token bound to `tenant` through the `tenant` claim.
+pub fn issue_tenant_token(subject: &str, tenant: Option<&str>) -> String {
+    let claims = JwtClaims {
+        sub: subject.to_string(),
+        exp: (chrono::Utc::now().timestamp() + 3600) as usize,
+        tenant: tenant.map(str::to_string),
+    };
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .expect("TEST_HARNESS: Can't sign the token")
+}
+
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    Anonymous,
+    Bearer(String),
+    ApiKey(String),
+}
+
+/// The storage and message bus a `TestService` runs against.
+pub struct TestBackends {
+    pub repositories: Repositories,
+    pub message_bus: Arc<dyn MessageBus>,
+    /// Directory of the database files, removed on shutdown.
+    pub database_dir: Option<PathBuf>,
+}
+
+impl TestBackends {
+    pub fn in_memory() -> Self {
+        TestBackends {
+            repositories: Repositories::in_memory(),
+            message_bus: Arc::new(InMemoryMessageBus::new()),
+            database_dir: None,
+        }
+    }
+
+    /// The backends of `TestService::start`: SQLite when built with the
+    /// `sqlite` feature, so that `cargo test --features sqlite` runs every
+    /// scenario against it, in memory otherwise.
+    #[cfg(feature = "sqlite")]
+    pub async fn standard() -> Self {
+        TestBackends::sqlite().await
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    pub async fn standard() -> Self {
+        TestBackends::in_memory()
+    }
+
+    /// A SQLite database in a fresh temporary file, migrated, with the
+    /// in-memory message bus.
+    #[cfg(feature = "sqlite")]
+    pub async fn sqlite() -> Self {
+        let database_dir =
+            std::env::temp_dir().join(format!("ninoverse-sqlite-{:032x}", rand::random::<u128>()));
+        std::fs::create_dir_all(&database_dir)
+            .expect("TEST_HARNESS: Can't create the SQLite directory");
+        let path = database_dir.join("ninoverse.db");
+        let pool = db_handler::sqlite::get_sqlite_pool(&path.to_string_lossy())
+            .await
+            .expect("TEST_HARNESS: Can't open the SQLite database");
+        db_handler::migration::run_migrations(&pool)
+            .await
+            .expect("TEST_HARNESS: Can't migrate the SQLite database");
+        TestBackends {
+            repositories: Repositories::sqlite(Arc::new(pool)),
+            message_bus: Arc::new(InMemoryMessageBus::new()),
+            database_dir: Some(database_dir),
+        }
+    }
+
+    /// The Postgres and Kafka configured through the environment (`.env`),
+    /// e.g. the docker-compose services.
+    pub async fn local() -> Self {
+        configuration_handler::load_configuration();
+        let pool = db_handler::init_db()
+            .await
+            .expect("TEST_HARNESS: Local Postgres not reachable");
+        TestBackends {
+            repositories: Repositories::postgres(Arc::new(pool)),
+            message_bus: Arc::new(KafkaMessageBus::new()),
+            database_dir: None,
+        }
+    }
+}
+
+/// `run_threads` listening on a random local port.
+pub struct TestService {
+    pub client: ApiClient,
+    /// Of the gRPC server, see `grpc_client`.
+    pub grpc_url: String,
+    pub repositories: Repositories,
+    pub message_bus: Arc<dyn MessageBus>,
+    /// Root of the local blob store, removed on shutdown.
+    pub blob_root: PathBuf,
+    database_dir: Option<PathBuf>,
+    shutdown_sender: oneshot::Sender<()>,
+    threads: JoinHandle<Result<(), String>>,
+}
+
+impl TestService {
+    pub async fn start() -> Self {
+        TestService::start_with(TestBackends::standard().await).await
+    }
+
+    pub async fn start_with(backends: TestBackends) -> Self {
+        TestService::launch(backends, true).await
+    }
+
+    /// With `AUTH_ENABLED=false`, every request is let through.
+    pub async fn start_without_auth() -> Self {
+        TestService::launch(TestBackends::standard().await, false).await
+    }
+
+    async fn launch(backends: TestBackends, auth_enabled: bool) -> Self {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("TEST_HARNESS: Can't bind");
+        let address = listener
+            .local_addr()
+            .expect("TEST_HARNESS: No local address");
+        let grpc_listener = TcpListener::bind(("127.0.0.1", 0)).expect("TEST_HARNESS: Can't bind");
+        let grpc_address = grpc_listener
+            .local_addr()
+            .expect("TEST_HARNESS: No local address");
+        let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+        let repositories = backends.repositories.clone();
+        let message_bus = backends.message_bus.clone();
+        let blob_root =
+            std::env::temp_dir().join(format!("ninoverse-blobs-{:032x}", rand::random::<u128>()));
+        let attachment_settings = AttachmentSettings {
+            blob_store: Arc::new(LocalBlobStore::new(blob_root.clone())),
+            max_bytes: ATTACHMENT_MAX_BYTES,
+            content_types: vec![
+                "application/pdf".to_string(),
+                "image/*".to_string(),
+                "text/plain".to_string(),
+            ],
+        };
+        let threads = tokio::spawn(async move {
+            let auth_settings = AuthSettings {
+                enabled: auth_enabled,
+                jwt: Some(JwtSettings::hs256(JWT_SECRET.as_bytes())),
+                admin_subjects: vec![ADMIN_SUBJECT.to_string()],
+            };
+            run_threads(
+                repositories,
+                message_bus,
+                Listeners {
+                    api: listener,
+                    grpc: grpc_listener,
+                },
+                auth_settings,
+                attachment_settings,
+                WebhookSettings {
+                    max_attempts: WEBHOOK_MAX_ATTEMPTS,
+                    retry_backoff: Duration::from_millis(10),
+                    timeout: Duration::from_secs(2),
+                    disable_after: WEBHOOK_DISABLE_AFTER,
+                    concurrency: 4,
+                },
+                async {
+                    let _ = shutdown_receiver.await;
+                },
+            )
+            .await
+            .map_err(|threads_error| threads_error.to_string())
+        });
+        let service = TestService {
+            client: ApiClient::new(
+                format!("http://{}", address),
+                Credentials::Bearer(issue_token(TEST_SUBJECT)),
+            ),
+            grpc_url: format!("http://{}", grpc_address),
+            repositories: backends.repositories,
+            message_bus: backends.message_bus,
+            blob_root,
+            database_dir: backends.database_dir,
+            shutdown_sender,
+            threads,
+        };
+        service.client.wait_until_ready().await;
+        service
+    }
+
+    /// Joins a fresh consumer group, only messages published from now on
+    /// are seen.
+    pub fn kafka_probe(&self, topics: &[&str]) -> KafkaProbe {
+        KafkaProbe::new(self.message_bus.as_ref(), topics)
+    }
+
+    /// A gRPC client of the service, its calls take the metadata of
+    /// `ApiClient::grpc_request`.
+    pub async fn grpc_client(&self) -> NinoverseClient<Channel> {
+        NinoverseClient::connect(self.grpc_url.clone())
+            .await
+            .expect("TEST_HARNESS: Can't connect to the gRPC server")
+    }
+
+    /// Stops the service and waits for `run_threads` to return.
+    pub async fn shutdown(self) -> Result<(), String> {
+        let _ = self.shutdown_sender.send(());
+        let threads_result = tokio::time::timeout(WAIT_TIMEOUT, self.threads)
+            .await
+            .expect("TEST_HARNESS: Service did not shut down in time")
+            .expect("TEST_HARNESS: Service thread panicked");
+        let _ = std::fs::remove_dir_all(&self.blob_root);
+        if let Some(database_dir) = &self.database_dir {
+            let _ = std::fs::remove_dir_all(database_dir);
+        }
+        threads_result
+    }
+}
@@ -0,0 +1,187 @@
+use futures::{SinkExt, StreamExt};
+use reqwest::{StatusCode, header};
+use serde::de::DeserializeOwned;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, connect_async,
+    tungstenite::{Message, client::IntoClientRequest, http::HeaderValue},
+};
+
+use super::{ApiClient, Credentials, WAIT_TIMEOUT};
+
+/// An event of `/events/stream`.
+#[derive(Debug)]
+pub struct StreamEvent {
+    pub id: Option<String>,
+    pub event: String,
+    pub data: String,
+}
+
+/// An open `/events/stream` response, read event by event.
+pub struct EventStream {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl EventStream {
+    /// The next event, comments and `retry` fields are skipped.
+    pub async fn next_event(&mut self) -> StreamEvent {
+        loop {
+            while let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                let mut event = StreamEvent {
+                    id: None,
+                    event: "message".to_string(),
+                    data: String::new(),
+                };
+                let mut has_data = false;
+                for line in block.lines() {
+                    match line.split_once(": ") {
+                        Some(("id", id)) => event.id = Some(id.to_string()),
+                        Some(("event", name)) => event.event = name.to_string(),
+                        Some(("data", data)) => {
+                            event.data.push_str(data);
+                            has_data = true;
+                        }
+                        _ => {}
+                    }
+                }
+                if has_data {
+                    return event;
+                }
+            }
+            let chunk = tokio::time::timeout(WAIT_TIMEOUT, self.response.chunk())
+                .await
+                .expect("TEST_HARNESS: No event received in time")
+                .expect("TEST_HARNESS: Event stream failed")
+                .expect("TEST_HARNESS: Event stream ended");
+            self.buffer.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    }
+
+    /// The data of the next event, parsed as JSON.
+    pub async fn next_json<T: DeserializeOwned>(&mut self) -> (StreamEvent, T) {
+        let event = self.next_event().await;
+        let data = serde_json::from_str(&event.data).expect("TEST_HARNESS: Unexpected event data");
+        (event, data)
+    }
+}
+
+/// An open `/events/ws` or `/graphql/ws` connection.
+pub struct EventSocket {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl EventSocket {
+    pub async fn send_json(&mut self, command: &serde_json::Value) {
+        self.socket
+            .send(Message::text(command.to_string()))
+            .await
+            .expect("TEST_HARNESS: Can't send to the socket");
+    }
+
+    /// The next text frame, parsed as JSON. Pings and pongs are skipped.
+    pub async fn next_frame(&mut self) -> serde_json::Value {
+        loop {
+            let message = tokio::time::timeout(WAIT_TIMEOUT, self.socket.next())
+                .await
+                .expect("TEST_HARNESS: No frame received in time")
+                .expect("TEST_HARNESS: Socket closed")
+                .expect("TEST_HARNESS: Socket failed");
+            if let Message::Text(text) = message {
+                return serde_json::from_str(text.as_str())
+                    .expect("TEST_HARNESS: Unexpected frame");
+            }
+        }
+    }
+}
+
+impl ApiClient {
+    /// Opens `/events/stream`, resuming after `last_event_id` when given.
+    pub async fn event_stream(
+        &self,
+        filters: &[(&str, &str)],
+        last_event_id: Option<&str>,
+    ) -> EventStream {
+        let mut request = self.http.get(self.url("/events/stream")).query(filters);
+        if let Some(last_event_id) = last_event_id {
+            request = request.header("last-event-id", last_event_id);
+        }
+        let response = self
+            .authenticated(request)
+            .send()
+            .await
+            .expect("TEST_HARNESS: Event stream request failed");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok()),
+            Some("text/event-stream")
+        );
+        EventStream {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// Opens `/events/ws` with the credentials of the client.
+    pub async fn event_socket(&self, filters: &[(&str, &str)]) -> EventSocket {
+        self.open_socket("/events/ws", filters, None).await
+    }
+
+    /// Opens `/graphql/ws` speaking `graphql-transport-ws`, the connection is
+    /// initialized.
+    pub async fn graphql_socket(&self) -> EventSocket {
+        let mut socket = self
+            .open_socket("/graphql/ws", &[], Some("graphql-transport-ws"))
+            .await;
+        socket
+            .send_json(&serde_json::json!({ "type": "connection_init" }))
+            .await;
+        assert_eq!(socket.next_frame().await["type"], "connection_ack");
+        socket
+    }
+
+    async fn open_socket(
+        &self,
+        path: &str,
+        filters: &[(&str, &str)],
+        protocol: Option<&str>,
+    ) -> EventSocket {
+        let mut url = reqwest::Url::parse(&self.url(path)).expect("TEST_HARNESS: Bad URL");
+        url.set_scheme("ws").expect("TEST_HARNESS: Bad URL");
+        url.query_pairs_mut().extend_pairs(filters);
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .expect("TEST_HARNESS: Bad socket request");
+        let headers = request.headers_mut();
+        match &self.credentials {
+            Credentials::Anonymous => {}
+            Credentials::Bearer(token) => {
+                headers.insert(
+                    "authorization",
+                    HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+                );
+            }
+            Credentials::ApiKey(key) => {
+                headers.insert("x-api-key", HeaderValue::from_str(key).unwrap());
+            }
+        }
+        if let Some(tenant) = &self.tenant {
+            headers.insert("x-tenant-id", HeaderValue::from_str(tenant).unwrap());
+        }
+        if let Some(protocol) = protocol {
+            headers.insert(
+                "sec-websocket-protocol",
+                HeaderValue::from_str(protocol).unwrap(),
+            );
+        }
+        let (socket, _) = connect_async(request)
+            .await
+            .expect("TEST_HARNESS: Can't open the socket");
+        EventSocket { socket }
+    }
+}
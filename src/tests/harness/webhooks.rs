@@ -0,0 +1,110 @@
+use std::{
+    net::TcpListener,
+    sync::{
+        Arc,
+        atomic::{AtomicU16, Ordering},
+    },
+};
+
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer, dev::ServerHandle, web};
+use reqwest::header::{self, HeaderMap};
+use tokio::sync::mpsc;
+
+use super::WAIT_TIMEOUT;
+
+/// A webhook delivery as received by a `WebhookReceiver`.
+pub struct ReceivedWebhook {
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl ReceivedWebhook {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+
+struct ReceiverState {
+    status: Arc<AtomicU16>,
+    sender: mpsc::UnboundedSender<ReceivedWebhook>,
+}
+
+async fn receive_webhook(
+    state: web::Data<ReceiverState>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let mut headers = HeaderMap::new();
+    for (name, value) in request.headers() {
+        if let (Ok(name), Ok(value)) = (
+            header::HeaderName::from_bytes(name.as_str().as_bytes()),
+            header::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let _ = state.sender.send(ReceivedWebhook {
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    });
+    let status = actix_web::http::StatusCode::from_u16(state.status.load(Ordering::SeqCst))
+        .expect("TEST_HARNESS: Invalid receiver status");
+    HttpResponse::build(status).finish()
+}
+
+/// An HTTP endpoint for webhooks to post to, answering every delivery with
+/// the status it is told to.
+pub struct WebhookReceiver {
+    pub url: String,
+    status: Arc<AtomicU16>,
+    requests: mpsc::UnboundedReceiver<ReceivedWebhook>,
+    server: ServerHandle,
+}
+
+impl WebhookReceiver {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).expect("TEST_HARNESS: Can't bind");
+        let address = listener
+            .local_addr()
+            .expect("TEST_HARNESS: No local address");
+        let status = Arc::new(AtomicU16::new(200));
+        let (sender, requests) = mpsc::unbounded_channel();
+        let state = web::Data::new(ReceiverState {
+            status: status.clone(),
+            sender,
+        });
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(state.clone())
+                .default_service(web::to(receive_webhook))
+        })
+        .workers(1)
+        .disable_signals()
+        .listen(listener)
+        .expect("TEST_HARNESS: Can't start the webhook receiver")
+        .run();
+        let handle = server.handle();
+        tokio::spawn(server);
+        WebhookReceiver {
+            url: format!("http://{}/hooks", address),
+            status,
+            requests,
+            server: handle,
+        }
+    }
+
+    pub fn respond_with(&self, status: u16) {
+        self.status.store(status, Ordering::SeqCst);
+    }
+
+    pub async fn next_request(&mut self) -> ReceivedWebhook {
+        tokio::time::timeout(WAIT_TIMEOUT, self.requests.recv())
+            .await
+            .expect("TEST_HARNESS: No webhook received in time")
+            .expect("TEST_HARNESS: Webhook receiver stopped")
+    }
+
+    pub async fn stop(self) {
+        self.server.stop(false).await;
+    }
+}
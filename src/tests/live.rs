@@ -0,0 +1,329 @@
+use serde_json::{Value, json};
+
+use super::harness::{
+    Credentials, TEST_SUBJECT, TestService, create_project, create_task, issue_token, new_project,
+    new_task,
+};
+use crate::{
+    db_handler::{
+        repository::DEFAULT_TENANT,
+        structs::{NewTag, ProjectUpdate, TagTargets, Task, TaskUpdate, TransitionRequest},
+    },
+    kafka_handler::{
+        live::{LiveFeed, LiveHistory, LiveMessage, LiveSubscription},
+        structs::{KafkaNinoverseEnvelope, KafkaNinoverseEvent},
+    },
+    permission_handler::Caller,
+};
+
+fn created_task(event: &KafkaNinoverseEvent) -> &Task {
+    match event {
+        KafkaNinoverseEvent::TaskCreated { task, .. } => task,
+        other => panic!("Unexpected event {:?}", other),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stream_sends_events_of_readable_projects() {
+    let service = TestService::start().await;
+    let owner = &service.client;
+    let outsider = owner.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let mut owner_stream = owner.event_stream(&[], None).await;
+    let mut outsider_stream = outsider.event_stream(&[], None).await;
+
+    let project = create_project(owner, &new_project("Launch")).await;
+    let task = create_task(owner, project.id, &new_task("Write the announcement", None)).await;
+    let (event, data) = owner_stream.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(event.event, "task_created");
+    assert!(event.id.is_some());
+    assert_eq!(created_task(&data).id, task.id);
+
+    // The first event the outsider gets is about its own project.
+    let own_project = create_project(&outsider, &new_project("Private")).await;
+    let own_task = create_task(&outsider, own_project.id, &new_task("Secret", None)).await;
+    let (_, data) = outsider_stream.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(created_task(&data).id, own_task.id);
+
+    drop(owner_stream);
+    drop(outsider_stream);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reconnecting_streams_resume_after_the_last_event() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let mut stream = client.event_stream(&[], None).await;
+    let project = create_project(client, &new_project("Resume")).await;
+    create_task(client, project.id, &new_task("First", None)).await;
+    let second = create_task(client, project.id, &new_task("Second", None)).await;
+    let first_event = stream.next_event().await;
+    let second_event = stream.next_event().await;
+    drop(stream);
+
+    let mut resumed = client.event_stream(&[], first_event.id.as_deref()).await;
+    let (event, data) = resumed.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(event.id, second_event.id);
+    assert_eq!(created_task(&data).id, second.id);
+    drop(resumed);
+
+    // Also accepted as a query parameter, for clients that can't set headers.
+    let last_event_id = second_event.id.clone().unwrap();
+    let mut resumed = client
+        .event_stream(&[("last_event_id", &last_event_id)], None)
+        .await;
+    let third = create_task(client, project.id, &new_task("Third", None)).await;
+    let (_, data) = resumed.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(created_task(&data).id, third.id);
+    drop(resumed);
+
+    let mut unknown = client.event_stream(&[], Some("ninoverse:0:999999")).await;
+    assert_eq!(unknown.next_event().await.event, "reset");
+    drop(unknown);
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_older_than_the_recent_ones_are_read_back_from_the_bus() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let mut stream = client.event_stream(&[], None).await;
+    let project = create_project(client, &new_project("History")).await;
+    create_task(client, project.id, &new_task("First", None)).await;
+    let second = create_task(client, project.id, &new_task("Second", None)).await;
+    let first_event = stream.next_event().await;
+    stream.next_event().await;
+    drop(stream);
+
+    // Keeping no recent event, the feed has to read the second one back.
+    let live_feed = LiveFeed::new(0).with_history(LiveHistory {
+        repositories: service.repositories.clone(),
+        message_bus: service.message_bus.clone(),
+        auth_enabled: true,
+    });
+    let mut receiver = live_feed.subscribe(
+        DEFAULT_TENANT,
+        Caller::Unrestricted,
+        LiveSubscription::default(),
+        first_event.id.as_deref(),
+    );
+    let Some(LiveMessage::Event(replayed)) = receiver.next().await else {
+        panic!("Expected the second event");
+    };
+    assert_eq!(created_task(&replayed.event).id, second.id);
+
+    // The consumer catching up on a replayed event doesn't send it twice.
+    live_feed.publish((*replayed).clone());
+    let mut later = (*replayed).clone();
+    later.id = "ninoverse:0:999999".to_string();
+    live_feed.publish(later);
+    let Some(LiveMessage::Event(next)) = receiver.next().await else {
+        panic!("Expected the later event");
+    };
+    assert_eq!(next.id, "ninoverse:0:999999");
+
+    let mut unknown = live_feed.subscribe(
+        DEFAULT_TENANT,
+        Caller::Unrestricted,
+        LiveSubscription::default(),
+        Some("ninoverse:0:999999"),
+    );
+    assert!(matches!(unknown.next().await, Some(LiveMessage::Reset)));
+
+    // Only the events topic of the tenant is read back.
+    let payload = serde_json::to_string(&KafkaNinoverseEnvelope::signed(
+        DEFAULT_TENANT,
+        replayed.event.clone(),
+    ))
+    .expect("Envelope not serialized");
+    for _ in 0..2 {
+        service
+            .message_bus
+            .publish("elsewhere", "default:elsewhere", &payload)
+            .await
+            .expect("Event not published");
+    }
+    let mut elsewhere = live_feed.subscribe(
+        DEFAULT_TENANT,
+        Caller::Unrestricted,
+        LiveSubscription::default(),
+        Some("elsewhere:0:0"),
+    );
+    assert!(matches!(elsewhere.next().await, Some(LiveMessage::Reset)));
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn streams_are_filtered_by_projects_and_tags() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let followed = create_project(client, &new_project("Followed")).await;
+    let other = create_project(client, &new_project("Other")).await;
+    let tagged = create_task(client, other.id, &new_task("Tagged", None)).await;
+    let tag = client
+        .create_tag(&NewTag {
+            name: "urgent".to_string(),
+            color: "#ff0000".to_string(),
+        })
+        .await
+        .into_body();
+    client
+        .assign_tag(
+            tag.id,
+            &TagTargets {
+                project_ids: vec![],
+                task_ids: vec![tagged.id],
+            },
+        )
+        .await;
+    let followed_id = followed.id.to_string();
+    let mut by_project = client
+        .event_stream(&[("projects", &followed_id)], None)
+        .await;
+    let mut by_tag = client.event_stream(&[("tags", "urgent")], None).await;
+
+    create_task(client, other.id, &new_task("Untagged", None)).await;
+    let task = create_task(client, followed.id, &new_task("Followed task", None)).await;
+    client
+        .update_task(
+            other.id,
+            tagged.id,
+            &TaskUpdate {
+                title: Some("Still tagged".to_string()),
+                ..TaskUpdate::default()
+            },
+        )
+        .await;
+
+    let (_, data) = by_project.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(created_task(&data).id, task.id);
+    let (event, data) = by_tag.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(event.event, "task_updated");
+    assert_eq!(data.task_id(), Some(tagged.id));
+
+    drop(by_project);
+    drop(by_tag);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sockets_change_their_subscription() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let followed = create_project(client, &new_project("Followed")).await;
+    let other = create_project(client, &new_project("Other")).await;
+    let mut socket = client.event_socket(&[("tags", "urgent")]).await;
+    assert_eq!(
+        socket.next_frame().await,
+        json!({"type": "subscribed", "projects": [], "tags": ["urgent"]})
+    );
+
+    socket
+        .send_json(&json!({"action": "subscribe", "projects": [followed.id]}))
+        .await;
+    assert_eq!(
+        socket.next_frame().await,
+        json!({"type": "subscribed", "projects": [followed.id], "tags": ["urgent"]})
+    );
+    socket
+        .send_json(&json!({"action": "unsubscribe", "tags": ["urgent"]}))
+        .await;
+    assert_eq!(
+        socket.next_frame().await,
+        json!({"type": "subscribed", "projects": [followed.id], "tags": []})
+    );
+    socket.send_json(&json!({"action": "follow"})).await;
+    assert_eq!(socket.next_frame().await["type"], "error");
+
+    create_task(client, other.id, &new_task("Ignored", None)).await;
+    let task = create_task(client, followed.id, &new_task("Sent", None)).await;
+    let frame: Value = socket.next_frame().await;
+    assert_eq!(frame["type"], "event");
+    assert!(frame["id"].is_string());
+    assert_eq!(frame["data"]["event"], "task_created");
+    assert_eq!(frame["data"]["task"]["id"], task.id);
+
+    drop(socket);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_of_other_services_need_a_permitted_actor() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Shared")).await;
+    let mut stream = client.event_stream(&[], None).await;
+
+    let reordered = |actor: &str| KafkaNinoverseEvent::TasksReordered {
+        project_id: project.id,
+        task_ids: vec![],
+        actor: actor.to_string(),
+    };
+    let mut forged = serde_json::to_value(reordered("stranger")).unwrap();
+    forged["origin"] = json!("ninoverse");
+    forged["signature"] = json!("00".repeat(32));
+    // Signed by the service, for another tenant.
+    let replayed = KafkaNinoverseEnvelope::signed("acme", reordered("stranger"));
+    let payloads = [
+        serde_json::to_string(&reordered("stranger")).unwrap(),
+        forged.to_string(),
+        serde_json::to_string(&replayed).unwrap(),
+        serde_json::to_string(&reordered(TEST_SUBJECT)).unwrap(),
+    ];
+    let key = format!("default:{}", reordered(TEST_SUBJECT).key());
+    for payload in payloads {
+        service
+            .message_bus
+            .publish("ninoverse", &key, &payload)
+            .await
+            .unwrap();
+    }
+    let (_, data) = stream.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(data.actor(), TEST_SUBJECT);
+
+    // The events of the service are trusted.
+    let described = ProjectUpdate {
+        name: None,
+        description: Some("Ready".to_string()),
+        status: None,
+        owner_id: None,
+        team_id: None,
+    };
+    client
+        .update_project(project.id, &described, None)
+        .await
+        .into_body();
+    client
+        .transition_project(
+            project.id,
+            &TransitionRequest {
+                to_status: "active".to_string(),
+            },
+        )
+        .await
+        .into_body();
+    let (_, data) = stream.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(data.name(), "project_status_changed");
+    assert_eq!(data.actor(), TEST_SUBJECT);
+
+    drop(stream);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_are_streamed_without_authentication() {
+    let service = TestService::start_without_auth().await;
+    let client = service.client.with_credentials(Credentials::Anonymous);
+    let mut stream = client.event_stream(&[], None).await;
+
+    let project = create_project(&client, &new_project("Open")).await;
+    let task = create_task(&client, project.id, &new_task("Anyone", None)).await;
+    let (_, data) = stream.next_json::<KafkaNinoverseEvent>().await;
+    assert_eq!(data.actor(), "anonymous");
+    assert_eq!(created_task(&data).id, task.id);
+
+    drop(stream);
+    service.shutdown().await.expect("Service failed");
+}
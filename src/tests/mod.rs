@@ -0,0 +1,25 @@
+//! End-to-end scenarios: every test boots `run_threads` on a random port and
+//! talks to it over HTTP. The in-memory backends are used unless stated
+//! otherwise, the `local_*` tests run against the docker-compose Postgres and
+//! Kafka and are ignored by default (`cargo test -- --ignored`).
+
+mod access;
+mod attachments;
+mod auth;
+mod cli;
+mod comments;
+mod dependencies;
+mod events;
+mod graphql;
+mod grpc;
+mod harness;
+mod live;
+mod openapi;
+mod projects;
+mod search;
+mod shutdown;
+mod tags;
+mod tasks;
+mod tenancy;
+mod topics;
+mod webhooks;
@@ -0,0 +1,93 @@
+use std::{env, fs};
+
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use super::harness::{Credentials, TestService};
+
+/// The committed OpenAPI document, for consumers of the API and for spotting
+/// changes to it in reviews.
+const SNAPSHOT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+/// Run with `UPDATE_OPENAPI_SNAPSHOT=1` to write the served document to the
+/// snapshot.
+#[tokio::test(flavor = "multi_thread")]
+async fn openapi_document_matches_the_snapshot() {
+    let service = TestService::start().await;
+    let anonymous = service.client.with_credentials(Credentials::Anonymous);
+
+    let response = anonymous.get_openapi().await;
+    assert_eq!(response.status, StatusCode::OK);
+    let document = response.into_body();
+    if env::var("UPDATE_OPENAPI_SNAPSHOT").is_ok() {
+        let pretty = serde_json::to_string_pretty(&document).unwrap();
+        fs::write(SNAPSHOT_PATH, pretty + "\n").expect("Can't write the OpenAPI snapshot");
+    }
+    let snapshot: Value = serde_json::from_str(
+        &fs::read_to_string(SNAPSHOT_PATH).expect("Can't read the OpenAPI snapshot"),
+    )
+    .expect("The OpenAPI snapshot is not JSON");
+    assert!(
+        document == snapshot,
+        "The OpenAPI document differs from {}, run the tests with UPDATE_OPENAPI_SNAPSHOT=1 \
+         and commit the snapshot if the change is intended.",
+        SNAPSHOT_PATH
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn openapi_document_describes_the_handlers() {
+    let service = TestService::start().await;
+    let anonymous = service.client.with_credentials(Credentials::Anonymous);
+
+    let document = anonymous.get_openapi().await.into_body();
+    assert_eq!(document["openapi"], "3.1.0");
+    let task = &document["paths"]["/projects/{id}/tasks/{task_id}"];
+    for method in ["get", "put", "delete"] {
+        assert_eq!(task[method]["tags"][0], "tasks");
+        let parameters: Vec<&str> = task[method]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|parameter| parameter["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(parameters, vec!["id", "task_id", "X-Tenant-Id"]);
+        assert_eq!(
+            task[method]["responses"]["401"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorBody"
+        );
+    }
+    assert_eq!(
+        task["put"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/TaskUpdate"
+    );
+    assert_eq!(
+        document["paths"]["/projects"]["get"]["responses"]["200"]["content"]["application/json"]["schema"]
+            ["items"]["$ref"],
+        "#/components/schemas/Project"
+    );
+    let webhook = &document["components"]["schemas"]["Webhook"]["properties"];
+    assert!(webhook.get("url").is_some());
+    assert!(webhook.get("secret").is_none());
+    assert!(document["components"]["securitySchemes"]["bearer"].is_object());
+
+    let docs = anonymous.get_docs().await;
+    assert_eq!(docs.status, StatusCode::OK);
+    assert!(
+        docs.header("content-type")
+            .is_some_and(|content_type| content_type.starts_with("text/html"))
+    );
+    let page = String::from_utf8(docs.body).unwrap();
+    assert!(page.contains("/openapi.json"));
+    // A pinned release, the test service has no bundle of its own.
+    assert!(page.contains("redoc@2.5.0/bundles/redoc.standalone.js"));
+    assert!(!page.contains("latest"));
+    assert_eq!(
+        anonymous.get_redoc_bundle().await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,233 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    ADMIN_SUBJECT, Credentials, TEST_SUBJECT, TestBackends, TestService, issue_token, new_project,
+};
+use crate::db_handler::structs::{
+    AuditFilter, NewStatusType, Project, ProjectUpdate, StatusTransition, StatusTypeUpdate,
+};
+
+async fn project_crud_scenario(service: &TestService) {
+    let client = &service.client;
+
+    let created = client.create_project(&new_project("Harness")).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    let project = created.into_body();
+    assert_eq!(project.name, "Harness");
+    assert_eq!(project.version, 1);
+
+    let fetched = client.get_project(project.id).await;
+    assert_eq!(fetched.etag.as_deref(), Some("\"1\""));
+    assert_eq!(fetched.into_body().id, project.id);
+
+    let update = ProjectUpdate {
+        name: Some("Harness renamed".to_string()),
+        description: Some("Described".to_string()),
+        status: None,
+        owner_id: None,
+        team_id: None,
+    };
+    let updated = client
+        .update_project(project.id, &update, Some("\"1\""))
+        .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.etag.as_deref(), Some("\"2\""));
+    assert_eq!(updated.into_body().name, "Harness renamed");
+
+    let stale = client
+        .update_project(project.id, &update, Some("\"1\""))
+        .await;
+    assert_eq!(stale.status, StatusCode::PRECONDITION_FAILED);
+
+    let deleted = client.delete_project(project.id, None).await;
+    assert_eq!(deleted.status, StatusCode::NO_CONTENT);
+    assert_eq!(
+        client.get_project(project.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert!(
+        client
+            .list_projects(true)
+            .await
+            .into_body()
+            .iter()
+            .any(|listed| listed.id == project.id && listed.deleted_at.is_some())
+    );
+
+    let restored = client.restore_project(project.id).await.into_body();
+    assert!(restored.deleted_at.is_none());
+    assert_eq!(client.get_project(project.id).await.status, StatusCode::OK);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn project_crud() {
+    let service = TestService::start().await;
+    project_crud_scenario(&service).await;
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs the docker-compose Postgres and Kafka"]
+async fn local_project_crud() {
+    let service = TestService::start_with(TestBackends::local().await).await;
+    project_crud_scenario(&service).await;
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_status_is_rejected() {
+    let service = TestService::start().await;
+    let mut project = new_project("Unknown status");
+    project.status = "archived".to_string();
+    let created = service.client.create_project(&project).await;
+    assert_eq!(created.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(created.into_error().additional_info.contains("archived"));
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mutations_are_audited_with_the_request_id() {
+    let service = TestService::start().await;
+    let created = service.client.create_project(&new_project("Audited")).await;
+    let request_id = created.request_id.clone();
+    let project = created.into_body();
+    let filter = AuditFilter {
+        entity_id: Some(project.id),
+        ..Default::default()
+    };
+    assert_eq!(
+        service.client.list_audit_records(&filter).await.status,
+        StatusCode::FORBIDDEN
+    );
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let records = admin.list_audit_records(&filter).await.into_body();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].action, "create");
+    assert_eq!(records[0].request_id, request_id);
+    assert_eq!(records[0].actor, TEST_SUBJECT);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_types_change_only_while_unused() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let review = admin
+        .create_status_type(&NewStatusType {
+            name: "review".to_string(),
+            completed: false,
+        })
+        .await
+        .into_body();
+    let rename = |name: &str| StatusTypeUpdate {
+        name: Some(name.to_string()),
+        completed: None,
+    };
+
+    assert_eq!(
+        client
+            .update_status_type(review.id, &rename("audit"))
+            .await
+            .status,
+        StatusCode::FORBIDDEN
+    );
+    let shared = admin
+        .update_status_type(1, &rename("sketch"))
+        .await
+        .into_error();
+    assert!(shared.additional_info.contains("shared"));
+    assert_eq!(
+        admin
+            .update_status_type(review.id, &rename("done"))
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let renamed = admin
+        .update_status_type(review.id, &rename("audit"))
+        .await
+        .into_body();
+    assert_eq!(renamed.name, "audit");
+
+    // Projects refer to their status by name, it can't change under them.
+    let mut project = new_project("Audited");
+    project.status = "audit".to_string();
+    client.create_project(&project).await.into_body();
+    let in_use = admin.update_status_type(review.id, &rename("check")).await;
+    assert_eq!(in_use.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(in_use.into_error().additional_info.contains("in use"));
+    assert_eq!(
+        admin.delete_status_type(review.id).await.status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let completed = admin
+        .update_status_type(
+            review.id,
+            &StatusTypeUpdate {
+                name: None,
+                completed: Some(true),
+            },
+        )
+        .await
+        .into_body();
+    assert!(completed.completed);
+
+    let unused = admin
+        .create_status_type(&NewStatusType {
+            name: "parked".to_string(),
+            completed: false,
+        })
+        .await
+        .into_body();
+    assert_eq!(
+        admin.delete_status_type(unused.id).await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        admin.delete_status_type(unused.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+    let filter = AuditFilter {
+        entity_type: Some("status_type".to_string()),
+        ..Default::default()
+    };
+    let actions: Vec<String> = admin
+        .list_audit_records(&filter)
+        .await
+        .into_body()
+        .into_iter()
+        .map(|record| record.action)
+        .collect();
+    assert!(actions.contains(&"update".to_string()));
+    assert!(actions.contains(&"delete".to_string()));
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[test]
+fn unknown_required_fields_are_always_missing() {
+    let project = Project {
+        id: 1,
+        name: "Guarded".to_string(),
+        description: Some("Described".to_string()),
+        status: "draft".to_string(),
+        created_at: None,
+        updated_at: None,
+        version: 1,
+        deleted_at: None,
+        owner_id: Some(1),
+        team_id: None,
+        blocked: false,
+    };
+    let transition = StatusTransition {
+        from_status: "draft".to_string(),
+        to_status: "active".to_string(),
+        required_fields: ["description", "owner_id", "team_id", "budget"]
+            .map(String::from)
+            .to_vec(),
+    };
+    assert_eq!(transition.missing_fields(&project), ["team_id", "budget"]);
+}
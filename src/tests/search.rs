@@ -0,0 +1,164 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    Credentials, TestService, create_project, create_task, issue_token, new_project, new_task,
+};
+use crate::db_handler::structs::{NewComment, NewProject, NewTag, SearchHit, TagTargets};
+
+fn described_project(name: &str, description: &str) -> NewProject {
+    NewProject {
+        description: Some(description.to_string()),
+        ..new_project(name)
+    }
+}
+
+fn kinds(hits: &[SearchHit]) -> Vec<(&str, i32)> {
+    hits.iter()
+        .map(|hit| (hit.entity_type.as_str(), hit.id))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_matches_word_prefixes_across_items() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(
+        client,
+        &described_project("Website relaunch", "New landing pages"),
+    )
+    .await;
+    let task = create_task(
+        client,
+        project.id,
+        &new_task("Design the landing page", None),
+    )
+    .await;
+    let comment = client
+        .create_comment(
+            &format!("/projects/{}", project.id),
+            &NewComment {
+                body: "The landing copy is ready.".to_string(),
+                parent_id: None,
+            },
+        )
+        .await
+        .into_body();
+    create_project(client, &described_project("Billing", "Invoices")).await;
+
+    let hits = client.search("LAND", &[]).await.into_body();
+    // The title of the task matches, the others only in their text.
+    assert_eq!(hits[0].entity_type, "task");
+    assert_eq!(hits[0].title.as_deref(), Some("Design the landing page"));
+    assert_eq!(hits[0].task_id, Some(task.id));
+    let mut found = kinds(&hits);
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            ("comment", comment.id),
+            ("project", project.id),
+            ("task", task.id)
+        ]
+    );
+    let comment_hit = hits
+        .iter()
+        .find(|hit| hit.entity_type == "comment")
+        .unwrap();
+    assert!(comment_hit.snippet.contains("<mark>landing</mark>"));
+    assert_eq!(comment_hit.project_id, project.id);
+
+    // Every word has to match.
+    let hits = client.search("landing pag", &[]).await.into_body();
+    let mut found = kinds(&hits);
+    found.sort();
+    assert_eq!(found, vec![("project", project.id), ("task", task.id)]);
+    assert!(
+        client
+            .search("landing invoices", &[])
+            .await
+            .into_body()
+            .is_empty()
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn search_filters_by_type_status_tags_and_access() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let outsider = client.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let roadmap = create_project(client, &described_project("Roadmap", "Quarterly goals")).await;
+    let task = create_task(client, roadmap.id, &new_task("Collect goals", None)).await;
+    let archived = create_project(client, &described_project("Old goals", "")).await;
+    let tag = client
+        .create_tag(&NewTag {
+            name: "planning".to_string(),
+            color: "#00ff00".to_string(),
+        })
+        .await
+        .into_body();
+    client
+        .assign_tag(
+            tag.id,
+            &TagTargets {
+                project_ids: vec![roadmap.id],
+                task_ids: vec![],
+            },
+        )
+        .await;
+
+    assert_eq!(
+        kinds(
+            &client
+                .search("goals", &[("type", "task")])
+                .await
+                .into_body()
+        ),
+        vec![("task", task.id)]
+    );
+    assert_eq!(
+        kinds(
+            &client
+                .search("goals", &[("status", "todo,in_progress")])
+                .await
+                .into_body()
+        ),
+        vec![("task", task.id)]
+    );
+    assert_eq!(
+        kinds(
+            &client
+                .search("goals", &[("tags", "planning")])
+                .await
+                .into_body()
+        ),
+        vec![("project", roadmap.id)]
+    );
+    assert_eq!(
+        client
+            .search("goals", &[("limit", "1")])
+            .await
+            .into_body()
+            .len(),
+        1
+    );
+    assert!(outsider.search("goals", &[]).await.into_body().is_empty());
+
+    client.delete_project(archived.id, None).await;
+    let hits = client.search("goals", &[]).await.into_body();
+    let mut found = kinds(&hits);
+    found.sort();
+    assert_eq!(found, vec![("project", roadmap.id), ("task", task.id)]);
+
+    assert_eq!(
+        client.search(" - ", &[]).await.status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(
+        client.search("goals", &[("type", "team")]).await.status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
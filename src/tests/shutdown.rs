@@ -0,0 +1,37 @@
+use super::harness::TestService;
+use crate::db_handler::structs::NewProject;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_stops_the_api() {
+    let service = TestService::start().await;
+    let base_url = service.client.base_url().to_string();
+    service.shutdown().await.expect("Service failed");
+
+    let connection = reqwest::get(format!("{}/projects", base_url)).await;
+    assert!(connection.is_err(), "The API still accepts connections");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_keeps_the_stored_data() {
+    let service = TestService::start().await;
+    service
+        .client
+        .create_project(&NewProject {
+            name: "Survivor".to_string(),
+            description: None,
+            status: "draft".to_string(),
+            team_id: None,
+        })
+        .await
+        .into_body();
+    let repositories = service.repositories.clone();
+    service.shutdown().await.expect("Service failed");
+
+    let projects = repositories
+        .projects
+        .list(false)
+        .await
+        .expect("Listing failed");
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "Survivor");
+}
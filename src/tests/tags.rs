@@ -0,0 +1,221 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    ADMIN_SUBJECT, ApiClient, Credentials, TestService, create_project, issue_token, new_project,
+};
+use crate::db_handler::structs::{NewTag, NewTask, Project, Tag, TagTargets, TagUpdate};
+
+async fn create_tag(client: &ApiClient, name: &str, color: &str) -> Tag {
+    let response = client
+        .create_tag(&NewTag {
+            name: name.to_string(),
+            color: color.to_string(),
+        })
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    response.into_body()
+}
+
+fn projects_only(project_ids: Vec<i32>) -> TagTargets {
+    TagTargets {
+        project_ids,
+        task_ids: Vec::new(),
+    }
+}
+
+fn names(projects: &[Project]) -> Vec<&str> {
+    projects
+        .iter()
+        .map(|project| project.name.as_str())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn projects_filter_by_any_or_all_tags() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let urgent = create_tag(client, "urgent", "#ff0000").await;
+    let backend = create_tag(client, "backend", "#0000ff").await;
+    let api = create_project(client, &new_project("API")).await;
+    let storage = create_project(client, &new_project("Storage")).await;
+    let website = create_project(client, &new_project("Website")).await;
+
+    assert_eq!(
+        client
+            .assign_tag(urgent.id, &projects_only(vec![api.id, storage.id]))
+            .await
+            .status,
+        StatusCode::NO_CONTENT
+    );
+    client
+        .assign_tag(backend.id, &projects_only(vec![storage.id, website.id]))
+        .await;
+    // Assigning twice is not an error.
+    client
+        .assign_tag(backend.id, &projects_only(vec![storage.id]))
+        .await;
+
+    let any = client
+        .list_tagged_projects("urgent,backend", "any")
+        .await
+        .into_body();
+    assert_eq!(names(&any), vec!["API", "Storage", "Website"]);
+    let all = client
+        .list_tagged_projects("urgent,backend", "all")
+        .await
+        .into_body();
+    assert_eq!(names(&all), vec!["Storage"]);
+    assert_eq!(
+        client.list_tagged_projects("someday", "any").await.status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    // Items point at the tag, the new name shows up everywhere.
+    assert_eq!(
+        client
+            .update_tag(
+                urgent.id,
+                &TagUpdate {
+                    name: Some("critical".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .status,
+        StatusCode::FORBIDDEN
+    );
+    let renamed = admin
+        .update_tag(
+            urgent.id,
+            &TagUpdate {
+                name: Some("critical".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .into_body();
+    assert_eq!(
+        (renamed.name.as_str(), renamed.color.as_str()),
+        ("critical", "#ff0000")
+    );
+    let api_tags = client
+        .list_item_tags(&format!("/projects/{}", api.id))
+        .await
+        .into_body();
+    assert_eq!(
+        api_tags
+            .iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["critical"]
+    );
+    assert_eq!(
+        names(
+            &client
+                .list_tagged_projects("critical", "any")
+                .await
+                .into_body()
+        ),
+        vec!["API", "Storage"]
+    );
+
+    client
+        .unassign_tag(urgent.id, &projects_only(vec![api.id]))
+        .await;
+    assert_eq!(
+        names(
+            &client
+                .list_tagged_projects("critical", "any")
+                .await
+                .into_body()
+        ),
+        vec!["Storage"]
+    );
+    assert_eq!(
+        admin.delete_tag(backend.id).await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert!(
+        client
+            .list_item_tags(&format!("/projects/{}", website.id))
+            .await
+            .into_body()
+            .is_empty()
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tagging_needs_write_access_to_every_item() {
+    let service = TestService::start().await;
+    let owner = &service.client;
+    let outsider = owner.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let tag = create_tag(owner, "review", "#00ff00").await;
+    let project = create_project(owner, &new_project("Private")).await;
+    let task = owner
+        .create_task(
+            project.id,
+            &NewTask {
+                title: "Proofread".to_string(),
+                description: None,
+                parent_id: None,
+                assignee_id: None,
+                status: "todo".to_string(),
+                priority: "medium".to_string(),
+                due_date: None,
+            },
+        )
+        .await
+        .into_body();
+    let own_project = create_project(&outsider, &new_project("Own")).await;
+
+    // One unauthorized item fails the whole request.
+    let intrusion = outsider
+        .assign_tag(
+            tag.id,
+            &TagTargets {
+                project_ids: vec![own_project.id],
+                task_ids: vec![task.id],
+            },
+        )
+        .await;
+    assert_eq!(intrusion.status, StatusCode::NOT_FOUND);
+    assert!(
+        outsider
+            .list_item_tags(&format!("/projects/{}", own_project.id))
+            .await
+            .into_body()
+            .is_empty()
+    );
+
+    owner
+        .assign_tag(
+            tag.id,
+            &TagTargets {
+                project_ids: Vec::new(),
+                task_ids: vec![task.id],
+            },
+        )
+        .await;
+    let task_tags = owner
+        .list_item_tags(&format!("/projects/{}/tasks/{}", project.id, task.id))
+        .await
+        .into_body();
+    assert_eq!(task_tags.len(), 1);
+
+    for (name, color) in [("review", "#123456"), ("", "#123456"), ("blue", "blue")] {
+        assert_eq!(
+            owner
+                .create_tag(&NewTag {
+                    name: name.to_string(),
+                    color: color.to_string(),
+                })
+                .await
+                .status,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+    assert_eq!(owner.list_tags().await.into_body().len(), 1);
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,154 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    Credentials, TestService, create_project, create_task, issue_token, new_project, new_task,
+};
+use crate::{
+    db_handler::structs::{Task, TaskOrder, TaskUpdate},
+    kafka_handler::structs::KafkaNinoverseEvent,
+};
+
+fn titles(tasks: &[Task]) -> Vec<&str> {
+    tasks.iter().map(|task| task.title.as_str()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tasks_nest_and_reorder_within_their_project() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let project = create_project(client, &new_project("Planned")).await;
+
+    let design = create_task(client, project.id, &new_task("Design", None)).await;
+    let build = create_task(client, project.id, &new_task("Build", None)).await;
+    let sketch = create_task(client, project.id, &new_task("Sketch", Some(design.id))).await;
+    assert_eq!(
+        (design.position, build.position, sketch.position),
+        (1, 2, 3)
+    );
+    assert_eq!(sketch.parent_id, Some(design.id));
+
+    let reordered = client
+        .reorder_tasks(
+            project.id,
+            &TaskOrder {
+                task_ids: vec![build.id, design.id, sketch.id],
+            },
+        )
+        .await
+        .into_body();
+    assert_eq!(titles(&reordered), vec!["Build", "Design", "Sketch"]);
+    let incomplete = client
+        .reorder_tasks(
+            project.id,
+            &TaskOrder {
+                task_ids: vec![build.id],
+            },
+        )
+        .await;
+    assert_eq!(incomplete.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // A task can't end up under its own subtask.
+    let cycle = client
+        .update_task(
+            project.id,
+            design.id,
+            &TaskUpdate {
+                parent_id: Some(sketch.id),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert_eq!(cycle.status, StatusCode::UNPROCESSABLE_ENTITY);
+    let other_project = create_project(client, &new_project("Planned")).await;
+    let foreign_parent = client
+        .create_task(other_project.id, &new_task("Elsewhere", Some(design.id)))
+        .await;
+    assert_eq!(foreign_parent.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        client.get_task(other_project.id, design.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    let done = client
+        .update_task(
+            project.id,
+            build.id,
+            &TaskUpdate {
+                status: Some("done".to_string()),
+                priority: Some("high".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .into_body();
+    assert_eq!(
+        (done.status.as_str(), done.priority.as_str()),
+        ("done", "high")
+    );
+    let invalid = client
+        .update_task(
+            project.id,
+            build.id,
+            &TaskUpdate {
+                status: Some("someday".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+    assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Deleting a task takes its subtasks along.
+    assert_eq!(
+        client.delete_task(project.id, design.id).await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        titles(&client.list_tasks(project.id).await.into_body()),
+        vec!["Build"]
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tasks_follow_project_permissions() {
+    let service = TestService::start().await;
+    let owner = &service.client;
+    let outsider = owner.with_credentials(Credentials::Bearer(issue_token("outsider")));
+    let project = create_project(owner, &new_project("Planned")).await;
+    create_task(owner, project.id, &new_task("Private", None)).await;
+
+    assert_eq!(
+        outsider.list_tasks(project.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        outsider
+            .create_task(project.id, &new_task("Intrusion", None))
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn task_events_share_the_project_key() {
+    let service = TestService::start().await;
+    let mut probe = service.kafka_probe(&["ninoverse"]);
+    let project = create_project(&service.client, &new_project("Planned")).await;
+    let task = create_task(&service.client, project.id, &new_task("Publish", None)).await;
+
+    let expected_key = format!("default:project-{}", project.id);
+    let event: KafkaNinoverseEvent = probe
+        .wait_for_json(|message| message.key.as_deref() == Some(expected_key.as_str()))
+        .await;
+    let KafkaNinoverseEvent::TaskCreated {
+        task: published, ..
+    } = event
+    else {
+        panic!("Expected a task_created event");
+    };
+    assert_eq!(published.id, task.id);
+    assert_eq!(published.project_id, project.id);
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,248 @@
+use reqwest::StatusCode;
+
+use super::harness::{
+    ADMIN_SUBJECT, ApiClient, Credentials, TEST_SUBJECT, TestService, issue_tenant_token,
+    issue_token,
+};
+use crate::db_handler::structs::{NewProject, NewTeam, NewTenant, TransitionRequest};
+
+const TENANT: &str = "acme";
+
+fn new_project(name: &str) -> NewProject {
+    NewProject {
+        name: name.to_string(),
+        description: Some("Scoped to its tenant".to_string()),
+        status: "draft".to_string(),
+        team_id: None,
+    }
+}
+
+/// A client of `TEST_SUBJECT` with a token bound to `tenant`.
+fn tenant_client(client: &ApiClient, tenant: &str) -> ApiClient {
+    client.with_credentials(Credentials::Bearer(issue_tenant_token(
+        TEST_SUBJECT,
+        Some(tenant),
+    )))
+}
+
+async fn create_tenant(client: &ApiClient, id: &str) {
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let tenant = admin
+        .create_tenant(&NewTenant {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+        })
+        .await;
+    assert_eq!(tenant.status, StatusCode::CREATED);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tenants_keep_their_data_apart() {
+    let service = TestService::start().await;
+    create_tenant(&service.client, TENANT).await;
+    let default_client = &service.client;
+    let tenant_client = tenant_client(default_client, TENANT);
+
+    let default_project = default_client
+        .create_project(&new_project("Default"))
+        .await
+        .into_body();
+    assert!(
+        tenant_client
+            .list_projects(true)
+            .await
+            .into_body()
+            .is_empty()
+    );
+    assert_eq!(
+        tenant_client.get_project(default_project.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    let tenant_project = tenant_client
+        .create_project(&new_project("Acme"))
+        .await
+        .into_body();
+    let default_names: Vec<String> = default_client
+        .list_projects(true)
+        .await
+        .into_body()
+        .into_iter()
+        .map(|project| project.name)
+        .collect();
+    assert_eq!(default_names, vec!["Default".to_string()]);
+    assert_eq!(
+        tenant_client
+            .get_project(tenant_project.id)
+            .await
+            .into_body()
+            .name,
+        "Acme"
+    );
+
+    // Names only have to be unique within a tenant.
+    let team = NewTeam {
+        name: "core".to_string(),
+    };
+    assert_eq!(
+        default_client.create_team(&team).await.status,
+        StatusCode::CREATED
+    );
+    assert_eq!(
+        tenant_client.create_team(&team).await.status,
+        StatusCode::CREATED
+    );
+    let tenant_user = tenant_client.current_user().await.into_body();
+    assert_eq!(tenant_user.user.subject, TEST_SUBJECT);
+    assert_eq!(tenant_user.memberships.len(), 1);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tenants_are_managed_by_administrators() {
+    let service = TestService::start().await;
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let unknown = admin.with_tenant("nowhere");
+    assert_eq!(
+        unknown.list_projects(false).await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    let tenant = NewTenant {
+        id: TENANT.to_string(),
+        name: "Acme".to_string(),
+    };
+    assert_eq!(
+        service.client.create_tenant(&tenant).await.status,
+        StatusCode::FORBIDDEN
+    );
+    let invalid = admin
+        .create_tenant(&NewTenant {
+            id: "Not a host name".to_string(),
+            name: "Invalid".to_string(),
+        })
+        .await;
+    assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        admin.create_tenant(&tenant).await.status,
+        StatusCode::CREATED
+    );
+    assert_eq!(
+        admin.create_tenant(&tenant).await.status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let tenant_ids: Vec<String> = admin
+        .list_tenants()
+        .await
+        .into_body()
+        .into_iter()
+        .map(|tenant| tenant.id)
+        .collect();
+    assert_eq!(tenant_ids, vec![TENANT.to_string(), "default".to_string()]);
+    assert_eq!(
+        unknown
+            .with_tenant(TENANT)
+            .list_projects(false)
+            .await
+            .status,
+        StatusCode::OK
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tokens_are_bound_to_their_tenant() {
+    let service = TestService::start().await;
+    create_tenant(&service.client, TENANT).await;
+    let bound = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_tenant_token(
+            TEST_SUBJECT,
+            Some(TENANT),
+        )));
+    let project = bound
+        .create_project(&new_project("Bound"))
+        .await
+        .into_body();
+    assert_eq!(
+        service
+            .client
+            .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)))
+            .with_tenant(TENANT)
+            .get_project(project.id)
+            .await
+            .status,
+        StatusCode::OK
+    );
+    assert_eq!(
+        bound
+            .with_tenant("default")
+            .list_projects(false)
+            .await
+            .status,
+        StatusCode::UNAUTHORIZED
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tokens_without_tenant_stay_in_the_default_tenant() {
+    let service = TestService::start().await;
+    create_tenant(&service.client, TENANT).await;
+
+    let intruder = service.client.with_tenant(TENANT);
+    assert_eq!(
+        intruder.list_projects(false).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert!(
+        service
+            .repositories
+            .for_tenant(TENANT)
+            .users
+            .find_by_subject(TEST_SUBJECT)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(
+        service
+            .client
+            .with_tenant("default")
+            .list_projects(false)
+            .await
+            .status,
+        StatusCode::OK
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn events_are_keyed_by_tenant() {
+    let service = TestService::start().await;
+    create_tenant(&service.client, TENANT).await;
+    let mut probe = service.kafka_probe(&["ninoverse"]);
+    let tenant_client = tenant_client(&service.client, TENANT);
+    let project = tenant_client
+        .create_project(&new_project("Evented"))
+        .await
+        .into_body();
+    let transition = tenant_client
+        .transition_project(
+            project.id,
+            &TransitionRequest {
+                to_status: "active".to_string(),
+            },
+        )
+        .await;
+    assert_eq!(transition.status, StatusCode::CREATED);
+
+    let expected_key = format!("{}:project-{}", TENANT, project.id);
+    let message = probe
+        .wait_for(|message| message.key.as_deref() == Some(expected_key.as_str()))
+        .await;
+    assert_eq!(message.topic, "ninoverse");
+    service.shutdown().await.expect("Service failed");
+}
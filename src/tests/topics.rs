@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use reqwest::StatusCode;
+
+use super::harness::{ADMIN_SUBJECT, Credentials, TestService, issue_token};
+use crate::{db_handler::structs::NewTenant, kafka_handler::message_bus::TopicSettings};
+
+fn configs(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ensuring_a_topic_twice_reports_drift_instead_of_failing() {
+    let service = TestService::start().await;
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let settings = TopicSettings {
+        partitions: 2,
+        replication: 1,
+        configs: configs(&[("retention.ms", "60000")]),
+    };
+
+    let created = admin.ensure_topic("exports", &settings).await;
+    assert_eq!(created.status, StatusCode::CREATED);
+    let created = created.into_body();
+    assert!(created.created);
+    assert!(created.drift.is_empty());
+    assert_eq!(created.topic.partitions.len(), 2);
+
+    let again = admin.ensure_topic("exports", &settings).await;
+    assert_eq!(again.status, StatusCode::OK);
+    let again = again.into_body();
+    assert!(!again.created);
+    assert!(again.drift.is_empty());
+
+    let drifted = admin
+        .ensure_topic(
+            "exports",
+            &TopicSettings {
+                partitions: 3,
+                configs: configs(&[("retention.ms", "120000"), ("cleanup.policy", "compact")]),
+                ..settings
+            },
+        )
+        .await
+        .into_body();
+    let drift: Vec<(&str, Option<&str>)> = drifted
+        .drift
+        .iter()
+        .map(|drift| (drift.setting.as_str(), drift.actual.as_deref()))
+        .collect();
+    assert_eq!(
+        drift,
+        [
+            ("partitions", Some("2")),
+            ("cleanup.policy", None),
+            ("retention.ms", Some("60000")),
+        ]
+    );
+    // Drift is reported, not corrected.
+    assert_eq!(
+        admin
+            .get_topic("exports")
+            .await
+            .into_body()
+            .partitions
+            .len(),
+        2
+    );
+
+    let invalid = admin
+        .ensure_topic(
+            "empty",
+            &TopicSettings {
+                partitions: 0,
+                replication: 1,
+                configs: BTreeMap::new(),
+            },
+        )
+        .await;
+    assert_eq!(invalid.status, StatusCode::UNPROCESSABLE_ENTITY);
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn topics_are_altered_grown_and_deleted() {
+    let service = TestService::start().await;
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    admin
+        .ensure_topic(
+            "reports",
+            &TopicSettings {
+                partitions: 1,
+                replication: 1,
+                configs: configs(&[("retention.ms", "60000")]),
+            },
+        )
+        .await
+        .into_body();
+
+    let altered = admin
+        .update_topic_configs("reports", &configs(&[("cleanup.policy", "compact")]))
+        .await
+        .into_body();
+    assert_eq!(
+        altered.configs,
+        configs(&[("cleanup.policy", "compact"), ("retention.ms", "60000")])
+    );
+
+    let grown = admin.add_topic_partitions("reports", 4).await.into_body();
+    assert_eq!(grown.partitions.len(), 4);
+    let shrunk = admin.add_topic_partitions("reports", 2).await;
+    assert_eq!(shrunk.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let names: Vec<String> = admin
+        .list_topics()
+        .await
+        .into_body()
+        .into_iter()
+        .map(|topic| topic.name)
+        .collect();
+    assert!(names.contains(&"reports".to_string()));
+
+    assert_eq!(
+        admin.delete_topic("reports").await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        admin.get_topic("reports").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        admin.delete_topic("reports").await.status,
+        StatusCode::NOT_FOUND
+    );
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn topics_are_reserved_to_administrators() {
+    let service = TestService::start().await;
+    let client = &service.client;
+
+    assert_eq!(client.list_topics().await.status, StatusCode::FORBIDDEN);
+    let ensured = client
+        .ensure_topic(
+            "forbidden",
+            &TopicSettings {
+                partitions: 1,
+                replication: 1,
+                configs: BTreeMap::new(),
+            },
+        )
+        .await;
+    assert_eq!(ensured.status, StatusCode::FORBIDDEN);
+    assert!(
+        service
+            .message_bus
+            .describe_topic("forbidden")
+            .await
+            .is_err()
+    );
+
+    // Topics are shared, the administrators of other tenants can't see them.
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    admin
+        .create_tenant(&NewTenant {
+            id: "acme".to_string(),
+            name: "Acme".to_string(),
+        })
+        .await
+        .into_body();
+    assert_eq!(
+        admin.with_tenant("acme").list_topics().await.status,
+        StatusCode::FORBIDDEN
+    );
+
+    // The topics of the service are left alone.
+    for topic in ["ninoverse", "ninoverse.acme"] {
+        assert_eq!(
+            admin.delete_topic(topic).await.status,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            admin
+                .update_topic_configs(topic, &configs(&[("retention.ms", "1")]))
+                .await
+                .status,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            admin.add_topic_partitions(topic, 8).await.status,
+            StatusCode::FORBIDDEN
+        );
+    }
+    service.shutdown().await.expect("Service failed");
+}
@@ -0,0 +1,426 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use serde_json::Value;
+use sha2::Sha256;
+
+use super::harness::{
+    ADMIN_SUBJECT, ApiClient, Credentials, ReceivedWebhook, TestBackends, TestService,
+    WEBHOOK_DISABLE_AFTER, WEBHOOK_MAX_ATTEMPTS, WebhookReceiver, create_project, create_task,
+    issue_token, new_project, new_task,
+};
+use crate::{
+    configuration_handler::WEBHOOK_MAX_ATTEMPTS_LIMIT,
+    db_handler::{
+        repository::Repositories,
+        structs::{
+            NewPendingWebhookDelivery, NewWebhook, TaskUpdate, Webhook, WebhookDelivery,
+            WebhookUpdate,
+        },
+    },
+    webhook_handler::{DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, WebhookSettings},
+};
+
+const SECRET: &str = "0123456789abcdef";
+
+async fn create_webhook(admin: &ApiClient, url: &str, event_types: &[&str]) -> Webhook {
+    let response = admin
+        .create_webhook(&NewWebhook {
+            url: url.to_string(),
+            secret: SECRET.to_string(),
+            event_types: event_types.iter().map(|name| name.to_string()).collect(),
+        })
+        .await;
+    assert_eq!(response.status, StatusCode::CREATED);
+    response.into_body()
+}
+
+/// A delivery of `event_id` queued for the webhook, as the consumer does.
+fn pending_delivery(webhook_id: i32, event_id: &str) -> NewPendingWebhookDelivery {
+    NewPendingWebhookDelivery {
+        webhook_id,
+        event_id: event_id.to_string(),
+        event_type: "task_created".to_string(),
+        body: format!("{{\"id\":\"{}\"}}", event_id),
+    }
+}
+
+/// Checks the signature and returns the body.
+fn verified_body(request: &ReceivedWebhook) -> Value {
+    let signature = request.header(SIGNATURE_HEADER).unwrap();
+    let (timestamp, signature) = signature
+        .strip_prefix("t=")
+        .and_then(|rest| rest.split_once(",v1="))
+        .unwrap();
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(format!("{}.{}", timestamp, request.body).as_bytes());
+    assert_eq!(hex::encode(mac.finalize().into_bytes()), signature);
+    serde_json::from_str(&request.body).unwrap()
+}
+
+/// Deliveries are logged after the response, the outcome after the last one.
+async fn wait_for_webhook(
+    admin: &ApiClient,
+    id: i32,
+    predicate: impl Fn(&Webhook, &[WebhookDelivery]) -> bool,
+) -> (Webhook, Vec<WebhookDelivery>) {
+    let started = tokio::time::Instant::now();
+    loop {
+        let webhook = admin.get_webhook(id).await.into_body();
+        let deliveries = admin.list_webhook_deliveries(id).await.into_body();
+        if predicate(&webhook, &deliveries) {
+            return (webhook, deliveries);
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(5),
+            "Webhook {:?} not in the expected state, deliveries {:?}",
+            webhook,
+            deliveries
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn webhooks_receive_signed_events() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let mut receiver = WebhookReceiver::start().await;
+    let webhook = create_webhook(&admin, &receiver.url, &["task_created"]).await;
+    assert!(webhook.enabled);
+    assert_eq!(webhook.event_types, vec!["task_created".to_string()]);
+    // Never sent back.
+    assert!(webhook.secret.is_empty());
+
+    let project = create_project(client, &new_project("Hooks")).await;
+    let first = create_task(client, project.id, &new_task("First", None)).await;
+    client
+        .update_task(
+            project.id,
+            first.id,
+            &TaskUpdate {
+                title: Some("Renamed".to_string()),
+                ..TaskUpdate::default()
+            },
+        )
+        .await;
+    let second = create_task(client, project.id, &new_task("Second", None)).await;
+
+    let request = receiver.next_request().await;
+    assert_eq!(request.header(EVENT_HEADER), Some("task_created"));
+    assert_eq!(request.header("content-type"), Some("application/json"));
+    let body = verified_body(&request);
+    assert_eq!(body["id"].as_str(), request.header(DELIVERY_HEADER));
+    assert_eq!(body["tenant_id"], "default");
+    assert_eq!(body["event"], "task_created");
+    assert_eq!(body["task"]["id"], first.id);
+    // The update is filtered out.
+    let body = verified_body(&receiver.next_request().await);
+    assert_eq!(body["task"]["id"], second.id);
+
+    let (webhook, deliveries) =
+        wait_for_webhook(&admin, webhook.id, |_, deliveries| deliveries.len() == 2).await;
+    assert_eq!(webhook.consecutive_failures, 0);
+    assert!(deliveries.iter().all(|delivery| delivery.succeeded
+        && delivery.attempt == 1
+        && delivery.status_code == Some(200)
+        && delivery.event_type == "task_created"));
+
+    assert_eq!(client.list_webhooks().await.status, StatusCode::FORBIDDEN);
+    assert_eq!(
+        client.list_webhook_deliveries(webhook.id).await.status,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        admin
+            .list_webhooks()
+            .await
+            .into_body()
+            .iter()
+            .map(|webhook| webhook.id)
+            .collect::<Vec<i32>>(),
+        vec![webhook.id]
+    );
+
+    receiver.stop().await;
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failing_webhooks_are_retried_then_disabled() {
+    let service = TestService::start().await;
+    let client = &service.client;
+    let admin = client.with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let mut receiver = WebhookReceiver::start().await;
+    receiver.respond_with(500);
+    let webhook = create_webhook(&admin, &receiver.url, &["task_created"]).await;
+    let project = create_project(client, &new_project("Flaky")).await;
+
+    for failures in 1..=WEBHOOK_DISABLE_AFTER {
+        create_task(client, project.id, &new_task("Lost", None)).await;
+        let mut delivery_ids = vec![];
+        for _ in 0..WEBHOOK_MAX_ATTEMPTS {
+            let request = receiver.next_request().await;
+            delivery_ids.push(request.header(DELIVERY_HEADER).unwrap().to_string());
+        }
+        delivery_ids.dedup();
+        assert_eq!(delivery_ids.len(), 1);
+        let (webhook, deliveries) = wait_for_webhook(&admin, webhook.id, |webhook, _| {
+            webhook.consecutive_failures == failures
+        })
+        .await;
+        assert_eq!(webhook.enabled, failures < WEBHOOK_DISABLE_AFTER);
+        assert_eq!(
+            deliveries
+                .iter()
+                .take(WEBHOOK_MAX_ATTEMPTS as usize)
+                .map(|delivery| delivery.attempt)
+                .collect::<Vec<i32>>(),
+            vec![3, 2, 1]
+        );
+        assert!(deliveries.iter().all(|delivery| !delivery.succeeded
+            && delivery.status_code == Some(500)
+            && delivery.error.is_some()));
+    }
+    let disabled = admin.get_webhook(webhook.id).await.into_body();
+    assert!(disabled.disabled_at.is_some());
+
+    receiver.respond_with(204);
+    let enabled = admin
+        .update_webhook(
+            webhook.id,
+            &WebhookUpdate {
+                enabled: Some(true),
+                ..WebhookUpdate::default()
+            },
+        )
+        .await
+        .into_body();
+    assert!(enabled.enabled);
+    assert_eq!(enabled.consecutive_failures, 0);
+    assert!(enabled.disabled_at.is_none());
+    let task = create_task(client, project.id, &new_task("Delivered", None)).await;
+    let body = verified_body(&receiver.next_request().await);
+    assert_eq!(body["task"]["id"], task.id);
+    wait_for_webhook(&admin, webhook.id, |_, deliveries| {
+        deliveries
+            .first()
+            .is_some_and(|delivery| delivery.succeeded)
+    })
+    .await;
+
+    assert_eq!(
+        admin.delete_webhook(webhook.id).await.status,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        admin.get_webhook(webhook.id).await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    receiver.stop().await;
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn webhooks_are_validated() {
+    let service = TestService::start().await;
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let valid = NewWebhook {
+        url: "https://hooks.example.com/ninoverse".to_string(),
+        secret: SECRET.to_string(),
+        event_types: vec![],
+    };
+
+    for invalid in [
+        NewWebhook {
+            url: "ftp://hooks.example.com".to_string(),
+            ..valid.clone()
+        },
+        NewWebhook {
+            url: "not a url".to_string(),
+            ..valid.clone()
+        },
+        NewWebhook {
+            secret: "short".to_string(),
+            ..valid.clone()
+        },
+        NewWebhook {
+            event_types: vec!["task_exploded".to_string()],
+            ..valid.clone()
+        },
+    ] {
+        assert_eq!(
+            admin.create_webhook(&invalid).await.status,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{:?}",
+            invalid
+        );
+    }
+    let webhook = admin.create_webhook(&valid).await.into_body();
+    assert_eq!(
+        admin
+            .update_webhook(
+                webhook.id,
+                &WebhookUpdate {
+                    event_types: Some(vec!["nothing".to_string()]),
+                    ..WebhookUpdate::default()
+                },
+            )
+            .await
+            .status,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let disabled = admin
+        .update_webhook(
+            webhook.id,
+            &WebhookUpdate {
+                enabled: Some(false),
+                event_types: Some(vec!["task_deleted".to_string()]),
+                ..WebhookUpdate::default()
+            },
+        )
+        .await
+        .into_body();
+    assert!(!disabled.enabled);
+    assert!(disabled.disabled_at.is_some());
+    assert_eq!(disabled.event_types, vec!["task_deleted".to_string()]);
+    assert_eq!(
+        service.client.create_webhook(&valid).await.status,
+        StatusCode::FORBIDDEN
+    );
+
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pending_deliveries_are_resumed_after_a_restart() {
+    let mut receiver = WebhookReceiver::start().await;
+    let backends = TestBackends::in_memory();
+    // Left by an earlier run, after its first attempt failed.
+    let webhooks = &backends.repositories.webhooks;
+    let webhook = webhooks
+        .insert(
+            &NewWebhook {
+                url: receiver.url.clone(),
+                secret: SECRET.to_string(),
+                event_types: vec![],
+            },
+            ADMIN_SUBJECT,
+        )
+        .await
+        .unwrap();
+    let pending = webhooks
+        .insert_pending(&pending_delivery(webhook.id, "ninoverse:0:41"))
+        .await
+        .unwrap();
+    webhooks
+        .reschedule_pending(pending.id, 2, Duration::ZERO)
+        .await
+        .unwrap();
+
+    let service = TestService::start_with(backends).await;
+    let admin = service
+        .client
+        .with_credentials(Credentials::Bearer(issue_token(ADMIN_SUBJECT)));
+    let request = receiver.next_request().await;
+    assert_eq!(request.header(DELIVERY_HEADER), Some("ninoverse:0:41"));
+    assert_eq!(verified_body(&request)["id"], "ninoverse:0:41");
+    let (_, deliveries) =
+        wait_for_webhook(&admin, webhook.id, |_, deliveries| deliveries.len() == 1).await;
+    assert!(deliveries[0].succeeded);
+    assert_eq!(deliveries[0].attempt, 2);
+    // Delivered, it left the queue.
+    assert!(
+        service
+            .repositories
+            .webhooks
+            .claim_pending(10, Duration::ZERO)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    receiver.stop().await;
+    service.shutdown().await.expect("Service failed");
+}
+
+#[tokio::test]
+async fn claimed_deliveries_are_not_taken_again_before_their_lease_runs_out() {
+    let repositories = Repositories::in_memory();
+    let webhook = repositories
+        .webhooks
+        .insert(
+            &NewWebhook {
+                url: "http://127.0.0.1:9/hook".to_string(),
+                secret: SECRET.to_string(),
+                event_types: vec![],
+            },
+            ADMIN_SUBJECT,
+        )
+        .await
+        .unwrap();
+    for event_id in ["ninoverse:0:1", "ninoverse:0:2", "ninoverse:0:3"] {
+        repositories
+            .webhooks
+            .insert_pending(&pending_delivery(webhook.id, event_id))
+            .await
+            .unwrap();
+    }
+    let lease = Duration::from_secs(60);
+    let claim = |limit| repositories.webhooks.claim_pending(limit, lease);
+
+    let first = claim(2).await.unwrap();
+    assert_eq!(
+        first
+            .iter()
+            .map(|delivery| delivery.event_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["ninoverse:0:1", "ninoverse:0:2"]
+    );
+    let second = claim(2).await.unwrap();
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].event_id, "ninoverse:0:3");
+    assert!(claim(2).await.unwrap().is_empty());
+
+    // A retry is due once its delay has passed.
+    repositories
+        .webhooks
+        .reschedule_pending(first[0].id, 2, Duration::ZERO)
+        .await
+        .unwrap();
+    let retried = claim(2).await.unwrap();
+    assert_eq!(retried.len(), 1);
+    assert_eq!(retried[0].attempt, 2);
+
+    // Deleting the webhook drops its queue.
+    repositories.webhooks.delete(webhook.id).await.unwrap();
+    repositories
+        .webhooks
+        .reschedule_pending(second[0].id, 2, Duration::ZERO)
+        .await
+        .unwrap();
+    assert!(claim(2).await.unwrap().is_empty());
+}
+
+#[test]
+fn retry_backoff_doubles_up_to_an_hour() {
+    let settings = WebhookSettings {
+        max_attempts: WEBHOOK_MAX_ATTEMPTS_LIMIT,
+        retry_backoff: Duration::from_secs(1),
+        timeout: Duration::from_secs(1),
+        disable_after: 1,
+        concurrency: 1,
+    };
+    assert_eq!(settings.backoff_after(1), Duration::from_secs(1));
+    assert_eq!(settings.backoff_after(3), Duration::from_secs(4));
+    assert_eq!(settings.backoff_after(20), Duration::from_secs(60 * 60));
+    assert_eq!(
+        settings.backoff_after(i32::MAX as u32),
+        Duration::from_secs(60 * 60)
+    );
+}
@@ -0,0 +1,327 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, header, redirect};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::{sync::Notify, task::JoinSet, time::Instant};
+
+use crate::{
+    configuration_handler::{
+        get_webhook_concurrency, get_webhook_disable_after, get_webhook_max_attempts,
+        get_webhook_retry_backoff, get_webhook_timeout,
+    },
+    db_handler::{
+        repository::Repositories,
+        structs::{NewWebhookDelivery, PendingWebhookDelivery, Webhook},
+    },
+    kafka_handler::structs::KafkaNinoverseEvent,
+};
+
+/// `t=<unix timestamp>,v1=<signature>`, the signature being the hex encoded
+/// HMAC-SHA256 of `<timestamp>.<body>` keyed with the secret of the webhook.
+pub const SIGNATURE_HEADER: &str = "x-ninoverse-signature";
+pub const EVENT_HEADER: &str = "x-ninoverse-event";
+/// The id of the event, the same for every attempt at delivering it.
+pub const DELIVERY_HEADER: &str = "x-ninoverse-delivery";
+
+/// How often the pending deliveries are looked at when nothing else wakes the
+/// dispatcher, for those left by another instance or a restart.
+const PENDING_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Added to the timeout of an attempt for how long a claimed delivery is not
+/// taken again.
+const CLAIM_MARGIN: Duration = Duration::from_secs(30);
+/// Longest wait between two attempts, however many came before.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// How deliveries are retried and when a failing webhook is disabled.
+pub struct WebhookSettings {
+    /// Attempts at delivering an event, the first one included.
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for every next one.
+    pub retry_backoff: Duration,
+    pub timeout: Duration,
+    /// Failed deliveries in a row after which a webhook is disabled.
+    pub disable_after: i32,
+    /// Deliveries attempted at the same time.
+    pub concurrency: usize,
+}
+
+impl WebhookSettings {
+    pub fn from_configuration() -> Self {
+        WebhookSettings {
+            max_attempts: get_webhook_max_attempts(),
+            retry_backoff: get_webhook_retry_backoff(),
+            timeout: get_webhook_timeout(),
+            disable_after: get_webhook_disable_after(),
+            concurrency: get_webhook_concurrency(),
+        }
+    }
+
+    /// Wait after the failed `attempt`, doubled for every attempt and capped
+    /// to `MAX_RETRY_BACKOFF`.
+    pub fn backoff_after(&self, attempt: u32) -> Duration {
+        2u32.checked_pow(attempt.saturating_sub(1))
+            .and_then(|factor| self.retry_backoff.checked_mul(factor))
+            .map_or(MAX_RETRY_BACKOFF, |backoff| backoff.min(MAX_RETRY_BACKOFF))
+    }
+
+    /// Redirects are not followed, the URL of a webhook is where its events
+    /// go.
+    pub fn client(&self) -> Client {
+        Client::builder()
+            .timeout(self.timeout)
+            .redirect(redirect::Policy::none())
+            .build()
+            .expect("WEBHOOKS: Error building the HTTP client")
+    }
+}
+
+/// The body of every delivery, the event with its id and tenant.
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    id: &'a str,
+    tenant_id: &'a str,
+    #[serde(flatten)]
+    event: &'a KafkaNinoverseEvent,
+}
+
+/// An event ready to be posted to the webhooks of its tenant.
+pub struct EventDelivery {
+    pub id: String,
+    pub event_type: &'static str,
+    pub body: String,
+}
+
+impl EventDelivery {
+    pub fn new(
+        id: String,
+        tenant_id: &str,
+        event: &KafkaNinoverseEvent,
+    ) -> Result<Self, serde_json::Error> {
+        let body = serde_json::to_string(&WebhookPayload {
+            id: &id,
+            tenant_id,
+            event,
+        })?;
+        Ok(EventDelivery {
+            id,
+            event_type: event.name(),
+            body,
+        })
+    }
+}
+
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// The status code received, if any, and why the attempt failed.
+async fn post(
+    client: &Client,
+    webhook: &Webhook,
+    delivery: &PendingWebhookDelivery,
+) -> (Option<i32>, Option<String>) {
+    let timestamp = Utc::now().timestamp();
+    let response = client
+        .post(&webhook.url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, &delivery.event_id)
+        .header(
+            SIGNATURE_HEADER,
+            format!(
+                "t={},v1={}",
+                timestamp,
+                signature(&webhook.secret, timestamp, &delivery.body)
+            ),
+        )
+        .body(delivery.body.clone())
+        .send()
+        .await;
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            let error = (!status.is_success()).then(|| format!("Unexpected status {}.", status));
+            (Some(i32::from(status.as_u16())), error)
+        }
+        Err(request_error) => (None, Some(request_error.to_string())),
+    }
+}
+
+/// Makes the next attempt at a pending delivery and logs it. The delivery is
+/// dropped when the webhook was disabled, deleted or stopped accepting the
+/// event meanwhile, and once it succeeded or ran out of attempts, which counts
+/// as one failure of the webhook. Returns when the next attempt is due, if
+/// there is one.
+async fn attempt(
+    repositories: Repositories,
+    client: Client,
+    settings: Arc<WebhookSettings>,
+    delivery: PendingWebhookDelivery,
+) -> Option<Instant> {
+    let webhook = match repositories.webhooks.get(delivery.webhook_id).await {
+        Ok(Some(webhook)) if webhook.enabled && webhook.accepts(&delivery.event_type) => webhook,
+        Ok(_) => {
+            drop_pending(&repositories, &delivery).await;
+            return None;
+        }
+        // Taken again once its claim runs out.
+        Err(db_error) => {
+            println!(
+                "WEBHOOKS: Error reloading webhook {}: {:?}",
+                delivery.webhook_id, db_error
+            );
+            return None;
+        }
+    };
+    let started = Instant::now();
+    let (status_code, error) = post(&client, &webhook, &delivery).await;
+    let succeeded = error.is_none();
+    let logged = repositories
+        .webhooks
+        .insert_delivery(&NewWebhookDelivery {
+            webhook_id: webhook.id,
+            event_id: delivery.event_id.clone(),
+            event_type: delivery.event_type.clone(),
+            attempt: delivery.attempt,
+            status_code,
+            error,
+            succeeded,
+            duration_ms: started.elapsed().as_millis() as i64,
+        })
+        .await;
+    if let Err(db_error) = logged {
+        println!(
+            "WEBHOOKS: Error logging delivery {} to webhook {}: {:?}",
+            delivery.event_id, webhook.id, db_error
+        );
+    }
+    if succeeded || delivery.attempt as u32 >= settings.max_attempts {
+        drop_pending(&repositories, &delivery).await;
+        record_outcome(&repositories, &settings, webhook.id, succeeded).await;
+        return None;
+    }
+    let backoff = settings.backoff_after(delivery.attempt as u32);
+    let rescheduled = repositories
+        .webhooks
+        .reschedule_pending(delivery.id, delivery.attempt + 1, backoff)
+        .await;
+    match rescheduled {
+        Ok(()) => Some(Instant::now() + backoff),
+        Err(db_error) => {
+            println!(
+                "WEBHOOKS: Error rescheduling delivery {} to webhook {}: {:?}",
+                delivery.event_id, webhook.id, db_error
+            );
+            None
+        }
+    }
+}
+
+async fn drop_pending(repositories: &Repositories, delivery: &PendingWebhookDelivery) {
+    if let Err(db_error) = repositories.webhooks.delete_pending(delivery.id).await {
+        println!(
+            "WEBHOOKS: Error removing delivery {} to webhook {}: {:?}",
+            delivery.event_id, delivery.webhook_id, db_error
+        );
+    }
+}
+
+/// Attempts the pending deliveries of every tenant as they become due, at
+/// most `concurrency` at a time. `queued` wakes it for the deliveries just
+/// queued, the retries it schedules itself. Dropping it leaves the claimed
+/// deliveries to be taken again.
+pub async fn run_deliveries(
+    repositories: Repositories,
+    settings: Arc<WebhookSettings>,
+    queued: Arc<Notify>,
+) {
+    let client = settings.client();
+    let lease = settings.timeout + CLAIM_MARGIN;
+    let mut attempts = JoinSet::new();
+    let mut wake_at = Instant::now();
+    println!("WEBHOOKS: Dispatcher started.");
+    loop {
+        tokio::select! {
+            _ = queued.notified() => {}
+            _ = tokio::time::sleep_until(wake_at) => {}
+            Some(attempted) = attempts.join_next() => {
+                if let Ok(Some(retry_at)) = attempted {
+                    wake_at = wake_at.min(retry_at);
+                }
+            }
+        }
+        if wake_at <= Instant::now() {
+            wake_at = Instant::now() + PENDING_POLL_INTERVAL;
+        }
+        if attempts.len() >= settings.concurrency {
+            continue;
+        }
+        let tenants = match repositories.tenants.list().await {
+            Ok(tenants) => tenants,
+            Err(list_error) => {
+                println!("WEBHOOKS: Error listing tenants: {:?}", list_error);
+                continue;
+            }
+        };
+        for tenant in tenants {
+            let available = settings.concurrency.saturating_sub(attempts.len());
+            if available == 0 {
+                break;
+            }
+            let tenant_repositories = repositories.for_tenant(&tenant.id);
+            let claimed = tenant_repositories
+                .webhooks
+                .claim_pending(available as i64, lease)
+                .await;
+            match claimed {
+                Ok(deliveries) => {
+                    for delivery in deliveries {
+                        attempts.spawn(attempt(
+                            tenant_repositories.clone(),
+                            client.clone(),
+                            settings.clone(),
+                            delivery,
+                        ));
+                    }
+                }
+                Err(db_error) => println!(
+                    "WEBHOOKS: Error claiming the deliveries of tenant {}: {:?}",
+                    tenant.id, db_error
+                ),
+            }
+        }
+    }
+}
+
+async fn record_outcome(
+    repositories: &Repositories,
+    settings: &WebhookSettings,
+    webhook_id: i32,
+    succeeded: bool,
+) {
+    let outcome = repositories
+        .webhooks
+        .record_outcome(webhook_id, succeeded, settings.disable_after)
+        .await;
+    match outcome {
+        Ok(Some(webhook))
+            if !succeeded && webhook.consecutive_failures == settings.disable_after =>
+        {
+            println!(
+                "WEBHOOKS: Webhook {} disabled after {} failed deliveries.",
+                webhook.id, webhook.consecutive_failures
+            )
+        }
+        Ok(_) => {}
+        Err(db_error) => println!(
+            "WEBHOOKS: Error recording the outcome of webhook {}: {:?}",
+            webhook_id, db_error
+        ),
+    }
+}